@@ -0,0 +1,49 @@
+use std::env;
+use std::process::Command;
+
+/// Runs git with the given arguments, yielding its trimmed
+/// output if git is available and the command succeeded.
+fn git(args: &[&str]) -> Option<String> {
+    let output = Command::new("git").args(args).output().ok()?;
+    Some(String::from_utf8(output.stdout).ok()?.trim().to_owned()).filter(|s| output.status.success() && !s.is_empty())
+}
+
+/// Formats seconds since the Unix epoch as an
+/// ISO 8601 timestamp in UTC (e.g. 2020-06-01T12:00:00Z).
+fn format_timestamp(secs: i64) -> String {
+    let (days, secs_of_day) = (secs.div_euclid(86400), secs.rem_euclid(86400));
+    // Converts the days to a civil date, see http://howardhinnant.github.io/date_algorithms.html
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z", year, month, day, secs_of_day / 3600, secs_of_day % 3600 / 60, secs_of_day % 60)
+}
+
+fn main() {
+    // The build timestamp is derived from SOURCE_DATE_EPOCH (as set by
+    // reproducible build environments) or the commit time, never from
+    // the clock, so that rebuilding a revision yields the same binary
+    println!("cargo:rerun-if-env-changed=SOURCE_DATE_EPOCH");
+    if let Some(git_dir) = git(&["rev-parse", "--git-dir"]) {
+        println!("cargo:rerun-if-changed={}/HEAD", git_dir);
+        if let Some(head_ref) = git(&["symbolic-ref", "HEAD"]) {
+            println!("cargo:rerun-if-changed={}/{}", git_dir, head_ref);
+        }
+    }
+
+    if let Some(hash) = git(&["rev-parse", "--short=12", "HEAD"]) {
+        println!("cargo:rustc-env=SC_GIT_HASH={}", hash);
+    }
+    let epoch = env::var("SOURCE_DATE_EPOCH").ok()
+        .or_else(|| git(&["log", "-1", "--format=%ct"]))
+        .and_then(|raw| raw.trim().parse::<i64>().ok());
+    if let Some(epoch) = epoch {
+        println!("cargo:rustc-env=SC_BUILD_TIMESTAMP={}", format_timestamp(epoch));
+    }
+}
@@ -0,0 +1,46 @@
+use std::cmp::Ordering;
+use std::fmt;
+use crate::eval::Evaluator;
+use crate::game::{GameState, Move, PlayerColor};
+
+/// A suggested move together with its evaluation and
+/// a one-line justification.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Hint {
+    pub game_move: Move,
+    pub evaluation: f64,
+    pub justification: String
+}
+
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:+.2} {} ({})", self.evaluation, self.game_move, self.justification)
+    }
+}
+
+/// Ranks the possible moves of the given color by the
+/// evaluation of the resulting positions and returns the
+/// best `k` together with justifications, which are derived
+/// from the feature of the evaluation's breakdown that
+/// changes the most through the move.
+pub fn hints(state: &GameState, color: PlayerColor, evaluator: &impl Evaluator, k: usize) -> Vec<Hint> {
+    let features_before = evaluator.breakdown(state, color);
+    let mut hints: Vec<_> = state.possible_moves(color).into_iter()
+        .filter_map(|game_move| {
+            let next = state.apply_move(&game_move).ok()?;
+            let features_after = evaluator.breakdown(&next, color);
+            let justification = features_before.iter().zip(features_after.iter())
+                .map(|(before, after)| (before, after, after.weighted() - before.weighted()))
+                .filter(|(_, _, delta)| *delta != 0.0)
+                .max_by(|(_, _, a), (_, _, b)| a.abs().partial_cmp(&b.abs()).unwrap_or(Ordering::Equal))
+                .map(|(before, after, delta)| format!("{}: {} -> {} ({:+.2})", after.name, before.value, after.value, delta))
+                .unwrap_or_else(|| "no change in evaluation features".to_owned());
+            Some(Hint { evaluation: evaluator.evaluate(&next, color), game_move, justification })
+        })
+        .collect();
+
+    // Break ties by the canonical move order to keep the output deterministic
+    hints.sort_by(|a, b| b.evaluation.partial_cmp(&a.evaluation).unwrap_or(Ordering::Equal).then_with(|| a.game_move.cmp(&b.game_move)));
+    hints.truncate(k);
+    hints
+}
@@ -0,0 +1,10 @@
+//! Tools for analyzing positions, e.g. for
+//! building training tools on top of the engine.
+
+mod hints;
+mod puzzle;
+mod puzzle_suite;
+
+pub use hints::*;
+pub use puzzle::*;
+pub use puzzle_suite::*;
@@ -0,0 +1,137 @@
+use std::collections::HashSet;
+use std::fmt;
+use std::str::FromStr;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::util::{SCError, SCResult};
+
+/// Tests whether the game is decided, returning whether
+/// the given color won (a draw counting as not won).
+fn decided(state: &GameState, color: PlayerColor) -> Option<bool> {
+    match (state.board.is_bee_surrounded(color), state.board.is_bee_surrounded(color.opponent())) {
+        (false, true) => Some(true),
+        (false, false) if !state.is_turn_limit_reached() => None,
+        _ => Some(false)
+    }
+}
+
+/// Finds a line that wins for the player to move within the given
+/// number of plies regardless of the opponent's replies, following
+/// the first reply (in the canonical move order) for the opponent.
+fn forced_win(state: &GameState, plies: u32) -> Option<Vec<Move>> {
+    let attacker = state.current_player_color;
+    let mut moves = state.possible_moves(attacker);
+    moves.sort();
+    for game_move in moves {
+        let next = match state.apply_move(&game_move) {
+            Ok(next) => next,
+            Err(_) => continue
+        };
+        let continuation = match decided(&next, attacker) {
+            Some(true) => Some(Vec::new()),
+            Some(false) => None,
+            None if plies >= 3 => losing_replies(&next, attacker, plies - 1),
+            None => None
+        };
+        if let Some(continuation) = continuation {
+            return Some(std::iter::once(game_move).chain(continuation).collect());
+        }
+    }
+    None
+}
+
+/// Ensures that every reply of the opponent loses within the given
+/// number of plies, returning the line starting with the first reply.
+fn losing_replies(state: &GameState, attacker: PlayerColor, plies: u32) -> Option<Vec<Move>> {
+    let mut replies = state.possible_moves(attacker.opponent());
+    replies.sort();
+    let mut line = None;
+    for reply in replies {
+        let next = state.apply_move(&reply).ok()?;
+        let continuation = match decided(&next, attacker) {
+            Some(true) => Vec::new(),
+            Some(false) => return None,
+            None => forced_win(&next, plies - 1)?
+        };
+        line.get_or_insert_with(|| std::iter::once(reply).chain(continuation).collect());
+    }
+    // Positions in which the opponent would have to pass are not considered
+    line
+}
+
+/// A position in which the player to move can force a
+/// win, together with a solution line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Puzzle {
+    pub state: GameState,
+    /// The winning moves, alternating with the opponent's replies.
+    pub solution: Vec<Move>
+}
+
+impl Puzzle {
+    /// Creates a puzzle from the position if the player to move can
+    /// force a win within the given number of plies, using the shortest win.
+    pub fn find(state: &GameState, max_plies: u32) -> Option<Self> {
+        (1..=max_plies).step_by(2)
+            .find_map(|plies| forced_win(state, plies))
+            .map(|solution| Self { state: state.clone(), solution })
+    }
+
+    /// Finds the puzzles among the positions of a game,
+    /// skipping positions that occurred before (in any orientation).
+    pub fn extract(states: &[GameState], max_plies: u32) -> Vec<Self> {
+        let mut seen = HashSet::new();
+        states.iter()
+            .filter(|s| decided(s, s.current_player_color).is_none() && seen.insert(s.canonical_form().0))
+            .filter_map(|s| Self::find(s, max_plies))
+            .collect()
+    }
+
+    /// The number of plies until the win.
+    pub fn plies(&self) -> usize { self.solution.len() }
+
+    /// Tests whether the given move solves the puzzle, i.e. whether
+    /// it is the solution's first move or another move forcing
+    /// a win within the same number of plies.
+    pub fn accepts(&self, game_move: &Move) -> bool {
+        if self.solution.first() == Some(game_move) {
+            return true;
+        }
+        let attacker = self.state.current_player_color;
+        match self.state.apply_move(game_move).map(|next| (decided(&next, attacker), next)) {
+            Ok((Some(won), _)) => won,
+            Ok((None, next)) => self.plies() >= 3 && losing_replies(&next, attacker, self.plies() as u32 - 1).is_some(),
+            Err(_) => false
+        }
+    }
+}
+
+impl fmt::Display for Puzzle {
+    /// Formats the puzzle as the position notation followed by
+    /// the solution's moves, separated by semicolons.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.state.position_notation())?;
+        for game_move in &self.solution {
+            write!(f, "; {}", game_move)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Puzzle {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        let mut parts = raw.split(';').map(str::trim);
+        let state = GameState::from_position_notation(parts.next().unwrap_or_default())?;
+        let mut current = state.clone();
+        let mut solution = Vec::new();
+        for formatted in parts {
+            let game_move = current.possible_moves(current.current_player_color).into_iter()
+                .find(|m| m.to_string() == formatted)
+                .ok_or_else(|| format!("{} is not a legal move in the puzzle {}", formatted, raw))?;
+            current = current.apply_move(&game_move)?;
+            solution.push(game_move);
+        }
+        Ok(Self { state, solution })
+    }
+}
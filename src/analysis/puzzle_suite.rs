@@ -0,0 +1,43 @@
+use std::time::{Duration, Instant};
+use crate::eval::HeuristicEvaluator;
+use crate::game::Move;
+use crate::search::AlphaBetaSearch;
+use crate::util::{CancellationToken, SCResult};
+use super::Puzzle;
+
+/// The outcome of letting the engine solve a puzzle.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PuzzleAttempt {
+    /// The move chosen by the engine.
+    pub chosen: Option<Move>,
+    /// Whether the chosen move solves the puzzle.
+    pub solved: bool,
+    /// The number of nodes searched.
+    pub nodes: u64,
+    pub elapsed: Duration
+}
+
+/// Parses a suite of puzzles, one per line. Empty
+/// lines and lines starting with `#` are skipped.
+pub fn parse_puzzle_suite(raw: &str) -> SCResult<Vec<Puzzle>> {
+    raw.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .map(str::parse)
+        .collect()
+}
+
+/// Lets the alpha-beta search solve the puzzle within the
+/// given node and time budget, searching one ply deeper
+/// than the solution.
+pub fn attempt_puzzle(puzzle: &Puzzle, node_limit: u64, time_limit: Duration) -> PuzzleAttempt {
+    let start = Instant::now();
+    let mut search = AlphaBetaSearch::new(HeuristicEvaluator::default()).with_node_limit(node_limit);
+    let result = search.search(&puzzle.state, puzzle.plies() as u32 + 1, Some(start + time_limit), &CancellationToken::new());
+    PuzzleAttempt {
+        solved: result.best_move.as_ref().map(|m| puzzle.accepts(m)).unwrap_or(false),
+        chosen: result.best_move,
+        nodes: result.stats.nodes,
+        elapsed: start.elapsed()
+    }
+}
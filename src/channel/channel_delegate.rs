@@ -0,0 +1,106 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::Duration;
+use log::warn;
+use crate::client::SCClientDelegate;
+use crate::game::HivePlugin;
+use crate::protocol::{GamePlugin, GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use crate::util::CancellationToken;
+use super::{DelegateEvent, MoveResponder};
+
+/// The interval in which a pending move request
+/// checks whether it has been cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// A delegate that sends the client's callbacks as events
+/// to a channel. Move requests block the client until the
+/// receiving side answers them via the event's responder, but
+/// at most until the request is cancelled (e.g. at the soft
+/// timeout), after which the delegate returns a fallback move.
+pub struct ChannelDelegate<P = HivePlugin> where P: GamePlugin {
+    sender: Sender<DelegateEvent<P>>,
+    last_annotation: Option<MoveAnnotation<P::Move>>
+}
+
+impl<P> ChannelDelegate<P> where P: GamePlugin {
+    /// Creates a delegate together with the
+    /// receiving end of its events.
+    pub fn new() -> (Self, Receiver<DelegateEvent<P>>) {
+        let (sender, receiver) = mpsc::channel();
+        (Self { sender, last_annotation: None }, receiver)
+    }
+
+    fn send(&self, event: DelegateEvent<P>) {
+        // The receiving side may have lost interest in the events
+        let _ = self.sender.send(event);
+    }
+
+    /// Picks the first legal move for a request that was not answered.
+    fn fallback_move(state: &P::GameState, my_color: P::PlayerColor, reason: &str) -> P::Move {
+        warn!("{}, sending the first legal move instead", reason);
+        // Without any legal move, the client reports the (caught) panic
+        P::fallback_move(state, my_color).expect("No legal move available")
+    }
+}
+
+impl<P> SCClientDelegate<P> for ChannelDelegate<P> where P: GamePlugin {
+    fn on_update_state(&mut self, state: &P::GameState, my_color: Option<P::PlayerColor>) {
+        self.send(DelegateEvent::StateUpdated { state: state.clone(), my_color });
+    }
+
+    fn on_game_end(&mut self, result: GameResult<P>, my_color: Option<P::PlayerColor>) {
+        self.send(DelegateEvent::GameEnded { result, my_color });
+    }
+
+    fn on_welcome_message(&mut self, color: &P::PlayerColor) {
+        self.send(DelegateEvent::WelcomeMessage { color: *color });
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.send(DelegateEvent::TimeSettings(*settings));
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.send(DelegateEvent::Idle(duration));
+    }
+
+    fn on_game_paused(&mut self) {
+        self.send(DelegateEvent::GamePaused);
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.send(DelegateEvent::GameResumed);
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.send(DelegateEvent::RoundTrip(stats.clone()));
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<P::Move>) {
+        self.send(DelegateEvent::Opponent { name: name.to_owned(), stats: stats.clone() });
+    }
+
+    fn on_move_observed(&mut self, game_move: &P::Move) {
+        self.send(DelegateEvent::MoveObserved(game_move.clone()));
+    }
+
+    fn request_move(&mut self, state: &P::GameState, my_color: P::PlayerColor, cancel: &CancellationToken) -> P::Move {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        self.last_annotation = None;
+        self.send(DelegateEvent::MoveRequested { state: state.clone(), my_color, cancel: cancel.clone(), responder: MoveResponder::new(sender) });
+        loop {
+            match receiver.recv_timeout(CANCEL_POLL_INTERVAL) {
+                Ok((game_move, annotation)) => {
+                    self.last_annotation = annotation;
+                    return game_move;
+                },
+                Err(RecvTimeoutError::Timeout) if !cancel.is_cancelled() => {},
+                Err(RecvTimeoutError::Timeout) => return Self::fallback_move(state, my_color, "No move was sent back through the channel before the request was cancelled"),
+                Err(RecvTimeoutError::Disconnected) => return Self::fallback_move(state, my_color, "The move request was dropped without a response")
+            }
+        }
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<P::Move>> {
+        self.last_annotation.clone()
+    }
+}
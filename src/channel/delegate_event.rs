@@ -0,0 +1,34 @@
+use std::time::Duration;
+use crate::game::HivePlugin;
+use crate::protocol::{GamePlugin, GameResult, OpponentStats, RttStats, TimeSettings};
+use crate::util::CancellationToken;
+use super::MoveResponder;
+
+/// A callback of the client, as delivered
+/// by a `ChannelDelegate`.
+#[derive(Debug)]
+pub enum DelegateEvent<P = HivePlugin> where P: GamePlugin {
+    /// The game state has been updated.
+    StateUpdated { state: P::GameState, my_color: Option<P::PlayerColor> },
+    /// The game has ended.
+    GameEnded { result: GameResult<P>, my_color: Option<P::PlayerColor> },
+    /// The server assigned the own color.
+    WelcomeMessage { color: P::PlayerColor },
+    /// The time limits the server enforces.
+    TimeSettings(TimeSettings),
+    /// No message has arrived from the server for the given duration.
+    Idle(Duration),
+    /// The server paused the game.
+    GamePaused,
+    /// The server resumed the game.
+    GameResumed,
+    /// A round trip to the server has been measured.
+    RoundTrip(RttStats),
+    /// The opponent is known, with what is remembered about it.
+    Opponent { name: String, stats: OpponentStats<P::Move> },
+    /// The server relayed a move.
+    MoveObserved(P::Move),
+    /// The server requests a move, which has to be answered
+    /// using the responder before the token is cancelled.
+    MoveRequested { state: P::GameState, my_color: P::PlayerColor, cancel: CancellationToken, responder: MoveResponder<P> }
+}
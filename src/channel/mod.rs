@@ -0,0 +1,12 @@
+//! A delegate that forwards the client's callbacks over a
+//! channel, which lets the game logic live on a different
+//! thread than the client (e.g. a GUI thread or an inference
+//! worker).
+
+mod channel_delegate;
+mod delegate_event;
+mod move_responder;
+
+pub use channel_delegate::*;
+pub use delegate_event::*;
+pub use move_responder::*;
@@ -0,0 +1,32 @@
+use std::sync::mpsc::SyncSender;
+use crate::game::HivePlugin;
+use crate::protocol::{GamePlugin, MoveAnnotation};
+
+/// A move sent back through the channel, optionally annotated.
+pub(super) type MoveResponse<M> = (M, Option<MoveAnnotation<M>>);
+
+/// Answers a single move request, which is sent
+/// back to the client's thread.
+#[derive(Debug)]
+pub struct MoveResponder<P = HivePlugin> where P: GamePlugin {
+    sender: SyncSender<MoveResponse<P::Move>>
+}
+
+impl<P> MoveResponder<P> where P: GamePlugin {
+    pub(super) fn new(sender: SyncSender<MoveResponse<P::Move>>) -> Self {
+        Self { sender }
+    }
+
+    /// Sends the chosen move. Returns false if the
+    /// client no longer waits for it (e.g. after the
+    /// request has been cancelled).
+    pub fn respond(self, game_move: P::Move) -> bool {
+        self.sender.send((game_move, None)).is_ok()
+    }
+
+    /// Sends the chosen move together with a
+    /// description of how it was chosen.
+    pub fn respond_with_annotation(self, game_move: P::Move, annotation: MoveAnnotation<P::Move>) -> bool {
+        self.sender.send((game_move, Some(annotation))).is_ok()
+    }
+}
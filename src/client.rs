@@ -1,32 +1,115 @@
+use std::collections::HashMap;
 use std::convert::TryFrom;
-use std::net::TcpStream;
+use std::marker::PhantomData;
+use std::net::{Shutdown as SocketShutdown, TcpStream};
 use std::io::{self, BufWriter, BufReader, Read, Write};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
 use log::{info, debug, warn, error};
 use xml::reader::{XmlEvent as XmlReadEvent, EventReader};
-use xml::writer::EmitterConfig;
-use crate::game::{GameState, PlayerColor, Move};
-use crate::util::{SCResult, XmlNode, FromXmlNode};
-use crate::protocol::{Joined, Left, Room, Data, GameResult};
-
-const GAME_TYPE: &str = "swc_2020_hive";
+use xml::writer::EventWriter;
+use crate::game::HivePlugin;
+use crate::net::{self, Proxy};
+use crate::util::{CancellationToken, SCError, SCResult, XmlFormat, XmlNode, FromXmlNode};
+use crate::protocol::{Join, Joined, Left, Room, Data, Memento, WelcomeMessage, GamePlugin, GameOutcome, GameResult, IllegalTransition, MoveAnnotation, MovePreflight, MoveRecord, MoveSequence, ParseMode, ProtocolEvent, Session, SessionEnd, TimeSettings, TurnClock, RttStats, OpponentStats, ChaosConfig, ChaosStream, Direction, Middleware, MiddlewareAction, log_wire};
 
 /// A handler that implements the game player's
 /// behavior, usually employing some custom move
 /// selection strategy.
-pub trait SCClientDelegate {
-    /// Invoked whenever the game state updates.
-    fn on_update_state(&mut self, _state: &GameState) {}
+pub trait SCClientDelegate<P = HivePlugin> where P: GamePlugin {
+    /// Invoked whenever the game state updates, with the
+    /// own color if the welcome message has been received.
+    fn on_update_state(&mut self, _state: &P::GameState, _my_color: Option<P::PlayerColor>) {}
     
-    /// Invoked when the game ends.
-    fn on_game_end(&mut self, _result: GameResult) {}
+    /// Invoked when the game ends, with the own color if
+    /// the welcome message has been received.
+    fn on_game_end(&mut self, _result: GameResult<P>, _my_color: Option<P::PlayerColor>) {}
     
     /// Invoked when the welcome message is received
     /// with the player's color.
-    fn on_welcome_message(&mut self, _color: &PlayerColor) {}
+    fn on_welcome_message(&mut self, _color: &P::PlayerColor) {}
+    
+    /// Invoked once before the game starts with the time
+    /// limits the server is expected to enforce.
+    fn on_time_settings(&mut self, _settings: &TimeSettings) {}
+    
+    /// Invoked periodically while no message has arrived
+    /// from the server, with the time since the last one.
+    fn on_idle(&mut self, duration: Duration) {
+        warn!("No message from the server for {:.1}s", duration.as_secs_f64());
+    }
+    
+    /// Invoked when the server pauses the game. No moves
+    /// are requested until the game is resumed.
+    fn on_game_paused(&mut self) {}
+    
+    /// Invoked when the server resumes a paused game.
+    fn on_game_resumed(&mut self) {}
+    
+    /// Invoked whenever a round trip to the server has been
+    /// measured, i.e. when the game state following an own
+    /// move arrives, e.g. to adapt the time management.
+    fn on_round_trip(&mut self, _stats: &RttStats) {}
+    
+    /// Invoked at the start of a game once the opponent's name is
+    /// known, with what is remembered about it from previous games
+    /// (only if an opponent memory is used), e.g. to adapt the strategy.
+    fn on_opponent(&mut self, _name: &str, _stats: &OpponentStats<P::Move>) {}
+    
+    /// Invoked when the server relays a move (e.g. to observers),
+    /// which precedes the game state resulting from it.
+    fn on_move_observed(&mut self, _game_move: &P::Move) {}
     
     /// Requests a move from the delegate. This method
-    /// should implement the "main" game logic.
-    fn request_move(&mut self, state: &GameState, my_color: PlayerColor) -> Move;
+    /// should implement the "main" game logic. Long computations
+    /// should stop once the token is cancelled, which happens when
+    /// the soft timeout passes or the client shuts down.
+    fn request_move(&mut self, state: &P::GameState, my_color: P::PlayerColor, cancel: &CancellationToken) -> P::Move;
+
+    /// Describes how the move last returned from `request_move`
+    /// was chosen, e.g. for annotated replays.
+    fn move_annotation(&self) -> Option<MoveAnnotation<P::Move>> { None }
+}
+
+impl<D, P> SCClientDelegate<P> for Box<D> where D: SCClientDelegate<P> + ?Sized, P: GamePlugin {
+    fn on_update_state(&mut self, state: &P::GameState, my_color: Option<P::PlayerColor>) { (**self).on_update_state(state, my_color) }
+
+    fn on_game_end(&mut self, result: GameResult<P>, my_color: Option<P::PlayerColor>) { (**self).on_game_end(result, my_color) }
+
+    fn on_welcome_message(&mut self, color: &P::PlayerColor) { (**self).on_welcome_message(color) }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) { (**self).on_time_settings(settings) }
+
+    fn on_idle(&mut self, duration: Duration) { (**self).on_idle(duration) }
+
+    fn on_game_paused(&mut self) { (**self).on_game_paused() }
+
+    fn on_game_resumed(&mut self) { (**self).on_game_resumed() }
+
+    fn on_round_trip(&mut self, stats: &RttStats) { (**self).on_round_trip(stats) }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<P::Move>) { (**self).on_opponent(name, stats) }
+
+    fn on_move_observed(&mut self, game_move: &P::Move) { (**self).on_move_observed(game_move) }
+
+    fn request_move(&mut self, state: &P::GameState, my_color: P::PlayerColor, cancel: &CancellationToken) -> P::Move { (**self).request_move(state, my_color, cancel) }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<P::Move>> { (**self).move_annotation() }
+}
+
+/// Runs a delegate callback, turning panics into errors
+/// so that they don't tear down the connection.
+fn guarded<T>(callback_name: &str, callback: impl FnOnce() -> T) -> SCResult<T> {
+    panic::catch_unwind(AssertUnwindSafe(callback)).map_err(|payload| {
+        let message = payload.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| payload.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown cause".to_owned());
+        format!("Delegate panicked in {}: {}", callback_name, message).into()
+    })
 }
 
 /// A configuration that determines whether
@@ -37,37 +120,192 @@ pub struct DebugMode {
     pub debug_writer: bool,
 }
 
+/// Signals a running client to end its session, which
+/// also closes the connection to unblock pending reads
+/// and cancels the pending move request.
+#[derive(Debug, Default)]
+struct ShutdownSignal {
+    requested: AtomicBool,
+    stream: Mutex<Option<TcpStream>>,
+    pending_move: Mutex<Option<CancellationToken>>
+}
+
+impl ShutdownSignal {
+    fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+        if let Some(stream) = self.stream.lock().unwrap().take() {
+            let _ = stream.shutdown(SocketShutdown::Both);
+        }
+        if let Some(cancel) = self.pending_move.lock().unwrap().take() {
+            cancel.cancel();
+        }
+    }
+
+    /// Creates the token for a new move request, which
+    /// is cancelled right away if a shutdown is pending.
+    fn begin_move(&self) -> CancellationToken {
+        let cancel = CancellationToken::new();
+        let mut pending_move = self.pending_move.lock().unwrap();
+        if self.is_requested() {
+            cancel.cancel();
+        }
+        *pending_move = Some(cancel.clone());
+        cancel
+    }
+
+    fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+
+    /// Remembers the connection to close on shutdown.
+    fn attach(&self, stream: &TcpStream) {
+        *self.stream.lock().unwrap() = stream.try_clone().ok();
+    }
+}
+
+/// Cancels a move request once it exceeds the given time,
+/// unless the watchdog is dropped before.
+struct Watchdog {
+    _stop: Sender<()>
+}
+
+impl Watchdog {
+    fn start(cancel: CancellationToken, timeout: Duration) -> Self {
+        let (stop, stopped) = mpsc::channel::<()>();
+        thread::spawn(move || {
+            if let Err(RecvTimeoutError::Timeout) = stopped.recv_timeout(timeout) {
+                warn!("The move request exceeded the soft timeout, cancelling it");
+                cancel.cancel();
+            }
+        });
+        Self { _stop: stop }
+    }
+}
+
+/// The interval in which a waiting client checks
+/// whether it has been asked to shut down.
+const SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 /// The client which handles XML requests, manages
-/// the game state and invokes the delegate.
-pub struct SCClient<D> where D: SCClientDelegate {
+/// the game state and invokes the delegate. The
+/// game-specific parts are provided by the plugin.
+pub struct SCClient<D, P = HivePlugin> where D: SCClientDelegate<P>, P: GamePlugin {
     delegate: D,
     debug_mode: DebugMode,
-    game_state: Option<GameState>,
+    game_state: Option<P::GameState>,
+    my_color: Option<P::PlayerColor>,
+    idle_timeout: Option<Duration>,
+    time_settings: TimeSettings,
+    clock: TurnClock,
+    parse_mode: ParseMode,
+    xml_format: XmlFormat,
+    preflight: MovePreflight,
+    paused: bool,
+    middlewares: Vec<Middleware>,
+    chaos: ChaosConfig,
+    proxy: Option<Proxy>,
+    events: Option<Sender<ProtocolEvent>>,
+    shutdown: Arc<ShutdownSignal>,
+    last_move: Option<(P::GameState, P::PlayerColor, P::Move)>,
+    history: Vec<MoveRecord<P>>,
+    session: Session<P>,
+    plugin: PhantomData<P>,
 }
 
-impl<D> SCClient<D> where D: SCClientDelegate {
+/// The default period of silence after which the
+/// delegate is notified via `on_idle`.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(30);
+
+impl<D, P> SCClient<D, P> where D: SCClientDelegate<P>, P: GamePlugin {
     /// Creates a new client using the specified delegate.
     pub fn new(delegate: D, debug_mode: DebugMode) -> Self {
-        Self { delegate, debug_mode, game_state: None }
+        Self { delegate, debug_mode, game_state: None, my_color: None, idle_timeout: Some(DEFAULT_IDLE_TIMEOUT), time_settings: TimeSettings::default(), clock: TurnClock::default(), parse_mode: ParseMode::default(), xml_format: XmlFormat::compact(), preflight: MovePreflight::default(), paused: false, middlewares: vec![Box::new(log_wire)], chaos: ChaosConfig::default(), proxy: None, events: None, shutdown: Arc::default(), last_move: None, history: Vec::new(), session: Session::new(), plugin: PhantomData }
+    }
+    
+    /// Sets the period of silence from the server after which
+    /// the delegate is notified (`None` disables notifications).
+    pub fn with_idle_timeout(self, idle_timeout: Option<Duration>) -> Self {
+        Self { idle_timeout, ..self }
+    }
+    
+    /// Sets the time limits passed to the delegate,
+    /// which should match the server's configuration.
+    pub fn with_time_settings(self, time_settings: TimeSettings) -> Self {
+        Self { time_settings, ..self }
+    }
+    
+    /// Sets how messages that the client does not understand are handled.
+    pub fn with_parse_mode(self, parse_mode: ParseMode) -> Self {
+        Self { parse_mode, ..self }
+    }
+    
+    /// Sets the layout of the XML sent to the server.
+    pub fn with_xml_format(self, xml_format: XmlFormat) -> Self {
+        Self { xml_format, ..self }
+    }
+    
+    /// Sets how moves of the delegate that violate the local rules are handled.
+    pub fn with_preflight(self, preflight: MovePreflight) -> Self {
+        Self { preflight, ..self }
+    }
+    
+    /// Appends a middleware to the chain intercepting the messages
+    /// exchanged with the server (after the default wire logging).
+    pub fn with_middleware(mut self, middleware: impl FnMut(&mut XmlNode, Direction) -> MiddlewareAction + Send + 'static) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+    
+    /// Tunnels the connection to the server through the given proxy.
+    pub fn with_proxy(self, proxy: Option<Proxy>) -> Self {
+        Self { proxy, ..self }
+    }
+    
+    /// Sends the protocol events (e.g. joining a room) to the given channel.
+    pub fn with_events(self, events: Sender<ProtocolEvent>) -> Self {
+        Self { events: Some(events), ..self }
+    }
+    
+    /// Injects faults into the connection, for testing.
+    pub fn with_chaos(self, chaos: ChaosConfig) -> Self {
+        Self { chaos, ..self }
     }
     
+    /// Fetches the clock measuring the time spent on moves.
+    pub fn clock(&self) -> &TurnClock { &self.clock }
+    
+    /// Fetches the own color, once the welcome message has been received.
+    pub fn my_color(&self) -> Option<P::PlayerColor> { self.my_color }
+    
+    /// Fetches the protocol-level state of the (last) session.
+    pub fn session(&self) -> &Session<P> { &self.session }
+    
+    /// Fetches the moves sent in the (last) game along with the
+    /// delegate's annotations, which are never sent to the server.
+    pub fn move_history(&self) -> &[MoveRecord<P>] { &self.history }
+    
+    /// Tests whether the server has paused the game.
+    pub fn is_paused(&self) -> bool { self.paused }
+    
     /// Blocks the thread and begins reading XML messages
-    /// from the provided address via TCP.
-    pub fn run(self, host: &str, port: u16, reservation: Option<&str>) -> SCResult<()> {
-        let address = format!("{}:{}", host, port);
-        let stream = TcpStream::connect(&address)?;
-        info!("Connected to {}", address);
+    /// from the provided address via TCP until the server
+    /// closes the connection.
+    pub fn run(&mut self, host: &str, port: u16, reservation: Option<&str>) -> SCResult<GameOutcome<P>> {
+        let stream = match &self.proxy {
+            Some(proxy) => proxy.connect(host, port)?,
+            None => net::connect(host, port)?
+        };
+        self.shutdown.attach(&stream);
         
         {
             let mut writer = BufWriter::new(&stream);
-            writer.write("<protocol>".as_bytes())?;
+            writer.write_all("<protocol>".as_bytes())?;
             
-            let join_xml = match reservation {
-                Some(res) => format!("<joinPrepared reservationCode=\"{}\" />", res),
-                None => format!("<join gameType=\"{}\" />", GAME_TYPE)
-            };
-            info!("Sending join message {}", join_xml);
-            writer.write(join_xml.as_bytes())?;
+            let join_node = XmlNode::from(Join::new(P::GAME_TYPE, reservation));
+            info!("Sending join message {}", join_node);
+            if let Some(join_node) = self.intercept(join_node, Direction::Outgoing) {
+                join_node.write_to(&mut self.xml_format.create_writer(&mut writer))?;
+            }
         }
         
         // Begin parsing game messages from the stream.
@@ -76,107 +314,460 @@ impl<D> SCClient<D> where D: SCClientDelegate {
         // of `run_game`.
 
         let mode = &self.debug_mode;
-        if mode.debug_reader && !mode.debug_writer {
-            self.run_game(io::stdin(), BufWriter::new(stream))?;
+        let outcome = if mode.debug_reader && !mode.debug_writer {
+            self.run_game(io::stdin(), BufWriter::new(stream))
         } else if !mode.debug_reader && mode.debug_writer {
-            self.run_game(BufReader::new(stream), io::stdout())?;
+            self.run_game(BufReader::new(stream), io::stdout())
         } else if mode.debug_reader && mode.debug_writer {
-            self.run_game(io::stdin(), io::stdout())?;
+            self.run_game(io::stdin(), io::stdout())
         } else {
             let reader = BufReader::new(stream.try_clone()?);
             let writer = BufWriter::new(stream);
-            self.run_game(reader, writer)?;
-        }
+            self.run_game(reader, writer)
+        };
         
-        Ok(())
+        match outcome {
+            // Closing the connection may interrupt reads or writes
+            Err(e) if self.shutdown.is_requested() => {
+                debug!("Ignoring error after shutdown: {}", e);
+                let end = self.session.shut_down();
+                Ok(self.outcome(end))
+            },
+            outcome => outcome
+        }
     }
     
-    /// Blocks the thread and parses/handles game messages
-    /// from the provided reader.
-    fn run_game<R, W>(mut self, reader: R, writer: W) -> SCResult<()> where R: Read, W: Write {
-        let mut xml_reader = EventReader::new(reader);
-
-        let mut emitter_config = EmitterConfig::new();
-        emitter_config.write_document_declaration = false;
+    /// Parses game messages from the provided reader on the
+    /// current thread and sends them to the given channel.
+    fn read_messages<R>(reader: R, sender: Sender<SCResult<XmlNode>>, chaos: ChaosConfig) where R: Read {
+        let mut xml_reader = EventReader::new(ChaosStream::new(reader, &chaos));
 
-        let mut xml_writer = emitter_config.create_writer(writer);
-        
         // Read initial protocol element
         info!("Waiting for initial <protocol>...");
-        while match xml_reader.next() {
-            Ok(XmlReadEvent::StartElement { name, .. }) => Some(name),
+        loop {
+            match xml_reader.next() {
+                Ok(XmlReadEvent::StartElement { name, .. }) if name.local_name == "protocol" => break,
+                Ok(XmlReadEvent::EndDocument) => {
+                    let _ = sender.send(Err(SCError::Disconnected));
+                    return;
+                },
+                Err(e) => {
+                    let _ = sender.send(Err(e.into()));
+                    return;
+                },
+                _ => {}
+            }
+        }
+
+        for index in 0.. {
+            let node = XmlNode::read_from(&mut xml_reader);
+            thread::sleep(chaos.latency);
+            if chaos.duplicates(index) {
+                if let Ok(node) = &node {
+                    let _ = sender.send(Ok(node.clone()));
+                }
+            }
+            let failed = node.is_err();
+            if sender.send(node).is_err() || failed {
+                break;
+            }
+        }
+    }
+
+    /// Waits for the next message from the server, notifying the
+    /// delegate while none arrives. Returns `None` once the client
+    /// has been asked to shut down.
+    fn next_message(&mut self, receiver: &Receiver<SCResult<XmlNode>>, last_message: Instant) -> SCResult<Option<XmlNode>> {
+        // Timeouts too large to represent never elapse
+        let mut next_idle = self.idle_timeout.and_then(|t| last_message.checked_add(t));
+        loop {
+            if self.shutdown.is_requested() {
+                return Ok(None);
+            }
+            let wait = next_idle.map(|t| t.saturating_duration_since(Instant::now())).unwrap_or(SHUTDOWN_POLL_INTERVAL).min(SHUTDOWN_POLL_INTERVAL);
+            match receiver.recv_timeout(wait) {
+                Ok(_) if self.shutdown.is_requested() => return Ok(None),
+                Ok(node) => return node.map(Some),
+                Err(RecvTimeoutError::Timeout) => match (next_idle, self.idle_timeout) {
+                    (Some(t), Some(timeout)) if Instant::now() >= t => {
+                        next_idle = t.checked_add(timeout);
+                        if self.paused {
+                            debug!("Waiting for the game to be resumed...");
+                        } else {
+                            self.notify("on_idle", |d| d.on_idle(last_message.elapsed()));
+                        }
+                    },
+                    _ => {}
+                },
+                Err(RecvTimeoutError::Disconnected) => return Err(SCError::Disconnected)
+            }
+        }
+    }
+    
+    /// Advances the session by the given room message, re-checking
+    /// the own last move if the game was lost by a rule violation.
+    fn advance_session(&mut self, room: &Room<P>) -> Result<(), IllegalTransition> {
+        match &room.data {
+            Data::GameResult(result) => {
+                if let SessionEnd::LostByViolation { reason } = self.session.finish(&room.room_id, result.clone(), self.my_color)? {
+                    self.diagnose_violation(reason.as_deref());
+                }
+            },
+            Data::Error { message } => self.session.fail(message.clone()),
+            _ => self.session.play(&room.room_id)?
+        }
+        Ok(())
+    }
+
+    /// Summarizes the session once it ended.
+    fn outcome(&self, end: SessionEnd) -> GameOutcome<P> {
+        GameOutcome {
+            result: self.session.result().cloned(),
+            color: self.my_color,
+            turns: self.game_state.as_ref().map(P::turn).unwrap_or(0),
+            end
+        }
+    }
+    
+    /// Fetches the turn of the given state if the own player is to move
+    /// in it (assuming so as long as the own color is unknown).
+    fn own_turn(&self, state: &P::GameState) -> Option<u32> {
+        Some(P::turn(state)).filter(|_| self.my_color.is_none_or(|c| c == P::current_player_color(state)))
+    }
+
+    /// Validates the delegate's move before sending it, since the
+    /// server would end the game on a rule violation anyway.
+    fn preflight(&self, state: &P::GameState, color: P::PlayerColor, game_move: P::Move) -> P::Move {
+        let violation = match P::validate_move(state, color, &game_move) {
+            Ok(()) => return game_move,
+            Err(e) => e
+        };
+        error!("Move {:?} by {:?} @ turn {} violates the rules: {}", game_move, color, P::turn(state), violation);
+        match (self.preflight, P::fallback_move(state, color)) {
+            (MovePreflight::Substitute, Some(fallback)) => {
+                warn!("Sending the first legal move {:?} instead", fallback);
+                fallback
+            },
+            (MovePreflight::Substitute, None) => {
+                warn!("No legal move available, sending the move anyway");
+                game_move
+            },
+            (MovePreflight::Log, _) => game_move
+        }
+    }
+
+    /// Re-checks the own last move after the server reported that it
+    /// violated the rules, logging whether our validator agrees.
+    fn diagnose_violation(&self, reason: Option<&str>) {
+        let server_verdict = reason.unwrap_or("no reason given");
+        match &self.last_move {
+            Some((state, color, game_move)) => match P::validate_move(state, *color, game_move) {
+                Ok(()) => error!("The server rejected our move {:?} ({}), although the local validator accepts it", game_move, server_verdict),
+                Err(e) => warn!("Our move {:?} violated the rules. Server: {}, local validator: {}", game_move, server_verdict, e)
+            },
+            None => warn!("The server reports a rule violation by us ({}), but we did not send any move", server_verdict)
+        }
+    }
+
+    /// Sends a protocol event to the listener, if any.
+    fn emit(&self, event: ProtocolEvent) {
+        if let Some(events) = &self.events {
+            let _ = events.send(event);
+        }
+    }
+    
+    /// Passes a message through the middlewares, returning
+    /// it unless one of them dropped it.
+    fn intercept(&mut self, mut node: XmlNode, direction: Direction) -> Option<XmlNode> {
+        let mut apply = |middleware: &mut Middleware| match middleware(&mut node, direction) {
+            MiddlewareAction::Continue => true,
+            MiddlewareAction::Delay(duration) => {
+                thread::sleep(duration);
+                true
+            },
+            MiddlewareAction::Drop => {
+                debug!("Dropped {:?} message by middleware", direction);
+                false
+            }
+        };
+        let passed = match direction {
+            Direction::Incoming => self.middlewares.iter_mut().all(&mut apply),
+            Direction::Outgoing => self.middlewares.iter_mut().rev().all(&mut apply)
+        };
+        if passed { Some(node) } else { None }
+    }
+    
+    /// Fails with the given error in strict mode
+    /// and only logs it in lenient mode.
+    fn tolerate(&self, error: SCError) -> SCResult<()> {
+        match self.parse_mode {
+            ParseMode::Strict => Err(error),
+            ParseMode::Lenient => {
+                warn!("{}", error);
+                Ok(())
+            }
+        }
+    }
+    
+    /// Invokes a delegate callback, logging panics.
+    fn notify(&mut self, callback_name: &str, callback: impl FnOnce(&mut D)) {
+        let delegate = &mut self.delegate;
+        if let Err(e) = guarded(callback_name, || callback(delegate)) {
+            error!("{}", e);
+        }
+    }
+    
+    /// Requests a move from the delegate and sends it to the given room.
+    fn answer_move_request<W>(&mut self, room_id: String, state: &P::GameState, xml_writer: &mut EventWriter<W>) -> SCResult<()> where W: Write {
+        let turn = P::turn(state);
+        let current_color = P::current_player_color(state);
+        let color = self.my_color.unwrap_or(current_color);
+        info!("Got move request @ turn: {}, color: {:?}", turn, color);
+        if color != current_color {
+            warn!("Got move request for {:?}, but the last state expects a move by {:?}", color, current_color);
+        }
+        self.clock.start_turn();
+
+        let cancel = self.shutdown.begin_move();
+        let watchdog = Watchdog::start(cancel.clone(), self.time_settings.soft_timeout);
+        let delegate = &mut self.delegate;
+        let new_move = guarded("request_move", || delegate.request_move(state, color, &cancel));
+        drop(watchdog);
+        let (new_move, chosen_move) = match new_move {
+            Ok(new_move) => (new_move.clone(), Some(new_move)),
+            Err(e) => {
+                error!("{}, sending a fallback move instead", e);
+                (P::fallback_move(state, color).ok_or("No fallback move available")?, None)
+            }
+        };
+        let new_move = self.preflight(state, color, new_move);
+        let delegate = &self.delegate;
+        let annotation = match chosen_move {
+            Some(chosen_move) if chosen_move == new_move => guarded("move_annotation", || delegate.move_annotation()).unwrap_or_else(|e| {
+                warn!("{}", e);
+                None
+            }),
             _ => None
-        }.filter(|n| n.local_name == "protocol").is_none() {}
+        };
+        self.history.push(MoveRecord { turn, color, game_move: new_move.clone(), annotation });
+        self.last_move = Some((state.clone(), color, new_move.clone()));
+        let move_node = XmlNode::try_from(Room::<P> {
+            room_id,
+            data: Data::Move(new_move)
+        })?;
+
+        if let Some(move_node) = self.intercept(move_node, Direction::Outgoing) {
+            move_node.write_to(xml_writer)?;
+            xml_writer.inner_mut().flush()?;
+        }
+
+        let elapsed = self.clock.end_turn();
+        if elapsed > self.time_settings.soft_timeout {
+            warn!("Sending the move took {:.3}s, exceeding the soft timeout", elapsed.as_secs_f64());
+        } else {
+            debug!("Sent move after {:.3}s", elapsed.as_secs_f64());
+        }
+        Ok(())
+    }
+    
+    /// Blocks the thread and parses/handles game messages
+    /// from the provided reader.
+    fn run_game<R, W>(&mut self, reader: R, writer: W) -> SCResult<GameOutcome<P>> where R: Read + Send + 'static, W: Write {
+        let (sender, receiver) = mpsc::channel();
+        let chaos = self.chaos;
+        if chaos.is_enabled() {
+            warn!("Injecting faults into the connection: {:?}", chaos);
+        }
+        thread::spawn(move || Self::read_messages(reader, sender, chaos));
+        let time_settings = self.time_settings;
+        self.notify("on_time_settings", |d| d.on_time_settings(&time_settings));
+
+        let mut xml_writer = self.xml_format.create_writer(ChaosStream::new(writer, &self.chaos));
+        let mut last_message = Instant::now();
+        self.session = Session::new();
+        self.history.clear();
+        let mut sequences: HashMap<String, MoveSequence> = HashMap::new();
 
         loop {
-            let node = XmlNode::read_from(&mut xml_reader)?;
-            debug!("Got XML node {}", node);
+            let node = match self.next_message(&receiver, last_message)? {
+                Some(node) => node,
+                None => {
+                    info!("Shutting down the session...");
+                    let end = self.session.shut_down();
+                    return Ok(self.outcome(end));
+                }
+            };
+            last_message = Instant::now();
+            let node = match self.intercept(node, Direction::Incoming) {
+                Some(node) => node,
+                None => continue
+            };
+            
+            if node.name() == "room" {
+                let unknown = Room::<P>::unknown_attributes(&node);
+                if !unknown.is_empty() {
+                    self.tolerate(format!("Got a message with unknown attributes {}", unknown.join(", ")).into())?;
+                }
+            }
             
             match node.name() {
                 // Try parsing as room message (the game is running)
-                "room" => match Room::from_node(&node) {
-                    Ok(room) => match room.data {
-                        Data::WelcomeMessage { color } => {
-                            info!("Got welcome message with color: {:?}", color);
-                            self.delegate.on_welcome_message(&color);
-                        },
-                        Data::Memento { state } => {
-                            info!("Got updated game state");
-                            self.delegate.on_update_state(&state);
-                            self.game_state = Some(state);
-                        },
-                        Data::MoveRequest => {
-                            if let Some(ref state) = self.game_state {
-                                let turn = state.turn;
-                                let color = state.current_player_color;
-                                info!("Got move request @ turn: {}, color: {:?}", turn, color);
-
-                                let new_move = self.delegate.request_move(state, color);
-                                let move_node = XmlNode::try_from(Room {
-                                    room_id: room.room_id,
-                                    data: Data::Move(new_move)
-                                })?;
-
-                                debug!("Sending move {}", move_node);
-                                move_node.write_to(&mut xml_writer)?;
-                                xml_writer.inner_mut().flush()?;
-                            } else {
-                                error!("Got move request, which cannot be fulfilled since no game state is present!");
-                            }
-                        },
-                        Data::GameResult(result) => {
-                            info!("Got game result: {:?}", result);
-                            self.delegate.on_game_end(result);
-                        },
-                        Data::Error { message } => {
-                            warn!("Got error from server: {}", message);
-                        },
-                        _ => warn!("Could not handle room data: {:?}", room.data)
+                "room" => match Room::<P>::from_node(&node) {
+                    Ok(room) => match self.advance_session(&room) {
+                        Err(e) => self.tolerate(e.into())?,
+                        Ok(()) => match room.data {
+                            Data::WelcomeMessage(WelcomeMessage { color }) => {
+                                info!("Got welcome message with color: {:?}", color);
+                                self.my_color = Some(color);
+                                self.notify("on_welcome_message", |d| d.on_welcome_message(&color));
+                            },
+                            Data::Memento(Memento { state }) => {
+                                info!("Got updated game state");
+                                if let Err(e) = P::validate_state(&state) {
+                                    self.tolerate(format!("Got an inconsistent game state: {}", e).into())?;
+                                }
+                                if let Some(rtt) = self.clock.record_state() {
+                                    debug!("Round trip took {:.3}s", rtt.as_secs_f64());
+                                    let stats = self.clock.rtt().clone();
+                                    self.notify("on_round_trip", |d| d.on_round_trip(&stats));
+                                }
+                                let my_color = self.my_color;
+                                self.notify("on_update_state", |d| d.on_update_state(&state, my_color));
+                                let own_turn = self.own_turn(&state);
+                                if sequences.entry(room.room_id.clone()).or_default().on_state(own_turn) {
+                                    info!("Answering the pending move request from the new state");
+                                    self.answer_move_request(room.room_id, &state, &mut xml_writer)?;
+                                }
+                                self.game_state = Some(state);
+                            },
+                            Data::MoveRequest(_) => {
+                                let state = self.game_state.take().or_else(|| {
+                                    let initial = self.my_color.and_then(P::initial_state);
+                                    warn!("Got move request before any game state, answering it {}", if initial.is_some() { "from the initial state" } else { "once the state arrives" });
+                                    initial
+                                });
+                                let own_turn = state.as_ref().and_then(|s| self.own_turn(s));
+                                let sequence = sequences.entry(room.room_id.clone()).or_default();
+                                let answer = sequence.on_request(own_turn);
+                                if !answer && state.is_some() {
+                                    if sequence.is_pending() {
+                                        warn!("Got move request without a new state to answer it from, answering it once the next state arrives");
+                                    } else {
+                                        info!("Ignoring move request for turn {:?}, which has already been answered", sequence.answered_turn());
+                                    }
+                                }
+                                if let Some(state) = state {
+                                    let answered = if answer { self.answer_move_request(room.room_id, &state, &mut xml_writer) } else { Ok(()) };
+                                    self.game_state = Some(state);
+                                    answered?;
+                                }
+                            },
+                            Data::Paused => {
+                                info!("Game was paused");
+                                self.paused = true;
+                                self.notify("on_game_paused", |d| d.on_game_paused());
+                            },
+                            Data::Resumed => {
+                                info!("Game was resumed");
+                                self.paused = false;
+                                self.notify("on_game_resumed", |d| d.on_game_resumed());
+                            },
+                            Data::GameResult(result) => {
+                                info!("Got game result: {:?}", result);
+                                let my_color = self.my_color;
+                                self.notify("on_game_end", |d| d.on_game_end(result, my_color));
+                            },
+                            Data::Move(game_move) => {
+                                debug!("Got move {:?}", game_move);
+                                self.notify("on_move_observed", |d| d.on_move_observed(&game_move));
+                            },
+                            Data::Observed => info!("Observing the game in room {}", room.room_id),
+                            Data::Error { message } => warn!("Got error from server: {}", message)
+                        }
                     },
-                    Err(e) => error!("Could not parse node as room: {:?}", e)
+                    Err(e) => self.tolerate(format!("Could not parse node as room: {}", e).into())?
                 },
 
                 // Try parsing as 'joined' message
                 "joined" => match Joined::from_node(&node) {
-                    Ok(joined) => info!("Joined room {}", joined.room_id),
-                    Err(e) => error!("Could not parse node as 'joined': {:?}", e)
+                    Ok(joined) => match self.session.join(&joined.room_id) {
+                        Ok(()) => {
+                            info!("Joined room {}", joined.room_id);
+                            self.emit(joined.into());
+                        },
+                        Err(e) => self.tolerate(e.into())?
+                    },
+                    Err(e) => self.tolerate(format!("Could not parse node as 'joined': {}", e).into())?
                 },
 
                 // Try parsing as 'left' message
                 "left" => match Left::from_node(&node) {
-                    Ok(left) => info!("Left room {}", left.room_id),
-                    Err(e) => error!("Could not parse node as 'left': {:?}", e)
+                    Ok(left) => {
+                        info!("Left room {}", left.room_id);
+                        self.emit(left.into());
+                    },
+                    Err(e) => self.tolerate(format!("Could not parse node as 'left': {}", e).into())?
                 },
                 
-                "close" | "sc.protocol.responses.CloseConnection" => {
+                name if ProtocolEvent::CLOSE_NAMES.contains(&name) => {
+                    let event = ProtocolEvent::from_node(&node)?;
                     info!("Closing connection as requested by server...");
-                    break;
+
+                    // Acknowledge by closing our side of the protocol, the
+                    // server may however already have dropped the connection
+                    let writer = xml_writer.inner_mut();
+                    if let Err(e) = writer.write_all("</protocol>".as_bytes()).and_then(|_| writer.flush()) {
+                        debug!("Could not acknowledge closing the connection: {}", e);
+                    }
+
+                    let end = self.session.close(&event);
+                    self.emit(event);
+                    return Ok(self.outcome(end));
                 },
                 
-                _ => warn!("Unrecognized message: <{}>", node.name())
+                _ => self.tolerate(format!("Unrecognized message: <{}>", node.name()).into())?
             }
         }
-        
-        Ok(())
+    }
+}
+
+impl<D, P> SCClient<D, P> where D: SCClientDelegate<P>, P: GamePlugin {
+    /// Runs the client on a background thread, returning
+    /// a handle to wait for or shut down the session and
+    /// to receive its protocol events.
+    pub fn spawn(mut self, host: &str, port: u16, reservation: Option<&str>) -> ClientHandle<P> where Self: Send + 'static, GameOutcome<P>: Send {
+        let (sender, events) = mpsc::channel();
+        self.events = Some(sender);
+        let shutdown = self.shutdown.clone();
+        let (host, reservation) = (host.to_owned(), reservation.map(str::to_owned));
+        let thread = thread::spawn(move || self.run(&host, port, reservation.as_deref()));
+        ClientHandle { thread, shutdown, events }
+    }
+}
+
+/// A handle to a client running on a background thread.
+pub struct ClientHandle<P = HivePlugin> where P: GamePlugin {
+    thread: JoinHandle<SCResult<GameOutcome<P>>>,
+    shutdown: Arc<ShutdownSignal>,
+    events: Receiver<ProtocolEvent>
+}
+
+impl<P> ClientHandle<P> where P: GamePlugin {
+    /// Fetches the receiver of the session's protocol events, which
+    /// disconnects once the session ended.
+    pub fn events(&self) -> &Receiver<ProtocolEvent> { &self.events }
+
+    /// Tests whether the session has ended.
+    pub fn is_finished(&self) -> bool { self.thread.is_finished() }
+
+    /// Asks the client to end the session as soon as possible,
+    /// which closes the connection to the server.
+    pub fn shutdown(&self) {
+        self.shutdown.request();
+    }
+
+    /// Waits for the session to end.
+    pub fn join(self) -> SCResult<GameOutcome<P>> {
+        self.thread.join().unwrap_or_else(|_| Err("The client thread panicked".into()))
     }
 }
@@ -0,0 +1,202 @@
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, Move, PlayerColor};
+use socha_client_2020::logic::Strategy;
+use socha_client_2020::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use socha_client_2020::util::{CancellationToken, SCError, SCResult};
+use super::ExitStatus;
+
+/// The names of the variants of an A/B test.
+const VARIANT_NAMES: [&str; 2] = ["A", "B"];
+
+/// How the games of a session are assigned
+/// to the variants of an A/B test.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum AbAssignment {
+    /// Variant A plays the even games, variant B the odd ones.
+    Alternate,
+    /// Variant A plays red, variant B plays blue.
+    Color
+}
+
+impl FromStr for AbAssignment {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "alternate" => Ok(Self::Alternate),
+            "color" => Ok(Self::Color),
+            _ => Err(format!("Did not recognize A/B assignment {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for AbAssignment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Alternate => write!(f, "alternate"),
+            Self::Color => write!(f, "color")
+        }
+    }
+}
+
+/// The games played by one variant of an A/B test.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+struct VariantTally {
+    wins: u32,
+    losses: u32,
+    draws: u32,
+    errors: u32
+}
+
+impl VariantTally {
+    fn games(&self) -> u32 { self.wins + self.losses + self.draws + self.errors }
+}
+
+/// A comparison of two strategies on the live server within one
+/// session, tallying the results of both variants separately.
+#[derive(Debug, Clone)]
+pub struct AbTest {
+    strategies: [Strategy; 2],
+    assignment: AbAssignment,
+    tallies: [VariantTally; 2]
+}
+
+impl AbTest {
+    /// Creates a test comparing strategy A with strategy B.
+    pub fn new(a: Strategy, b: Strategy, assignment: AbAssignment) -> Self {
+        Self { strategies: [a, b], assignment, tallies: [VariantTally::default(); 2] }
+    }
+
+    /// How the games are assigned to the variants.
+    pub fn assignment(&self) -> AbAssignment { self.assignment }
+
+    /// The strategy of the given variant (0 for A, 1 for B).
+    pub fn strategy(&self, variant: usize) -> Strategy { self.strategies[variant] }
+
+    /// The variant playing the given game, which is only known
+    /// in advance if the variants alternate between games.
+    pub fn variant_for_game(&self, game: u32) -> Option<usize> {
+        match self.assignment {
+            AbAssignment::Alternate => Some(game as usize % 2),
+            AbAssignment::Color => None
+        }
+    }
+
+    /// The variant playing the given color if the
+    /// variants are assigned by color.
+    pub fn variant_for_color(color: PlayerColor) -> usize {
+        match color {
+            PlayerColor::Red => 0,
+            PlayerColor::Blue => 1
+        }
+    }
+
+    /// The name of the given variant.
+    pub fn variant_name(variant: usize) -> &'static str { VARIANT_NAMES[variant] }
+
+    /// Records the exit status of a game played by the given variant.
+    pub fn record(&mut self, variant: usize, status: ExitStatus) {
+        let tally = &mut self.tallies[variant];
+        match status {
+            ExitStatus::Success => tally.wins += 1,
+            ExitStatus::Loss => tally.losses += 1,
+            ExitStatus::Draw => tally.draws += 1,
+            _ => tally.errors += 1
+        }
+    }
+
+    /// Creates a machine-readable summary of both variants.
+    pub fn to_json(&self) -> serde_json::Value {
+        let variants: Vec<_> = self.tallies.iter().enumerate().map(|(variant, tally)| {
+            let decided = tally.wins + tally.losses + tally.draws;
+            serde_json::json!({
+                "variant": Self::variant_name(variant),
+                "strategy": self.strategies[variant].to_string(),
+                "games": tally.games(),
+                "wins": tally.wins,
+                "losses": tally.losses,
+                "draws": tally.draws,
+                "errors": tally.errors,
+                "score": Some(decided).filter(|&d| d > 0).map(|d| (f64::from(tally.wins) + 0.5 * f64::from(tally.draws)) / f64::from(d))
+            })
+        }).collect();
+        serde_json::json!({
+            "abTest": {
+                "assignment": self.assignment.to_string(),
+                "variants": variants
+            }
+        })
+    }
+}
+
+/// A delegate that lets one of two delegates (one per variant)
+/// choose the moves depending on the own color. Both delegates
+/// observe the game, so either can take over.
+pub struct ColorSplitDelegate<D> {
+    delegates: [D; 2],
+    active: usize
+}
+
+impl<D> ColorSplitDelegate<D> where D: SCClientDelegate {
+    /// Creates a delegate letting the first delegate
+    /// play red and the second one play blue.
+    pub fn new(delegates: [D; 2]) -> Self {
+        Self { delegates, active: 0 }
+    }
+}
+
+impl<D> SCClientDelegate for ColorSplitDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.delegates.iter_mut().for_each(|d| d.on_update_state(state, my_color));
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.delegates.iter_mut().for_each(|d| d.on_game_end(result.clone(), my_color));
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.active = AbTest::variant_for_color(*color);
+        self.delegates.iter_mut().for_each(|d| d.on_welcome_message(color));
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegates.iter_mut().for_each(|d| d.on_time_settings(settings));
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegates[self.active].on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegates.iter_mut().for_each(|d| d.on_game_paused());
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegates.iter_mut().for_each(|d| d.on_game_resumed());
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegates.iter_mut().for_each(|d| d.on_round_trip(stats));
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegates.iter_mut().for_each(|d| d.on_opponent(name, stats));
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegates.iter_mut().for_each(|d| d.on_move_observed(game_move));
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        self.active = AbTest::variant_for_color(my_color);
+        log::debug!("Variant {} chooses the move", AbTest::variant_name(self.active));
+        self.delegates[self.active].request_move(state, my_color, cancel)
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegates[self.active].move_annotation()
+    }
+}
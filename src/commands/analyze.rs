@@ -0,0 +1,105 @@
+use getopts::Options;
+use socha_client_2020::analysis::hints;
+use socha_client_2020::eval::{Evaluator, HeuristicEvaluator};
+use socha_client_2020::game::{GameState, HivePlugin, PlayerColor};
+use socha_client_2020::protocol::{AnnotatedReplay, Replay};
+use socha_client_2020::util::{SCResult, FromXmlNode, XmlNode};
+use super::{print_usage, parse_args, parse_opt, CommandError, CommandResult};
+
+/// Finds the first node with the given name in the tree (in pre-order).
+fn find_node<'a>(node: &'a XmlNode, name: &str) -> Option<&'a XmlNode> {
+    if node.name() == name {
+        Some(node)
+    } else {
+        node.childs().iter().find_map(|c| find_node(c, name))
+    }
+}
+
+/// Loads the first `<state>` found in the given XML file (or its
+/// binary dump), which may e.g. be a single memento or a recorded
+/// protocol transcript.
+pub fn load_state(path: &str) -> SCResult<GameState> {
+    let root = XmlNode::load(path)?;
+    GameState::from_node(find_node(&root, "state").ok_or_else(|| format!("No <state> found in {}", path))?)
+}
+
+/// Loads the state from the given file, if any, and
+/// otherwise creates the initial state with red to move.
+pub fn load_state_or_initial(path: Option<&str>) -> SCResult<GameState> {
+    path.map(load_state).unwrap_or_else(|| Ok(GameState::initial(PlayerColor::Red)))
+}
+
+/// Prints the annotated moves of a replay (which may also be an
+/// official server replay without annotations) along with the
+/// static evaluation of the positions they were chosen in.
+fn print_replay(path: &str) -> SCResult<()> {
+    let root = XmlNode::load(path)?;
+    let official = Replay::<HivePlugin>::from_node(&root)?;
+    let annotated = AnnotatedReplay::<HivePlugin>::from_node(&root)?;
+    let result = official.result.clone();
+    let replay = if annotated.entries.is_empty() { official.into() } else { annotated };
+    let evaluator = HeuristicEvaluator::default();
+    for (state, annotation) in &replay.entries {
+        let color = state.current_player_color;
+        let static_evaluation = evaluator.evaluate(state, color);
+        match annotation {
+            Some(annotation) => println!(
+                "Turn {:>2} {:?}: static {:+.2}, engine {} at depth {} in {}ms, PV {}",
+                state.turn, color, static_evaluation,
+                annotation.evaluation.map(|e| format!("{:+.2}", e)).unwrap_or_else(|| "-".to_owned()),
+                annotation.depth.map(|d| d.to_string()).unwrap_or_else(|| "-".to_owned()),
+                annotation.time.map(|t| t.as_millis()).unwrap_or(0),
+                annotation.principal_variation.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" ")
+            ),
+            None => println!("Turn {:>2} {:?}: static {:+.2}", state.turn, color, static_evaluation)
+        }
+    }
+    if let Some(result) = result {
+        let termination = result.termination();
+        let winners: Vec<_> = result.winners.iter().map(|w| w.display_name.as_str()).collect();
+        println!("Result: {:?}{}, won by {}", termination.cause,
+            termination.reason.map(|r| format!(" ({})", r)).unwrap_or_default(),
+            if winners.is_empty() { "nobody".to_owned() } else { winners.join(", ") });
+    }
+    Ok(())
+}
+
+/// Analyzes a position, optionally printing the best moves.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("s", "state", "An XML file containing the <state> to analyze", "FILE");
+    options.optopt("c", "color", "The color to analyze for (the current player by default)", "COLOR");
+    options.optopt("r", "replay", "An annotated or official server replay whose moves are listed instead", "FILE");
+    options.optopt("k", "hints", "Prints the given number of best moves with justifications", "K");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} analyze", program), &options);
+        return Ok(());
+    }
+
+    if let Some(path) = parsed_args.opt_str("replay") {
+        print_replay(&path)?;
+        return Ok(());
+    }
+
+    let path = parsed_args.opt_str("state").ok_or_else(|| CommandError::Usage("Please specify a state file using --state or a replay using --replay".to_owned()))?;
+    let state = load_state(&path)?;
+    let color = parse_opt::<PlayerColor>(&parsed_args, "color", "'red' or 'blue'")?.unwrap_or(state.current_player_color);
+    let evaluator = HeuristicEvaluator::default();
+
+    println!("{}", state.board);
+    println!("Turn {}, evaluation for {:?}: {:+.2}", state.turn, color, evaluator.evaluate(&state, color));
+    for feature in evaluator.breakdown(&state, color) {
+        println!("  {:<24} {:>6.2} x {:>5.2} = {:+.2}", feature.name, feature.value, feature.weight, feature.weighted());
+    }
+
+    if let Some(k) = parse_opt::<usize>(&parsed_args, "hints", "a number of moves")? {
+        for (i, hint) in hints(&state, color, &evaluator, k).into_iter().enumerate() {
+            println!("{}. {}", i + 1, hint);
+        }
+    }
+
+    Ok(())
+}
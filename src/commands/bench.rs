@@ -0,0 +1,180 @@
+#[cfg(feature = "alloc-stats")]
+use std::alloc::{GlobalAlloc, Layout, System};
+#[cfg(feature = "alloc-stats")]
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+use getopts::Options;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom, Rng};
+use socha_client_2020::game::GameState;
+use socha_client_2020::util::{FromXmlNode, XmlNode, XmlNodeRef};
+use xml::reader::EventReader;
+use super::analyze::load_state;
+use super::{print_usage, parse_args, parse_opt, CommandError, CommandResult};
+
+/// The number of heap allocations performed so far.
+#[cfg(feature = "alloc-stats")]
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A global allocator that counts heap allocations,
+/// which lets the benchmarks report allocations per move.
+#[cfg(feature = "alloc-stats")]
+pub struct CountingAllocator;
+
+#[cfg(feature = "alloc-stats")]
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Fetches the number of heap allocations performed so far.
+#[cfg(feature = "alloc-stats")]
+fn allocations() -> usize {
+    ALLOCATIONS.load(Ordering::Relaxed)
+}
+
+/// Allocations are only counted with the `alloc-stats` feature.
+#[cfg(not(feature = "alloc-stats"))]
+fn allocations() -> usize { 0 }
+
+/// Generates positions by playing random moves from the given one.
+fn generate_positions(root: &GameState, count: usize, random: &mut StdRng) -> Vec<GameState> {
+    let mut positions = Vec::with_capacity(count);
+    while positions.len() < count {
+        let mut state = root.clone();
+        for _ in 0..random.gen_range(0..40) {
+            let moves = state.possible_moves(state.current_player_color);
+            match moves.choose(random).and_then(|m| state.apply_move(m).ok()) {
+                Some(next) => state = next,
+                None => break
+            }
+        }
+        positions.push(state);
+    }
+    positions
+}
+
+/// Fetches the given percentile of the sorted durations.
+fn percentile(sorted: &[Duration], p: f64) -> Duration {
+    let index = ((sorted.len() as f64 - 1.0) * p).round() as usize;
+    sorted.get(index).copied().unwrap_or_default()
+}
+
+/// Prints throughput, percentiles of per-position timings and
+/// the number of heap allocations per item (if they are counted).
+fn report(name: &str, mut timings: Vec<Duration>, items: usize, unit: &str, allocations: usize) {
+    timings.sort();
+    let total: Duration = timings.iter().sum();
+    let seconds = total.as_secs_f64().max(f64::EPSILON);
+    let allocations = if cfg!(feature = "alloc-stats") {
+        format!("   {:>6.1} allocs/{}", allocations as f64 / items.max(1) as f64, unit.trim_end_matches('s'))
+    } else {
+        String::new()
+    };
+    println!(
+        "{:<24} {:>10.0} positions/s {:>12.0} {}/s   p50 {:>8.1}µs   p90 {:>8.1}µs   p99 {:>8.1}µs{}",
+        name,
+        timings.len() as f64 / seconds,
+        items as f64 / seconds,
+        unit,
+        percentile(&timings, 0.5).as_secs_f64() * 1e6,
+        percentile(&timings, 0.9).as_secs_f64() * 1e6,
+        percentile(&timings, 0.99).as_secs_f64() * 1e6,
+        allocations
+    );
+}
+
+/// Measures the throughput of the rules engine and the
+/// protocol on positions generated from a given position.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("s", "state", "An XML file containing the <state> to generate positions from", "FILE");
+    options.optopt("n", "positions", "The number of positions to generate (200 by default)", "N");
+    options.optopt("S", "seed", "Seeds the position generator (0 by default)", "SEED");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} bench", program), &options);
+        return Ok(());
+    }
+
+    let path = parsed_args.opt_str("state").ok_or_else(|| CommandError::Usage("Please specify a state file using --state".to_owned()))?;
+    let root = load_state(&path)?;
+    let count = parse_opt::<usize>(&parsed_args, "positions", "a number of positions")?.unwrap_or(200);
+    let seed = parse_opt::<u64>(&parsed_args, "seed", "a non-negative number")?.unwrap_or(0);
+    let mut random = StdRng::seed_from_u64(seed);
+
+    let positions = generate_positions(&root, count, &mut random);
+    println!("Generated {} positions (seed {})", positions.len(), seed);
+
+    let mut generation_timings = Vec::with_capacity(positions.len());
+    let mut validation_timings = Vec::with_capacity(positions.len());
+    let mut generation_allocations = 0;
+    let mut validation_allocations = 0;
+    let mut move_count = 0;
+
+    for state in &positions {
+        let color = state.current_player_color;
+        let start = Instant::now();
+        let allocations_before = allocations();
+        let moves = state.possible_moves(color);
+        generation_allocations += allocations() - allocations_before;
+        generation_timings.push(start.elapsed());
+
+        let start = Instant::now();
+        let allocations_before = allocations();
+        let valid = moves.iter().filter(|m| state.validate_move(color, m).is_ok()).count();
+        validation_allocations += allocations() - allocations_before;
+        validation_timings.push(start.elapsed());
+
+        if valid != moves.len() {
+            return Err(format!("{} generated moves failed validation at turn {}", moves.len() - valid, state.turn).into());
+        }
+        move_count += moves.len();
+    }
+
+    // Measure parsing the positions as received from the server
+    let mut parsing_timings = Vec::with_capacity(positions.len());
+    let mut parsing_allocations = 0;
+    let mut borrowed_parsing_timings = Vec::with_capacity(positions.len());
+    let mut borrowed_parsing_allocations = 0;
+    for state in &positions {
+        let xml = XmlNode::from(state.clone()).to_string();
+        let start = Instant::now();
+        let allocations_before = allocations();
+        let parsed = GameState::from_node(&XmlNode::read_from(&mut EventReader::new(xml.as_bytes()))?)?;
+        parsing_allocations += allocations() - allocations_before;
+        parsing_timings.push(start.elapsed());
+
+        if &parsed != state {
+            return Err(format!("Parsing the serialized state changed it at turn {}", state.turn).into());
+        }
+
+        let start = Instant::now();
+        let allocations_before = allocations();
+        let parsed = GameState::from_node(&XmlNodeRef::parse(&xml)?)?;
+        borrowed_parsing_allocations += allocations() - allocations_before;
+        borrowed_parsing_timings.push(start.elapsed());
+
+        if &parsed != state {
+            return Err(format!("Parsing the serialized state without a tree changed it at turn {}", state.turn).into());
+        }
+    }
+
+    report("Move generation", generation_timings, move_count, "moves", generation_allocations);
+    report("Validation", validation_timings, move_count, "moves", validation_allocations);
+    report("State parsing", parsing_timings, positions.len(), "states", parsing_allocations);
+    report("State parsing (borrowed)", borrowed_parsing_timings, positions.len(), "states", borrowed_parsing_allocations);
+    Ok(())
+}
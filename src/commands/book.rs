@@ -0,0 +1,68 @@
+use getopts::Options;
+use socha_client_2020::replaydb::{OpeningBook, ReplayDb};
+use super::analyze::load_state;
+use super::{print_usage, parse_args, parse_opt, CommandError, CommandResult};
+
+/// Builds an opening book from a replay database.
+fn build(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("d", "db", "The replay database to build the book from", "FILE");
+    options.optopt("o", "out", "The book file to write", "FILE");
+    options.optopt("g", "min-games", "The number of finished games a move must occur in (5 by default)", "N");
+    options.optopt("w", "min-win-rate", "The win rate a move must have, draws counting half (0.5 by default)", "RATE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} book build", program), &options);
+        return Ok(());
+    }
+
+    let db_path = parsed_args.opt_str("db").ok_or_else(|| CommandError::Usage("Please specify a replay database using --db".to_owned()))?;
+    let out_path = parsed_args.opt_str("out").ok_or_else(|| CommandError::Usage("Please specify the book file using --out".to_owned()))?;
+    let min_games = parse_opt::<u32>(&parsed_args, "min-games", "a number of games")?.unwrap_or(5);
+    let min_win_rate = parse_opt::<f64>(&parsed_args, "min-win-rate", "a rate between 0 and 1")?.unwrap_or(0.5);
+
+    let book = OpeningBook::build(&ReplayDb::load(&db_path)?, min_games, min_win_rate);
+    book.save(&out_path)?;
+    println!("Wrote {} positions to {}", book.len(), out_path);
+    Ok(())
+}
+
+/// Lists the book moves of a position.
+fn probe(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("b", "book", "The book file to probe", "FILE");
+    options.optopt("s", "state", "An XML file containing the <state> to probe", "FILE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} book probe", program), &options);
+        return Ok(());
+    }
+
+    let book_path = parsed_args.opt_str("book").ok_or_else(|| CommandError::Usage("Please specify a book file using --book".to_owned()))?;
+    let state_path = parsed_args.opt_str("state").ok_or_else(|| CommandError::Usage("Please specify a state file using --state".to_owned()))?;
+    let book = OpeningBook::load(&book_path)?;
+    let state = load_state(&state_path)?;
+
+    let moves = book.probe(&state);
+    if moves.is_empty() {
+        println!("Position not in book");
+    }
+    for (game_move, weight) in moves {
+        println!("{:<20} {:>6}", game_move.to_string(), weight);
+    }
+    Ok(())
+}
+
+/// Builds or inspects opening books.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    match args.first().map(|s| s.as_str()) {
+        Some("build") => build(program, &args[1..]),
+        Some("probe") => probe(program, &args[1..]),
+        Some(other) => Err(CommandError::Usage(format!("Unknown book command '{}' (expected 'build' or 'probe')", other))),
+        None => Err(CommandError::Usage("Please specify a book command ('build' or 'probe')".to_owned()))
+    }
+}
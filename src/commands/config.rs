@@ -0,0 +1,418 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::str::FromStr;
+use std::time::SystemTime;
+use getopts::Matches;
+use toml::Value;
+use socha_client_2020::eval::HeuristicEvaluator;
+use socha_client_2020::logic::{Strategy, TieBreak};
+use socha_client_2020::net::Proxy;
+use socha_client_2020::protocol::{MovePreflight, TimeSettings};
+use socha_client_2020::util::SCResult;
+use super::{parse_opt, AbAssignment, CommandError, CommandResult, Profile, AB_ASSIGNMENT_NAMES, PREFLIGHT_NAMES, PROFILE_NAMES, STRATEGY_NAMES, TIE_BREAK_NAMES};
+
+/// The settings of the `play` subcommand. Every setting is
+/// optional, so configurations from different sources (e.g. a
+/// file and the command line) can be layered using `or`.
+///
+/// A configuration file looks like this:
+///
+/// ```toml
+/// profile = "contest"
+/// host = "localhost"
+/// port = 13050
+/// reservation = "..."
+/// strategy = "greedy"
+///
+/// [search]
+/// seed = 42
+/// tie_break = "canonical"
+/// hash_size_mb = 64
+///
+/// [eval]
+/// opponent_bee_neighbors = 3.0
+/// own_bee_neighbors = -3.0
+/// movable_pieces = 0.5
+/// deployed_pieces = 0.2
+/// bee_placed = 1.0
+///
+/// [log]
+/// level = "Debug"
+/// events = "events.jsonl"
+///
+/// [network]
+/// idle_timeout = 30
+/// proxy = "socks5://proxy.example.org:1080"
+///
+/// [time]
+/// soft_timeout_ms = 2000
+/// hard_timeout_ms = 10000
+/// move_time_percent = 75
+///
+/// [report]
+/// dir = "reports"
+///
+/// [memory]
+/// opponents = "opponents.bin"
+///
+/// [ab]
+/// strategy = "alphabeta"
+/// assign = "alternate"
+///
+/// [rules]
+/// preflight = "substitute"
+///
+/// [wire]
+/// debug_reader = false
+/// debug_writer = true
+/// strict = false
+/// log = "wire.log"
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ClientConfig {
+    pub profile: Option<Profile>,
+    pub host: Option<String>,
+    pub port: Option<u16>,
+    pub reservation: Option<String>,
+    pub strategy: Option<Strategy>,
+    pub seed: Option<u64>,
+    pub tie_break: Option<TieBreak>,
+    pub hash_size_mb: Option<u64>,
+    pub evaluator: Option<HeuristicEvaluator>,
+    pub level: Option<String>,
+    pub event_log: Option<String>,
+    pub debug_reader: Option<bool>,
+    pub debug_writer: Option<bool>,
+    pub strict: Option<bool>,
+    pub wire_log: Option<String>,
+    pub preflight: Option<MovePreflight>,
+    pub idle_timeout: Option<u64>,
+    pub proxy: Option<Proxy>,
+    pub soft_timeout_ms: Option<u64>,
+    pub hard_timeout_ms: Option<u64>,
+    pub move_time_percent: Option<u64>,
+    pub report_dir: Option<String>,
+    pub opponent_memory: Option<String>,
+    pub ab_strategy: Option<Strategy>,
+    pub ab_assignment: Option<AbAssignment>
+}
+
+/// Looks up a (possibly nested) value in a TOML table.
+fn lookup<'a>(table: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(table, |value, key| value.get(key))
+}
+
+fn string(table: &Value, path: &[&str]) -> SCResult<Option<String>> {
+    lookup(table, path)
+        .map(|v| v.as_str().map(|s| s.to_owned()).ok_or_else(|| format!("Expected a string for {}", path.join(".")).into()))
+        .transpose()
+}
+
+fn integer(table: &Value, path: &[&str]) -> SCResult<Option<i64>> {
+    lookup(table, path)
+        .map(|v| v.as_integer().ok_or_else(|| format!("Expected an integer for {}", path.join(".")).into()))
+        .transpose()
+}
+
+fn unsigned(table: &Value, path: &[&str]) -> SCResult<Option<u64>> {
+    integer(table, path)?
+        .map(|i| u64::try_from(i).map_err(|_| format!("Expected a non-negative integer for {}, got {}", path.join("."), i).into()))
+        .transpose()
+}
+
+fn float(table: &Value, path: &[&str]) -> SCResult<Option<f64>> {
+    lookup(table, path)
+        .map(|v| v.as_float().or_else(|| v.as_integer().map(|i| i as f64)).ok_or_else(|| format!("Expected a number for {}", path.join(".")).into()))
+        .transpose()
+}
+
+/// Reads the evaluation weights, using the
+/// defaults for those that are not specified.
+fn evaluator(table: &Value) -> SCResult<Option<HeuristicEvaluator>> {
+    if lookup(table, &["eval"]).is_none() {
+        return Ok(None);
+    }
+    let defaults = HeuristicEvaluator::default();
+    Ok(Some(HeuristicEvaluator {
+        opponent_bee_neighbors_weight: float(table, &["eval", "opponent_bee_neighbors"])?.unwrap_or(defaults.opponent_bee_neighbors_weight),
+        own_bee_neighbors_weight: float(table, &["eval", "own_bee_neighbors"])?.unwrap_or(defaults.own_bee_neighbors_weight),
+        movable_pieces_weight: float(table, &["eval", "movable_pieces"])?.unwrap_or(defaults.movable_pieces_weight),
+        deployed_pieces_weight: float(table, &["eval", "deployed_pieces"])?.unwrap_or(defaults.deployed_pieces_weight),
+        bee_placed_weight: float(table, &["eval", "bee_placed"])?.unwrap_or(defaults.bee_placed_weight)
+    }))
+}
+
+fn boolean(table: &Value, path: &[&str]) -> SCResult<Option<bool>> {
+    lookup(table, path)
+        .map(|v| v.as_bool().ok_or_else(|| format!("Expected a boolean for {}", path.join(".")).into()))
+        .transpose()
+}
+
+/// Reads the reservation code from `--reservation` (`-` reading it
+/// from stdin) or `--reservation-file`, which keeps it out of the
+/// process listing.
+fn reservation(args: &Matches) -> CommandResult<Option<String>> {
+    let raw = match (args.opt_str("reservation"), args.opt_str("reservation-file")) {
+        (Some(code), _) if code == "-" => {
+            let mut code = String::new();
+            io::stdin().read_to_string(&mut code).map_err(|e| format!("Could not read the reservation from stdin: {}", e))?;
+            code
+        },
+        (Some(code), _) => code,
+        (None, Some(path)) => fs::read_to_string(&path).map_err(|e| format!("Could not read the reservation file {}: {}", path, e))?,
+        (None, None) => return Ok(None)
+    };
+    validate_reservation(&raw).map(Some).map_err(|e| CommandError::Usage(e.to_string()))
+}
+
+/// Trims the given reservation code and checks that it is non-empty
+/// and consists of printable characters without whitespace. The code
+/// is not included in the error, since it is meant to be secret.
+fn validate_reservation(raw: &str) -> SCResult<String> {
+    let code = raw.trim();
+    if code.is_empty() {
+        Err("The reservation code is empty".into())
+    } else if code.chars().any(|c| c.is_whitespace() || c.is_control()) {
+        Err("The reservation code must not contain whitespace or control characters".into())
+    } else {
+        Ok(code.to_owned())
+    }
+}
+
+/// Fetches a non-empty environment variable.
+fn var(name: &str) -> Option<String> {
+    env::var(name).ok().filter(|v| !v.is_empty())
+}
+
+fn parsed_var<T>(name: &str) -> SCResult<Option<T>> where T: FromStr {
+    var(name).map(|v| v.parse().map_err(|_| format!("Invalid value {} for {}", v, name).into())).transpose()
+}
+
+impl ClientConfig {
+    /// Parses a configuration in TOML format.
+    pub fn parse(raw: &str) -> SCResult<Self> {
+        let table = raw.parse::<Value>().map_err(|e| format!("Invalid configuration: {}", e))?;
+        Ok(Self {
+            profile: string(&table, &["profile"])?.map(|s| s.parse()).transpose()?,
+            host: string(&table, &["host"])?,
+            port: integer(&table, &["port"])?.map(|p| u16::try_from(p).map_err(|_| format!("Invalid port {}", p))).transpose()?,
+            reservation: string(&table, &["reservation"])?.map(|r| validate_reservation(&r)).transpose()?,
+            strategy: string(&table, &["strategy"])?.map(|s| s.parse()).transpose()?,
+            seed: unsigned(&table, &["search", "seed"])?,
+            tie_break: string(&table, &["search", "tie_break"])?.map(|s| s.parse()).transpose()?,
+            hash_size_mb: unsigned(&table, &["search", "hash_size_mb"])?,
+            evaluator: evaluator(&table)?,
+            level: string(&table, &["log", "level"])?,
+            event_log: string(&table, &["log", "events"])?,
+            debug_reader: boolean(&table, &["wire", "debug_reader"])?,
+            debug_writer: boolean(&table, &["wire", "debug_writer"])?,
+            strict: boolean(&table, &["wire", "strict"])?,
+            wire_log: string(&table, &["wire", "log"])?,
+            preflight: string(&table, &["rules", "preflight"])?.map(|s| s.parse()).transpose()?,
+            idle_timeout: unsigned(&table, &["network", "idle_timeout"])?,
+            proxy: string(&table, &["network", "proxy"])?.map(|s| s.parse()).transpose()?,
+            soft_timeout_ms: unsigned(&table, &["time", "soft_timeout_ms"])?,
+            hard_timeout_ms: unsigned(&table, &["time", "hard_timeout_ms"])?,
+            move_time_percent: unsigned(&table, &["time", "move_time_percent"])?,
+            report_dir: string(&table, &["report", "dir"])?,
+            opponent_memory: string(&table, &["memory", "opponents"])?,
+            ab_strategy: string(&table, &["ab", "strategy"])?.map(|s| s.parse()).transpose()?,
+            ab_assignment: string(&table, &["ab", "assign"])?.map(|s| s.parse()).transpose()?
+        })
+    }
+
+    /// Reads a configuration file in TOML format.
+    pub fn from_file(path: &str) -> SCResult<Self> {
+        Self::parse(&fs::read_to_string(path)?).map_err(|e| format!("{} (in {})", e, path).into())
+    }
+
+    /// Reads the configuration from the environment variables `SC_PROFILE`, `SC_HOST`,
+    /// `SC_PORT`, `SC_RESERVATION`, `SC_STRATEGY`, `SC_SEED`, `SC_TIE_BREAK`, `SC_HASH_SIZE_MB`, `SC_LOG_LEVEL`,
+    /// `SC_EVENT_LOG`, `SC_DEBUG_READER`, `SC_DEBUG_WRITER`, `SC_STRICT`, `SC_WIRE_LOG`, `SC_PREFLIGHT`, `SC_IDLE_TIMEOUT`, `SC_PROXY`,
+    /// `SC_SOFT_TIMEOUT_MS`, `SC_HARD_TIMEOUT_MS`, `SC_MOVE_TIME_PERCENT`, `SC_REPORT_DIR`,
+    /// `SC_OPPONENT_MEMORY`, `SC_AB_STRATEGY` and `SC_AB_ASSIGN`.
+    pub fn from_env() -> SCResult<Self> {
+        Ok(Self {
+            profile: var("SC_PROFILE").map(|s| s.parse()).transpose()?,
+            host: var("SC_HOST"),
+            port: parsed_var("SC_PORT")?,
+            reservation: var("SC_RESERVATION").map(|r| validate_reservation(&r).map_err(|e| format!("{} (in SC_RESERVATION)", e))).transpose()?,
+            strategy: var("SC_STRATEGY").map(|s| s.parse()).transpose()?,
+            seed: parsed_var("SC_SEED")?,
+            tie_break: var("SC_TIE_BREAK").map(|s| s.parse()).transpose()?,
+            hash_size_mb: parsed_var("SC_HASH_SIZE_MB")?,
+            evaluator: None,
+            level: var("SC_LOG_LEVEL"),
+            event_log: var("SC_EVENT_LOG"),
+            debug_reader: parsed_var("SC_DEBUG_READER")?,
+            debug_writer: parsed_var("SC_DEBUG_WRITER")?,
+            strict: parsed_var("SC_STRICT")?,
+            wire_log: var("SC_WIRE_LOG"),
+            preflight: var("SC_PREFLIGHT").map(|s| s.parse()).transpose()?,
+            idle_timeout: parsed_var("SC_IDLE_TIMEOUT")?,
+            proxy: var("SC_PROXY").map(|s| s.parse()).transpose()?,
+            soft_timeout_ms: parsed_var("SC_SOFT_TIMEOUT_MS")?,
+            hard_timeout_ms: parsed_var("SC_HARD_TIMEOUT_MS")?,
+            move_time_percent: parsed_var("SC_MOVE_TIME_PERCENT")?,
+            report_dir: var("SC_REPORT_DIR"),
+            opponent_memory: var("SC_OPPONENT_MEMORY"),
+            ab_strategy: var("SC_AB_STRATEGY").map(|s| s.parse()).transpose()?,
+            ab_assignment: var("SC_AB_ASSIGN").map(|s| s.parse()).transpose()?
+        })
+    }
+
+    /// Reads the configuration from parsed command line arguments.
+    pub fn from_args(args: &Matches) -> CommandResult<Self> {
+        let flag = |name| if args.opt_present(name) { Some(true) } else { None };
+        Ok(Self {
+            profile: parse_opt(args, "profile", PROFILE_NAMES)?,
+            host: args.opt_str("host"),
+            port: parse_opt(args, "port", "a number between 0 and 65535")?,
+            reservation: reservation(args)?,
+            strategy: parse_opt(args, "strategy", STRATEGY_NAMES)?,
+            seed: parse_opt(args, "seed", "a non-negative number")?,
+            tie_break: parse_opt(args, "tie-break", TIE_BREAK_NAMES)?,
+            hash_size_mb: parse_opt(args, "hash-size", "a number of megabytes")?,
+            evaluator: None,
+            level: args.opt_str("level"),
+            event_log: args.opt_str("event-log"),
+            debug_reader: flag("debug-reader"),
+            debug_writer: flag("debug-writer"),
+            strict: flag("strict"),
+            wire_log: args.opt_str("wire-log"),
+            preflight: parse_opt(args, "preflight", PREFLIGHT_NAMES)?,
+            idle_timeout: parse_opt(args, "idle-timeout", "a number of seconds")?,
+            proxy: parse_opt(args, "proxy", "a proxy URL (e.g. socks5://host:1080 or http://host:8080)")?,
+            soft_timeout_ms: parse_opt(args, "soft-timeout", "a number of milliseconds")?,
+            hard_timeout_ms: parse_opt(args, "hard-timeout", "a number of milliseconds")?,
+            move_time_percent: parse_opt(args, "move-time", "a percentage")?,
+            report_dir: args.opt_str("report-dir"),
+            opponent_memory: args.opt_str("opponent-memory"),
+            ab_strategy: parse_opt(args, "ab-strategy", STRATEGY_NAMES)?,
+            ab_assignment: parse_opt(args, "ab-assign", AB_ASSIGNMENT_NAMES)?
+        })
+    }
+
+    /// Combines two configurations, preferring the values of `self`.
+    pub fn or(self, fallback: Self) -> Self {
+        Self {
+            profile: self.profile.or(fallback.profile),
+            host: self.host.or(fallback.host),
+            port: self.port.or(fallback.port),
+            reservation: self.reservation.or(fallback.reservation),
+            strategy: self.strategy.or(fallback.strategy),
+            seed: self.seed.or(fallback.seed),
+            tie_break: self.tie_break.or(fallback.tie_break),
+            hash_size_mb: self.hash_size_mb.or(fallback.hash_size_mb),
+            evaluator: self.evaluator.or(fallback.evaluator),
+            level: self.level.or(fallback.level),
+            event_log: self.event_log.or(fallback.event_log),
+            debug_reader: self.debug_reader.or(fallback.debug_reader),
+            debug_writer: self.debug_writer.or(fallback.debug_writer),
+            strict: self.strict.or(fallback.strict),
+            wire_log: self.wire_log.or(fallback.wire_log),
+            preflight: self.preflight.or(fallback.preflight),
+            idle_timeout: self.idle_timeout.or(fallback.idle_timeout),
+            proxy: self.proxy.or(fallback.proxy),
+            soft_timeout_ms: self.soft_timeout_ms.or(fallback.soft_timeout_ms),
+            hard_timeout_ms: self.hard_timeout_ms.or(fallback.hard_timeout_ms),
+            move_time_percent: self.move_time_percent.or(fallback.move_time_percent),
+            report_dir: self.report_dir.or(fallback.report_dir),
+            opponent_memory: self.opponent_memory.or(fallback.opponent_memory),
+            ab_strategy: self.ab_strategy.or(fallback.ab_strategy),
+            ab_assignment: self.ab_assignment.or(fallback.ab_assignment)
+        }
+    }
+
+    /// Fills in the settings that are not configured
+    /// with the defaults of the selected profile.
+    pub fn with_profile_defaults(self) -> Self {
+        match self.profile {
+            Some(profile) => self.or(profile.defaults()),
+            None => self
+        }
+    }
+
+    /// Checks the settings that depend on each other or are
+    /// restricted to a range, which the parsers cannot check.
+    pub fn validate(&self) -> CommandResult<()> {
+        let default_time = TimeSettings::default();
+        let soft_timeout = self.soft_timeout_ms.unwrap_or(default_time.soft_timeout.as_millis() as u64);
+        let hard_timeout = self.hard_timeout_ms.unwrap_or(default_time.hard_timeout.as_millis() as u64);
+        if soft_timeout > hard_timeout {
+            return Err(CommandError::Usage("The soft timeout may not exceed the hard timeout".to_owned()));
+        }
+        if !(1..=100).contains(&self.move_time_percent.unwrap_or(75)) {
+            return Err(CommandError::Usage("The move time has to be between 1 and 100 percent of the soft timeout".to_owned()));
+        }
+        Ok(())
+    }
+
+    /// Creates a JSON representation of the settings for
+    /// game reports (omitting the reservation).
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "profile": self.profile.map(|p| p.to_string()),
+            "host": self.host,
+            "port": self.port,
+            "strategy": self.strategy.map(|s| s.to_string()),
+            "seed": self.seed,
+            "tieBreak": self.tie_break.map(|t| t.to_string()),
+            "hashSizeMb": self.hash_size_mb,
+            "eval": self.evaluator.as_ref().map(|e| serde_json::json!({
+                "opponentBeeNeighbors": e.opponent_bee_neighbors_weight,
+                "ownBeeNeighbors": e.own_bee_neighbors_weight,
+                "movablePieces": e.movable_pieces_weight,
+                "deployedPieces": e.deployed_pieces_weight,
+                "beePlaced": e.bee_placed_weight
+            })),
+            "level": self.level,
+            "eventLog": self.event_log,
+            "strict": self.strict,
+            "wireLog": self.wire_log,
+            "preflight": self.preflight.map(|p| p.to_string()),
+            "idleTimeout": self.idle_timeout,
+            "proxy": self.proxy.as_ref().map(|p| p.to_string()),
+            "softTimeoutMs": self.soft_timeout_ms,
+            "hardTimeoutMs": self.hard_timeout_ms,
+            "moveTimePercent": self.move_time_percent,
+            "opponentMemory": self.opponent_memory,
+            "abStrategy": self.ab_strategy.map(|s| s.to_string()),
+            "abAssign": self.ab_assignment.map(|a| a.to_string())
+        })
+    }
+}
+
+/// Watches a configuration file for changes, which lets
+/// long sessions pick up new settings without a restart.
+#[derive(Debug, Clone)]
+pub struct ConfigWatcher {
+    path: String,
+    modified: Option<SystemTime>
+}
+
+impl ConfigWatcher {
+    /// Reads the configuration file, remembering its modification time.
+    pub fn load(path: &str) -> SCResult<(Self, ClientConfig)> {
+        let modified = fs::metadata(path)?.modified().ok();
+        let config = ClientConfig::from_file(path)?;
+        Ok((Self { path: path.to_owned(), modified }, config))
+    }
+
+    /// Fetches the path of the watched file.
+    pub fn path(&self) -> &str { &self.path }
+
+    /// Re-reads the configuration file if it has been modified
+    /// since it was last read. A file that cannot be read or parsed
+    /// (e.g. while it is being edited) is retried on the next call.
+    pub fn poll(&mut self) -> SCResult<Option<ClientConfig>> {
+        let modified = fs::metadata(&self.path)?.modified().ok();
+        if modified == self.modified {
+            return Ok(None);
+        }
+        let config = ClientConfig::from_file(&self.path)?;
+        self.modified = modified;
+        Ok(Some(config))
+    }
+}
@@ -0,0 +1,41 @@
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::time::Instant;
+use getopts::Options;
+use socha_client_2020::util::{XmlNode, BINARY_XML_MAGIC};
+use super::{print_usage, parse_args, CommandError, CommandResult};
+
+/// Converts a recorded XML file (e.g. a transcript or a replay) into
+/// a compact binary dump or a binary dump back into XML.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("i", "in", "The XML file or binary dump to convert", "FILE");
+    options.optopt("o", "out", "The file to write the converted node to", "FILE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} dump", program), &options);
+        return Ok(());
+    }
+
+    let in_path = parsed_args.opt_str("in").ok_or_else(|| CommandError::Usage("Please specify the input file using --in".to_owned()))?;
+    let out_path = parsed_args.opt_str("out").ok_or_else(|| CommandError::Usage("Please specify the output file using --out".to_owned()))?;
+    let is_binary = fs::read(&in_path)?.starts_with(BINARY_XML_MAGIC);
+
+    let start = Instant::now();
+    let node = XmlNode::load(&in_path)?;
+    let load_time = start.elapsed();
+
+    let mut writer = BufWriter::new(File::create(&out_path)?);
+    if is_binary {
+        write!(writer, "{}", node)?;
+    } else {
+        node.write_binary(&mut writer)?;
+    }
+    writer.flush()?;
+    println!("Converted {} ({} bytes, loaded in {:.1}ms) to {} ({} bytes)",
+        in_path, fs::metadata(&in_path)?.len(), load_time.as_secs_f64() * 1000.0,
+        out_path, fs::metadata(&out_path)?.len());
+    Ok(())
+}
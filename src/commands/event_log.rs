@@ -0,0 +1,207 @@
+use std::cell::RefCell;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, Move, PlayerColor};
+use socha_client_2020::protocol::{GameResult, MoveAnnotation, OpponentStats, ProtocolEvent, RttStats, TimeSettings};
+use socha_client_2020::util::{CancellationToken, SCResult};
+
+/// A machine-readable stream of the client's events in the
+/// JSON Lines format, i.e. one object per line, each carrying
+/// its kind (`event`), the game index and the milliseconds
+/// since the log was opened. Lines are flushed immediately,
+/// so the log can be tailed by external tools.
+pub struct EventLog {
+    writer: Box<dyn Write>,
+    is_stdout: bool,
+    start: Instant,
+    game: u32,
+    protocol_events: Option<Receiver<ProtocolEvent>>
+}
+
+impl EventLog {
+    /// Tests whether the given path denotes stdout.
+    pub fn is_stdout_path(path: &str) -> bool { path == "-" }
+
+    /// Opens the log at the given path, truncating an
+    /// existing file. The path `-` denotes stdout.
+    pub fn open(path: &str) -> SCResult<Self> {
+        let is_stdout = Self::is_stdout_path(path);
+        let writer: Box<dyn Write> = if is_stdout {
+            Box::new(io::stdout())
+        } else {
+            Box::new(BufWriter::new(File::create(path)?))
+        };
+        Ok(Self { writer, is_stdout, start: Instant::now(), game: 0, protocol_events: None })
+    }
+
+    /// Tests whether the events are written to stdout, which
+    /// thus should not be mixed with any other output.
+    pub fn is_stdout(&self) -> bool { self.is_stdout }
+
+    /// Starts logging the given game, returning the sender
+    /// through which the client reports protocol events.
+    pub fn begin_game(&mut self, game: u32) -> Sender<ProtocolEvent> {
+        self.drain_protocol_events();
+        let (sender, receiver) = mpsc::channel();
+        self.game = game;
+        self.protocol_events = Some(receiver);
+        sender
+    }
+
+    /// Writes an event with the given fields (a JSON object),
+    /// after the protocol events that arrived in the meantime.
+    pub fn record(&mut self, event: &str, fields: serde_json::Value) {
+        self.drain_protocol_events();
+        self.write(event, fields);
+    }
+
+    fn drain_protocol_events(&mut self) {
+        let events: Vec<_> = self.protocol_events.iter().flat_map(|r| r.try_iter()).collect();
+        for event in events {
+            match event {
+                ProtocolEvent::Joined { room_id } => self.write("joined", serde_json::json!({ "roomId": room_id })),
+                ProtocolEvent::Left { room_id } => self.write("left", serde_json::json!({ "roomId": room_id })),
+                ProtocolEvent::Closed { reason } => self.write("closed", serde_json::json!({ "reason": reason }))
+            }
+        }
+    }
+
+    fn write(&mut self, event: &str, fields: serde_json::Value) {
+        let mut line = serde_json::json!({
+            "event": event,
+            "game": self.game,
+            "timeMs": self.start.elapsed().as_millis() as u64
+        });
+        if let (Some(line), serde_json::Value::Object(fields)) = (line.as_object_mut(), fields) {
+            line.extend(fields);
+        }
+        // The event log must never interrupt the game
+        if let Err(e) = writeln!(self.writer, "{}", line).and_then(|_| self.writer.flush()) {
+            log::warn!("Could not write to the event log: {}", e);
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u64 { duration.as_millis() as u64 }
+
+/// A delegate that records its callbacks in an event
+/// log and otherwise forwards to the wrapped delegate.
+pub struct EventLogDelegate<D> {
+    delegate: D,
+    log: Rc<RefCell<EventLog>>
+}
+
+impl<D> EventLogDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate, recording into the given log.
+    pub fn new(delegate: D, log: Rc<RefCell<EventLog>>) -> Self {
+        Self { delegate, log }
+    }
+
+    fn record(&self, event: &str, fields: serde_json::Value) {
+        self.log.borrow_mut().record(event, fields);
+    }
+}
+
+impl<D> SCClientDelegate for EventLogDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.record("state", serde_json::json!({
+            "turn": state.turn,
+            "currentColor": serde_json::Value::from(state.current_player_color)
+        }));
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        let termination = result.termination();
+        self.record("gameEnd", serde_json::json!({
+            "winners": result.winners.iter().map(serde_json::Value::from).collect::<Vec<_>>(),
+            "cause": format!("{:?}", termination.cause),
+            "reason": termination.reason
+        }));
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.record("welcome", serde_json::json!({ "color": serde_json::Value::from(*color) }));
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.record("timeSettings", serde_json::json!({
+            "softTimeoutMs": millis(settings.soft_timeout),
+            "hardTimeoutMs": millis(settings.hard_timeout)
+        }));
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.record("idle", serde_json::json!({ "idleMs": millis(duration) }));
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.record("paused", serde_json::json!({}));
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.record("resumed", serde_json::json!({}));
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.record("roundTrip", serde_json::json!({
+            "lastMs": stats.last().map(millis),
+            "meanMs": stats.mean().map(millis),
+            "jitterMs": stats.jitter().map(millis)
+        }));
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.record("opponent", serde_json::json!({
+            "name": name,
+            "wins": stats.wins,
+            "draws": stats.draws,
+            "losses": stats.losses,
+            "favoriteOpening": stats.favorite_opening().map(|m| m.to_string())
+        }));
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.record("observedMove", serde_json::json!({ "move": game_move.to_string() }));
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let start = Instant::now();
+        let game_move = self.delegate.request_move(state, my_color, cancel);
+        if let Some(annotation) = self.delegate.move_annotation() {
+            self.record("search", serde_json::json!({
+                "turn": state.turn,
+                "evaluation": annotation.evaluation,
+                "depth": annotation.depth,
+                "timeMs": annotation.time.map(millis),
+                "principalVariation": annotation.principal_variation.iter().map(|m| m.to_string()).collect::<Vec<_>>(),
+                "debug": serde_json::Value::from(&annotation.debug)
+            }));
+        }
+        self.record("move", serde_json::json!({
+            "turn": state.turn,
+            "color": serde_json::Value::from(my_color),
+            "move": game_move.to_string(),
+            "cancelled": cancel.is_cancelled(),
+            "thinkingTimeMs": millis(start.elapsed())
+        }));
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
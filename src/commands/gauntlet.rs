@@ -0,0 +1,121 @@
+use std::time::Duration;
+use getopts::Options;
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, PlayerColor};
+use socha_client_2020::logic::{OwnGameLogic, Strategy};
+use socha_client_2020::protocol::TimeSettings;
+use super::selfplay::play_local_game;
+use super::{print_usage, parse_args, parse_opt, CommandResult, STRATEGY_NAMES};
+
+/// The results of the candidate in a gauntlet.
+#[derive(Debug, Default)]
+struct GauntletStats {
+    wins: u32,
+    draws: u32,
+    losses: u32
+}
+
+impl GauntletStats {
+    fn games(&self) -> u32 { self.wins + self.draws + self.losses }
+
+    /// The mean score per game (a win counting 1, a draw 1/2).
+    fn score(&self) -> f64 {
+        (f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(self.games().max(1))
+    }
+
+    /// The 95% confidence interval of the mean score,
+    /// using the normal approximation.
+    fn confidence_interval(&self) -> (f64, f64) {
+        let n = f64::from(self.games().max(1));
+        let mean = self.score();
+        let variance = (f64::from(self.wins) * (1.0 - mean).powi(2)
+            + f64::from(self.draws) * (0.5 - mean).powi(2)
+            + f64::from(self.losses) * mean.powi(2)) / n;
+        let margin = 1.96 * (variance / n).sqrt();
+        ((mean - margin).max(0.0), (mean + margin).min(1.0))
+    }
+}
+
+/// Converts a mean score into the corresponding Elo difference.
+fn elo_difference(score: f64) -> f64 {
+    let score = score.clamp(0.001, 0.999);
+    -400.0 * (1.0 / score - 1.0).log10()
+}
+
+/// Plays the candidate strategy against a pinned reference
+/// strategy for a number of games and reports its score.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("n", "games", "The number of games, alternating the colors (20 by default)", "N");
+    options.optopt("c", "candidate", "The strategy under test ('alphabeta' by default)", "STRATEGY");
+    options.optopt("r", "reference", "The reference strategy ('greedy' by default)", "STRATEGY");
+    options.optopt("S", "seed", "Seeds the candidate (randomly seeded by default)", "SEED");
+    options.optopt("R", "reference-seed", "Seeds the reference, incremented per game (0 by default)", "SEED");
+    options.optopt("m", "move-time", "The soft time limit per move in milliseconds (200 by default)", "MS");
+    options.optflag("v", "verbose", "Prints every move");
+    options.optflag("j", "json", "Prints the final statistics as JSON (and the progress to stderr)");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} gauntlet", program), &options);
+        return Ok(());
+    }
+
+    let games = parse_opt::<u32>(&parsed_args, "games", "a number of games")?.unwrap_or(20);
+    let candidate = parse_opt(&parsed_args, "candidate", STRATEGY_NAMES)?.unwrap_or(Strategy::AlphaBeta);
+    let reference = parse_opt(&parsed_args, "reference", STRATEGY_NAMES)?.unwrap_or(Strategy::Greedy);
+    let seed = parse_opt::<u64>(&parsed_args, "seed", "a non-negative number")?;
+    let reference_seed = parse_opt::<u64>(&parsed_args, "reference-seed", "a non-negative number")?.unwrap_or(0);
+    let move_time = Duration::from_millis(parse_opt::<u64>(&parsed_args, "move-time", "a number of milliseconds")?.unwrap_or(200));
+    let time_settings = TimeSettings::new(move_time, move_time * 5);
+    let verbose = parsed_args.opt_present("verbose");
+    let json = parsed_args.opt_present("json");
+
+    let mut stats = GauntletStats::default();
+    for game in 0..games {
+        let candidate_color = if game % 2 == 0 { PlayerColor::Red } else { PlayerColor::Blue };
+        let mut players = [
+            (candidate_color, OwnGameLogic::new(candidate, seed.map(|s| s.wrapping_add(u64::from(game))))),
+            (candidate_color.opponent(), OwnGameLogic::new(reference, Some(reference_seed.wrapping_add(u64::from(game)))))
+        ];
+        for (_, logic) in players.iter_mut() {
+            logic.on_time_settings(&time_settings);
+        }
+        let state = GameState::initial(PlayerColor::Red);
+        let max_turns = state.rules().turn_limit();
+        let (state, end) = play_local_game(state, &mut players, max_turns, verbose)?;
+        match end.winner {
+            Some(winner) if winner == candidate_color => stats.wins += 1,
+            Some(_) => stats.losses += 1,
+            None => stats.draws += 1
+        }
+        let line = format!("Game {:>3} as {:?}: {} after {} turns (+{} ={} -{})", game + 1, candidate_color, end.description, state.turn, stats.wins, stats.draws, stats.losses);
+        // Keeps stdout parseable in JSON mode
+        if json {
+            eprintln!("{}", line);
+        } else {
+            println!("{}", line);
+        }
+    }
+
+    let (low, high) = stats.confidence_interval();
+    if json {
+        println!("{}", serde_json::json!({
+            "candidate": candidate.to_string(),
+            "reference": reference.to_string(),
+            "games": stats.games(),
+            "wins": stats.wins,
+            "draws": stats.draws,
+            "losses": stats.losses,
+            "score": stats.score(),
+            "scoreInterval": [low, high],
+            "eloDifference": elo_difference(stats.score())
+        }));
+    } else {
+        println!("{} vs {}: score {:.1}% (95% CI {:.1}%..{:.1}%), Elo difference {:+.0} ({:+.0}..{:+.0})",
+            candidate, reference, stats.score() * 100.0, low * 100.0, high * 100.0,
+            elo_difference(stats.score()), elo_difference(low), elo_difference(high));
+    }
+    Ok(())
+}
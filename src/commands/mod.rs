@@ -0,0 +1,158 @@
+//! The subcommands of the binary.
+
+mod ab_test;
+pub mod analyze;
+pub mod bench;
+pub mod book;
+mod config;
+pub mod doctor;
+pub mod dump;
+mod event_log;
+pub mod gauntlet;
+pub mod perft;
+pub mod play;
+mod profile;
+pub mod puzzles;
+pub mod repl;
+pub mod replaydb;
+pub mod selfplay;
+pub mod selftest;
+mod report;
+mod summary;
+
+use std::fmt;
+use std::str::FromStr;
+use getopts::{Matches, Options};
+use socha_client_2020::util::SCError;
+
+pub use ab_test::*;
+pub use config::*;
+pub use event_log::*;
+pub use profile::*;
+pub use report::*;
+pub use summary::*;
+
+/// The available subcommands with short descriptions.
+pub const SUBCOMMANDS: &[(&str, &str)] = &[
+    ("play", "Connects to the game server and plays a game (default)"),
+    ("analyze", "Evaluates a position and suggests moves"),
+    ("repl", "Opens an interactive shell for exploring positions"),
+    ("selfplay", "Plays a game between two strategies without a server"),
+    ("gauntlet", "Plays a strategy against a pinned reference and reports its score"),
+    ("selftest", "Plays a game between two local clients on a running server"),
+    ("replaydb", "Indexes the positions of recorded games and lists their moves"),
+    ("book", "Builds ('book build') or inspects ('book probe') opening books"),
+    ("puzzles", "Extracts positions with a forced win from replays or solves them"),
+    ("dump", "Converts recorded XML into a compact binary dump (or back)"),
+    ("perft", "Counts the move tree of a position to verify the move generator"),
+    ("bench", "Measures the throughput of the rules engine"),
+    ("doctor", "Checks the connection, the XML handling and the rules engine")
+];
+
+/// A description of the accepted strategy names for error messages.
+pub const STRATEGY_NAMES: &str = "'random', 'greedy', 'alphabeta' or 'mcts'";
+
+/// A description of the accepted tie-breaking policies for error messages.
+pub const TIE_BREAK_NAMES: &str = "'canonical', 'random' or 'quiet'";
+
+/// A description of the accepted A/B assignments for error messages.
+pub const AB_ASSIGNMENT_NAMES: &str = "'alternate' or 'color'";
+
+/// A description of the accepted preflight modes for error messages.
+pub const PREFLIGHT_NAMES: &str = "'substitute' or 'log'";
+
+/// A description of the accepted profile names for error messages.
+pub const PROFILE_NAMES: &str = "'contest' or 'dev'";
+
+/// The exit status of the binary.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExitStatus {
+    /// The command succeeded (or the game was won).
+    Success = 0,
+    /// The command failed.
+    Failure = 1,
+    /// The command was invoked incorrectly.
+    Usage = 2,
+    /// The game was lost.
+    Loss = 3,
+    /// The game ended in a draw.
+    Draw = 4,
+    /// The server sent unexpected messages or none at all.
+    ProtocolError = 5,
+    /// The connection to the server failed.
+    ConnectionError = 6
+}
+
+/// An error that aborts a subcommand.
+#[derive(Debug)]
+pub enum CommandError {
+    /// The command was invoked incorrectly.
+    Usage(String),
+    /// The command failed while running.
+    Failure(SCError)
+}
+
+impl CommandError {
+    /// The exit status corresponding to this error.
+    pub fn exit_status(&self) -> ExitStatus {
+        match self {
+            Self::Usage(_) => ExitStatus::Usage,
+            Self::Failure(_) => ExitStatus::Failure
+        }
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Usage(e) => write!(f, "{}", e),
+            Self::Failure(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+impl<E> From<E> for CommandError where E: Into<SCError> {
+    fn from(error: E) -> Self { Self::Failure(error.into()) }
+}
+
+/// The result of running a subcommand.
+pub type CommandResult<T = ()> = Result<T, CommandError>;
+
+/// Parses the command line arguments, turning
+/// invalid ones into usage errors.
+pub fn parse_args(options: &Options, args: &[String]) -> CommandResult<Matches> {
+    options.parse(args).map_err(|e| CommandError::Usage(e.to_string()))
+}
+
+/// Parses the value of an option, if present, describing
+/// the expected value in the error message otherwise.
+pub fn parse_opt<T>(args: &Matches, name: &str, expected: &str) -> CommandResult<Option<T>> where T: FromStr {
+    args.opt_str(name)
+        .map(|raw| raw.parse().map_err(|_| CommandError::Usage(format!("Invalid value '{}' for --{} (expected {})", raw, name, expected))))
+        .transpose()
+}
+
+/// Ensures that at most one of the given options is present.
+pub fn exclusive(args: &Matches, names: &[&str]) -> CommandResult<()> {
+    let present: Vec<_> = names.iter().filter(|n| args.opt_present(n)).map(|n| format!("--{}", n)).collect();
+    if present.len() > 1 {
+        Err(CommandError::Usage(format!("The options {} cannot be used together", present.join(" and "))))
+    } else {
+        Ok(())
+    }
+}
+
+/// Prints the usage info of a (sub)command.
+pub fn print_usage(program: &str, options: &Options) {
+    let brief = format!("Usage: {} [options]", program);
+    print!("{}", options.usage(&brief));
+}
+
+/// Prints the available subcommands.
+pub fn print_subcommands(program: &str) {
+    println!("Usage: {} [SUBCOMMAND] [options]\n\nSubcommands:", program);
+    for (name, description) in SUBCOMMANDS {
+        println!("    {:<10} {}", name, description);
+    }
+    println!("\nUse '{} SUBCOMMAND --help' for the options of a subcommand.", program);
+}
@@ -0,0 +1,54 @@
+use std::time::Instant;
+use getopts::Options;
+use socha_client_2020::game::GameState;
+use socha_client_2020::util::SCResult;
+use super::analyze::load_state_or_initial;
+use super::{print_usage, parse_args, parse_opt, CommandResult};
+
+/// Counts the leaves of the move tree of the given depth.
+pub fn perft(state: &GameState, depth: usize) -> SCResult<u64> {
+    if depth == 0 {
+        return Ok(1);
+    }
+    let moves = state.possible_moves(state.current_player_color);
+    if depth == 1 {
+        return Ok(moves.len() as u64);
+    }
+    moves.iter().map(|m| perft(&state.apply_move(m)?, depth - 1)).sum()
+}
+
+/// Counts the positions reachable from a given position, e.g.
+/// for validating the move generator against other implementations.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("s", "state", "An XML file containing the root <state> (the initial state by default)", "FILE");
+    options.optopt("d", "depth", "The maximum depth (2 by default)", "DEPTH");
+    options.optflag("D", "divide", "Prints the counts below each root move at the maximum depth");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} perft", program), &options);
+        return Ok(());
+    }
+
+    let state = load_state_or_initial(parsed_args.opt_str("state").as_deref())?;
+    let depth = parse_opt::<usize>(&parsed_args, "depth", "a number of plies")?.unwrap_or(2);
+
+    for d in 1..=depth {
+        let start = Instant::now();
+        let nodes = perft(&state, d)?;
+        let seconds = start.elapsed().as_secs_f64();
+        println!("perft({}) = {} ({:.3}s, {:.0} nodes/s)", d, nodes, seconds, nodes as f64 / seconds.max(f64::EPSILON));
+    }
+
+    if parsed_args.opt_present("divide") && depth > 0 {
+        let mut moves = state.possible_moves(state.current_player_color);
+        moves.sort();
+        for game_move in moves {
+            println!("{}: {}", game_move, perft(&state.apply_move(&game_move)?, depth - 1)?);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,61 @@
+use std::fmt;
+use std::str::FromStr;
+use socha_client_2020::util::{SCError, SCResult};
+use super::ClientConfig;
+
+/// A bundle of defaults for the `play` subcommand, which
+/// only applies to settings that are not configured otherwise.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Profile {
+    /// Conservative settings for constrained tournament hardware:
+    /// a small transposition table, a generous safety margin
+    /// for the network and only warnings in the log.
+    Contest,
+    /// Settings for local development: a large transposition
+    /// table, verbose logging, strict parsing and game reports.
+    Dev
+}
+
+impl Profile {
+    /// Fetches the settings bundled by the profile.
+    pub fn defaults(self) -> ClientConfig {
+        match self {
+            Self::Contest => ClientConfig {
+                hash_size_mb: Some(4),
+                move_time_percent: Some(50),
+                level: Some("Warn".to_owned()),
+                strict: Some(false),
+                ..ClientConfig::default()
+            },
+            Self::Dev => ClientConfig {
+                hash_size_mb: Some(64),
+                move_time_percent: Some(75),
+                level: Some("Debug".to_owned()),
+                strict: Some(true),
+                report_dir: Some("reports".to_owned()),
+                ..ClientConfig::default()
+            }
+        }
+    }
+}
+
+impl FromStr for Profile {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "contest" => Ok(Self::Contest),
+            "dev" => Ok(Self::Dev),
+            _ => Err(format!("Did not recognize profile {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for Profile {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Contest => write!(f, "contest"),
+            Self::Dev => write!(f, "dev")
+        }
+    }
+}
@@ -0,0 +1,79 @@
+use std::fs::{self, File};
+use std::io::{self, BufReader, Write};
+use std::time::Duration;
+use getopts::Options;
+use socha_client_2020::analysis::{Puzzle, attempt_puzzle, parse_puzzle_suite};
+use socha_client_2020::protocol::Replay;
+use socha_client_2020::util::BINARY_XML_EXTENSION;
+use super::{print_usage, parse_args, parse_opt, CommandError, CommandResult};
+
+/// Lets the engine solve each puzzle of the suite within
+/// the budget, returning an error if any puzzle is missed.
+fn solve_suite(path: &str, node_limit: u64, time_limit: Duration) -> CommandResult {
+    let puzzles = parse_puzzle_suite(&fs::read_to_string(path)?)?;
+    let mut missed = 0;
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let attempt = attempt_puzzle(puzzle, node_limit, time_limit);
+        let chosen = attempt.chosen.map(|m| m.to_string()).unwrap_or_else(|| "no move".to_owned());
+        println!("{:>3}. {} {} ({} nodes, {:.3}s)", i + 1, if attempt.solved { "solved" } else { "MISSED" }, chosen, attempt.nodes, attempt.elapsed.as_secs_f64());
+        missed += (!attempt.solved) as usize;
+    }
+    println!("Solved {}/{} puzzles", puzzles.len() - missed, puzzles.len());
+    if missed > 0 {
+        Err(format!("Missed {} puzzles", missed).into())
+    } else {
+        Ok(())
+    }
+}
+
+/// Extracts positions with a forced win from a directory
+/// of replays or lets the engine solve a suite of puzzles.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("r", "replays", "A directory of XML replays to search", "DIR");
+    options.optopt("p", "plies", "The maximum length of the forced win in plies (3 by default)", "N");
+    options.optopt("s", "suite", "A file of puzzles for the engine to solve instead", "FILE");
+    options.optopt("n", "nodes", "The node budget per puzzle when solving (500000 by default)", "N");
+    options.optopt("t", "time", "The time budget per puzzle in milliseconds when solving (5000 by default)", "MS");
+    options.optopt("o", "out", "The file to write the puzzles to, one per line (stdout by default)", "FILE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} puzzles", program), &options);
+        return Ok(());
+    }
+
+    if let Some(path) = parsed_args.opt_str("suite") {
+        let node_limit = parse_opt::<u64>(&parsed_args, "nodes", "a number of nodes")?.unwrap_or(500_000);
+        let time_limit = parse_opt::<u64>(&parsed_args, "time", "a number of milliseconds")?.unwrap_or(5000);
+        return solve_suite(&path, node_limit, Duration::from_millis(time_limit));
+    }
+
+    let dir = parsed_args.opt_str("replays").ok_or_else(|| CommandError::Usage("Please specify a replay directory using --replays or a puzzle suite using --suite".to_owned()))?;
+    let max_plies = parse_opt::<u32>(&parsed_args, "plies", "a number of plies")?.unwrap_or(3);
+    let mut out: Box<dyn Write> = match parsed_args.opt_str("out") {
+        Some(path) => Box::new(File::create(path)?),
+        None => Box::new(io::stdout())
+    };
+
+    let mut paths: Vec<_> = fs::read_dir(&dir)?.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    paths.retain(|p| p.extension().map(|e| e == "xml" || e == BINARY_XML_EXTENSION).unwrap_or(false));
+    paths.sort();
+    let mut count = 0;
+    for path in paths {
+        let replay: Replay = match File::open(&path).map_err(Into::into).and_then(|f| Replay::read_from(BufReader::new(f))) {
+            Ok(replay) => replay,
+            Err(e) => {
+                eprintln!("Skipping replay {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        for puzzle in Puzzle::extract(&replay.states, max_plies) {
+            writeln!(out, "{}", puzzle)?;
+            count += 1;
+        }
+    }
+    eprintln!("Found {} puzzles", count);
+    Ok(())
+}
@@ -0,0 +1,197 @@
+use std::io::{self, BufRead, Write};
+use std::mem;
+use getopts::Options;
+use socha_client_2020::analysis::hints;
+use socha_client_2020::eval::{Evaluator, HeuristicEvaluator};
+use socha_client_2020::game::{AxialCoords, GameState, Lineage, Move, PieceTracker, PlayerColor};
+use socha_client_2020::util::SCResult;
+use super::analyze::load_state;
+use super::{print_usage, parse_args, CommandResult};
+
+const HELP: &str = "Commands:
+  load FILE   Loads the first <state> from an XML file
+  new [COLOR] Starts a new game (with red to move by default)
+  board       Prints the board
+  field X,Y   Prints the piece stack at the given axial coordinates
+  moves       Lists the legal moves of the current player
+  play MOVE   Plays a move, given by its number in 'moves',
+              in compact form (e.g. 'RS@(0, 1)') or in notation
+  undo        Takes back the last move
+  history     Lists the moves played since loading
+  eval        Evaluates the position for the current player
+  hints [K]   Prints the K best moves (3 by default)
+  help        Prints this help
+  quit        Exits the shell";
+
+/// A position together with the moves played from it. The previous
+/// positions are kept as compact snapshots for undoing moves.
+struct Session {
+    state: GameState,
+    tracker: PieceTracker,
+    history: Vec<(Vec<u8>, Lineage, PieceTracker)>
+}
+
+impl Session {
+    fn new(state: GameState) -> Self {
+        let tracker = PieceTracker::from_board(&state.board);
+        Self { state: state.with_lineage(), tracker, history: Vec::new() }
+    }
+
+    fn state(&self) -> &GameState { &self.state }
+
+    fn tracker(&self) -> &PieceTracker { &self.tracker }
+
+    fn moves(&self) -> Vec<Move> {
+        let state = self.state();
+        let mut moves = state.possible_moves(state.current_player_color);
+        // Identical undeployed pieces yield repeated set moves
+        moves.dedup();
+        moves
+    }
+
+    /// Finds the legal move described by the given input.
+    fn find_move(&self, input: &str) -> SCResult<Move> {
+        let moves = self.moves();
+        if let Ok(i) = input.parse::<usize>() {
+            return moves.get(i.wrapping_sub(1)).cloned().ok_or_else(|| format!("There is no move number {}", i).into());
+        }
+        let normalize = |s: &str| s.chars().filter(|c| !c.is_whitespace()).collect::<String>().to_uppercase();
+        let input = normalize(input);
+        moves.into_iter()
+            .find(|m| normalize(&m.to_string()) == input || self.tracker().notation(m).map(|n| normalize(&n) == input).unwrap_or(false))
+            .ok_or_else(|| "Not a legal move (see 'moves')".into())
+    }
+
+    fn play(&mut self, game_move: &Move) -> SCResult<()> {
+        let mut tracker = self.tracker.clone();
+        tracker.apply(game_move)?;
+        let state = self.state.apply_move(game_move)?;
+        let snapshot = self.state.encode()?;
+        let previous = mem::replace(&mut self.state, state);
+        let previous_tracker = mem::replace(&mut self.tracker, tracker);
+        self.history.push((snapshot, previous.lineage().clone(), previous_tracker));
+        Ok(())
+    }
+
+    fn undo(&mut self) -> SCResult<bool> {
+        match self.history.pop() {
+            Some((snapshot, lineage, tracker)) => {
+                self.state = GameState::decode_with_rules(&snapshot, self.state.rules().clone())?.with_restored_lineage(lineage);
+                self.tracker = tracker;
+                Ok(true)
+            },
+            None => Ok(false)
+        }
+    }
+}
+
+/// Executes a single shell command, returning whether to continue.
+fn execute(session: &mut Option<Session>, line: &str) -> SCResult<bool> {
+    let (command, argument) = match line.trim().split_once(char::is_whitespace) {
+        Some((command, argument)) => (command, argument.trim()),
+        None => (line.trim(), "")
+    };
+    let evaluator = HeuristicEvaluator::default();
+
+    match (command, session.as_mut()) {
+        ("", _) => {},
+        ("quit" | "exit", _) => return Ok(false),
+        ("help", _) => println!("{}", HELP),
+        ("load", _) => {
+            *session = Some(Session::new(load_state(argument)?));
+            println!("Loaded position at turn {}", session.as_ref().unwrap().state().turn);
+        },
+        ("new", _) => {
+            let color = if argument.is_empty() { PlayerColor::Red } else { argument.parse()? };
+            *session = Some(Session::new(GameState::initial(color)));
+            println!("Started a new game with {:?} to move", color);
+        },
+        (_, None) => return Err("No position loaded (use 'load FILE' or 'new')".into()),
+        ("board", Some(session)) => {
+            let state = session.state();
+            println!("{}Turn {}, {:?} to move", state.board.display_with_axes(), state.turn, state.current_player_color);
+        },
+        ("field", Some(session)) => {
+            let coords: AxialCoords = argument.parse()?;
+            let field = session.state().board.field(coords).ok_or_else(|| format!("{} is not on the board", coords))?;
+            let stack = field.piece_stack().iter().map(|p| format!("{:?} {:?}", p.owner, p.piece_type)).collect::<Vec<_>>();
+            println!("{}: {}{}", coords, if stack.is_empty() { "empty".to_owned() } else { stack.join(", ") }, if field.is_obstructed() { " (obstructed)" } else { "" });
+        },
+        ("moves", Some(session)) => for (i, game_move) in session.moves().iter().enumerate() {
+            println!("{:>3}. {:<20} {}", i + 1, game_move.to_string(), session.tracker().notation(game_move)?);
+        },
+        ("play", Some(session)) => {
+            let game_move = session.find_move(argument)?;
+            session.play(&game_move)?;
+            println!("Played {}", game_move);
+        },
+        ("undo", Some(session)) => if session.undo()? {
+            println!("Took back the last move");
+        } else {
+            println!("Nothing to undo");
+        },
+        ("history", Some(session)) => {
+            let state = session.state();
+            if state.lineage().is_empty() {
+                println!("No moves played yet");
+            } else {
+                println!("{}", state.lineage());
+            }
+            if state.repetitions() > 0 {
+                println!("The position occurred {} time(s) before", state.repetitions());
+            }
+        },
+        ("eval", Some(session)) => {
+            let state = session.state();
+            let color = state.current_player_color;
+            for feature in evaluator.breakdown(state, color) {
+                println!("{:<24} {:>6.2} x {:>5.2} = {:+.2}", feature.name, feature.value, feature.weight, feature.weighted());
+            }
+            println!("Evaluation for {:?}: {:+.2}", color, evaluator.evaluate(state, color));
+        },
+        ("hints", Some(session)) => {
+            let k = if argument.is_empty() { 3 } else { argument.parse::<usize>()? };
+            let state = session.state();
+            for (i, hint) in hints(state, state.current_player_color, &evaluator, k).into_iter().enumerate() {
+                println!("{}. {}", i + 1, hint);
+            }
+        },
+        (command, _) => return Err(format!("Unknown command '{}' (see 'help')", command).into())
+    }
+
+    Ok(true)
+}
+
+/// Runs an interactive shell for exploring positions.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("s", "state", "An XML file containing the initial <state>", "FILE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} repl", program), &options);
+        println!("\n{}", HELP);
+        return Ok(());
+    }
+
+    let mut session = parsed_args.opt_str("state").map(|path| load_state(&path).map(Session::new)).transpose()?;
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+
+    loop {
+        print!("> ");
+        io::stdout().flush()?;
+        let line = match lines.next() {
+            Some(line) => line?,
+            None => break
+        };
+        match execute(&mut session, &line) {
+            Ok(true) => {},
+            Ok(false) => break,
+            Err(e) => println!("Error: {}", e)
+        }
+    }
+
+    Ok(())
+}
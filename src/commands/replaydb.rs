@@ -0,0 +1,46 @@
+use getopts::Options;
+use socha_client_2020::replaydb::ReplayDb;
+use super::analyze::load_state;
+use super::{print_usage, parse_args, CommandError, CommandResult};
+
+/// Builds a database of the positions in a directory
+/// of replays or queries the moves played in a position.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("i", "ingest", "A directory of XML replays to add to the database", "DIR");
+    options.optopt("d", "db", "The database file, which is loaded if it exists and saved after ingesting", "FILE");
+    options.optopt("s", "state", "An XML file containing a <state> whose moves are listed", "FILE");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} replaydb", program), &options);
+        return Ok(());
+    }
+
+    let db_path = parsed_args.opt_str("db");
+    let mut db = match &db_path {
+        Some(path) if std::path::Path::new(path).exists() => ReplayDb::load(path)?,
+        _ => ReplayDb::new()
+    };
+
+    if let Some(dir) = parsed_args.opt_str("ingest") {
+        let ingested = db.ingest_dir(&dir)?;
+        println!("Ingested {} replays, {} games with {} distinct positions in total", ingested, db.games(), db.len());
+        if let Some(path) = &db_path {
+            db.save(path)?;
+        }
+    } else if db_path.is_none() {
+        return Err(CommandError::Usage("Please specify replays using --ingest or a database using --db".to_owned()));
+    }
+
+    if let Some(path) = parsed_args.opt_str("state") {
+        let state = load_state(&path)?;
+        for (game_move, stats) in db.lookup(&state) {
+            let score = stats.score().map(|s| format!("{:.0}%", s * 100.0)).unwrap_or_else(|| "-".to_owned());
+            println!("{:<20} {:>6} plays, +{} ={} -{} ({})", game_move.to_string(), stats.plays, stats.wins, stats.draws, stats.losses, score);
+        }
+    }
+
+    Ok(())
+}
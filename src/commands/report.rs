@@ -0,0 +1,184 @@
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::eval::{Evaluator, HeuristicEvaluator};
+use socha_client_2020::game::{GameState, HivePlugin, Move, PieceTracker, PlayerColor};
+use socha_client_2020::protocol::{AnnotatedReplay, GameResult, MoveAnnotation, MoveDebugInfo, OpponentStats, RttStats, TimeSettings};
+use socha_client_2020::util::{BuildInfo, CancellationToken, SCResult, XmlNode};
+
+/// A move of either player as recorded in the report.
+#[derive(Debug, Clone)]
+pub struct ReportedMove {
+    pub turn: u32,
+    pub color: PlayerColor,
+    pub game_move: Move,
+    pub notation: String,
+    /// The evaluation of the resulting position from
+    /// the perspective of the moving player.
+    pub evaluation: f64,
+    /// The time spent choosing the move (only known for own moves).
+    pub thinking_time: Option<Duration>,
+    /// The metadata the engine attached to the move (only for own moves).
+    pub debug: Option<MoveDebugInfo>
+}
+
+impl From<&ReportedMove> for serde_json::Value {
+    fn from(reported: &ReportedMove) -> Self {
+        serde_json::json!({
+            "turn": reported.turn,
+            "color": serde_json::Value::from(reported.color),
+            "move": reported.notation,
+            "compact": reported.game_move.to_string(),
+            "evaluation": reported.evaluation,
+            "thinkingTimeMs": reported.thinking_time.map(|t| t.as_millis() as u64),
+            "debug": reported.debug.as_ref().map(serde_json::Value::from)
+        })
+    }
+}
+
+/// The moves of a game, reconstructed from the
+/// successive states received from the server.
+#[derive(Debug, Default)]
+pub struct GameReport {
+    pub moves: Vec<ReportedMove>,
+    last_state: Option<GameState>,
+    tracker: Option<PieceTracker>,
+    thinking_times: HashMap<u32, Duration>,
+    debug_infos: HashMap<u32, MoveDebugInfo>,
+    replay: AnnotatedReplay<HivePlugin>
+}
+
+impl GameReport {
+    /// Records the move leading to the given state, if it
+    /// directly succeeds the previously observed state.
+    fn observe(&mut self, state: &GameState) {
+        let tracker = self.tracker.get_or_insert_with(|| PieceTracker::from_board(&state.board));
+        if let Some(last) = self.last_state.as_ref().filter(|s| s.turn + 1 == state.turn) {
+            if let Some(game_move) = last.infer_move(state) {
+                let notation = tracker.notation(&game_move).unwrap_or_else(|_| game_move.to_string());
+                if tracker.apply(&game_move).is_err() {
+                    *tracker = PieceTracker::from_board(&state.board);
+                }
+                self.moves.push(ReportedMove {
+                    turn: last.turn,
+                    color: last.current_player_color,
+                    evaluation: HeuristicEvaluator::default().evaluate(state, last.current_player_color),
+                    thinking_time: self.thinking_times.get(&last.turn).cloned(),
+                    debug: self.debug_infos.get(&last.turn).cloned(),
+                    notation,
+                    game_move
+                });
+            }
+        }
+        self.last_state = Some(state.clone());
+        self.replay.push(state.clone(), None);
+    }
+
+    /// Attaches the annotation to the move chosen in the given turn.
+    fn annotate(&mut self, turn: u32, annotation: MoveAnnotation<Move>) {
+        if !annotation.debug.is_empty() {
+            self.debug_infos.insert(turn, annotation.debug.clone());
+        }
+        if let Some((_, slot)) = self.replay.entries.iter_mut().rev().find(|(s, _)| s.turn == turn) {
+            *slot = Some(annotation);
+        }
+    }
+
+    /// Creates the report, consisting of the build info, the given
+    /// configuration, the game summary and the recorded moves.
+    pub fn to_json(&self, config: serde_json::Value, summary: serde_json::Value) -> serde_json::Value {
+        serde_json::json!({
+            "build": serde_json::Value::from(BuildInfo::CURRENT),
+            "config": config,
+            "summary": summary,
+            "moves": self.moves.iter().map(serde_json::Value::from).collect::<Vec<_>>()
+        })
+    }
+
+    /// Writes the report and the annotated replay into new files in the
+    /// given directory (which is created if needed) and returns the report's path.
+    pub fn write_to_dir(&self, dir: impl AsRef<Path>, config: serde_json::Value, summary: serde_json::Value) -> SCResult<PathBuf> {
+        fs::create_dir_all(&dir)?;
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis()).unwrap_or(0);
+        let path = dir.as_ref().join(format!("game-{}.json", timestamp));
+        fs::write(&path, serde_json::to_string_pretty(&self.to_json(config, summary)).map_err(|e| e.to_string())?)?;
+        fs::write(dir.as_ref().join(format!("game-{}.replay.xml", timestamp)), XmlNode::from(self.replay.clone()).to_string())?;
+        Ok(path)
+    }
+}
+
+/// A delegate that records a game report and
+/// otherwise forwards to the wrapped delegate.
+pub struct ReportDelegate<D> {
+    delegate: D,
+    report: Rc<RefCell<GameReport>>
+}
+
+impl<D> ReportDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate, recording into the given report.
+    pub fn new(delegate: D, report: Rc<RefCell<GameReport>>) -> Self {
+        Self { delegate, report }
+    }
+}
+
+impl<D> SCClientDelegate for ReportDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.report.borrow_mut().observe(state);
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let start = Instant::now();
+        let game_move = self.delegate.request_move(state, my_color, cancel);
+        let mut report = self.report.borrow_mut();
+        report.thinking_times.insert(state.turn, start.elapsed());
+        if let Some(annotation) = self.delegate.move_annotation() {
+            report.annotate(state.turn, annotation);
+        }
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
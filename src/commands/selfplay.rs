@@ -0,0 +1,98 @@
+use getopts::Options;
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, PlayerColor};
+#[cfg(feature = "expansions")]
+use socha_client_2020::game::RuleSet;
+use socha_client_2020::logic::{OwnGameLogic, Strategy, TieBreak};
+use socha_client_2020::util::{CancellationToken, SCResult};
+use super::analyze::load_state_or_initial;
+#[cfg(feature = "expansions")]
+use super::exclusive;
+use super::{print_usage, parse_args, parse_opt, CommandResult, STRATEGY_NAMES, TIE_BREAK_NAMES};
+
+/// How a local game ended.
+pub struct LocalGameEnd {
+    /// The winner or `None` if the game ended in a draw.
+    pub winner: Option<PlayerColor>,
+    pub description: String
+}
+
+/// Plays a game between the given players from the given position
+/// until a bee is surrounded, the turn limit is reached or the
+/// player to move is stuck, returning the final state.
+pub fn play_local_game(mut state: GameState, players: &mut [(PlayerColor, OwnGameLogic)], max_turns: u32, verbose: bool) -> SCResult<(GameState, LocalGameEnd)> {
+    let end = |winner, description: String| LocalGameEnd { winner, description };
+    let end = loop {
+        let surrounded = [PlayerColor::Red, PlayerColor::Blue].iter().map(|&c| state.board.is_bee_surrounded(c)).collect::<Vec<_>>();
+        match surrounded.as_slice() {
+            [true, true] => break end(None, "Draw, both bees are surrounded".to_owned()),
+            [true, false] => break end(Some(PlayerColor::Blue), "Blue won by surrounding the red bee".to_owned()),
+            [false, true] => break end(Some(PlayerColor::Red), "Red won by surrounding the blue bee".to_owned()),
+            _ => {}
+        }
+        if state.turn >= max_turns {
+            break end(None, format!("Draw, turn limit of {} reached", max_turns));
+        }
+
+        let color = state.current_player_color;
+        if !state.has_any_legal_move(color) {
+            break end(None, format!("{:?} has no legal moves", color));
+        }
+        let (_, logic) = players.iter_mut().find(|(c, _)| *c == color).expect("No player for color");
+        let game_move = logic.request_move(&state, color, &CancellationToken::new());
+        if verbose {
+            println!("{:>3}. {:?}: {}", state.turn, color, game_move);
+        }
+        state = state.apply_move(&game_move)?;
+        logic.on_update_state(&state, Some(color));
+    };
+    Ok((state, end))
+}
+
+/// Plays a game between two strategies locally,
+/// starting from a given position.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("s", "state", "An XML file containing the starting <state> (the initial state by default)", "FILE");
+    options.optopt("1", "first", "The strategy of the player to move first ('greedy' by default)", "STRATEGY");
+    options.optopt("2", "second", "The strategy of the other player ('random' by default)", "STRATEGY");
+    options.optopt("S", "seed", "Seeds the first player (the second one uses SEED + 1)", "SEED");
+    options.optopt("", "tie-break", "Chooses between equally rated moves ('canonical', 'random' or 'quiet', 'random' by default)", "POLICY");
+    options.optopt("t", "max-turns", "The turn after which the game ends in a draw (the turn limit of the rules by default)", "TURNS");
+    #[cfg(feature = "expansions")]
+    options.optflag("x", "expansions", "Starts both players with the expansion pieces (Mosquito, Ladybug and Pillbug)");
+    options.optflag("v", "verbose", "Prints every move");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} selfplay", program), &options);
+        return Ok(());
+    }
+
+    #[cfg(feature = "expansions")]
+    exclusive(&parsed_args, &["state", "expansions"])?;
+    let state = load_state_or_initial(parsed_args.opt_str("state").as_deref())?;
+    #[cfg(feature = "expansions")]
+    let state = if parsed_args.opt_present("expansions") { GameState::initial_with_rules(PlayerColor::Red, RuleSet::with_expansions()) } else { state };
+    let strategies = [
+        parse_opt(&parsed_args, "first", STRATEGY_NAMES)?.unwrap_or(Strategy::Greedy),
+        parse_opt(&parsed_args, "second", STRATEGY_NAMES)?.unwrap_or(Strategy::Random)
+    ];
+    let seed = parse_opt::<u64>(&parsed_args, "seed", "a non-negative number")?;
+    let tie_break = parse_opt(&parsed_args, "tie-break", TIE_BREAK_NAMES)?.unwrap_or(TieBreak::Random);
+    let max_turns = parse_opt::<u32>(&parsed_args, "max-turns", "a number of turns")?.unwrap_or_else(|| state.rules().turn_limit());
+    let verbose = parsed_args.opt_present("verbose");
+
+    let first_color = state.current_player_color;
+    let mut players = [
+        (first_color, OwnGameLogic::new(strategies[0], seed).with_tie_break(tie_break)),
+        (first_color.opponent(), OwnGameLogic::new(strategies[1], seed.map(|s| s + 1)).with_tie_break(tie_break))
+    ];
+    println!("{:?} ({}) vs {:?} ({})", players[0].0, strategies[0], players[1].0, strategies[1]);
+
+    let (state, end) = play_local_game(state, &mut players, max_turns, verbose)?;
+    println!("{}", state.board);
+    println!("{} after {} turns", end.description, state.turn);
+    Ok(())
+}
@@ -0,0 +1,127 @@
+use std::str::FromStr;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use simplelog::{SimpleLogger, Config};
+use log::LevelFilter;
+use getopts::Options;
+use socha_client_2020::client::{SCClient, SCClientDelegate, DebugMode};
+use socha_client_2020::game::{GameState, Move, PlayerColor};
+use socha_client_2020::logic::{OwnGameLogic, Strategy};
+use socha_client_2020::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats};
+use socha_client_2020::util::CancellationToken;
+use super::{print_usage, parse_args, parse_opt, CommandError, CommandResult, STRATEGY_NAMES};
+
+/// The outcome of a game from one client's perspective.
+struct Report {
+    client: usize,
+    color: Option<PlayerColor>,
+    result: GameResult
+}
+
+/// Wraps a client's logic and reports the game's outcome.
+struct Reporter {
+    client: usize,
+    logic: OwnGameLogic,
+    sender: Sender<Report>
+}
+
+impl SCClientDelegate for Reporter {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.logic.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        let _ = self.sender.send(Report { client: self.client, color: my_color, result });
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.logic.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.logic.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.logic.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        self.logic.request_move(state, my_color, cancel)
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.logic.move_annotation()
+    }
+}
+
+/// Plays a game between two local clients on a
+/// running game server and reports the result.
+pub fn run(program: &str, args: &[String]) -> CommandResult {
+    let mut options = Options::new();
+    options.optopt("h", "host", "The game server's host address", "HOST");
+    options.optopt("p", "port", "The game server's port", "PORT");
+    options.optopt("1", "first", "The first client's strategy ('greedy' by default)", "STRATEGY");
+    options.optopt("2", "second", "The second client's strategy ('random' by default)", "STRATEGY");
+    options.optopt("S", "seed", "Seeds the first client (the second one uses SEED + 1)", "SEED");
+    options.optopt("l", "level", "Optionally provides a custom log level ('Warn' by default)", "LEVEL");
+    options.optflag("H", "help", "Prints usage info");
+
+    let parsed_args = parse_args(&options, args)?;
+    if parsed_args.opt_present("help") {
+        print_usage(&format!("{} selftest", program), &options);
+        return Ok(());
+    }
+
+    let host = parsed_args.opt_str("host").unwrap_or("localhost".to_owned());
+    let port = parse_opt(&parsed_args, "port", "a number between 0 and 65535")?.unwrap_or(13050);
+    let strategies = [
+        parse_opt(&parsed_args, "first", STRATEGY_NAMES)?.unwrap_or(Strategy::Greedy),
+        parse_opt(&parsed_args, "second", STRATEGY_NAMES)?.unwrap_or(Strategy::Random)
+    ];
+    let seed = parse_opt::<u64>(&parsed_args, "seed", "a non-negative number")?;
+    let level = parsed_args.opt_str("level").unwrap_or("Warn".to_owned());
+    let level = LevelFilter::from_str(&level).map_err(|_| CommandError::Usage(format!("Invalid log level '{}' (expected e.g. 'Info' or 'Debug')", level)))?;
+
+    SimpleLogger::init(level, Config::default()).map_err(|e| format!("Could not initialize logger: {}", e))?;
+
+    let (sender, receiver) = mpsc::channel();
+    let clients: Vec<_> = strategies.iter().enumerate().map(|(i, &strategy)| {
+        let delegate = Reporter {
+            client: i + 1,
+            logic: OwnGameLogic::new(strategy, seed.map(|s| s + i as u64)),
+            sender: sender.clone()
+        };
+        let host = host.clone();
+        thread::spawn(move || {
+            let mut client = SCClient::new(delegate, DebugMode { debug_reader: false, debug_writer: false });
+            client.run(&host, port, None)
+        })
+    }).collect();
+    drop(sender);
+
+    for (i, client) in clients.into_iter().enumerate() {
+        match client.join() {
+            Ok(Err(e)) => eprintln!("Client {} failed: {}", i + 1, e),
+            Err(_) => eprintln!("Client {} panicked", i + 1),
+            Ok(Ok(outcome)) if !outcome.end.is_completed() => eprintln!("Client {} ended early: {}", i + 1, outcome.end),
+            Ok(Ok(_)) => {}
+        }
+    }
+
+    let reports: Vec<_> = receiver.iter().collect();
+    let report = reports.first().ok_or("The game did not finish")?;
+    println!("Client 1 ({}) vs client 2 ({})", strategies[0], strategies[1]);
+    for (color, score) in [PlayerColor::Red, PlayerColor::Blue].iter().zip(&report.result.scores) {
+        println!("{:?}: {:?} {}", color, score.cause, score.reason);
+    }
+    match report.result.winners.as_slice() {
+        [] => println!("Draw"),
+        winners => for winner in winners {
+            let client = reports.iter().find(|r| r.color == Some(winner.color)).map(|r| format!(" (client {})", r.client)).unwrap_or_default();
+            println!("{:?} won{}", winner.color, client);
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,139 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, Move, PlayerColor};
+use socha_client_2020::protocol::{GameOutcome, GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings, TurnClock};
+use socha_client_2020::util::{CancellationToken, SCError};
+use super::ExitStatus;
+
+/// What the client observed of a game.
+#[derive(Debug, Default)]
+pub struct GameSummary {
+    pub color: Option<PlayerColor>,
+    pub turn: Option<u32>,
+    pub result: Option<GameResult>,
+    pub clock: TurnClock
+}
+
+impl GameSummary {
+    /// Takes over the outcome reported by the client, which
+    /// supersedes what the delegate observed so far.
+    pub fn record(&mut self, outcome: GameOutcome) {
+        self.color = outcome.color.or(self.color);
+        self.turn = Some(outcome.turns);
+        self.result = outcome.result.or(self.result.take());
+    }
+
+    /// Determines the exit status of a client that
+    /// finished its game with the given error, if any.
+    pub fn exit_status(&self, error: Option<&SCError>) -> ExitStatus {
+        match (error, &self.result) {
+            (Some(SCError::Io(_) | SCError::Disconnected), _) => ExitStatus::ConnectionError,
+            (Some(_), _) | (None, None) => ExitStatus::ProtocolError,
+            (None, Some(result)) => match result.winners.as_slice() {
+                [] => ExitStatus::Draw,
+                winners if winners.iter().any(|w| Some(w.color) == self.color) => ExitStatus::Success,
+                _ => ExitStatus::Loss
+            }
+        }
+    }
+
+    /// Creates a one-line, machine-readable summary.
+    pub fn to_json(&self, error: Option<&SCError>) -> serde_json::Value {
+        let outcome = match self.exit_status(error) {
+            ExitStatus::Success => "win",
+            ExitStatus::Loss => "loss",
+            ExitStatus::Draw => "draw",
+            ExitStatus::ConnectionError => "connectionError",
+            _ => "protocolError"
+        };
+        serde_json::json!({
+            "outcome": outcome,
+            "color": self.color.map(serde_json::Value::from),
+            "turn": self.turn,
+            "winners": self.result.iter().flat_map(|r| r.winners.iter().map(serde_json::Value::from)).collect::<Vec<_>>(),
+            "termination": self.result.as_ref().map(|r| {
+                let termination = r.termination();
+                serde_json::json!({ "cause": format!("{:?}", termination.cause), "reason": termination.reason })
+            }),
+            "scores": self.result.iter().flat_map(|r| r.scores.iter().map(|s| serde_json::json!({
+                "cause": format!("{:?}", s.cause),
+                "reason": s.reason
+            }))).collect::<Vec<_>>(),
+            "moves": self.clock.moves(),
+            "thinkingTimeMs": self.clock.thinking_time().as_millis() as u64,
+            "longestTurnMs": self.clock.longest_turn().as_millis() as u64,
+            "meanRttMs": self.clock.rtt().mean().map(|d| d.as_millis() as u64),
+            "maxRttMs": self.clock.rtt().max().map(|d| d.as_millis() as u64),
+            "error": error.map(|e| e.to_string())
+        })
+    }
+}
+
+/// A delegate that records a game summary and
+/// otherwise forwards to the wrapped delegate.
+pub struct SummaryDelegate<D> {
+    delegate: D,
+    summary: Rc<RefCell<GameSummary>>
+}
+
+impl<D> SummaryDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate, recording into the given summary.
+    pub fn new(delegate: D, summary: Rc<RefCell<GameSummary>>) -> Self {
+        Self { delegate, summary }
+    }
+}
+
+impl<D> SCClientDelegate for SummaryDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.summary.borrow_mut().turn = Some(state.turn);
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.summary.borrow_mut().result = Some(result.clone());
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.summary.borrow_mut().color = Some(*color);
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        self.delegate.request_move(state, my_color, cancel)
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
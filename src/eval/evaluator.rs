@@ -0,0 +1,27 @@
+use crate::game::{GameState, PlayerColor};
+use super::Feature;
+
+/// A static evaluation function that rates
+/// game states from a player's perspective.
+pub trait Evaluator {
+    /// Evaluates the state from the perspective of the
+    /// given color. Higher values are better for that color.
+    fn evaluate(&self, state: &GameState, color: PlayerColor) -> f64;
+
+    /// Explains the evaluation by the features it is composed
+    /// of, whose weighted values should sum up to the evaluation.
+    /// Evaluators without separable features return no breakdown.
+    fn breakdown(&self, _state: &GameState, _color: PlayerColor) -> Vec<Feature> {
+        Vec::new()
+    }
+}
+
+impl<E> Evaluator for &E where E: Evaluator + ?Sized {
+    fn evaluate(&self, state: &GameState, color: PlayerColor) -> f64 {
+        (**self).evaluate(state, color)
+    }
+
+    fn breakdown(&self, state: &GameState, color: PlayerColor) -> Vec<Feature> {
+        (**self).breakdown(state, color)
+    }
+}
@@ -0,0 +1,15 @@
+/// A single, weighted feature of a position.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Feature {
+    /// A short, human-readable name of the feature.
+    pub name: &'static str,
+    /// The raw value of the feature.
+    pub value: f64,
+    /// The weight with which the feature enters the evaluation.
+    pub weight: f64
+}
+
+impl Feature {
+    /// The value multiplied by the feature's weight.
+    pub fn weighted(&self) -> f64 { self.value * self.weight }
+}
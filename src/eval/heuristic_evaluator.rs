@@ -0,0 +1,69 @@
+use crate::game::{Board, GameState, PieceType, PlayerColor, SearchContext};
+use super::{Evaluator, Feature};
+
+/// A hand-written evaluation function based on
+/// a small set of weighted features, most importantly
+/// the number of pieces surrounding each bee.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HeuristicEvaluator {
+    pub opponent_bee_neighbors_weight: f64,
+    pub own_bee_neighbors_weight: f64,
+    pub movable_pieces_weight: f64,
+    pub deployed_pieces_weight: f64,
+    pub bee_placed_weight: f64
+}
+
+impl HeuristicEvaluator {
+    /// Computes the (weighted) features of the state from
+    /// the perspective of the given color.
+    pub fn features(&self, state: &GameState, color: PlayerColor) -> Vec<Feature> {
+        let opponent = color.opponent();
+        let deployed = |c: PlayerColor| state.board.fields().flat_map(|(_, f)| f.piece_stack()).filter(|p| p.owner == c).count() as f64;
+        vec![
+            Feature { name: "opponent bee neighbors", value: bee_neighbors(&state.board, opponent), weight: self.opponent_bee_neighbors_weight },
+            Feature { name: "own bee neighbors", value: bee_neighbors(&state.board, color), weight: self.own_bee_neighbors_weight },
+            Feature { name: "movable pieces", value: movable_pieces(&state.board, color) - movable_pieces(&state.board, opponent), weight: self.movable_pieces_weight },
+            Feature { name: "deployed pieces", value: deployed(color) - deployed(opponent), weight: self.deployed_pieces_weight },
+            Feature { name: "bee placed", value: if state.board.has_placed_bee(color) { 1.0 } else { 0.0 }, weight: self.bee_placed_weight }
+        ]
+    }
+}
+
+/// Counts the occupied fields next to the bee of the given color.
+fn bee_neighbors(board: &Board, color: PlayerColor) -> f64 {
+    board.fields()
+        .find(|(_, f)| f.piece_stack().iter().any(|p| p.owner == color && p.piece_type == PieceType::Bee))
+        .map(|(c, _)| board.neighbors(c).filter(|(_, f)| f.is_occupied()).count() as f64)
+        .unwrap_or(0.0)
+}
+
+/// Counts the pieces of the given color that could be
+/// removed without disconnecting the swarm.
+fn movable_pieces(board: &Board, color: PlayerColor) -> f64 {
+    let mut ctx = SearchContext::new();
+    board.fields_owned_by(color)
+        .filter(|&(c, _)| board.is_swarm_connected_except(&mut ctx, Some(c)))
+        .count() as f64
+}
+
+impl Default for HeuristicEvaluator {
+    fn default() -> Self {
+        Self {
+            opponent_bee_neighbors_weight: 3.0,
+            own_bee_neighbors_weight: -3.0,
+            movable_pieces_weight: 0.5,
+            deployed_pieces_weight: 0.2,
+            bee_placed_weight: 1.0
+        }
+    }
+}
+
+impl Evaluator for HeuristicEvaluator {
+    fn evaluate(&self, state: &GameState, color: PlayerColor) -> f64 {
+        self.features(state, color).iter().map(Feature::weighted).sum()
+    }
+
+    fn breakdown(&self, state: &GameState, color: PlayerColor) -> Vec<Feature> {
+        self.features(state, color)
+    }
+}
@@ -0,0 +1,9 @@
+//! Static evaluation of game states.
+
+mod evaluator;
+mod feature;
+mod heuristic_evaluator;
+
+pub use evaluator::*;
+pub use feature::*;
+pub use heuristic_evaluator::*;
@@ -1,10 +1,15 @@
-use std::{cmp::max, cmp::min, collections::HashMap, collections::HashSet, collections::VecDeque, convert::TryFrom, fmt, str::FromStr};
+use std::{cmp::max, cmp::min, collections::HashMap, convert::TryFrom, fmt, str::FromStr};
 
 use arrayvec::ArrayVec;
-use itertools::Itertools;
 use log::{debug, trace};
-use super::{Field, Piece, PieceType, PlayerColor, AxialCoords, CubeCoords, DoubledCoords};
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use super::{Adjacentable, BoundaryField, Field, Piece, PieceType, PlayerColor, AxialCoords, DoubledCoords, SearchContext};
+use crate::util::SCResult;
+#[cfg(feature = "protocol")]
+use super::CubeCoords;
+#[cfg(feature = "protocol")]
+use crate::util::{FromXmlNode, XmlElement, XmlNode, XmlNodeBuilder};
+#[cfg(feature = "protocol")]
+use super::PositionedField;
 
 /// The game board which is a symmetric hex grid with
 /// a side length of 6 fields.
@@ -37,10 +42,10 @@ impl Board {
                 .map(move |x| AxialCoords::new(x, y)));
         
         for coords in all_coords {
-            if !fields_mut.contains_key(&coords) {
-                fields_mut.insert(coords, Field::default());
+            fields_mut.entry(coords).or_insert_with(|| {
                 trace!("Filling up field at {}", coords);
-            }
+                Field::default()
+            });
         }
         
         let board = Self { fields: fields_mut };
@@ -156,11 +161,30 @@ impl Board {
         self.fields().filter(|(_, f)| f.is_occupied())
     }
     
-    /// Fetches empty fields connected to the swarm.
+    /// Fetches empty fields connected to the swarm. A field is
+    /// yielded once per adjacent occupied field, see
+    /// `swarm_boundary_fields` for a deduplicated variant.
     pub fn swarm_boundary(&self) -> impl Iterator<Item=(AxialCoords, &Field)> {
         self.fields().filter(|(_, f)| f.is_occupied())
             .flat_map(move |(c, _)| self.empty_neighbors(c))
     }
+
+    /// Fetches each empty field connected to the swarm once (sorted
+    /// by coordinates), along with the colors of adjacent pieces.
+    pub fn swarm_boundary_fields(&self) -> Vec<BoundaryField> {
+        let mut boundary: HashMap<AxialCoords, BoundaryField> = HashMap::new();
+        for (coords, field) in self.occupied_fields() {
+            for (neighbor, _) in self.empty_neighbors(coords) {
+                let boundary_field = boundary.entry(neighbor).or_insert_with(|| BoundaryField::new(neighbor));
+                if let Some(owner) = field.owner() {
+                    boundary_field.add_bordering(owner);
+                }
+            }
+        }
+        let mut fields: Vec<_> = boundary.into_values().collect();
+        fields.sort_by_key(|f| f.coords);
+        fields
+    }
     
     /// Fetches all fields.
     #[inline]
@@ -181,7 +205,7 @@ impl Board {
     
     /// Fetches the (existing) neighbor fields on the board.
     #[inline]
-    pub fn neighbors<'a>(&'a self, coords: impl Into<AxialCoords>) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn neighbors<'a>(&'a self, coords: impl Into<AxialCoords>) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         coords.into().coord_neighbors().into_iter().filter_map(move |c| self.field(c).map(|f| (c, f)))
     }
     
@@ -196,6 +220,26 @@ impl Board {
         self.fields().flat_map(|(_, f)| f.piece_stack()).any(|&p| p == bee)
     }
     
+    /// Tests whether the bee of the given color has been placed and
+    /// is blocked on all sides (by pieces, obstructions or the board's
+    /// edge), which means that the color has lost.
+    pub fn is_bee_surrounded(&self, color: PlayerColor) -> bool {
+        let bee = Piece { piece_type: PieceType::Bee, owner: color };
+        self.fields()
+            .find(|(_, f)| f.piece_stack().contains(&bee))
+            .map(|(c, _)| c.coord_neighbors().iter().all(|&n| self.field(n).map(|f| f.is_occupied()).unwrap_or(true)))
+            .unwrap_or(false)
+    }
+    
+    /// Counts the empty fields next to the bee of the given
+    /// color, if it has been placed.
+    pub fn bee_freedom(&self, color: PlayerColor) -> Option<usize> {
+        let bee = Piece { piece_type: PieceType::Bee, owner: color };
+        self.fields()
+            .find(|(_, f)| f.piece_stack().contains(&bee))
+            .map(|(c, _)| c.coord_neighbors().iter().filter(|&&n| self.field(n).map(|f| f.is_empty()).unwrap_or(false)).count())
+    }
+
     /// Tests whether the field at the given coordinates is next to
     /// a given color.
     pub fn is_next_to(&self, color: PlayerColor, coords: impl Into<AxialCoords>) -> bool {
@@ -216,44 +260,28 @@ impl Board {
         trace!("Fields owned by {:?}: {:#?}", color, self.fields_owned_by(color).collect::<Vec<_>>());
         trace!("Fields owned by {:?} (opponent): {:#?}", opponent, self.fields_owned_by(opponent).collect::<Vec<_>>());
 
-        self.fields_owned_by(color)
-            .flat_map(move |(c, _)| self.empty_neighbors(c))
-            .unique()
-            .filter_map(move |(c, _)| if self.is_next_to(opponent, c) { None } else {
-                trace!("SetMove destination {} does not touch an opponent's ({:?}'s) piece, neighbors: {:#?}", c, opponent, self.neighbors(c).collect::<Vec<_>>());
-                Some(c)
-            })
-    }
-    
-    /// Performs a depth-first search on the board's non-empty fields
-    /// starting at the given coordinates and removing visited
-    /// locations from the set.
-    fn dfs_swarm(&self, coords: AxialCoords, unvisited: &mut HashSet<AxialCoords>) {
-        if self.field(coords).filter(|f| f.has_pieces()).is_some() {
-            unvisited.remove(&coords);
-            for (neighbor, _) in self.neighbors(coords) {
-                if unvisited.contains(&neighbor) {
-                    self.dfs_swarm(neighbor, unvisited)
-                }
-            }
-        }
+        self.swarm_boundary_fields().into_iter()
+            .filter(move |f| f.borders(color) && !f.borders(opponent))
+            .map(|f| f.coords)
     }
     
     /// Tests whether a field satisfying the search condition can be
     /// reached by breadth-first searching the accessible fields.
-    fn bfs_accessible(&self, start: AxialCoords, search_condition: impl Fn(AxialCoords, &Field) -> bool) -> bool {
-        let mut queue = VecDeque::new();
-        let mut visited = HashSet::new();
-        queue.push_back(start);
+    fn bfs_accessible(&self, ctx: &mut SearchContext, start: AxialCoords, search_condition: impl Fn(AxialCoords, &Field) -> bool) -> bool {
+        ctx.queue.clear();
+        ctx.visited.clear();
+        ctx.queue.push_back(start);
+        ctx.visited.insert(start);
         
-        while let Some(coords) = queue.pop_front() {
-            visited.insert(coords);
-
+        while let Some(coords) = ctx.queue.pop_front() {
             if let Some(field) = self.field(coords) {
                 if search_condition(coords, field) {
                     return true;
-                } else {
-                    queue.extend(self.accessible_neighbors_except(Some(start), coords).filter_map(|(c, _)| if !visited.contains(&c) { Some(c) } else { None }));
+                }
+                for (neighbor, _) in self.accessible_neighbors_except(Some(start), coords) {
+                    if ctx.visited.insert(neighbor) {
+                        ctx.queue.push_back(neighbor);
+                    }
                 }
             }
         }
@@ -264,17 +292,23 @@ impl Board {
     /// Tests whether the given field can be reached in 3 moves
     /// by breadth-first searching the accessible fields.
     pub fn bfs_reachable_in_3_steps(&self, start: AxialCoords, destination: AxialCoords) -> bool {
-        let mut paths_queue: VecDeque<ArrayVec<AxialCoords, 3>> = VecDeque::new();
-        paths_queue.push_back({
+        self.bfs_reachable_in_3_steps_with(&mut SearchContext::new(), start, destination)
+    }
+    
+    /// Tests whether the given field can be reached in 3 moves
+    /// using the context's buffers.
+    pub fn bfs_reachable_in_3_steps_with(&self, ctx: &mut SearchContext, start: AxialCoords, destination: AxialCoords) -> bool {
+        ctx.paths.clear();
+        ctx.paths.push_back({
             let mut path = ArrayVec::new();
             path.push(start);
             path
         });
 
-        while let Some(path) = paths_queue.pop_front() {
+        while let Some(path) = ctx.paths.pop_front() {
             let mut neighbors = self.accessible_neighbors_except(Some(start), path.last().cloned().unwrap()).filter(|(c, _)| !path.contains(c));
             if path.len() < 3 {
-                paths_queue.extend(neighbors.map(|(c, _)| {
+                ctx.paths.extend(neighbors.map(|(c, _)| {
                     let mut next_path = path.clone();
                     next_path.push(c);
                     next_path
@@ -291,18 +325,21 @@ impl Board {
     /// optionally given an exception whose field won't be included
     /// if it contains exactly one piece.
     pub fn shared_neighbors(&self, a: impl Into<AxialCoords>, b: impl Into<AxialCoords>, exception: Option<AxialCoords>) -> Vec<(AxialCoords, &Field)> {
-        let a_neighbors: HashSet<_> = self.neighbors(a).collect();
-        let b_neighbors: HashSet<_> = self.neighbors(b).collect();
-        a_neighbors.intersection(&b_neighbors)
-            .filter(|(c, f)| f.piece_stack().len() != 1 || exception == Some(*c))
-            .cloned().collect()
+        self.shared_neighbors_iter(a.into(), b.into(), exception).collect()
+    }
+
+    /// Iterates the shared neighbors without allocating.
+    fn shared_neighbors_iter(&self, a: AxialCoords, b: AxialCoords, exception: Option<AxialCoords>) -> impl Iterator<Item=(AxialCoords, &Field)> {
+        self.neighbors(a)
+            .filter(move |&(c, _)| c != b && c.is_adjacent_to(b))
+            .filter(move |(c, f)| f.piece_stack().len() != 1 || exception == Some(*c))
     }
     
     /// Tests whether a move between the given two
     /// locations is possible, optionally given an
     /// exception.
     pub fn can_move_between_except(&self, exception: Option<AxialCoords>, a: impl Into<AxialCoords>, b: impl Into<AxialCoords>) -> bool {
-        let shared = self.shared_neighbors(a, b, exception);
+        let shared: ArrayVec<_, 6> = self.shared_neighbors_iter(a.into(), b.into(), exception).collect();
         (shared.len() == 1 || shared.iter().any(|(_, f)| f.is_empty())) && shared.iter().any(|(_, f)| f.has_pieces())
     }
     
@@ -313,52 +350,101 @@ impl Board {
     }
     
     /// Finds the accessible neighbors, optionally except an ignored field.
-    pub fn accessible_neighbors_except<'a>(&'a self, exception: Option<AxialCoords>, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn accessible_neighbors_except<'a>(&'a self, exception: Option<AxialCoords>, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         self.neighbors(coords).filter(move |(c, f)| f.is_empty() && self.can_move_between_except(exception, coords, *c))
     }
     
     /// Finds the accessible neighbors.
-    pub fn accessible_neighbors<'a>(&'a self, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &Field)> + 'a {
+    pub fn accessible_neighbors<'a>(&'a self, coords: impl Into<AxialCoords> + Copy + 'a) -> impl Iterator<Item=(AxialCoords, &'a Field)> + 'a {
         self.neighbors(coords).filter(move |(c, f)| f.is_empty() && self.can_move_between(coords, *c))
     }
     
     /// Tests whether two coordinates are connected by a path
     /// along the swarm's boundary.
     pub fn connected_by_boundary_path(&self, start_coords: impl Into<AxialCoords>, destination_coords: impl Into<AxialCoords>) -> bool {
+        self.connected_by_boundary_path_with(&mut SearchContext::new(), start_coords, destination_coords)
+    }
+
+    /// Tests whether two coordinates are connected by a path
+    /// along the swarm's boundary using the context's buffers.
+    pub fn connected_by_boundary_path_with(&self, ctx: &mut SearchContext, start_coords: impl Into<AxialCoords>, destination_coords: impl Into<AxialCoords>) -> bool {
         let start = start_coords.into();
         let destination = destination_coords.into();
-        self.bfs_accessible(start, |c, _| c == destination)
+        self.bfs_accessible(ctx, start, |c, _| c == destination)
     }
     
     /// Performs a depth-first search on the board at the given
     /// position to test whether the swarm is connected.
     pub fn is_swarm_connected(&self) -> bool {
-        let mut unvisited = self.fields.iter()
-            .filter_map(|(&c, f)| if f.has_pieces() { Some(c) } else { None })
-            .collect::<HashSet<AxialCoords>>();
+        self.is_swarm_connected_except(&mut SearchContext::new(), None)
+    }
+
+    /// Tests whether the swarm is connected, optionally as if the
+    /// top-most piece at the given exception were removed, using
+    /// the context's buffers instead of cloning the board.
+    pub fn is_swarm_connected_except(&self, ctx: &mut SearchContext, exception: Option<AxialCoords>) -> bool {
+        let has_pieces = |c: AxialCoords| self.field(c)
+            .map(|f| f.piece_stack().len() > if exception == Some(c) { 1 } else { 0 })
+            .unwrap_or(false);
+        let mut occupied = self.fields.keys().filter(|&&c| has_pieces(c));
+        let start = match occupied.next() {
+            Some(&start) => start,
+            None => return true // An empty swarm is connected
+        };
+        let count = 1 + occupied.count();
+
+        ctx.stack.clear();
+        ctx.visited.clear();
+        ctx.stack.push(start);
+        ctx.visited.insert(start);
+        let mut visited_count = 0;
 
-        if let Some(start) = unvisited.iter().next() {
-            self.dfs_swarm(*start, &mut unvisited);
-            unvisited.is_empty()
-        } else {
-            true // An empty swarm is connected
+        while let Some(coords) = ctx.stack.pop() {
+            visited_count += 1;
+            for neighbor in coords.coord_neighbors() {
+                if has_pieces(neighbor) && ctx.visited.insert(neighbor) {
+                    ctx.stack.push(neighbor);
+                }
+            }
         }
+
+        visited_count == count
     }
 }
 
-impl fmt::Display for Board {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+impl Board {
+    /// Renders the board like its `Display` implementation, but labels
+    /// the rows and columns with their axial coordinates and marks an
+    /// empty origin with `<>`.
+    pub fn display_with_axes(&self) -> AxesDisplay<'_> { AxesDisplay { board: self } }
+
+    /// Renders the board as a grid of fields, optionally with axes.
+    fn render(&self, f: &mut fmt::Formatter, axes: bool) -> fmt::Result {
         let min_x = self.fields().map(|(c, _)| c.x()).min().ok_or(fmt::Error)?;
         let min_y = self.fields().map(|(c, _)| c.y()).min().ok_or(fmt::Error)?;
         let max_x = self.fields().map(|(c, _)| c.x()).max().ok_or(fmt::Error)?;
         let max_y = self.fields().map(|(c, _)| c.y()).max().ok_or(fmt::Error)?;
 
+        // Rows correspond to the (negated) x-axis and columns
+        // to the (negated) y-axis of the axial coordinates
+        if axes {
+            write!(f, "x\\y ")?;
+            for x in min_x..=max_x {
+                write!(f, "{:>2}", -x)?;
+            }
+            writeln!(f)?;
+        }
+
         for y in min_y..=max_y {
+            if axes {
+                write!(f, "{:>3} ", -y)?;
+            }
             for x in min_x..=max_x {
-                if let Some(field) = self.field(AxialCoords::new(-y, -x)) {
-                    write!(f, "{}", field)?;
-                } else {
-                    write!(f, "00")?;
+                let coords = AxialCoords::new(-y, -x);
+                match self.field(coords) {
+                    Some(field) if axes && coords == AxialCoords::new(0, 0) && !field.is_occupied() => write!(f, "<>")?,
+                    Some(field) => write!(f, "{}", field)?,
+                    None => write!(f, "00")?
                 }
             }
             writeln!(f)?;
@@ -368,8 +454,26 @@ impl fmt::Display for Board {
     }
 }
 
+impl fmt::Display for Board {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.render(f, false)
+    }
+}
+
+/// A board rendered with coordinate axes, see `Board::display_with_axes`.
+pub struct AxesDisplay<'a> {
+    board: &'a Board
+}
+
+impl fmt::Display for AxesDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.board.render(f, true)
+    }
+}
+
+#[cfg(feature = "protocol")]
 impl FromXmlNode for Board {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self::filling_radius(6, node.childs_by_name("fields")
             .flat_map(|child| child.childs_by_name("field")
                 .map(|f| Ok((
@@ -384,3 +488,30 @@ impl FromXmlNode for Board {
         ))
     }
 }
+
+#[cfg(feature = "protocol")]
+impl From<&Board> for XmlNode {
+    fn from(board: &Board) -> Self {
+        let mut fields: Vec<_> = board.fields().collect();
+        fields.sort_by_key(|&(c, _)| c);
+        XmlNode::new("board")
+            .child(XmlNode::new("fields").childs(fields.into_iter()
+                .map(|(c, f)| XmlNodeBuilder::from(PositionedField::with_field(c, f.clone())).name("field").build())))
+            .build()
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&Board> for serde_json::Value {
+    fn from(board: &Board) -> Self {
+        let mut fields: Vec<_> = board.fields().collect();
+        fields.sort_by_key(|(c, _)| *c);
+        serde_json::json!({
+            "fields": fields.into_iter().map(|(c, f)| {
+                let mut value = serde_json::Value::from(f);
+                value["coords"] = c.into();
+                value
+            }).collect::<Vec<_>>()
+        })
+    }
+}
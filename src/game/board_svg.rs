@@ -0,0 +1,42 @@
+use std::fmt::Write;
+use super::{Board, PlayerColor};
+
+impl Board {
+    /// Renders the board as an SVG image using pointy-top
+    /// hexagons of the given size (i.e. the distance between
+    /// a hexagon's center and its corners). The hexagons are
+    /// laid out like in the ASCII representation.
+    pub fn to_svg(&self, size: f64) -> String {
+        let width = 3f64.sqrt() * size;
+        let height = 2.0 * size;
+        let centers: Vec<_> = self.fields().map(|(c, f)| (c.to_pixel(size), f)).collect();
+
+        let min_x = centers.iter().map(|((x, _), _)| *x).fold(0.0, f64::min) - width;
+        let min_y = centers.iter().map(|((_, y), _)| *y).fold(0.0, f64::min) - height;
+        let max_x = centers.iter().map(|((x, _), _)| *x).fold(0.0, f64::max) + width;
+        let max_y = centers.iter().map(|((_, y), _)| *y).fold(0.0, f64::max) + height;
+
+        let mut svg = format!(r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{:.1} {:.1} {:.1} {:.1}">"#, min_x, min_y, max_x - min_x, max_y - min_y);
+        for ((x, y), field) in centers {
+            let corners: Vec<_> = (0..6)
+                .map(|i| (60.0 * f64::from(i) - 30.0).to_radians())
+                .map(|a| format!("{:.1},{:.1}", x + size * a.cos(), y + size * a.sin()))
+                .collect();
+            let fill = match field.owner() {
+                Some(PlayerColor::Red) => "#e57373",
+                Some(PlayerColor::Blue) => "#64b5f6",
+                None if field.is_obstructed() => "#616161",
+                None => "#eeeeee"
+            };
+            let _ = write!(svg, r##"<polygon points="{}" fill="{}" stroke="#9e9e9e"/>"##, corners.join(" "), fill);
+
+            if let Some(piece) = field.piece() {
+                let stack_height = field.piece_stack().len();
+                let label = if stack_height > 1 { format!("{}{}", char::from(piece.piece_type), stack_height) } else { char::from(piece.piece_type).to_string() };
+                let _ = write!(svg, r#"<text x="{:.1}" y="{:.1}" text-anchor="middle" dominant-baseline="central" font-family="sans-serif" font-size="{:.1}">{}</text>"#, x, y, size * 0.8, label);
+            }
+        }
+        svg += "</svg>";
+        svg
+    }
+}
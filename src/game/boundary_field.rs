@@ -0,0 +1,36 @@
+use super::{AxialCoords, PlayerColor};
+
+/// An empty field bordering the swarm, together with
+/// the colors of the adjacent pieces (i.e. the owners
+/// of the top-most pieces on the neighboring fields).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct BoundaryField {
+    pub coords: AxialCoords,
+    borders_red: bool,
+    borders_blue: bool
+}
+
+impl BoundaryField {
+    /// Creates a boundary field without adjacent pieces.
+    pub fn new(coords: AxialCoords) -> Self {
+        Self { coords, borders_red: false, borders_blue: false }
+    }
+
+    /// Tests whether a piece of the given color is adjacent.
+    #[inline]
+    pub fn borders(&self, color: PlayerColor) -> bool {
+        match color {
+            PlayerColor::Red => self.borders_red,
+            PlayerColor::Blue => self.borders_blue
+        }
+    }
+
+    /// Records that a piece of the given color is adjacent.
+    #[inline]
+    pub fn add_bordering(&mut self, color: PlayerColor) {
+        match color {
+            PlayerColor::Red => self.borders_red = true,
+            PlayerColor::Blue => self.borders_blue = true
+        }
+    }
+}
@@ -16,3 +16,12 @@ pub const INITIAL_PIECE_TYPES: [PieceType; 11] = [
     PieceType::Ant,
     PieceType::Ant
 ];
+
+/// The pieces added by the official expansions, which each
+/// player additionally starts with when playing with expansions.
+#[cfg(feature = "expansions")]
+pub const EXPANSION_PIECE_TYPES: [PieceType; 3] = [
+    PieceType::Mosquito,
+    PieceType::Ladybug,
+    PieceType::Pillbug
+];
@@ -5,7 +5,10 @@ use arrayvec::ArrayVec;
 use std::ops::{Add, AddAssign, Sub, SubAssign, Mul, MulAssign, Div};
 use std::collections::HashMap;
 use std::fmt;
+use std::str::FromStr;
 use crate::hashmap;
+use crate::util::{SCError, SCResult};
+use super::HexDirection;
 
 /// Axial coordinates on the hex grid.
 /// 
@@ -60,6 +63,35 @@ pub struct DoubledCoords {
     y: i32
 }
 
+/// The variants of offset coordinates, which shift every
+/// other row (for pointy-top hexagons) or column (for
+/// flat-top hexagons) by half a hexagon.
+///
+/// See https://www.redblobgames.com/grids/hexagons/#coordinates-offset
+/// for a description.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum OffsetLayout {
+    /// Shoves odd rows to the right.
+    OddR,
+    /// Shoves even rows to the right.
+    EvenR,
+    /// Shoves odd columns down.
+    OddQ,
+    /// Shoves even columns down.
+    EvenQ
+}
+
+/// Offset coordinates on the hex grid, i.e. a column and a
+/// row in the given layout. When converting from and to
+/// `AxialCoords`, the rows follow the z-axis of the cube
+/// coordinates (like the rows of `DoubledCoords`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct OffsetCoords {
+    col: i32,
+    row: i32,
+    layout: OffsetLayout
+}
+
 /// An iterator that returns coordinates on
 /// a straight line.
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -81,34 +113,95 @@ pub trait LineFormable {
 
 pub trait Adjacentable {
     /// Tests whether `self` and `rhs` are neighbors.
+    #[allow(clippy::wrong_self_convention)]
     fn is_adjacent_to(self, rhs: Self) -> bool;
 }
 
 impl AxialCoords {
     /// Creates new axial coordinates.
     #[inline]
-    pub fn new(x: i32, y: i32) -> Self { Self { x: x, y: y } }
+    pub const fn new(x: i32, y: i32) -> Self { Self { x, y } }
     
     /// Fetches the x-coordinate
     #[inline]
-    pub fn x(self) -> i32 { self.x }
+    pub const fn x(self) -> i32 { self.x }
     
     /// Fetches the y-coordinate
     #[inline]
-    pub fn y(self) -> i32 { self.y }
+    pub const fn y(self) -> i32 { self.y }
 
     /// Fetches all 6 neighbors, regardless of any board
     /// boundaries.
     #[inline]
     pub fn coord_neighbors(self) -> ArrayVec<AxialCoords, 6> {
-        ArrayVec::from([
-            self + AxialCoords::new(0, 1),
-            self + AxialCoords::new(1, 0),
-            self + AxialCoords::new(1, -1),
-            self + AxialCoords::new(0, -1),
-            self + AxialCoords::new(-1, 0),
-            self + AxialCoords::new(-1, 1)
-        ])
+        ArrayVec::from(HexDirection::ALL.map(|d| self + d.offset()))
+    }
+
+    /// Fetches the neighbor in the given direction.
+    #[inline]
+    pub fn neighbor(self, direction: HexDirection) -> Self { self + direction.offset() }
+
+    /// Finds the direction in which the other coordinates lie,
+    /// if both are distinct and on a straight line.
+    pub fn direction_to(self, other: AxialCoords) -> Option<HexDirection> {
+        let step = AxialCoords::from(CubeCoords::from(other - self).normalized()?);
+        HexDirection::ALL.iter().copied().find(|d| d.offset() == step)
+    }
+
+    /// Computes the number of steps between the coordinates.
+    #[inline]
+    pub fn distance_to(self, other: AxialCoords) -> i32 {
+        CubeCoords::from(other - self).length()
+    }
+
+    /// Fetches the coordinates on a line to the other
+    /// coordinates (including both ends), which need
+    /// not be on a straight line along an axis.
+    pub fn line_to(self, other: AxialCoords) -> Vec<AxialCoords> {
+        let distance = self.distance_to(other);
+        let (start, end) = (CubeCoords::from(self), CubeCoords::from(other));
+        (0..=distance)
+            .map(|i| AxialCoords::from(start.lerp(end, if distance == 0 { 0.0 } else { f64::from(i) / f64::from(distance) })))
+            .collect()
+    }
+
+    /// Computes the center of the pointy-top hexagon of the given size
+    /// (i.e. the distance between its center and its corners) in pixels,
+    /// laid out like in the ASCII and SVG representation.
+    pub fn to_pixel(self, size: f64) -> (f64, f64) {
+        let doubled = DoubledCoords::from(self);
+        (f64::from(doubled.x) * 3f64.sqrt() / 2.0 * size, f64::from(doubled.y) * 1.5 * size)
+    }
+
+    /// Finds the hexagon of the given size containing
+    /// the given pixel, inverting `to_pixel`.
+    pub fn from_pixel(pixel: (f64, f64), size: f64) -> Self {
+        let doubled_x = pixel.0 / (3f64.sqrt() / 2.0 * size);
+        let doubled_y = pixel.1 / (1.5 * size);
+        let x = (doubled_x - doubled_y) / 2.0;
+        let y = -(doubled_x + doubled_y) / 2.0;
+        Self::from(CubeCoords::round(x, y, -x - y))
+    }
+
+    /// Rotates the coordinates by 60 degrees around the origin.
+    #[inline]
+    pub fn rotated(self) -> Self {
+        Self { x: self.x + self.y, y: -self.x }
+    }
+
+    /// Reflects the coordinates across the x-axis, i.e.
+    /// swaps the y- and z-coordinate in cube coordinates.
+    #[inline]
+    pub fn reflected(self) -> Self {
+        Self { x: self.x, y: -(self.x + self.y) }
+    }
+
+    /// Applies one of the 12 symmetries of the hex grid around
+    /// the origin (the index modulo 6 rotations, reflected
+    /// for indices from 6 to 11).
+    pub fn symmetry(self, index: usize) -> Self {
+        let reflected = if index % 12 >= 6 { self.reflected() } else { self };
+        (0..index % 6).fold(reflected, |c, _| c.rotated())
     }
 }
 
@@ -116,14 +209,14 @@ impl CubeCoords {
     /// Creates new (unvalidated) cube coordinates.
     #[inline]
     pub fn new(x: i32, y: i32, z: i32) -> Self {
-        Self { x: x, y: y, z: z }
+        Self { x, y, z }
     }
 
     /// Creates new cube coordinates if they are valid.
     #[inline]
     pub fn new_valid(x: i32, y: i32, z: i32) -> Option<Self> {
         if (x + y + z) == 0 {
-            Some(CubeCoords { x: x, y: y, z: z })
+            Some(CubeCoords { x, y, z })
         } else {
             None
         }
@@ -140,13 +233,49 @@ impl CubeCoords {
     /// Fetches the z-coordinate
     #[inline]
     pub fn z(self) -> i32 { self.z }
+
+    /// Computes the number of steps from the origin.
+    #[inline]
+    pub fn length(self) -> i32 { (self.x.abs() + self.y.abs() + self.z.abs()) / 2 }
+
+    /// Scales the coordinates to a single step, if
+    /// they are on a straight line from the origin.
+    pub fn normalized(self) -> Option<Self> {
+        let length = self.length();
+        if length > 0 && (self.x == 0 || self.y == 0 || self.z == 0) {
+            Some(self / length)
+        } else {
+            None
+        }
+    }
+
+    /// Rounds fractional cube coordinates to the nearest hexagon.
+    pub fn round(x: f64, y: f64, z: f64) -> Self {
+        let (mut rx, mut ry, mut rz) = (x.round(), y.round(), z.round());
+        let (dx, dy, dz) = ((rx - x).abs(), (ry - y).abs(), (rz - z).abs());
+        if dx > dy && dx > dz {
+            rx = -ry - rz;
+        } else if dy > dz {
+            ry = -rx - rz;
+        } else {
+            rz = -rx - ry;
+        }
+        Self::new(rx as i32, ry as i32, rz as i32)
+    }
+
+    /// Linearly interpolates between the coordinates with
+    /// the given factor and rounds to the nearest hexagon.
+    pub fn lerp(self, other: CubeCoords, t: f64) -> Self {
+        let lerp = |a: i32, b: i32| f64::from(a) + (f64::from(b) - f64::from(a)) * t;
+        Self::round(lerp(self.x, other.x), lerp(self.y, other.y), lerp(self.z, other.z))
+    }
 }
 
 impl DoubledCoords {
     /// Creates new doubled coordinates.
     #[inline]
     pub fn new(x: i32, y: i32) -> Self {
-        Self { x: x, y: y }
+        Self { x, y }
     }
 
     /// Fetches the x-coordinate
@@ -158,6 +287,38 @@ impl DoubledCoords {
     pub fn y(self) -> i32 { self.y }
 }
 
+impl OffsetCoords {
+    /// Creates new offset coordinates.
+    #[inline]
+    pub fn new(col: i32, row: i32, layout: OffsetLayout) -> Self {
+        Self { col, row, layout }
+    }
+
+    /// Converts axial coordinates to the given layout.
+    pub fn from_axial(coords: AxialCoords, layout: OffsetLayout) -> Self {
+        let (q, r) = (coords.x, -(coords.x + coords.y));
+        let (col, row) = match layout {
+            OffsetLayout::OddR => (q + (r - (r & 1)) / 2, r),
+            OffsetLayout::EvenR => (q + (r + (r & 1)) / 2, r),
+            OffsetLayout::OddQ => (q, r + (q - (q & 1)) / 2),
+            OffsetLayout::EvenQ => (q, r + (q + (q & 1)) / 2)
+        };
+        Self { col, row, layout }
+    }
+
+    /// Fetches the column
+    #[inline]
+    pub fn col(self) -> i32 { self.col }
+
+    /// Fetches the row
+    #[inline]
+    pub fn row(self) -> i32 { self.row }
+
+    /// Fetches the layout
+    #[inline]
+    pub fn layout(self) -> OffsetLayout { self.layout }
+}
+
 impl fmt::Display for AxialCoords {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "({}, {})", self.x, self.y)
@@ -176,6 +337,44 @@ impl fmt::Display for DoubledCoords {
     }
 }
 
+// Parsing
+
+/// Parses a comma-separated tuple of integers, optionally
+/// enclosed in parentheses, e.g. `(1, -2)` or `1,-2`.
+fn parse_tuple<const N: usize>(raw: &str) -> SCResult<[i32; N]> {
+    let trimmed = raw.trim();
+    let inner = trimmed.strip_prefix('(').and_then(|s| s.strip_suffix(')')).unwrap_or(trimmed);
+    let components = inner.split(',').map(|c| c.trim().parse::<i32>()).collect::<Result<Vec<_>, _>>()?;
+    <[i32; N]>::try_from(components).map_err(|c| format!("Expected {} coordinates, but got {} in '{}'", N, c.len(), raw).into())
+}
+
+impl FromStr for AxialCoords {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        let [x, y] = parse_tuple(raw)?;
+        Ok(Self::new(x, y))
+    }
+}
+
+impl FromStr for CubeCoords {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        let [x, y, z] = parse_tuple(raw)?;
+        Self::new_valid(x, y, z).ok_or_else(|| format!("Cube coordinates {} do not sum up to zero", raw).into())
+    }
+}
+
+impl FromStr for DoubledCoords {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        let [x, y] = parse_tuple(raw)?;
+        Ok(Self::new(x, y))
+    }
+}
+
 impl<C> LineFormable for C where C: Into<CubeCoords> {
     fn forms_line_with(self, rhs: Self) -> bool {
         let lhs_cube = self.into();
@@ -194,7 +393,7 @@ impl<C> LineFormable for C where C: Into<CubeCoords> {
 
 impl LineIter {
     pub fn new(start: CubeCoords, step: CubeCoords, destination: CubeCoords) -> Self {
-        Self { current: start, step: step, destination: destination }
+        Self { current: start, step, destination }
     }
 }
 
@@ -216,7 +415,7 @@ impl<C> Adjacentable for C where C: Into<AxialCoords> {
     fn is_adjacent_to(self, rhs: Self) -> bool {
         let lhs_axial = self.into();
         let rhs_axial = rhs.into();
-        lhs_axial.coord_neighbors().iter().any(|&c| c == rhs_axial)
+        lhs_axial.coord_neighbors().contains(&rhs_axial)
     }
 }
 
@@ -277,7 +476,7 @@ impl<R> MulAssign<R> for AxialCoords where R: Into<i32> {
 impl Add for CubeCoords {
     type Output = Self;
 
-    fn add(self, rhs: Self) -> Self { Self { x: self.x + rhs.x, y: self.y + rhs.y, z: self.y + rhs.z } }
+    fn add(self, rhs: Self) -> Self { Self { x: self.x + rhs.x, y: self.y + rhs.y, z: self.z + rhs.z } }
 }
 
 impl Sub for CubeCoords {
@@ -325,7 +524,7 @@ impl<R> MulAssign<R> for CubeCoords where R: Into<i32> {
         let r = rhs.into();
         self.x *= r;
         self.y *= r;
-        self.z += r;
+        self.z *= r;
     }
 }
 
@@ -397,6 +596,19 @@ impl From<AxialCoords> for DoubledCoords {
     }
 }
 
+impl From<OffsetCoords> for AxialCoords {
+    fn from(coords: OffsetCoords) -> Self {
+        let (col, row) = (coords.col, coords.row);
+        let (q, r) = match coords.layout {
+            OffsetLayout::OddR => (col - (row - (row & 1)) / 2, row),
+            OffsetLayout::EvenR => (col - (row + (row & 1)) / 2, row),
+            OffsetLayout::OddQ => (col, row - (col - (col & 1)) / 2),
+            OffsetLayout::EvenQ => (col, row - (col + (col & 1)) / 2)
+        };
+        Self { x: q, y: -(q + r) }
+    }
+}
+
 impl From<DoubledCoords> for AxialCoords {
     fn from(coords: DoubledCoords) -> Self {
         Self { x: (coords.x - coords.y) / 2, y: -(coords.x + coords.y) / 2 }
@@ -420,3 +632,10 @@ impl From<CubeCoords> for HashMap<String, String> {
         hashmap!["x" => coords.x.to_string(), "y" => coords.y.to_string(), "z" => coords.z.to_string()]
     }
 }
+
+#[cfg(feature = "json")]
+impl From<AxialCoords> for serde_json::Value {
+    fn from(coords: AxialCoords) -> Self {
+        serde_json::json!({ "x": coords.x, "y": coords.y })
+    }
+}
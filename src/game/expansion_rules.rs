@@ -0,0 +1,122 @@
+//! The movement rules for the official Hive expansion
+//! pieces (Mosquito, Ladybug and Pillbug). These are not
+//! part of the Software Challenge and only available with
+//! the `expansions` feature.
+
+use std::collections::HashSet;
+use crate::util::SCResult;
+use super::{Adjacentable, AxialCoords, Field, GameState, PieceType, PlayerColor, SearchContext};
+
+impl GameState {
+    /// Tests whether the mosquito at the given coordinates
+    /// may climb, i.e. whether it is already on top of the
+    /// swarm or next to a beetle whose movement it can mimic.
+    pub(super) fn can_mosquito_climb(&self, start: AxialCoords) -> bool {
+        self.board.field(start).map(|f| f.piece_stack().len() > 1).unwrap_or(false)
+            || self.board.neighbors(start).any(|(_, f)| f.piece().map(|p| p.piece_type == PieceType::Beetle).unwrap_or(false))
+    }
+
+    /// The mosquito moves like a beetle when on top of the swarm,
+    /// otherwise it mimics the movement of any adjacent piece
+    /// (except other mosquitos).
+    pub(super) fn validate_mosquito_move(&self, ctx: &mut SearchContext, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        if self.board.field(start).map(|f| f.piece_stack().len() > 1).unwrap_or(false) {
+            return self.validate_beetle_move(start, destination);
+        }
+
+        let mimicked_types: HashSet<_> = self.board.neighbors(start)
+            .filter_map(|(_, f)| f.piece())
+            .map(|p| p.piece_type)
+            .filter(|&t| t != PieceType::Mosquito)
+            .collect();
+
+        if mimicked_types.is_empty() {
+            Err("Mosquito has no adjacent piece to mimic".into())
+        } else if mimicked_types.into_iter().any(|t| self.validate_piece_move(ctx, t, start, destination).is_ok()) {
+            Ok(())
+        } else {
+            Err("Mosquito cannot reach the destination by mimicking any adjacent piece".into())
+        }
+    }
+
+    /// The ladybug moves exactly three steps: two on top
+    /// of the swarm and one down into an empty field.
+    pub(super) fn validate_ladybug_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        let mut without_piece = self.board.clone();
+        without_piece.field_mut(start).ok_or("Start field does not exist")?.pop();
+        let has_pieces = |c: AxialCoords| without_piece.field(c).map(|f| f.has_pieces()).unwrap_or(false);
+        let is_empty = |c: AxialCoords| without_piece.field(c).map(|f| f.is_empty()).unwrap_or(false);
+
+        let reachable = start.coord_neighbors().into_iter()
+            .filter(|&first| has_pieces(first))
+            .flat_map(|first| first.coord_neighbors().into_iter().filter(move |&second| second != start))
+            .filter(|&second| has_pieces(second))
+            .flat_map(|second| second.coord_neighbors().into_iter())
+            .any(|third| third == destination && is_empty(third));
+
+        if reachable { Ok(()) } else { Err("No 3-step path over the swarm found for Ladybug move".into()) }
+    }
+
+    /// The pillbug moves like the bee.
+    pub(super) fn validate_pillbug_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        self.validate_bee_move(start, destination)
+    }
+
+    /// Tests whether the field contains an uncovered piece
+    /// of the given color that can use the pillbug's ability.
+    fn is_pillbug_ability_holder(&self, color: PlayerColor, coords: AxialCoords, field: &Field) -> bool {
+        field.piece_stack().len() == 1 && field.piece().filter(|p| p.owner == color).map(|p| match p.piece_type {
+            PieceType::Pillbug => true,
+            PieceType::Mosquito => self.board.neighbors(coords).any(|(_, f)| f.piece().map(|p| p.piece_type == PieceType::Pillbug).unwrap_or(false)),
+            _ => false
+        }).unwrap_or(false)
+    }
+
+    /// Tests whether a piece carried over the pillbug cannot cross
+    /// the edge between the given adjacent fields, since both fields
+    /// next to the edge are stacked above the pillbug (a gate).
+    fn is_gated_above(&self, a: AxialCoords, b: AxialCoords) -> bool {
+        let height = |c: AxialCoords| self.board.field(c).map(|f| f.piece_stack().len()).unwrap_or(0);
+        let shared: Vec<_> = a.coord_neighbors().into_iter().filter(|n| n.is_adjacent_to(b)).collect();
+        shared.len() == 2 && shared.into_iter().all(|c| height(c) >= 2)
+    }
+
+    /// Fetches the fields whose pieces can be moved by one of the
+    /// player's pillbugs (or mosquitos mimicking them).
+    pub(super) fn pillbug_throw_starts(&self, color: PlayerColor) -> Vec<(AxialCoords, &Field)> {
+        self.board.fields()
+            .filter(|&(c, f)| self.is_pillbug_ability_holder(color, c, f))
+            .flat_map(|(c, _)| self.board.neighbors(c))
+            .filter(|(_, f)| f.piece_stack().len() == 1)
+            .collect()
+    }
+
+    /// Validates the pillbug's special ability, which moves an
+    /// adjacent, uncovered piece (of either color) to an empty
+    /// field adjacent to the pillbug. The piece is carried over the
+    /// pillbug, thus neither step may pass through a gate of stacks.
+    /// 
+    /// Note that the restriction on moving the piece most recently
+    /// moved by the opponent is not checked, since the game state
+    /// does not track the previous move.
+    pub(super) fn validate_pillbug_throw(&self, ctx: &mut SearchContext, color: PlayerColor, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        let start_field = self.board.field(start).ok_or("Start field does not exist")?;
+        let holders: Vec<_> = self.board.neighbors(start)
+            .filter(|&(c, f)| c.is_adjacent_to(destination) && self.is_pillbug_ability_holder(color, c, f))
+            .map(|(c, _)| c)
+            .collect();
+        if start_field.piece_stack().len() != 1 {
+            Err("Pillbug can only move uncovered, unstacked pieces".into())
+        } else if !self.board.field(destination).map(|f| f.is_empty()).unwrap_or(false) {
+            Err("Pillbug can only move pieces to empty fields".into())
+        } else if holders.is_empty() {
+            Err("No pillbug is adjacent to both the start and the destination".into())
+        } else if holders.iter().all(|&c| self.is_gated_above(start, c) || self.is_gated_above(c, destination)) {
+            Err("Pillbug cannot move pieces through a gate".into())
+        } else if !self.is_swarm_connected_without(ctx, start)? {
+            Err("Pillbug move would disconnect the swarm".into())
+        } else {
+            Ok(())
+        }
+    }
+}
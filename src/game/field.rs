@@ -2,7 +2,9 @@ use std::{convert::TryFrom, fmt, str::FromStr};
 
 use regex::Regex;
 use lazy_static::lazy_static;
-use crate::util::{SCError, SCResult, FromXmlNode, XmlNode};
+use crate::util::{SCError, SCResult};
+#[cfg(feature = "protocol")]
+use crate::util::{FromXmlNode, XmlElement};
 use super::{Piece, PieceType, PlayerColor};
 
 /// A field on the game board.
@@ -11,7 +13,7 @@ use super::{Piece, PieceType, PlayerColor};
 /// store a position. If this is desired, you should use
 /// `PositionedField` or a tuple, depending on whether you
 /// want to express ownership over the field.
-#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default, PartialOrd, Ord)]
 pub struct Field {
     piece_stack: Vec<Piece>,
     is_obstructed: bool
@@ -20,7 +22,7 @@ pub struct Field {
 impl Field {
     /// Creates a new field.
     pub fn new(piece_stack: impl IntoIterator<Item=Piece>, is_obstructed: bool) -> Self {
-        Self { piece_stack: piece_stack.into_iter().collect(), is_obstructed: is_obstructed }
+        Self { piece_stack: piece_stack.into_iter().collect(), is_obstructed }
     }
 
     /// Fetches the player color "owning" the field.
@@ -87,7 +89,7 @@ impl FromStr for Field {
             let groups = FIELD_SYNTAX.captures(raw).ok_or_else(|| SCError::from(format!("{} does not match field syntax {}", raw, FIELD_SYNTAX.as_str())))?;
             let owner = PlayerColor::try_from(groups[1].chars().next().unwrap())?;
             let piece_type = PieceType::try_from(groups[2].chars().next().unwrap())?;
-            let piece = Piece { piece_type: piece_type, owner: owner };
+            let piece = Piece { piece_type, owner };
             Ok(Self { piece_stack: vec![piece], is_obstructed: false })
         }
     }
@@ -103,11 +105,22 @@ impl fmt::Display for Field {
     }
 }
 
+#[cfg(feature = "protocol")]
 impl FromXmlNode for Field {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             piece_stack: node.childs_by_name("piece").map(Piece::from_node).collect::<Result<_, _>>()?,
             is_obstructed: node.attribute("isObstructed")?.parse()?
         })
     }
 }
+
+#[cfg(feature = "json")]
+impl From<&Field> for serde_json::Value {
+    fn from(field: &Field) -> Self {
+        serde_json::json!({
+            "pieces": field.piece_stack().iter().map(|&p| serde_json::Value::from(p)).collect::<Vec<_>>(),
+            "isObstructed": field.is_obstructed()
+        })
+    }
+}
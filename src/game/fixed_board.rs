@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::convert::TryFrom;
+use arrayvec::ArrayVec;
+use crate::util::{SCError, SCResult};
+use super::{AxialCoords, Board, Field, HexDirection, Piece, PieceType, PlayerColor, BOARD_RADIUS};
+
+/// The maximum height of a piece stack on a `FixedBoard`.
+pub const MAX_STACK_HEIGHT: usize = 8;
+
+/// The number of slots of a fixed board covering the game board.
+pub const GAME_BOARD_SLOTS: usize = (2 * BOARD_RADIUS - 1) * (2 * BOARD_RADIUS - 1);
+
+/// A fixed board covering the game board.
+pub type GameFixedBoard = FixedBoard<BOARD_RADIUS, GAME_BOARD_SLOTS>;
+
+/// A field whose piece stack is stored inline.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct FixedField {
+    piece_stack: ArrayVec<Piece, MAX_STACK_HEIGHT>,
+    is_obstructed: bool
+}
+
+impl FixedField {
+    /// Fetches the top-most piece.
+    #[inline]
+    pub fn piece(&self) -> Option<Piece> { self.piece_stack.last().cloned() }
+
+    /// Fetches the piece stack.
+    #[inline]
+    pub fn piece_stack(&self) -> &[Piece] { &self.piece_stack }
+
+    /// Tests whether the field is (directly) obstructed.
+    #[inline]
+    pub fn is_obstructed(&self) -> bool { self.is_obstructed }
+
+    /// Tests whether the field contains pieces.
+    #[inline]
+    pub fn has_pieces(&self) -> bool { !self.piece_stack.is_empty() }
+
+    /// Tests whether the field is occupied.
+    #[inline]
+    pub fn is_occupied(&self) -> bool { self.is_obstructed || self.has_pieces() }
+}
+
+impl TryFrom<&Field> for FixedField {
+    type Error = SCError;
+
+    fn try_from(field: &Field) -> SCResult<Self> {
+        if field.piece_stack().len() > MAX_STACK_HEIGHT {
+            Err(format!("Piece stack is higher than {}", MAX_STACK_HEIGHT).into())
+        } else {
+            Ok(Self { piece_stack: field.piece_stack().iter().cloned().collect(), is_obstructed: field.is_obstructed() })
+        }
+    }
+}
+
+impl From<&FixedField> for Field {
+    fn from(field: &FixedField) -> Self {
+        Field::new(field.piece_stack.iter().cloned(), field.is_obstructed)
+    }
+}
+
+/// A hexagonal board with the given radius whose fields are
+/// stored in an array of `N = (2R - 1)^2` slots, indexed by
+/// the axial coordinates. Unlike `Board`, it does not allocate
+/// and is thus used internally by the search.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FixedBoard<const R: usize, const N: usize> {
+    slots: [Option<FixedField>; N]
+}
+
+impl<const R: usize, const N: usize> FixedBoard<R, N> {
+    /// The number of slots along each axis.
+    pub const SIDE: usize = 2 * R - 1;
+    /// The largest absolute coordinate on the board.
+    const INNER: i32 = R as i32 - 1;
+    /// Ensures at compile time that the slot count matches the radius.
+    const VALID: () = assert!(N == Self::SIDE * Self::SIDE, "FixedBoard requires N = (2R - 1)^2 slots");
+    /// The slot indices of the neighbors of each slot (in the order
+    /// of `HexDirection::ALL`), which are `None` outside the radius.
+    const NEIGHBORS: [[Option<usize>; 6]; N] = Self::ring_table::<6>(1);
+    /// The slot indices of the fields two steps away from each slot.
+    const SECOND_RING: [[Option<usize>; 12]; N] = Self::ring_table::<12>(2);
+
+    /// Computes the slot indices of the ring with the given radius
+    /// (consisting of `K = 6 * radius` fields) around each slot.
+    const fn ring_table<const K: usize>(radius: i32) -> [[Option<usize>; K]; N] {
+        let mut table = [[None; K]; N];
+        let mut index = 0;
+        while index < N {
+            let center = Self::coords(index);
+            if Self::contains_coords(center) {
+                // Walk the ring clockwise, starting `radius` steps in
+                // the first direction, thus visiting the neighbors in
+                // the order of the directions if the radius is 1
+                let start = HexDirection::ALL[0].offset();
+                let (mut x, mut y) = (center.x() + radius * start.x(), center.y() + radius * start.y());
+                let mut k = 0;
+                let mut side = 0;
+                while side < 6 {
+                    let step = HexDirection::ALL[(side + 2) % 6].offset();
+                    let mut i = 0;
+                    while i < radius {
+                        table[index][k] = Self::index(AxialCoords::new(x, y));
+                        x += step.x();
+                        y += step.y();
+                        k += 1;
+                        i += 1;
+                    }
+                    side += 1;
+                }
+            }
+            index += 1;
+        }
+        table
+    }
+
+    /// Creates a board with empty fields up to the radius.
+    pub fn empty() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        Self { slots: std::array::from_fn(|i| if Self::contains_coords(Self::coords(i)) { Some(FixedField::default()) } else { None }) }
+    }
+
+    /// Tests whether the coordinates lie within the radius.
+    #[inline]
+    pub const fn contains_coords(coords: AxialCoords) -> bool {
+        let (x, y) = (coords.x(), coords.y());
+        x.abs() <= Self::INNER && y.abs() <= Self::INNER && (x + y).abs() <= Self::INNER
+    }
+
+    /// Computes the slot index of the given coordinates.
+    #[inline]
+    pub const fn index(coords: AxialCoords) -> Option<usize> {
+        if Self::contains_coords(coords) {
+            Some((coords.x() + Self::INNER) as usize * Self::SIDE + (coords.y() + Self::INNER) as usize)
+        } else {
+            None
+        }
+    }
+
+    /// Computes the coordinates of the given slot index.
+    #[inline]
+    pub const fn coords(index: usize) -> AxialCoords {
+        AxialCoords::new((index / Self::SIDE) as i32 - Self::INNER, (index % Self::SIDE) as i32 - Self::INNER)
+    }
+
+    /// Fetches the field at the given coordinates.
+    #[inline]
+    pub fn field(&self, coords: AxialCoords) -> Option<&FixedField> {
+        Self::index(coords).and_then(|i| self.slots[i].as_ref())
+    }
+
+    /// Fetches all fields on the board.
+    pub fn fields(&self) -> impl Iterator<Item=(AxialCoords, &FixedField)> {
+        self.slots.iter().enumerate().filter_map(|(i, f)| f.as_ref().map(|f| (Self::coords(i), f)))
+    }
+
+    /// Fetches the slot indices of the neighbors of the given slot
+    /// (in the order of `HexDirection::ALL`) from a precomputed table.
+    #[inline]
+    pub fn neighbor_indices(index: usize) -> [Option<usize>; 6] { Self::NEIGHBORS[index] }
+
+    /// Fetches the slot indices of the fields two steps away
+    /// from the given slot from a precomputed table.
+    #[inline]
+    pub fn second_ring_indices(index: usize) -> [Option<usize>; 12] { Self::SECOND_RING[index] }
+
+    /// Fetches the neighboring fields on the board.
+    pub fn neighbors(&self, coords: AxialCoords) -> impl Iterator<Item=(AxialCoords, &FixedField)> {
+        Self::index(coords).into_iter()
+            .flat_map(|i| Self::neighbor_indices(i).into_iter().flatten())
+            .filter_map(move |n| self.slots[n].as_ref().map(|f| (Self::coords(n), f)))
+    }
+
+    /// Tests whether a given position is occupied.
+    #[inline]
+    pub fn is_occupied(&self, coords: AxialCoords) -> bool {
+        self.field(coords).map(|f| f.is_occupied()).unwrap_or(true)
+    }
+
+    /// Tests whether the bee of the given color has been placed and
+    /// is blocked on all sides (by pieces, obstructions or the board's
+    /// edge), mirroring `Board::is_bee_surrounded`.
+    pub fn is_bee_surrounded(&self, color: PlayerColor) -> bool {
+        let bee = Piece { piece_type: PieceType::Bee, owner: color };
+        self.slots.iter()
+            .position(|f| f.as_ref().map(|f| f.piece_stack().contains(&bee)).unwrap_or(false))
+            .map(|i| Self::neighbor_indices(i).iter().all(|n| n.and_then(|n| self.slots[n].as_ref()).map(|f| f.is_occupied()).unwrap_or(true)))
+            .unwrap_or(false)
+    }
+}
+
+impl<const R: usize, const N: usize> Default for FixedBoard<R, N> {
+    fn default() -> Self { Self::empty() }
+}
+
+impl<const R: usize, const N: usize> TryFrom<&Board> for FixedBoard<R, N> {
+    type Error = SCError;
+
+    /// Converts a board, failing if it has fields outside
+    /// the radius or too high piece stacks. Missing fields
+    /// within the radius are left off the board.
+    fn try_from(board: &Board) -> SCResult<Self> {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID;
+        let mut fixed = Self { slots: std::array::from_fn(|_| None) };
+        for (coords, field) in board.fields() {
+            let index = Self::index(coords).ok_or_else(|| format!("{} is outside of the radius {}", coords, R))?;
+            fixed.slots[index] = Some(FixedField::try_from(field)?);
+        }
+        Ok(fixed)
+    }
+}
+
+impl<const R: usize, const N: usize> From<&FixedBoard<R, N>> for Board {
+    fn from(board: &FixedBoard<R, N>) -> Self {
+        Board::new(board.fields().map(|(c, f)| (c, Field::from(f))).collect::<HashMap<_, _>>())
+    }
+}
@@ -1,11 +1,23 @@
+use std::collections::{BTreeMap, HashMap, hash_map::DefaultHasher};
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use arrayvec::ArrayVec;
 use log::trace;
-use crate::util::{SCResult, FromXmlNode, XmlNode};
-use super::{Board, INITIAL_PIECE_TYPES, Move, Piece, PieceType, Player, PlayerColor, PositionedField, Adjacentable, AxialCoords, LineFormable};
+#[cfg(feature = "expansions")]
+use itertools::Itertools;
+use crate::util::{SCError, SCResult};
+#[cfg(feature = "protocol")]
+use crate::util::{FromXmlNode, XmlElement, XmlNode, XmlNodeBuilder};
+use super::{Board, Field, Lineage, RuleSet, Move, Piece, PieceType, Player, PlayerColor, PositionedField, Adjacentable, AxialCoords, LineFormable, MoveDelta, OpeningPlacement, SearchContext};
+use super::r#move::{PIECE_TYPES, SYMMETRY_COUNT, mix, mix_coords, pack_coords, unpack_coords};
 
 /// A snapshot of the game's state at
 /// a specific turn. Consists of the
 /// board and information about both players.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// States are compared by everything but their lineage,
+/// thus equal positions reached via different moves are equal.
+#[derive(Debug, Clone)]
 pub struct GameState {
     pub turn: u32,
     pub start_player_color: PlayerColor,
@@ -14,10 +26,118 @@ pub struct GameState {
     red_player: Player,
     blue_player: Player,
     undeployed_red_pieces: Vec<Piece>,
-    undeployed_blue_pieces: Vec<Piece>
+    undeployed_blue_pieces: Vec<Piece>,
+    lineage: Lineage,
+    rules: Arc<RuleSet>,
+    /// Child elements of the `<state>` not parsed by the client
+    /// (e.g. the last move), which are preserved when serializing
+    /// the state, but dropped by `apply_move`.
+    #[cfg(feature = "protocol")]
+    pub extensions: Vec<XmlNode>,
+    /// Attributes of the `<state>` not parsed by the client,
+    /// which are preserved and dropped likewise.
+    #[cfg(feature = "protocol")]
+    pub extension_attributes: Vec<(String, String)>
+}
+
+/// The child elements of the `<state>` parsed by the client.
+#[cfg(feature = "protocol")]
+const STATE_CHILDS: [&str; 5] = ["red", "blue", "board", "undeployedRedPieces", "undeployedBluePieces"];
+/// The attributes of the `<state>` parsed (or, in the case of
+/// the class, implied) by the client.
+#[cfg(feature = "protocol")]
+const STATE_ATTRIBUTES: [&str; 4] = ["class", "turn", "startPlayerColor", "currentPlayerColor"];
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Facts about a state that are needed to validate moves of a
+/// color, computed lazily and shared between multiple validations.
+#[derive(Default)]
+struct LegalityCache {
+    has_placed_bee: Option<bool>,
+    has_pieces: Option<bool>,
+    has_own_pieces: Option<bool>,
+    /// Whether the swarm stays connected without the top-most
+    /// piece at the given coordinates (for a bounded number of starts).
+    connected_without: ArrayVec<(AxialCoords, bool), 32>
+}
+
+impl LegalityCache {
+    fn has_placed_bee(&mut self, board: &Board, color: PlayerColor) -> bool {
+        *self.has_placed_bee.get_or_insert_with(|| board.has_placed_bee(color))
+    }
+
+    fn has_pieces(&mut self, board: &Board) -> bool {
+        *self.has_pieces.get_or_insert_with(|| board.has_pieces())
+    }
+
+    fn has_own_pieces(&mut self, board: &Board, color: PlayerColor) -> bool {
+        *self.has_own_pieces.get_or_insert_with(|| board.fields_owned_by(color).next().is_some())
+    }
+
+    fn is_swarm_connected_without(&mut self, state: &GameState, ctx: &mut SearchContext, coords: AxialCoords) -> SCResult<bool> {
+        if let Some(&(_, connected)) = self.connected_without.iter().find(|(c, _)| *c == coords) {
+            return Ok(connected);
+        }
+        let connected = state.is_swarm_connected_without(ctx, coords)?;
+        let _ = self.connected_without.try_push((coords, connected));
+        Ok(connected)
+    }
+}
+
+/// Tests whether both slices contain the same
+/// pieces, regardless of their order.
+pub fn same_pieces(lhs: &[Piece], rhs: &[Piece]) -> bool {
+    let mut counts = HashMap::new();
+    for piece in lhs {
+        *counts.entry(piece).or_insert(0i32) += 1;
+    }
+    for piece in rhs {
+        *counts.entry(piece).or_insert(0) -= 1;
+    }
+    lhs.len() == rhs.len() && counts.values().all(|&c| c == 0)
+}
+
+/// Sorts pieces by their owner and the order of their
+/// types in the initial piece set (other types last).
+fn normalize_pieces(pieces: &mut [Piece], initial_types: &[PieceType]) {
+    pieces.sort_by_key(|p| (p.owner == PlayerColor::Blue, initial_types.iter().position(|&t| t == p.piece_type).unwrap_or(initial_types.len())));
 }
 
 impl GameState {
+    /// Creates the state at the start of a game, i.e. an empty
+    /// board (without obstructed fields) and both players' full
+    /// sets of undeployed pieces.
+    pub fn initial(start_color: PlayerColor) -> Self {
+        Self::initial_with_rules(start_color, RuleSet::default())
+    }
+
+    /// Creates the state at the start of a game played
+    /// with the given rules.
+    pub fn initial_with_rules(start_color: PlayerColor, rules: RuleSet) -> Self {
+        let pieces = |owner| rules.initial_piece_types.iter().map(|&piece_type| Piece { piece_type, owner }).collect();
+        Self {
+            turn: 0,
+            start_player_color: start_color,
+            current_player_color: start_color,
+            board: Board::filling_radius(rules.board_radius, HashMap::new()),
+            red_player: Player::new(PlayerColor::Red, "Red"),
+            blue_player: Player::new(PlayerColor::Blue, "Blue"),
+            undeployed_red_pieces: pieces(PlayerColor::Red),
+            undeployed_blue_pieces: pieces(PlayerColor::Blue),
+            lineage: Lineage::default(),
+            rules: Arc::new(rules),
+            #[cfg(feature = "protocol")]
+            extensions: Vec::new(),
+            #[cfg(feature = "protocol")]
+            extension_attributes: Vec::new()
+        }
+    }
+
     /// Fetches the undeployed pieces for a specific color.
     pub fn undeployed_pieces(&self, color: PlayerColor) -> &Vec<Piece> {
         match color {
@@ -34,24 +154,282 @@ impl GameState {
         }
     } 
 
+    /// Fetches the undeployed pieces for a specific color mutably.
+    fn undeployed_pieces_mut(&mut self, color: PlayerColor) -> &mut Vec<Piece> {
+        match color {
+            PlayerColor::Red => &mut self.undeployed_red_pieces,
+            PlayerColor::Blue => &mut self.undeployed_blue_pieces
+        }
+    }
+
     /// Fetches the current _round_ (which is half the turn).
     pub fn round(&self) -> u32 { self.turn / 2 }
 
+    /// Fetches the rules the game is played with.
+    pub fn rules(&self) -> &RuleSet { &self.rules }
+
+    /// Replaces the rules the game is played with
+    /// (without changing the board or the pieces).
+    pub fn with_rules(mut self, rules: RuleSet) -> Self {
+        self.rules = Arc::new(rules);
+        self
+    }
+
+    /// Tests whether the game has reached the turn limit.
+    pub fn is_turn_limit_reached(&self) -> bool {
+        self.turn >= self.rules.turn_limit()
+    }
+
+    /// Starts tracking the lineage, i.e. the positions and
+    /// moves leading to the successor states of this state.
+    pub fn with_lineage(mut self) -> Self {
+        self.lineage = Lineage::tracked();
+        self
+    }
+
+    /// Replaces the lineage, e.g. to restore the
+    /// history of a state decoded from a snapshot.
+    pub fn with_restored_lineage(mut self, lineage: Lineage) -> Self {
+        self.lineage = lineage;
+        self
+    }
+
+    /// Fetches the history of positions leading to this state
+    /// (which is empty unless tracked using `with_lineage`).
+    pub fn lineage(&self) -> &Lineage { &self.lineage }
+
+    /// Computes a 64-bit hash of the position (i.e. the board
+    /// and the player to move, but not the turn), which is
+    /// independent of the board's internal field order.
+    pub fn position_hash(&self) -> u64 {
+        self.board.fields()
+            .filter(|(_, f)| f.is_occupied())
+            .map(hash_of)
+            .fold(hash_of(self.current_player_color), |acc, h| acc ^ h)
+    }
+
+    /// Computes a stable 64-bit hash of the position including the
+    /// turn, which is the same for all rotations and reflections of
+    /// the board around the origin and thus identifies positions across
+    /// games. Also returns the indices of the symmetries that map the
+    /// board to the canonical orientation (see `AxialCoords::symmetry`),
+    /// of which there are several if the board itself is symmetric.
+    pub fn canonical_form(&self) -> (u64, Vec<usize>) {
+        let fields: Vec<_> = self.board.fields()
+            .filter(|(_, f)| f.is_occupied())
+            .map(|(c, f)| (c, f.piece_stack().iter().fold(mix(0, f.is_obstructed() as u64), |h, p| {
+                mix(mix(h, u64::from(char::from(p.owner))), u64::from(char::from(p.piece_type)))
+            })))
+            .collect();
+        let board_hashes: Vec<u64> = (0..SYMMETRY_COUNT)
+            .map(|i| fields.iter().fold(0, |acc, &(c, h)| acc ^ mix_coords(h, c.symmetry(i))))
+            .collect();
+        let board_hash = *board_hashes.iter().min().unwrap();
+        let symmetries = (0..SYMMETRY_COUNT).filter(|&i| board_hashes[i] == board_hash).collect();
+        (mix(mix(board_hash, u64::from(self.turn)), u64::from(char::from(self.current_player_color))), symmetries)
+    }
+
+    /// Sorts the undeployed pieces into the order of the initial
+    /// piece set. States parsed from the server are normalized,
+    /// since its ordering may differ from local bookkeeping.
+    pub fn normalize_undeployed_pieces(&mut self) {
+        normalize_pieces(&mut self.undeployed_red_pieces, &self.rules.initial_piece_types);
+        normalize_pieces(&mut self.undeployed_blue_pieces, &self.rules.initial_piece_types);
+    }
+
+    /// Tests whether both states describe the same position, i.e.
+    /// whether they have the same turn, colors, board and undeployed
+    /// pieces (in any order). Unlike `==`, this ignores the player
+    /// metadata and unparsed extensions, which the server may update
+    /// independently of the moves.
+    pub fn equivalent(&self, other: &GameState) -> bool {
+        self.turn == other.turn
+            && self.start_player_color == other.start_player_color
+            && self.current_player_color == other.current_player_color
+            && self.board == other.board
+            && same_pieces(&self.undeployed_red_pieces, &other.undeployed_red_pieces)
+            && same_pieces(&self.undeployed_blue_pieces, &other.undeployed_blue_pieces)
+    }
+
+    /// Counts how often the position occurred before
+    /// in the tracked lineage.
+    pub fn repetitions(&self) -> usize {
+        self.lineage.occurrences(self.position_hash())
+    }
+
+    /// Ensures that every color's deployed and undeployed pieces
+    /// together form the initial piece set of the rules.
+    pub fn validate_piece_counts(&self) -> SCResult<()> {
+        for &color in &[PlayerColor::Red, PlayerColor::Blue] {
+            let mut counts = BTreeMap::new();
+            for &piece_type in &self.rules.initial_piece_types {
+                *counts.entry(piece_type).or_insert(0i32) -= 1;
+            }
+            let deployed = self.board.fields().flat_map(|(_, f)| f.piece_stack().iter()).filter(|p| p.owner == color);
+            for piece in deployed.chain(self.undeployed_pieces(color).iter()) {
+                *counts.entry(piece.piece_type).or_insert(0) += 1;
+            }
+            let mismatches: Vec<_> = counts.into_iter()
+                .filter(|&(_, c)| c != 0)
+                .map(|(t, c)| if c < 0 { format!("{} {:?} missing", -c, t) } else { format!("{} {:?} too many", c, t) })
+                .collect();
+            if !mismatches.is_empty() {
+                return Err(format!("Inconsistent pieces of {:?}: {}", color, mismatches.join(", ")).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Formats the position compactly as the turn, the start and
+    /// current player's colors and the occupied fields with their
+    /// axial coordinates and piece stacks (bottom first), e.g.
+    /// `3 R B 0,0=RB 1,0=BGRT -2,1=#` (`#` denoting an obstructed field).
+    /// The undeployed pieces are implied by the rules' initial pieces.
+    pub fn position_notation(&self) -> String {
+        let mut fields: Vec<_> = self.board.occupied_fields().collect();
+        fields.sort_by_key(|&(c, _)| c);
+        let mut notation = format!("{} {} {}", self.turn, char::from(self.start_player_color), char::from(self.current_player_color));
+        for (coords, field) in fields {
+            let contents = if field.is_obstructed() {
+                "#".to_owned()
+            } else {
+                field.piece_stack().iter().map(|p| format!("{}{}", char::from(p.owner), char::from(p.piece_type))).collect()
+            };
+            notation += &format!(" {},{}={}", coords.x(), coords.y(), contents);
+        }
+        notation
+    }
+
+    /// Parses a position in the notation of `position_notation`,
+    /// played with the standard rules.
+    pub fn from_position_notation(raw: &str) -> SCResult<Self> {
+        Self::from_position_notation_with_rules(raw, RuleSet::default())
+    }
+
+    /// Parses a position in the notation of `position_notation`,
+    /// played with the given rules.
+    pub fn from_position_notation_with_rules(raw: &str, rules: RuleSet) -> SCResult<Self> {
+        let mut tokens = raw.split_whitespace();
+        let mut next_token = |what: &str| tokens.next().ok_or_else(|| SCError::from(format!("Missing {} in position {}", what, raw)));
+        let turn = next_token("turn")?.parse()?;
+        let color = |token: &str| token.chars().next().map(PlayerColor::try_from).unwrap_or_else(|| Err("Empty color".into()));
+        let start_color = color(next_token("start color")?)?;
+        let current_color = color(next_token("current color")?)?;
+
+        let fields = tokens.map(|token| {
+            let (coords, contents) = token.split_once('=').ok_or_else(|| format!("Field {} lacks '='", token))?;
+            let (x, y) = coords.split_once(',').ok_or_else(|| format!("Coordinates {} lack ','", coords))?;
+            let coords = AxialCoords::new(x.parse()?, y.parse()?);
+            if contents == "#" {
+                return Ok((coords, Field::new(Vec::new(), true)));
+            }
+            let chars: Vec<_> = contents.chars().collect();
+            let pieces = chars.chunks(2)
+                .map(|pair| match *pair {
+                    [owner, piece_type] => Ok(Piece { owner: PlayerColor::try_from(owner)?, piece_type: PieceType::try_from(piece_type)? }),
+                    _ => Err(SCError::from(format!("Incomplete piece in field {}", token)))
+                })
+                .collect::<SCResult<Vec<_>>>()?;
+            Ok((coords, Field::new(pieces, false)))
+        }).collect::<SCResult<Vec<_>>>()?;
+        Self::from_occupied_fields(turn, start_color, current_color, fields, rules)
+    }
+
+    /// Encodes the position into a compact binary snapshot, e.g. for
+    /// storing long histories. The snapshot consists of the turn (two
+    /// bytes), the colors (one byte) and, for each occupied field, its
+    /// coordinates, stack height and obstruction (two bytes) followed by
+    /// the stack (bottom first) with one piece per nibble. Like the
+    /// position notation, the players and undeployed pieces are omitted.
+    /// Fails if the turn, a stack or the coordinates of a field exceed
+    /// the snapshot's range.
+    pub fn encode(&self) -> SCResult<Vec<u8>> {
+        let mut fields: Vec<_> = self.board.occupied_fields().collect();
+        fields.sort_by_key(|&(c, _)| c);
+        let mut bytes = Vec::with_capacity(3 + 3 * fields.len());
+        let turn = u16::try_from(self.turn).map_err(|_| format!("Turn {} exceeds the snapshot's range", self.turn))?;
+        bytes.extend_from_slice(&turn.to_le_bytes());
+        bytes.push((self.start_player_color == PlayerColor::Blue) as u8 | ((self.current_player_color == PlayerColor::Blue) as u8) << 1);
+        for (coords, field) in fields {
+            let stack = field.piece_stack();
+            let coords_bits = pack_coords(coords).ok_or_else(|| format!("Field {} exceeds the snapshot's coordinate range", coords))?;
+            if stack.len() > 0xF {
+                return Err(format!("Stack of {} pieces at {} exceeds the snapshot's range", stack.len(), coords).into());
+            }
+            let header = coords_bits as u16 | (stack.len() as u16) << 10 | (field.is_obstructed() as u16) << 15;
+            bytes.extend_from_slice(&header.to_le_bytes());
+            let nibble = |piece: &Piece| PIECE_TYPES.iter().position(|&t| t == piece.piece_type).unwrap_or(0) as u8 | ((piece.owner == PlayerColor::Blue) as u8) << 3;
+            for pair in stack.chunks(2) {
+                bytes.push(nibble(&pair[0]) | pair.get(1).map(nibble).unwrap_or(0) << 4);
+            }
+        }
+        Ok(bytes)
+    }
+
+    /// Decodes a snapshot created by `encode`, played with the standard rules.
+    pub fn decode(bytes: &[u8]) -> SCResult<Self> {
+        Self::decode_with_rules(bytes, RuleSet::default())
+    }
+
+    /// Decodes a snapshot created by `encode`, played with the given rules.
+    pub fn decode_with_rules(bytes: &[u8], rules: RuleSet) -> SCResult<Self> {
+        let truncated = || SCError::from("Truncated snapshot");
+        let color = |bit: u8| if bit & 1 == 1 { PlayerColor::Blue } else { PlayerColor::Red };
+        let header = bytes.get(..3).ok_or_else(truncated)?;
+        let turn = u32::from(u16::from_le_bytes([header[0], header[1]]));
+        let mut fields = Vec::new();
+        let mut rest = &bytes[3..];
+        while !rest.is_empty() {
+            let field_header = u16::from_le_bytes([rest[0], *rest.get(1).ok_or_else(truncated)?]);
+            let height = usize::from((field_header >> 10) & 0xF);
+            let stack = rest.get(2..2 + height.div_ceil(2)).ok_or_else(truncated)?;
+            let pieces = (0..height)
+                .map(|i| {
+                    let nibble = (stack[i / 2] >> (4 * (i % 2))) & 0xF;
+                    let piece_type = *PIECE_TYPES.get(usize::from(nibble & 0x7)).ok_or_else(|| format!("Invalid piece type {} in snapshot", nibble & 0x7))?;
+                    Ok(Piece { piece_type, owner: color(nibble >> 3) })
+                })
+                .collect::<SCResult<Vec<_>>>()?;
+            fields.push((unpack_coords(u32::from(field_header & 0x3FF)), Field::new(pieces, field_header >> 15 == 1)));
+            rest = &rest[2 + stack.len()..];
+        }
+        Self::from_occupied_fields(turn, color(header[2]), color(header[2] >> 1), fields, rules)
+    }
+
+    /// Creates a position from its occupied fields, deriving
+    /// the undeployed pieces from the placed ones.
+    fn from_occupied_fields(turn: u32, start_color: PlayerColor, current_color: PlayerColor, fields: impl IntoIterator<Item=(AxialCoords, Field)>, rules: RuleSet) -> SCResult<Self> {
+        let mut state = Self::initial_with_rules(start_color, rules);
+        state.turn = turn;
+        state.current_player_color = current_color;
+        for (coords, field) in fields {
+            for piece in field.piece_stack() {
+                let undeployed = state.undeployed_pieces_mut(piece.owner);
+                if let Some(i) = undeployed.iter().position(|p| p == piece) {
+                    undeployed.remove(i);
+                }
+            }
+            *state.board.field_mut(coords).ok_or_else(|| format!("Field {:?} is not on the board", coords))? = field;
+        }
+        state.validate_piece_counts()?;
+        Ok(state)
+    }
+
     /// Ensures that the destination is a direct neighbor of the start.
     fn validate_adjacent(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
         if start.is_adjacent_to(destination) { Ok(()) } else { Err("Coords are not adjacent to each other".into()) }
     }
     
-    fn validate_ant_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
-        if self.board.connected_by_boundary_path(start, destination) { Ok(()) } else { Err("Could not find path for ant".into()) }
+    fn validate_ant_move(&self, ctx: &mut SearchContext, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        if self.board.connected_by_boundary_path_with(ctx, start, destination) { Ok(()) } else { Err("Could not find path for ant".into()) }
     }
     
-    fn validate_bee_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+    pub(super) fn validate_bee_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
         self.validate_adjacent(start, destination)?;
         if self.board.can_move_between(start, destination) { Ok(()) } else { Err(format!("Cannot move between {:?} and {:?}", start, destination).into()) }
     }
     
-    fn validate_beetle_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+    pub(super) fn validate_beetle_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
         self.validate_adjacent(start, destination)?;
         if self.board.shared_neighbors(start, destination, None).iter().any(|(_, f)| f.has_pieces()) || self.board.field(destination).map(|f| f.has_pieces()).unwrap_or(false) {
             Ok(())
@@ -65,74 +443,115 @@ impl GameState {
             Err("Grasshopper can only move along straight lines".into())
         } else if start.is_adjacent_to(destination) {
             Err("Grasshopper must not move to a neighbor".into())
-        } else if start.line_iter(destination).map(|c| AxialCoords::from(c)).any(|c| self.board.field(c).map(|f| f.is_empty()).unwrap_or(false)) {
+        } else if start.line_iter(destination).map(AxialCoords::from).any(|c| self.board.field(c).map(|f| f.is_empty()).unwrap_or(false)) {
             Err("Grasshopper cannot move over empty fields".into())
         } else {
             Ok(())
         }
     }
     
-    fn validate_spider_move(&self, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
-        if self.board.bfs_reachable_in_3_steps(start, destination) { Ok(()) } else { Err("No 3-step path found for Spider move".into()) }
+    fn validate_spider_move(&self, ctx: &mut SearchContext, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        if self.board.bfs_reachable_in_3_steps_with(ctx, start, destination) { Ok(()) } else { Err("No 3-step path found for Spider move".into()) }
     }
 
-    fn validate_set_move(&self, color: PlayerColor, piece: Piece, destination_coords: impl Into<AxialCoords>) -> SCResult<()> {
+    fn validate_set_move(&self, cache: &mut LegalityCache, color: PlayerColor, piece: Piece, destination_coords: impl Into<AxialCoords>) -> SCResult<()> {
         let destination = destination_coords.into();
         if !self.board.contains_coords(destination) {
             Err(format!("Move destination is out of bounds: {:?}", destination).into())
         } else if self.board.field(destination).map(|f| f.is_obstructed()).unwrap_or(true) {
             Err(format!("Move destination is obstructed: {:?}", destination).into())
-        } else if !self.board.fields().any(|(_, f)| f.has_pieces()) {
+        } else if self.board.field(destination).map(|f| f.has_pieces()).unwrap_or(true) {
+            Err(format!("Move destination is not empty: {:?}", destination).into())
+        } else if piece.owner != color {
+            Err("Cannot place opponent's piece".into())
+        } else if !self.undeployed_pieces(color).contains(&piece) {
+            Err("Piece is not undeployed".into())
+        } else if !cache.has_pieces(&self.board) {
             Ok(())
-        } else if self.board.fields_owned_by(color).count() == 0 {
+        } else if !cache.has_own_pieces(&self.board, color) {
             if self.board.is_next_to(color.opponent(), destination) {
                 Ok(())
             } else {
                 Err("Piece has to be placed next to an opponent's piece".into())
             }
-        } else if (self.round() == 3) && (!self.board.has_placed_bee(color)) && (piece.piece_type != PieceType::Bee) {
-            Err("Bee has to be placed in the fourth round or earlier".into())
-        } else if !self.undeployed_pieces(color).contains(&piece) {
-            Err("Piece is not undeployed".into())
+        } else if self.rules.must_place_bee(self.round()) && (piece.piece_type != PieceType::Bee) && !cache.has_placed_bee(&self.board, color) {
+            Err(format!("Bee has to be placed in round {} or earlier", self.round() + 1).into())
         } else if !self.board.neighbors(destination).any(|(_, f)| f.is_owned_by(color)) {
             Err("Piece is not placed next to an own piece".into())
-        } else if self.board.neighbors(destination).any(|(_, f)| f.is_owned_by(color)) {
+        } else if self.board.is_next_to(color.opponent(), destination) {
             Err("Piece must not be placed next to an opponent's piece".into())
         } else {
             Ok(())
         }
     }
 
-    fn validate_drag_move(&self, color: PlayerColor, start_coords: impl Into<AxialCoords>, destination_coords: impl Into<AxialCoords>) -> SCResult<()> {
+    /// Tests whether the swarm stays connected if the
+    /// top-most piece at the given coordinates is removed.
+    pub(super) fn is_swarm_connected_without(&self, ctx: &mut SearchContext, coords: AxialCoords) -> SCResult<bool> {
+        if self.board.contains_coords(coords) {
+            Ok(self.board.is_swarm_connected_except(ctx, Some(coords)))
+        } else {
+            Err("Start field does not exist".into())
+        }
+    }
+
+    /// Tests whether a piece of the given type located
+    /// at the given coordinates may climb onto other pieces.
+    pub(super) fn can_climb(&self, piece_type: PieceType, #[allow(unused_variables)] start: AxialCoords) -> bool {
+        match piece_type {
+            PieceType::Beetle => true,
+            #[cfg(feature = "expansions")]
+            PieceType::Mosquito => self.can_mosquito_climb(start),
+            _ => false
+        }
+    }
+
+    /// Validates the movement of a piece of the given type
+    /// (ignoring the general drag move constraints).
+    pub(super) fn validate_piece_move(&self, ctx: &mut SearchContext, piece_type: PieceType, start: AxialCoords, destination: AxialCoords) -> SCResult<()> {
+        match piece_type {
+            PieceType::Ant => self.validate_ant_move(ctx, start, destination),
+            PieceType::Bee => self.validate_bee_move(start, destination),
+            PieceType::Beetle => self.validate_beetle_move(start, destination),
+            PieceType::Grasshopper => self.validate_grasshopper_move(start, destination),
+            PieceType::Spider => self.validate_spider_move(ctx, start, destination),
+            #[cfg(feature = "expansions")]
+            PieceType::Mosquito => self.validate_mosquito_move(ctx, start, destination),
+            #[cfg(feature = "expansions")]
+            PieceType::Ladybug => self.validate_ladybug_move(start, destination),
+            #[cfg(feature = "expansions")]
+            PieceType::Pillbug => self.validate_pillbug_move(start, destination)
+        }
+    }
+
+    fn validate_drag_move(&self, ctx: &mut SearchContext, cache: &mut LegalityCache, color: PlayerColor, start_coords: impl Into<AxialCoords>, destination_coords: impl Into<AxialCoords>) -> SCResult<()> {
         let start = start_coords.into();
         let destination = destination_coords.into();
-        if !self.board.has_placed_bee(color) {
+        if !cache.has_placed_bee(&self.board, color) {
             Err("Bee has to be placed before committing a drag move".into())
         } else if !self.board.contains_coords(start) {
             Err(format!("Move start is out of bounds: {:?}", start).into())
         } else if !self.board.contains_coords(destination) {
             Err(format!("Move destination is out of bounds: {:?}", destination).into())
         } else if let Some(dragged_piece) = self.board.field(start).and_then(|f| f.piece()) {
+            // Opponent's pieces can only be moved by a pillbug
+            #[cfg(feature = "expansions")]
+            match self.validate_pillbug_throw(ctx, color, start, destination) {
+                Ok(()) => return Ok(()),
+                Err(e) if dragged_piece.owner != color && self.pillbug_throw_starts(color).iter().any(|&(c, _)| c == start) => return Err(e),
+                Err(_) => {}
+            }
+
             if dragged_piece.owner != color {
                 Err("Cannot move opponent's piece".into())
             } else if start == destination {
                 Err("Cannot move when start == destination".into())
-            } else if self.board.field(destination).and_then(|f| f.piece()).map(|p| p.piece_type == PieceType::Beetle).unwrap_or(false) {
+            } else if self.board.field(destination).map(|f| f.has_pieces()).unwrap_or(false) && !self.can_climb(dragged_piece.piece_type, start) {
                 Err("Only beetles can climb other pieces".into())
-            } else if {
-                let mut without_piece = self.board.clone();
-                without_piece.field_mut(start).ok_or_else(|| "Start field does not exist")?.pop();
-                !without_piece.is_swarm_connected()
-            } {
+            } else if !cache.is_swarm_connected_without(self, ctx, start)? {
                 Err("Drag move would disconnect the swarm".into())
             } else {
-                match dragged_piece.piece_type {
-                    PieceType::Ant => self.validate_ant_move(start, destination),
-                    PieceType::Bee => self.validate_bee_move(start, destination),
-                    PieceType::Beetle => self.validate_beetle_move(start, destination),
-                    PieceType::Grasshopper => self.validate_grasshopper_move(start, destination),
-                    PieceType::Spider => self.validate_spider_move(start, destination)
-                }
+                self.validate_piece_move(ctx, dragged_piece.piece_type, start, destination)
             }
         } else {
             Err("No piece to move".into())
@@ -141,93 +560,361 @@ impl GameState {
     
     //// Tests whether the given move is valid.
     pub fn validate_move(&self, color: PlayerColor, game_move: &Move) -> SCResult<()> {
+        self.validate_move_with(&mut SearchContext::new(), color, game_move)
+    }
+
+    /// Tests whether the given move is valid using the context's buffers.
+    pub fn validate_move_with(&self, ctx: &mut SearchContext, color: PlayerColor, game_move: &Move) -> SCResult<()> {
+        self.validate_move_cached(ctx, &mut LegalityCache::default(), color, game_move)
+    }
+
+    /// Tests whether the given move is valid, reusing the facts
+    /// about the state computed while validating other moves.
+    fn validate_move_cached(&self, ctx: &mut SearchContext, cache: &mut LegalityCache, color: PlayerColor, game_move: &Move) -> SCResult<()> {
         match game_move {
-            Move::SetMove { piece, destination } => self.validate_set_move(color, *piece, destination.coords),
-            Move::DragMove { start, destination } => self.validate_drag_move(color, start.coords, destination.coords)
+            Move::SetMove { piece, destination } => self.validate_set_move(cache, color, *piece, destination.coords),
+            Move::DragMove { start, destination } => self.validate_drag_move(ctx, cache, color, start.coords, destination.coords)
         }
     }
+
+    /// Keeps the legal moves among the given candidates. Unlike
+    /// validating each move separately, this computes facts such
+    /// as the bee placement or whether removing a piece disconnects
+    /// the swarm only once for all candidates.
+    pub fn filter_legal(&self, color: PlayerColor, moves: impl IntoIterator<Item=Move>) -> Vec<Move> {
+        let mut ctx = SearchContext::new();
+        let mut cache = LegalityCache::default();
+        moves.into_iter().filter(|m| self.validate_move_cached(&mut ctx, &mut cache, color, m).is_ok()).collect()
+    }
     
-    /// Fetches a list of possible `SetMove`s.
-    fn possible_set_moves(&self, color: PlayerColor) -> Vec<Move> {
-        trace!("Finding possible SetMoves");
+    /// Applies the given move (without validating it) and
+    /// returns the successor state, in which the turn has
+    /// passed to the opponent.
+    pub fn apply_move(&self, game_move: &Move) -> SCResult<GameState> {
+        let mut next = self.clone();
+        next.advance(game_move)?;
+        Ok(next)
+    }
 
-        let undeployed = self.undeployed_pieces(color);
-        let opponent = color.opponent();
-        let destination_coords: Vec<_> = if undeployed.len() == INITIAL_PIECE_TYPES.len() {
-            // No pieces placed yet
-            if self.undeployed_pieces(opponent).len() == INITIAL_PIECE_TYPES.len() {
-                // First turn
-                trace!("Finding SetMoves during first turn...");
-                self.board.empty_fields().map(|(c, _)| c).collect()
-            } else {
-                // Second turn
-                trace!("Finding SetMoves during second turn...");
-                self.board.fields_owned_by(opponent).flat_map(|(c, _)| self.board.empty_neighbors(c)).map(|(c, _)| c).collect()
+    /// Advances this state by the given move without validating it.
+    /// Leaves the state untouched if the move cannot be applied.
+    fn advance(&mut self, game_move: &Move) -> SCResult<()> {
+        let parent_hash = self.position_hash();
+        let destination = game_move.destination_coords();
+        if self.board.field(destination).is_none() {
+            return Err(format!("Move destination is out of bounds: {}", destination).into());
+        }
+
+        match game_move {
+            Move::SetMove { piece, .. } => {
+                let undeployed = self.undeployed_pieces_mut(piece.owner);
+                let index = undeployed.iter().position(|p| p == piece).ok_or_else(|| format!("{:?} is not undeployed", piece))?;
+                undeployed.remove(index);
+                self.board.field_mut(destination).unwrap().push(*piece);
+            },
+            Move::DragMove { start, .. } => {
+                let piece = self.board.field_mut(start.coords).and_then(|f| f.pop()).ok_or_else(|| format!("No piece to move at {}", start.coords))?;
+                self.board.field_mut(destination).unwrap().push(piece);
             }
+        }
+
+        self.turn += 1;
+        self.current_player_color = self.current_player_color.opponent();
+        self.lineage = self.lineage.extended(parent_hash, game_move);
+        #[cfg(feature = "protocol")]
+        self.extensions.clear();
+        #[cfg(feature = "protocol")]
+        self.extension_attributes.clear();
+        Ok(())
+    }
+
+    /// Validates the given move for the player to move and,
+    /// if it is legal, advances this state by it in place.
+    pub fn perform_move(&mut self, game_move: &Move) -> SCResult<()> {
+        self.validate_move(self.current_player_color, game_move)?;
+        self.advance(game_move)
+    }
+
+    /// Applies the given move like `apply_move` and additionally
+    /// returns the changes to the position caused by it.
+    pub fn apply_move_with_delta(&self, game_move: &Move) -> SCResult<(GameState, MoveDelta)> {
+        let next = self.apply_move(game_move)?;
+        let (start, destination) = match game_move {
+            Move::SetMove { destination, .. } => (None, destination.coords),
+            Move::DragMove { start, destination } => (Some(start.coords), destination.coords)
+        };
+        let field_hash = |state: &GameState, coords: AxialCoords| state.board.field(coords).filter(|f| f.is_occupied()).map(|f| hash_of((coords, f))).unwrap_or(0);
+        let hash_diff = start.into_iter().chain(Some(destination))
+            .map(|c| field_hash(self, c) ^ field_hash(&next, c))
+            .fold(hash_of(self.current_player_color) ^ hash_of(next.current_player_color), |acc, h| acc ^ h);
+        let freedoms = |state: &GameState| [PlayerColor::Red, PlayerColor::Blue].map(|c| state.board.bee_freedom(c).unwrap_or(0));
+        let vacated = start.filter(|&c| !next.board.is_occupied(c));
+        let occupied = Some(destination).filter(|&c| !self.board.is_occupied(c));
+        let delta = MoveDelta::new(hash_diff, vacated, occupied, freedoms(self), freedoms(&next));
+        Ok((next, delta))
+    }
+
+    /// Reconstructs the move leading from this state to the given
+    /// successor (e.g. the opponent's move between two states received
+    /// from the server) by comparing the boards.
+    pub fn infer_move(&self, next: &GameState) -> Option<Move> {
+        let changed: Vec<_> = self.board.fields()
+            .filter_map(|(c, f)| next.board.field(c).filter(|&n| n != f).map(|n| (c, f.piece_stack().len(), n)))
+            .collect();
+        let game_move = match changed.as_slice() {
+            [(destination, len, field)] if field.piece_stack().len() == len + 1 => Move::SetMove {
+                piece: field.piece()?,
+                destination: PositionedField::new(*destination)
+            },
+            [(a, a_len, a_field), (b, _, _)] => {
+                let (start, destination) = if a_field.piece_stack().len() < *a_len { (*a, *b) } else { (*b, *a) };
+                Move::DragMove { start: PositionedField::new(start), destination: PositionedField::new(destination) }
+            },
+            _ => return None
+        };
+        Some(game_move).filter(|m| self.apply_move(m).map(|s| s.board == next.board).unwrap_or(false))
+    }
+
+    /// Tests whether the color has to place its bee this turn, since
+    /// it has not done so yet and the deadline of the rules is reached.
+    pub fn must_place_bee(&self, color: PlayerColor) -> bool {
+        self.rules.must_place_bee(self.round()) && !self.board.has_placed_bee(color)
+    }
+
+    /// Fetches the moves the color is restricted to by the bee
+    /// placement deadline, i.e. the placements of its bee if it
+    /// has to place it this turn. Custom move generators should
+    /// return these moves instead of their own in that case.
+    pub fn possible_moves_forced(&self, color: PlayerColor) -> Option<Vec<Move>> {
+        if self.must_place_bee(color) {
+            let bee = Piece { piece_type: PieceType::Bee, owner: color };
+            Some(self.set_move_destinations(color).map(|d| Move::SetMove { piece: bee, destination: d }).collect())
+        } else {
+            None
+        }
+    }
+
+    /// Finds the destinations for pieces placed by the given color.
+    fn set_move_destinations(&self, color: PlayerColor) -> impl Iterator<Item=PositionedField> + '_ {
+        let destination_coords: Vec<_> = if let Some(opening) = OpeningPlacement::of(self, color) {
+            trace!("Finding SetMoves during {} turn...", if opening.is_first_turn() { "first" } else { "second" });
+            opening.destinations()
         } else {
             trace!("Querying SetMove destinations...");
             self.board.possible_set_move_destinations(color).collect()
         };
 
-        let destinations = destination_coords.into_iter()
-            .filter_map(|c| self.board.field(c).map(|f| PositionedField { coords: c, field: f.clone() }));
-        trace!("Found SetMove destinations at {:#?}", destinations);
-        
-        if !self.board.has_placed_bee(color) && self.turn > 5 {
+        destination_coords.into_iter()
+            .filter(move |&c| self.board.contains_coords(c))
+            .map(PositionedField::new)
+    }
+
+    /// Appends the possible `SetMove`s to the given vector.
+    fn possible_set_moves(&self, color: PlayerColor, moves: &mut Vec<Move>) {
+        trace!("Finding possible SetMoves");
+
+        if let Some(forced) = self.possible_moves_forced(color) {
             trace!("Player has not placed bee yet, therefore placing it is the only valid move.");
-            destinations
-                .map(|d| Move::SetMove {
-                    piece: Piece { piece_type: PieceType::Bee, owner: color },
-                    destination: d
-                })
-                .collect()
+            moves.extend(forced);
         } else {
-            trace!("Creating set moves from {:?} x {:?}", destinations, undeployed);
-            destinations
-                .flat_map(|d| undeployed.iter().map(move |&p| Move::SetMove { piece: p, destination: d.clone() }))
-                .collect()
+            let undeployed = self.undeployed_pieces(color);
+            trace!("Creating set moves from {:?}", undeployed);
+            moves.extend(self.set_move_destinations(color)
+                .flat_map(|d| undeployed.iter().map(move |&p| Move::SetMove { piece: p, destination: d.clone() })));
         }
     }
     
-    /// Returns the validated move.
-    fn validated(&self, color: PlayerColor, game_move: Move) -> SCResult<Move> {
-        self.validate_move(color, &game_move).map(|_| game_move)
+    /// Appends the possible `DragMove`s to the given vector.
+    fn possible_drag_moves(&self, ctx: &mut SearchContext, color: PlayerColor, moves: &mut Vec<Move>) {
+        self.visit_drag_moves(ctx, color, |game_move| {
+            moves.push(game_move);
+            true
+        });
     }
-    
-    /// Fetches a list of possible `DragMove`s.
-    fn possible_drag_moves(&self, color: PlayerColor) -> Vec<Move> {
+
+    /// Passes the possible `DragMove`s to the visitor until it returns
+    /// false, in which case false is returned too.
+    fn visit_drag_moves(&self, ctx: &mut SearchContext, color: PlayerColor, mut visit: impl FnMut(Move) -> bool) -> bool {
         trace!("Finding possible DragMoves");
 
-        self.board.fields_owned_by(color).flat_map(|(start_coords, start_field)| {
-            let mut targets: Vec<_> = self.board.swarm_boundary().collect();
+        let starts: Vec<_> = self.board.fields_owned_by(color).collect();
+        #[cfg(feature = "expansions")]
+        let starts: Vec<_> = starts.into_iter().chain(self.pillbug_throw_starts(color)).unique_by(|(c, _)| *c).collect();
+
+        let boundary: Vec<_> = self.board.swarm_boundary_fields().into_iter().map(|f| f.coords).collect();
+        let mut cache = LegalityCache::default();
+
+        for (start_coords, start_field) in starts {
+            let climbing = start_field.piece().filter(|p| self.can_climb(p.piece_type, start_coords)).is_some();
+            let neighbors = self.board.neighbors(start_coords).map(|(c, _)| c).filter(|c| climbing && boundary.binary_search(c).is_err());
+            let targets = boundary.iter().cloned().chain(neighbors);
 
-            if start_field.piece().filter(|f| f.piece_type == PieceType::Beetle).is_some() {
-                targets.extend(self.board.neighbors(start_coords));
+            for destination in targets {
+                let game_move = Move::DragMove {
+                    start: PositionedField::new(start_coords),
+                    destination: PositionedField::new(destination)
+                };
+                if self.validate_move_cached(ctx, &mut cache, color, &game_move).is_ok() && !visit(game_move) {
+                    return false;
+                }
             }
-            
-            trace!("Drag targets from {}: {:#?}", start_coords, targets);
-            targets.into_iter()
-                .filter_map(move |(c, f)| self.validated(color, Move::DragMove {
-                    start: PositionedField { coords: start_coords, field: start_field.clone() },
-                    destination: PositionedField { coords: c, field: f.clone() }
-                }).ok())
-        }).collect()
+        }
+        true
+    }
+
+    /// Tests whether the color has any legal move, stopping
+    /// at the first one found instead of generating all of them.
+    pub fn has_any_legal_move(&self, color: PlayerColor) -> bool {
+        self.has_any_legal_move_with(&mut SearchContext::new(), color)
+    }
+
+    /// Tests whether the color has any legal move
+    /// using the context's buffers.
+    pub fn has_any_legal_move_with(&self, ctx: &mut SearchContext, color: PlayerColor) -> bool {
+        let can_place = !self.undeployed_pieces(color).is_empty() && match OpeningPlacement::of(self, color) {
+            Some(opening) => !opening.destinations().is_empty(),
+            None => self.board.possible_set_move_destinations(color).any(|c| self.board.contains_coords(c))
+        };
+        can_place || !self.visit_drag_moves(ctx, color, |_| false)
     }
     
+    /// Finds the legal placement destinations of the given color that
+    /// survive a 1-ply analysis of the opponent's replies: no opponent
+    /// piece can move onto or next to the placed piece right away, and
+    /// the placement does not let the opponent take away more free fields
+    /// around the own bee than it already could.
+    pub fn safe_set_destinations(&self, color: PlayerColor) -> Vec<AxialCoords> {
+        let opponent = color.opponent();
+        let mut ctx = SearchContext::new();
+        let mut placements = Vec::new();
+        self.possible_set_moves(color, &mut placements);
+        // Placements are grouped by destination, one of which serves as a probe
+        placements.dedup_by_key(|m| m.destination_coords());
+
+        let mut replies = Vec::new();
+        self.possible_drag_moves(&mut ctx, opponent, &mut replies);
+        let freedom = self.board.bee_freedom(color);
+        let threat = self.bee_threat(color, &replies);
+
+        placements.into_iter().filter_map(|placement| {
+            let destination = placement.destination_coords();
+            let next = self.apply_move(&placement).ok()?;
+            replies.clear();
+            next.possible_drag_moves(&mut ctx, opponent, &mut replies);
+            let attacked = replies.iter().any(|r| {
+                let target = r.destination_coords();
+                target == destination || target.is_adjacent_to(destination)
+            });
+            let gives_tempo = match (freedom, next.board.bee_freedom(color)) {
+                (Some(before), Some(after)) => before - after + next.bee_threat(color, &replies) > threat,
+                _ => false
+            };
+            Some(destination).filter(|_| !attacked && !gives_tempo)
+        }).collect()
+    }
+
+    /// Counts the opponent's replies that would take away a free
+    /// field around the bee of the given color, capped at one (since
+    /// only one of them can be played), which makes the threat
+    /// comparable to the number of free fields lost.
+    fn bee_threat(&self, color: PlayerColor, replies: &[Move]) -> usize {
+        let bee_coords = match self.bee_coords(color) {
+            Some(c) => c,
+            None => return 0
+        };
+        let threatening = replies.iter().any(|r| match r {
+            Move::DragMove { start, destination } => destination.coords.is_adjacent_to(bee_coords)
+                && !self.board.is_occupied(destination.coords)
+                && !start.coords.is_adjacent_to(bee_coords),
+            Move::SetMove { .. } => false
+        });
+        usize::from(threatening)
+    }
+
+    /// Finds the coordinates of the given color's bee, if placed.
+    fn bee_coords(&self, color: PlayerColor) -> Option<AxialCoords> {
+        let bee = Piece { piece_type: PieceType::Bee, owner: color };
+        self.board.fields().find(|(_, f)| f.piece_stack().contains(&bee)).map(|(c, _)| c)
+    }
+
+    /// Counts the empty fields next to the given color's bee,
+    /// i.e. the fields the opponent still has to occupy to win
+    /// (none if the bee has not been placed yet).
+    pub fn bee_liberties(&self, color: PlayerColor) -> Option<usize> {
+        self.board.bee_freedom(color)
+    }
+
+    /// Finds the moves of the given color that decrease the
+    /// liberties of the opponent's bee, in move generation order.
+    pub fn surround_threats(&self, color: PlayerColor) -> Vec<Move> {
+        let bee_coords = match self.bee_coords(color.opponent()) {
+            Some(c) => c,
+            None => return Vec::new()
+        };
+        // Only moves ending next to the bee (or moving it, e.g. by
+        // a pillbug's throw) can take away one of its liberties
+        self.possible_moves(color).into_iter()
+            .filter(|m| {
+                let destination = m.destination_coords();
+                let moves_bee = matches!(m, Move::DragMove { start, .. } if start.coords == bee_coords);
+                (moves_bee || destination.is_adjacent_to(bee_coords)) && self.apply_move_with_delta(m)
+                    .map(|(_, delta)| delta.bee_freedom_diff(color.opponent()) < 0)
+                    .unwrap_or(false)
+            })
+            .collect()
+    }
+
+    /// Tests whether the opponent could surround the given color's
+    /// bee with its next move, i.e. whether it is about to lose.
+    pub fn is_in_check(&self, color: PlayerColor) -> bool {
+        self.bee_liberties(color) == Some(1) && !self.surround_threats(color.opponent()).is_empty()
+    }
+
     /// Fetches a list of possible moves for a given color.
     pub fn possible_moves(&self, color: PlayerColor) -> Vec<Move> {
+        let mut moves = Vec::new();
+        self.extend_possible_moves(&mut SearchContext::new(), color, &mut moves);
+        moves
+    }
+
+    /// Appends the possible moves for a given color to the
+    /// vector using the context's buffers.
+    pub fn extend_possible_moves(&self, ctx: &mut SearchContext, color: PlayerColor, moves: &mut Vec<Move>) {
         trace!("Finding possible moves for color {:?}", color);
         trace!("Current board state:\n{}", self.board);
 
-        let mut moves = self.possible_set_moves(color);
-        moves.extend(self.possible_drag_moves(color));
-        moves
+        self.possible_set_moves(color, moves);
+        self.possible_drag_moves(ctx, color, moves);
+    }
+}
+
+impl PartialEq for GameState {
+    fn eq(&self, other: &Self) -> bool {
+        let same_extensions = {
+            #[cfg(feature = "protocol")]
+            { self.extensions == other.extensions && self.extension_attributes == other.extension_attributes }
+            #[cfg(not(feature = "protocol"))]
+            { true }
+        };
+        self.turn == other.turn
+            && self.start_player_color == other.start_player_color
+            && self.current_player_color == other.current_player_color
+            && self.board == other.board
+            && self.red_player == other.red_player
+            && self.blue_player == other.blue_player
+            && self.undeployed_red_pieces == other.undeployed_red_pieces
+            && self.undeployed_blue_pieces == other.undeployed_blue_pieces
+            && self.rules == other.rules
+            && same_extensions
     }
 }
 
+impl Eq for GameState {}
+
+#[cfg(feature = "protocol")]
 impl FromXmlNode for GameState {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
-        Ok(Self {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let mut state = Self {
             turn: node.attribute("turn")?.parse()?,
             start_player_color: node.attribute("startPlayerColor")?.parse()?,
             current_player_color: node.attribute("currentPlayerColor")?.parse()?,
@@ -235,7 +922,50 @@ impl FromXmlNode for GameState {
             blue_player: Player::from_node(node.child_by_name("blue")?)?,
             board: Board::from_node(node.child_by_name("board")?)?,
             undeployed_red_pieces: node.child_by_name("undeployedRedPieces")?.childs_by_name("piece").map(Piece::from_node).collect::<Result<_, _>>()?,
-            undeployed_blue_pieces: node.child_by_name("undeployedBluePieces")?.childs_by_name("piece").map(Piece::from_node).collect::<Result<_, _>>()?
+            undeployed_blue_pieces: node.child_by_name("undeployedBluePieces")?.childs_by_name("piece").map(Piece::from_node).collect::<Result<_, _>>()?,
+            lineage: Lineage::default(),
+            rules: Arc::default(),
+            extensions: node.childs().iter().filter(|c| !STATE_CHILDS.contains(&c.name())).map(XmlElement::to_node).collect(),
+            extension_attributes: node.unknown_attributes(&STATE_ATTRIBUTES)
+        };
+        state.normalize_undeployed_pieces();
+        Ok(state)
+    }
+}
+
+#[cfg(feature = "protocol")]
+impl From<GameState> for XmlNode {
+    fn from(state: GameState) -> Self {
+        let pieces = |name, pieces: &Vec<Piece>| XmlNode::new(name).childs(pieces.iter().map(|&p| XmlNode::from(p))).build();
+        XmlNode::new("state")
+            .attributes(state.extension_attributes)
+            .attribute("turn", state.turn.to_string())
+            .attribute("startPlayerColor", state.start_player_color)
+            .attribute("currentPlayerColor", state.current_player_color)
+            .child(XmlNodeBuilder::from(&state.red_player).name("red"))
+            .child(XmlNodeBuilder::from(&state.blue_player).name("blue"))
+            .child(XmlNode::from(&state.board))
+            .child(pieces("undeployedRedPieces", &state.undeployed_red_pieces))
+            .child(pieces("undeployedBluePieces", &state.undeployed_blue_pieces))
+            .childs(state.extensions)
+            .build()
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&GameState> for serde_json::Value {
+    fn from(state: &GameState) -> Self {
+        let pieces = |pieces: &Vec<Piece>| pieces.iter().map(|&p| serde_json::Value::from(p)).collect::<Vec<_>>();
+        serde_json::json!({
+            "turn": state.turn,
+            "round": state.round(),
+            "startPlayerColor": serde_json::Value::from(state.start_player_color),
+            "currentPlayerColor": serde_json::Value::from(state.current_player_color),
+            "red": serde_json::Value::from(&state.red_player),
+            "blue": serde_json::Value::from(&state.blue_player),
+            "board": serde_json::Value::from(&state.board),
+            "undeployedRedPieces": pieces(&state.undeployed_red_pieces),
+            "undeployedBluePieces": pieces(&state.undeployed_blue_pieces)
         })
     }
 }
@@ -0,0 +1,63 @@
+use std::fmt;
+use super::AxialCoords;
+
+/// One of the 6 directions towards the neighbors of a
+/// (pointy-top) hexagon, named after the direction on
+/// screen when laid out like in the ASCII representation.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum HexDirection {
+    UpLeft,
+    UpRight,
+    Right,
+    DownRight,
+    DownLeft,
+    Left
+}
+
+impl HexDirection {
+    /// All directions in clockwise order, matching
+    /// the order of `AxialCoords::coord_neighbors`.
+    pub const ALL: [HexDirection; 6] = [
+        HexDirection::UpLeft,
+        HexDirection::UpRight,
+        HexDirection::Right,
+        HexDirection::DownRight,
+        HexDirection::DownLeft,
+        HexDirection::Left
+    ];
+
+    /// Fetches the index in `ALL`.
+    #[inline]
+    pub fn index(self) -> usize { self as usize }
+
+    /// Fetches the step to the neighbor in this direction.
+    #[inline]
+    pub const fn offset(self) -> AxialCoords {
+        match self {
+            HexDirection::UpLeft => AxialCoords::new(0, 1),
+            HexDirection::UpRight => AxialCoords::new(1, 0),
+            HexDirection::Right => AxialCoords::new(1, -1),
+            HexDirection::DownRight => AxialCoords::new(0, -1),
+            HexDirection::DownLeft => AxialCoords::new(-1, 0),
+            HexDirection::Left => AxialCoords::new(-1, 1)
+        }
+    }
+
+    /// Fetches the direction pointing the other way.
+    #[inline]
+    pub fn opposite(self) -> Self { Self::ALL[(self.index() + 3) % 6] }
+
+    /// Fetches the next direction in clockwise order.
+    #[inline]
+    pub fn clockwise(self) -> Self { Self::ALL[(self.index() + 1) % 6] }
+
+    /// Fetches the next direction in counter-clockwise order.
+    #[inline]
+    pub fn counter_clockwise(self) -> Self { Self::ALL[(self.index() + 5) % 6] }
+}
+
+impl fmt::Display for HexDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
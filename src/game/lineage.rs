@@ -0,0 +1,94 @@
+use std::fmt;
+use std::sync::Arc;
+use super::Move;
+
+/// A step in the history of a game state, consisting of
+/// the parent position's hash and the move played in it.
+#[derive(Debug)]
+struct LineageStep {
+    parent_hash: u64,
+    game_move: Move,
+    parent: Option<Arc<LineageStep>>
+}
+
+/// The optional history of positions leading to a game state.
+///
+/// The steps form a shared linked list, thus cloning a lineage
+/// (and the state holding it) stays cheap. Lineages are not
+/// comparable and thus ignored when comparing game states.
+#[derive(Debug, Clone, Default)]
+pub struct Lineage {
+    is_tracked: bool,
+    last: Option<Arc<LineageStep>>
+}
+
+impl Lineage {
+    /// Creates an empty, tracked lineage.
+    pub fn tracked() -> Self {
+        Self { is_tracked: true, last: None }
+    }
+
+    /// Tests whether successor states extend the lineage.
+    pub fn is_tracked(&self) -> bool { self.is_tracked }
+
+    /// Extends the lineage by a step if it is tracked.
+    pub(super) fn extended(&self, parent_hash: u64, game_move: &Move) -> Self {
+        if self.is_tracked {
+            Self {
+                is_tracked: true,
+                last: Some(Arc::new(LineageStep { parent_hash, game_move: game_move.clone(), parent: self.last.clone() }))
+            }
+        } else {
+            self.clone()
+        }
+    }
+
+    /// Iterates the parent hashes and moves, starting
+    /// with the most recent one.
+    pub fn iter(&self) -> impl Iterator<Item=(u64, &Move)> {
+        let mut next = self.last.as_deref();
+        std::iter::from_fn(move || {
+            let step = next?;
+            next = step.parent.as_deref();
+            Some((step.parent_hash, &step.game_move))
+        })
+    }
+
+    /// Fetches the number of recorded moves.
+    pub fn len(&self) -> usize { self.iter().count() }
+
+    /// Tests whether no moves have been recorded.
+    pub fn is_empty(&self) -> bool { self.last.is_none() }
+
+    /// Fetches the hash of the parent position.
+    pub fn parent_hash(&self) -> Option<u64> { self.last.as_ref().map(|s| s.parent_hash) }
+
+    /// Fetches the move that led to the state.
+    pub fn last_move(&self) -> Option<&Move> { self.last.as_ref().map(|s| &s.game_move) }
+
+    /// Fetches the recorded moves in the order in which they
+    /// were played, e.g. to reconstruct a principal variation.
+    pub fn moves(&self) -> Vec<Move> {
+        let mut moves: Vec<_> = self.iter().map(|(_, m)| m.clone()).collect();
+        moves.reverse();
+        moves
+    }
+
+    /// Counts the ancestor positions with the given hash.
+    pub fn occurrences(&self, hash: u64) -> usize {
+        self.iter().filter(|&(h, _)| h == hash).count()
+    }
+}
+
+impl fmt::Display for Lineage {
+    /// Formats the moves in the order in which they were played.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, game_move) in self.moves().iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", game_move)?;
+        }
+        Ok(())
+    }
+}
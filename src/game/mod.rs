@@ -2,25 +2,52 @@
 //! Source: Partially translated from https://github.com/software-challenge/backend/blob/8399e73673971427624a73ef42a1b023c69268ec/plugin/src/shared/sc/plugin2020/util/GameRuleLogic.kt
 
 mod board;
+mod board_svg;
+mod boundary_field;
 mod coords;
 mod constants;
+#[cfg(feature = "expansions")]
+mod expansion_rules;
 mod field;
+mod hex_direction;
+mod fixed_board;
 mod r#move;
 mod game_state;
+mod lineage;
+mod move_delta;
+mod opening_placement;
 mod piece_type;
 mod piece;
+mod piece_id;
 mod player_color;
 mod player;
+#[cfg(feature = "protocol")]
+mod plugin;
 mod positioned_field;
+mod rule_check;
+mod rule_set;
+mod search_context;
 
 pub use board::*;
+pub use boundary_field::*;
 pub use coords::*;
 pub use constants::*;
 pub use field::*;
+pub use hex_direction::*;
+pub use fixed_board::*;
 pub use r#move::*;
 pub use game_state::*;
+pub use lineage::*;
+pub use move_delta::*;
+pub use opening_placement::*;
 pub use piece_type::*;
 pub use piece::*;
+pub use piece_id::*;
 pub use player_color::*;
 pub use player::*;
+#[cfg(feature = "protocol")]
+pub use plugin::*;
 pub use positioned_field::*;
+pub use rule_check::*;
+pub use rule_set::*;
+pub use search_context::*;
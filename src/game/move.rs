@@ -1,13 +1,17 @@
-use crate::util::{XmlNode, XmlNodeBuilder};
-use super::{AxialCoords, Piece, PositionedField};
+use std::fmt;
+#[cfg(feature = "protocol")]
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode, XmlNodeBuilder};
+use super::{AxialCoords, Piece, PieceType, PlayerColor, PositionedField};
 
-/// A transition between two game states.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// A transition between two game states. Moves are ordered
+/// with set moves first, followed by the coordinates.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Move<C=AxialCoords> {
     SetMove { piece: Piece, destination: PositionedField<C> },
     DragMove { start: PositionedField<C>, destination: PositionedField<C> }
 }
 
+#[cfg(feature = "protocol")]
 impl From<Move> for XmlNode {
     fn from(game_move: Move) -> Self {
         match game_move {
@@ -24,3 +28,174 @@ impl From<Move> for XmlNode {
         }
     }
 }
+
+#[cfg(feature = "protocol")]
+impl FromXmlNode for Move {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let destination = PositionedField::from_node(node.child_by_name("destination")?)?;
+        match node.attribute("class")? {
+            "setmove" => Ok(Move::SetMove { piece: Piece::from_node(node.child_by_name("piece")?)?, destination }),
+            "dragmove" => Ok(Move::DragMove { start: PositionedField::from_node(node.child_by_name("start")?)?, destination }),
+            class => Err(format!("Unknown move class {}", class).into())
+        }
+    }
+}
+
+impl<C> fmt::Display for Move<C> where C: fmt::Display {
+    /// Formats the move compactly, i.e. `RS@(0, 1)` for placing
+    /// a red spider and `(0, 0)->(1, 0)` for dragging a piece.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Move::SetMove { piece, destination } => write!(f, "{}@{}", piece, destination.coords),
+            Move::DragMove { start, destination } => write!(f, "{}->{}", start.coords, destination.coords)
+        }
+    }
+}
+
+/// The number of symmetries of the hex grid around the origin.
+pub(super) const SYMMETRY_COUNT: usize = 12;
+
+/// The piece types in the order of their 3-bit codes.
+pub(super) const PIECE_TYPES: &[PieceType] = &[
+    PieceType::Ant,
+    PieceType::Bee,
+    PieceType::Beetle,
+    PieceType::Grasshopper,
+    PieceType::Spider,
+    #[cfg(feature = "expansions")]
+    PieceType::Mosquito,
+    #[cfg(feature = "expansions")]
+    PieceType::Ladybug,
+    #[cfg(feature = "expansions")]
+    PieceType::Pillbug
+];
+
+/// Packs coordinates with components in -16..16 into 10 bits.
+pub(super) fn pack_coords(coords: AxialCoords) -> Option<u32> {
+    let component = |v: i32| if (-16..16).contains(&v) { Some((v + 16) as u32) } else { None };
+    Some(component(coords.x())? | (component(coords.y())? << 5))
+}
+
+pub(super) fn unpack_coords(bits: u32) -> AxialCoords {
+    AxialCoords::new((bits & 0x1F) as i32 - 16, ((bits >> 5) & 0x1F) as i32 - 16)
+}
+
+/// Mixes a value into a hash using the SplitMix64 finalizer, which
+/// (unlike `DefaultHasher`) is stable across platforms and releases.
+pub(super) fn mix(hash: u64, value: u64) -> u64 {
+    let mut z = (hash ^ value).wrapping_add(0x9E37_79B9_7F4A_7C15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// Mixes coordinates into a hash.
+pub(super) fn mix_coords(hash: u64, coords: AxialCoords) -> u64 {
+    mix(mix(hash, coords.x() as u32 as u64), coords.y() as u32 as u64)
+}
+
+impl Move {
+    /// Fetches the move's coordinates (the start, if any,
+    /// followed by the destination).
+    fn coords(&self) -> (Option<AxialCoords>, AxialCoords) {
+        match self {
+            Move::SetMove { destination, .. } => (None, destination.coords),
+            Move::DragMove { start, destination } => (Some(start.coords), destination.coords)
+        }
+    }
+
+    /// Fetches the coordinates the move places or moves a piece to.
+    pub fn destination_coords(&self) -> AxialCoords { self.coords().1 }
+
+    /// Computes a stable 64-bit hash of the move from the
+    /// given coordinates, ignoring the fields' contents.
+    fn hash_coords(&self, start: Option<AxialCoords>, destination: AxialCoords) -> u64 {
+        let hash = match self {
+            Move::SetMove { piece, .. } => mix(mix(1, u64::from(char::from(piece.owner))), u64::from(char::from(piece.piece_type))),
+            Move::DragMove { .. } => 2
+        };
+        mix_coords(start.map(|s| mix_coords(hash, s)).unwrap_or(hash), destination)
+    }
+
+    /// A stable 64-bit hash of the move that only depends
+    /// on the piece (for set moves) and the coordinates, but
+    /// not on the contents of the fields.
+    pub fn stable_hash(&self) -> u64 {
+        let (start, destination) = self.coords();
+        self.hash_coords(start, destination)
+    }
+
+    /// Packs the move into 22 bits (a 2-bit kind followed by the
+    /// piece or start and the destination), if its coordinates fit.
+    pub fn packed(&self) -> Option<u32> {
+        match self {
+            Move::SetMove { piece, destination } => {
+                let piece_type = PIECE_TYPES.iter().position(|&t| t == piece.piece_type)? as u32;
+                let owner = (piece.owner == PlayerColor::Blue) as u32;
+                Some(1 | ((piece_type | (owner << 3)) << 2) | (pack_coords(destination.coords)? << 12))
+            },
+            Move::DragMove { start, destination } => Some(2 | (pack_coords(start.coords)? << 2) | (pack_coords(destination.coords)? << 12))
+        }
+    }
+
+    /// Unpacks a move packed using `packed`.
+    pub fn unpack(bits: u32) -> Option<Move> {
+        let destination = PositionedField::new(unpack_coords(bits >> 12));
+        match bits & 0x3 {
+            1 => Some(Move::SetMove {
+                piece: Piece {
+                    piece_type: *PIECE_TYPES.get(((bits >> 2) & 0x7) as usize)?,
+                    owner: if (bits >> 5) & 1 == 1 { PlayerColor::Blue } else { PlayerColor::Red }
+                },
+                destination
+            }),
+            2 => Some(Move::DragMove { start: PositionedField::new(unpack_coords(bits >> 2)), destination }),
+            _ => None
+        }
+    }
+
+    /// Applies one of the 12 symmetries of the hex grid around
+    /// the origin to the move's coordinates (see `AxialCoords::symmetry`).
+    pub fn symmetry(&self, index: usize) -> Move {
+        match self {
+            Move::SetMove { piece, destination } => Move::SetMove { piece: *piece, destination: PositionedField::new(destination.coords.symmetry(index)) },
+            Move::DragMove { start, destination } => Move::DragMove {
+                start: PositionedField::new(start.coords.symmetry(index)),
+                destination: PositionedField::new(destination.coords.symmetry(index))
+            }
+        }
+    }
+
+    /// A stable 64-bit hash of the move that is additionally
+    /// independent of the board's orientation, i.e. the same
+    /// for all rotations and reflections of the move around
+    /// the origin. The hash is computed from the canonicalized
+    /// coordinates, which are the smallest ones among the
+    /// symmetric variants.
+    pub fn canonical_hash(&self) -> u64 {
+        let (start, destination) = self.coords();
+        let (start, destination) = (0..SYMMETRY_COUNT)
+            .map(|i| (start.map(|s| s.symmetry(i)), destination.symmetry(i)))
+            .min()
+            .unwrap();
+        self.hash_coords(start, destination)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<C> From<&Move<C>> for serde_json::Value where C: Into<serde_json::Value> + Copy {
+    fn from(game_move: &Move<C>) -> Self {
+        match game_move {
+            Move::SetMove { piece, destination } => serde_json::json!({
+                "type": "set",
+                "piece": serde_json::Value::from(*piece),
+                "destination": destination.coords.into()
+            }),
+            Move::DragMove { start, destination } => serde_json::json!({
+                "type": "drag",
+                "start": start.coords.into(),
+                "destination": destination.coords.into()
+            })
+        }
+    }
+}
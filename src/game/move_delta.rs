@@ -0,0 +1,43 @@
+use super::{AxialCoords, PlayerColor};
+
+/// The changes caused by applying a move, which allow
+/// incremental evaluators and hashing schemes to update
+/// their features without rescanning the whole board.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MoveDelta {
+    /// The value to XOR into the parent's `position_hash`
+    /// to obtain the successor's position hash.
+    pub hash_diff: u64,
+    /// The field that became empty (the start of a drag
+    /// move, unless another piece remains on it).
+    pub vacated: Option<AxialCoords>,
+    /// The field that became occupied (the destination,
+    /// unless the piece climbed onto another one).
+    pub occupied: Option<AxialCoords>,
+    red_bee_freedom_diff: i32,
+    blue_bee_freedom_diff: i32
+}
+
+impl MoveDelta {
+    /// Creates a delta from the bee freedoms (the number of
+    /// empty fields next to each bee, zero if not placed)
+    /// before and after the move, ordered red, blue.
+    pub fn new(hash_diff: u64, vacated: Option<AxialCoords>, occupied: Option<AxialCoords>, freedom_before: [usize; 2], freedom_after: [usize; 2]) -> Self {
+        Self {
+            hash_diff,
+            vacated,
+            occupied,
+            red_bee_freedom_diff: freedom_after[0] as i32 - freedom_before[0] as i32,
+            blue_bee_freedom_diff: freedom_after[1] as i32 - freedom_before[1] as i32
+        }
+    }
+
+    /// Fetches the change in the number of empty fields next
+    /// to the bee of the given color.
+    pub fn bee_freedom_diff(&self, color: PlayerColor) -> i32 {
+        match color {
+            PlayerColor::Red => self.red_bee_freedom_diff,
+            PlayerColor::Blue => self.blue_bee_freedom_diff
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use super::{AxialCoords, Board, GameState, PlayerColor};
+
+/// The placement of a color's first piece, for which the
+/// general placement rules do not apply yet: The very first
+/// piece of the game may be placed anywhere, the opponent's
+/// first piece has to be placed next to it.
+#[derive(Debug, Copy, Clone)]
+pub struct OpeningPlacement<'a> {
+    board: &'a Board,
+    color: PlayerColor,
+    first_turn: bool
+}
+
+impl<'a> OpeningPlacement<'a> {
+    /// Creates an opening placement for the given color.
+    pub fn new(board: &'a Board, color: PlayerColor, first_turn: bool) -> Self {
+        Self { board, color, first_turn }
+    }
+
+    /// Detects whether the given color has yet to place its
+    /// first piece in the state, i.e. whether its undeployed
+    /// pieces are still the full initial set.
+    pub fn of(state: &'a GameState, color: PlayerColor) -> Option<Self> {
+        let initial_count = state.rules().initial_piece_types.len();
+        if state.undeployed_pieces(color).len() == initial_count {
+            let first_turn = state.undeployed_pieces(color.opponent()).len() == initial_count;
+            Some(Self::new(&state.board, color, first_turn))
+        } else {
+            None
+        }
+    }
+
+    /// Tests whether this is the first placement of the game.
+    pub fn is_first_turn(&self) -> bool { self.first_turn }
+
+    /// Fetches the destinations of the first piece of the
+    /// game, i.e. all empty fields.
+    pub fn first_turn_destinations(&self) -> impl Iterator<Item=AxialCoords> + 'a {
+        self.board.empty_fields().map(|(c, _)| c)
+    }
+
+    /// Fetches the destinations of the second piece of
+    /// the game, i.e. the empty neighbors of the opponent's pieces.
+    pub fn second_turn_destinations(&self) -> impl Iterator<Item=AxialCoords> + 'a {
+        let board = self.board;
+        board.fields_owned_by(self.color.opponent()).flat_map(move |(c, _)| board.empty_neighbors(c)).map(|(c, _)| c)
+    }
+
+    /// Fetches the destinations for the turn of this placement.
+    pub fn destinations(&self) -> Vec<AxialCoords> {
+        if self.first_turn {
+            self.first_turn_destinations().collect()
+        } else {
+            self.second_turn_destinations().collect()
+        }
+    }
+}
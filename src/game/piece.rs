@@ -1,15 +1,24 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use std::fmt;
+#[cfg(feature = "protocol")]
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
 use super::{PieceType, PlayerColor};
 
-/// A game piece.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+/// A game piece. Pieces are ordered by their owner first.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Piece {
     pub owner: PlayerColor,
     pub piece_type: PieceType
 }
 
+impl fmt::Display for Piece {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", char::from(self.owner), char::from(self.piece_type))
+    }
+}
+
+#[cfg(feature = "protocol")]
 impl FromXmlNode for Piece {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             owner: node.attribute("owner")?.parse()?,
             piece_type: node.attribute("type")?.parse()?
@@ -17,6 +26,7 @@ impl FromXmlNode for Piece {
     }
 }
 
+#[cfg(feature = "protocol")]
 impl From<Piece> for XmlNode {
     fn from(piece: Piece) -> Self {
         XmlNode::new("piece")
@@ -25,3 +35,10 @@ impl From<Piece> for XmlNode {
             .build()
     }
 }
+
+#[cfg(feature = "json")]
+impl From<Piece> for serde_json::Value {
+    fn from(piece: Piece) -> Self {
+        serde_json::json!({ "owner": serde_json::Value::from(piece.owner), "type": serde_json::Value::from(piece.piece_type) })
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::fmt;
+use crate::util::SCResult;
+use super::{AxialCoords, Board, Move, Piece};
+
+/// A piece together with a number distinguishing it
+/// from other pieces of the same owner and type, e.g.
+/// "the second spider". The numbers start at 1 and are
+/// assigned in the order in which the pieces are deployed.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct PieceId {
+    pub piece: Piece,
+    pub number: u8
+}
+
+/// Keeps track of the identities of the deployed pieces
+/// across moves. Since the protocol identifies pieces only
+/// by owner and type, the tracker has to observe every move
+/// to be able to tell apart pieces of the same kind.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PieceTracker {
+    stacks: HashMap<AxialCoords, Vec<PieceId>>,
+    deployed_counts: HashMap<Piece, u8>,
+    move_counts: HashMap<PieceId, usize>
+}
+
+impl PieceTracker {
+    /// Creates a new tracker for an empty board.
+    pub fn new() -> Self { Self::default() }
+
+    /// Creates a tracker for the pieces on the given board.
+    /// Since the deployment order is unknown, numbers are
+    /// assigned in the order of the fields' coordinates.
+    pub fn from_board(board: &Board) -> Self {
+        let mut tracker = Self::new();
+        let mut occupied: Vec<_> = board.fields().filter(|(_, f)| f.has_pieces()).collect();
+        occupied.sort_by_key(|(c, _)| *c);
+
+        for (coords, field) in occupied {
+            for &piece in field.piece_stack() {
+                let id = tracker.next_id(piece);
+                tracker.stacks.entry(coords).or_default().push(id);
+            }
+        }
+
+        tracker
+    }
+
+    /// Allocates the identity for a newly deployed piece.
+    fn next_id(&mut self, piece: Piece) -> PieceId {
+        let count = self.deployed_counts.entry(piece).or_insert(0);
+        *count += 1;
+        PieceId { piece, number: *count }
+    }
+
+    /// Fetches the identity of the top-most piece at the given coordinates.
+    pub fn id_at(&self, coords: impl Into<AxialCoords>) -> Option<PieceId> {
+        self.stack_at(coords).last().cloned()
+    }
+
+    /// Fetches the identities of the stacked pieces at the given
+    /// coordinates (from bottom to top).
+    pub fn stack_at(&self, coords: impl Into<AxialCoords>) -> &[PieceId] {
+        self.stacks.get(&coords.into()).map(|s| s.as_slice()).unwrap_or(&[])
+    }
+
+    /// Finds the position of the given piece, if it is deployed.
+    pub fn position_of(&self, id: PieceId) -> Option<AxialCoords> {
+        self.stacks.iter().find(|(_, s)| s.contains(&id)).map(|(&c, _)| c)
+    }
+
+    /// Fetches the number of drag moves performed with the given piece.
+    pub fn move_count(&self, id: PieceId) -> usize {
+        self.move_counts.get(&id).cloned().unwrap_or(0)
+    }
+
+    /// Fetches all deployed pieces with their positions.
+    pub fn deployed(&self) -> impl Iterator<Item=(AxialCoords, PieceId)> + '_ {
+        self.stacks.iter().flat_map(|(&c, s)| s.iter().map(move |&id| (c, id)))
+    }
+
+    /// Formats the given move (which has not been applied to
+    /// the tracker yet) in standard Hive notation, i.e. as the
+    /// moved piece followed by its destination relative to a
+    /// neighboring reference piece.
+    pub fn notation(&self, game_move: &Move) -> SCResult<String> {
+        let (id, start, destination) = match game_move {
+            Move::SetMove { piece, destination } => {
+                let number = self.deployed_counts.get(piece).cloned().unwrap_or(0) + 1;
+                (PieceId { piece: *piece, number }, None, destination.coords)
+            },
+            Move::DragMove { start, destination } => {
+                let id = self.id_at(start.coords).ok_or_else(|| format!("No tracked piece at {}", start.coords))?;
+                (id, Some(start.coords), destination.coords)
+            }
+        };
+
+        if let Some(&covered) = self.stack_at(destination).last() {
+            // Climbing on top of another piece
+            return Ok(format!("{} {}", id, covered));
+        }
+
+        let reference = destination.coord_neighbors().into_iter()
+            .filter(|&c| Some(c) != start || self.stack_at(c).len() > 1)
+            .find_map(|c| {
+                let stack = self.stack_at(c);
+                let top = if Some(c) == start { stack.get(stack.len() - 2) } else { stack.last() };
+                top.map(|&r| (c, r))
+            });
+
+        Ok(match reference {
+            Some((reference_coords, reference_id)) => {
+                let direction = destination - reference_coords;
+                match (direction.x(), direction.y()) {
+                    (1, -1) => format!("{} {}-", id, reference_id),
+                    (-1, 1) => format!("{} -{}", id, reference_id),
+                    (1, 0) => format!("{} {}/", id, reference_id),
+                    (-1, 0) => format!("{} /{}", id, reference_id),
+                    (0, 1) => format!("{} \\{}", id, reference_id),
+                    _ => format!("{} {}\\", id, reference_id)
+                }
+            },
+            None => format!("{}", id)
+        })
+    }
+
+    /// Updates the tracker with the given move and returns
+    /// the identity of the moved piece.
+    pub fn apply(&mut self, game_move: &Move) -> SCResult<PieceId> {
+        match game_move {
+            Move::SetMove { piece, destination } => {
+                let id = self.next_id(*piece);
+                self.stacks.entry(destination.coords).or_default().push(id);
+                Ok(id)
+            },
+            Move::DragMove { start, destination } => {
+                let id = self.stacks.get_mut(&start.coords)
+                    .and_then(|s| s.pop())
+                    .ok_or_else(|| format!("No tracked piece at {}", start.coords))?;
+                if self.stack_at(start.coords).is_empty() {
+                    self.stacks.remove(&start.coords);
+                }
+                self.stacks.entry(destination.coords).or_default().push(id);
+                *self.move_counts.entry(id).or_insert(0) += 1;
+                Ok(id)
+            }
+        }
+    }
+}
+
+impl fmt::Display for PieceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}{}", char::from(self.piece.owner), char::from(self.piece.piece_type), self.number)
+    }
+}
@@ -2,13 +2,19 @@ use std::{convert::TryFrom, str::FromStr};
 use crate::util::{SCError, SCResult};
 
 /// A game piece type.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PieceType {
     Ant,
     Bee,
     Beetle,
     Grasshopper,
-    Spider
+    Spider,
+    #[cfg(feature = "expansions")]
+    Mosquito,
+    #[cfg(feature = "expansions")]
+    Ladybug,
+    #[cfg(feature = "expansions")]
+    Pillbug
 }
 
 impl FromStr for PieceType {
@@ -21,6 +27,12 @@ impl FromStr for PieceType {
             "BEETLE" => Ok(Self::Beetle),
             "GRASSHOPPER" => Ok(Self::Grasshopper),
             "SPIDER" => Ok(Self::Spider),
+            #[cfg(feature = "expansions")]
+            "MOSQUITO" => Ok(Self::Mosquito),
+            #[cfg(feature = "expansions")]
+            "LADYBUG" => Ok(Self::Ladybug),
+            #[cfg(feature = "expansions")]
+            "PILLBUG" => Ok(Self::Pillbug),
             _ => Err(format!("Did not recognize piece type {}", raw).into())
         }
     }
@@ -36,6 +48,12 @@ impl TryFrom<char> for PieceType {
             Some('T') => Ok(Self::Beetle),
             Some('G') => Ok(Self::Grasshopper),
             Some('S') => Ok(Self::Spider),
+            #[cfg(feature = "expansions")]
+            Some('M') => Ok(Self::Mosquito),
+            #[cfg(feature = "expansions")]
+            Some('L') => Ok(Self::Ladybug),
+            #[cfg(feature = "expansions")]
+            Some('P') => Ok(Self::Pillbug),
             _ => Err(format!("Did not recognize piece type {}", c).into())
         }
     }
@@ -48,7 +66,13 @@ impl From<PieceType> for char {
             PieceType::Bee => 'B',
             PieceType::Beetle => 'T',
             PieceType::Grasshopper => 'G',
-            PieceType::Spider => 'S'
+            PieceType::Spider => 'S',
+            #[cfg(feature = "expansions")]
+            PieceType::Mosquito => 'M',
+            #[cfg(feature = "expansions")]
+            PieceType::Ladybug => 'L',
+            #[cfg(feature = "expansions")]
+            PieceType::Pillbug => 'P'
         }
     }
 }
@@ -60,7 +84,18 @@ impl From<PieceType> for String {
             PieceType::Bee => "BEE",
             PieceType::Beetle => "BEETLE",
             PieceType::Grasshopper => "GRASSHOPPER",
-            PieceType::Spider => "SPIDER"
+            PieceType::Spider => "SPIDER",
+            #[cfg(feature = "expansions")]
+            PieceType::Mosquito => "MOSQUITO",
+            #[cfg(feature = "expansions")]
+            PieceType::Ladybug => "LADYBUG",
+            #[cfg(feature = "expansions")]
+            PieceType::Pillbug => "PILLBUG"
         }.to_owned()
     }
 }
+
+#[cfg(feature = "json")]
+impl From<PieceType> for serde_json::Value {
+    fn from(piece_type: PieceType) -> Self { String::from(piece_type).into() }
+}
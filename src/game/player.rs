@@ -1,18 +1,116 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+#[cfg(feature = "protocol")]
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode, XmlNodeBuilder};
 use super::PlayerColor;
 
+/// The attributes of a player parsed by the client.
+#[cfg(feature = "protocol")]
+const PLAYER_ATTRIBUTES: [&str; 8] = ["color", "displayName", "canTimeout", "shouldBePaused", "softTimeout", "hardTimeout", "left", "violated"];
+
 /// Metadata about a player.
+/// 
+/// The optional flags are only known if the
+/// server includes them in the game state.
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Player {
     pub color: PlayerColor,
-    pub display_name: String
+    pub display_name: String,
+    /// Whether the player is subject to the timeouts.
+    pub can_timeout: Option<bool>,
+    /// Whether the player should be paused.
+    pub should_be_paused: Option<bool>,
+    /// Whether the player exceeded the soft timeout.
+    pub soft_timeout: Option<bool>,
+    /// Whether the player exceeded the hard timeout.
+    pub hard_timeout: Option<bool>,
+    /// Whether the player left the game.
+    pub left: Option<bool>,
+    /// Whether the player violated the rules.
+    pub violated: Option<bool>,
+    /// Child elements not parsed by the client, which
+    /// are preserved when serializing the player.
+    #[cfg(feature = "protocol")]
+    pub extensions: Vec<XmlNode>,
+    /// Attributes not parsed by the client, which are
+    /// likewise preserved when serializing the player.
+    #[cfg(feature = "protocol")]
+    pub extension_attributes: Vec<(String, String)>
+}
+
+impl Player {
+    /// Creates a player without any of the optional flags.
+    pub fn new(color: PlayerColor, display_name: impl Into<String>) -> Self {
+        Self {
+            color,
+            display_name: display_name.into(),
+            can_timeout: None,
+            should_be_paused: None,
+            soft_timeout: None,
+            hard_timeout: None,
+            left: None,
+            violated: None,
+            #[cfg(feature = "protocol")]
+            extensions: Vec::new(),
+            #[cfg(feature = "protocol")]
+            extension_attributes: Vec::new()
+        }
+    }
+
+    /// Tests whether the player exceeded any timeout.
+    pub fn has_timed_out(&self) -> bool {
+        self.soft_timeout.unwrap_or(false) || self.hard_timeout.unwrap_or(false)
+    }
+
+    /// Fetches the optional flags together with their XML attribute names.
+    #[cfg(any(feature = "protocol", feature = "json"))]
+    fn flags(&self) -> [(&'static str, Option<bool>); 6] {
+        [
+            ("canTimeout", self.can_timeout),
+            ("shouldBePaused", self.should_be_paused),
+            ("softTimeout", self.soft_timeout),
+            ("hardTimeout", self.hard_timeout),
+            ("left", self.left),
+            ("violated", self.violated)
+        ]
+    }
 }
 
+#[cfg(feature = "protocol")]
 impl FromXmlNode for Player {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             color: node.attribute("color")?.parse()?,
-            display_name: node.attribute("displayName")?.to_owned()
+            display_name: node.attribute("displayName")?.to_owned(),
+            can_timeout: node.optional_attribute("canTimeout")?,
+            should_be_paused: node.optional_attribute("shouldBePaused")?,
+            soft_timeout: node.optional_attribute("softTimeout")?,
+            hard_timeout: node.optional_attribute("hardTimeout")?,
+            left: node.optional_attribute("left")?,
+            violated: node.optional_attribute("violated")?,
+            extensions: node.childs().iter().map(XmlElement::to_node).collect(),
+            extension_attributes: node.unknown_attributes(&PLAYER_ATTRIBUTES)
         })
     }
 }
+
+#[cfg(feature = "protocol")]
+impl<'a> From<&Player> for XmlNodeBuilder<'a> {
+    fn from(player: &Player) -> Self {
+        XmlNodeBuilder::default()
+            .attribute("color", player.color)
+            .attribute("displayName", player.display_name.as_str())
+            .attributes(player.extension_attributes.iter().cloned())
+            .attributes(player.flags().iter().filter_map(|&(k, v)| v.map(|v| (k.to_owned(), v.to_string()))))
+            .childs(player.extensions.iter().cloned())
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&Player> for serde_json::Value {
+    fn from(player: &Player) -> Self {
+        let mut value = serde_json::json!({ "color": serde_json::Value::from(player.color), "displayName": player.display_name });
+        for (key, flag) in player.flags().iter().filter_map(|&(k, v)| v.map(|v| (k, v))) {
+            value[key] = serde_json::Value::from(flag);
+        }
+        value
+    }
+}
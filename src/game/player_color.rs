@@ -2,7 +2,7 @@ use std::{convert::TryFrom, str::FromStr};
 use crate::util::{SCError, SCResult};
 
 /// A player color in the game.
-#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub enum PlayerColor {
     Red,
     Blue
@@ -58,3 +58,8 @@ impl From<PlayerColor> for String {
         }.to_owned()
     }
 }
+
+#[cfg(feature = "json")]
+impl From<PlayerColor> for serde_json::Value {
+    fn from(color: PlayerColor) -> Self { String::from(color).into() }
+}
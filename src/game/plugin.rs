@@ -0,0 +1,43 @@
+use crate::protocol::GamePlugin;
+use crate::util::SCResult;
+use super::{GameState, Move, Player, PlayerColor};
+
+/// The plugin for the 2020 game "Hive".
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub struct HivePlugin;
+
+impl GamePlugin for HivePlugin {
+    const GAME_TYPE: &'static str = "swc_2020_hive";
+
+    type PlayerColor = PlayerColor;
+    type Player = Player;
+    type GameState = GameState;
+    type Move = Move;
+
+    fn turn(state: &GameState) -> u32 { state.turn }
+
+    fn current_player_color(state: &GameState) -> PlayerColor { state.current_player_color }
+
+    fn player_index(color: PlayerColor) -> usize {
+        match color {
+            PlayerColor::Red => 0,
+            PlayerColor::Blue => 1
+        }
+    }
+
+    fn validate_move(state: &GameState, color: PlayerColor, game_move: &Move) -> SCResult<()> {
+        state.validate_move(color, game_move)
+    }
+
+    fn validate_state(state: &GameState) -> SCResult<()> {
+        state.validate_piece_counts()
+    }
+
+    fn fallback_move(state: &GameState, color: PlayerColor) -> Option<Move> {
+        state.possible_moves(color).into_iter().next()
+    }
+
+    fn initial_state(start_color: PlayerColor) -> Option<GameState> {
+        Some(GameState::initial(start_color))
+    }
+}
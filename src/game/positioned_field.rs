@@ -1,25 +1,99 @@
-use crate::util::{XmlNode, XmlNodeBuilder};
-use super::{Field, AxialCoords, CubeCoords};
+use std::cmp::Ordering;
+use std::hash::{Hash, Hasher};
+#[cfg(feature = "protocol")]
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode, XmlNodeBuilder};
+#[cfg(feature = "protocol")]
+use super::CubeCoords;
+use super::{Board, Field, AxialCoords};
 
-/// An owned field and a position.
+/// A position and an optionally attached field.
 /// 
-/// If ownership over the field is not desired, you should
-/// use a tuple instead.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// Most positioned fields (e.g. those of generated moves) only
+/// carry coordinates, the field is then resolved lazily from
+/// the board using `resolve`. If ownership over the field is
+/// not desired, you should use a tuple instead.
+/// 
+/// Positioned fields are compared, ordered and hashed by their
+/// coordinates only, since the attached field is merely a cached
+/// view of the board (e.g. a move parsed from the server compares
+/// equal to the same move generated by the rules engine).
+#[derive(Debug, Clone)]
 pub struct PositionedField<C=AxialCoords> {
-    pub field: Field,
-    pub coords: C
+    pub coords: C,
+    pub field: Option<Field>
+}
+
+impl<C> PartialEq for PositionedField<C> where C: PartialEq {
+    fn eq(&self, other: &Self) -> bool { self.coords == other.coords }
+}
+
+impl<C> Eq for PositionedField<C> where C: Eq {}
+
+impl<C> PartialOrd for PositionedField<C> where C: PartialOrd {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { self.coords.partial_cmp(&other.coords) }
 }
 
+impl<C> Ord for PositionedField<C> where C: Ord {
+    fn cmp(&self, other: &Self) -> Ordering { self.coords.cmp(&other.coords) }
+}
+
+impl<C> Hash for PositionedField<C> where C: Hash {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.coords.hash(state) }
+}
+
+impl<C> PositionedField<C> {
+    /// Creates a positioned field without an attached field.
+    pub fn new(coords: C) -> Self {
+        Self { coords, field: None }
+    }
+
+    /// Creates a positioned field with an attached field.
+    pub fn with_field(coords: C, field: Field) -> Self {
+        Self { coords, field: Some(field) }
+    }
+}
+
+impl<C> PositionedField<C> where C: Into<AxialCoords> + Copy {
+    /// Fetches the attached field or looks it up on the given board.
+    pub fn resolve<'a>(&'a self, board: &'a Board) -> Option<&'a Field> {
+        self.field.as_ref().or_else(|| board.field(self.coords))
+    }
+}
+
+impl<C> From<C> for PositionedField<C> {
+    fn from(coords: C) -> Self { Self::new(coords) }
+}
+
+#[cfg(feature = "protocol")]
 impl<'a, C> From<PositionedField<C>> for XmlNodeBuilder<'a> where C: Into<CubeCoords> {
     fn from(field: PositionedField<C>) -> Self {
         let cube_coords = field.coords.into();
-        XmlNodeBuilder::default()
+        let builder = XmlNodeBuilder::default()
             .attribute("class", "field")
             .attribute("x", cube_coords.x().to_string())
             .attribute("y", cube_coords.y().to_string())
-            .attribute("z", cube_coords.z().to_string())
-            .attribute("isObstructed", field.field.is_obstructed().to_string())
-            .childs(field.field.piece_stack().iter().map(|&p| XmlNode::from(p)))
+            .attribute("z", cube_coords.z().to_string());
+        match field.field {
+            Some(field) => builder
+                .attribute("isObstructed", field.is_obstructed().to_string())
+                .childs(field.piece_stack().iter().map(|&p| XmlNode::from(p))),
+            None => builder
+        }
+    }
+}
+
+#[cfg(feature = "protocol")]
+impl FromXmlNode for PositionedField {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let coords = CubeCoords::new(
+            node.attribute("x")?.parse()?,
+            node.attribute("y")?.parse()?,
+            node.attribute("z")?.parse()?
+        ).into();
+        if node.attribute("isObstructed").is_ok() {
+            Ok(Self::with_field(coords, Field::from_node(node)?))
+        } else {
+            Ok(Self::new(coords))
+        }
     }
 }
@@ -0,0 +1,117 @@
+use std::fmt;
+use super::{AxialCoords, GameState, Move, Piece, PieceType, PlayerColor, SearchContext};
+
+/// The outcome of checking a single game rule
+/// against a move.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuleCheck {
+    /// A short name of the checked rule.
+    pub rule: &'static str,
+    /// Whether the move satisfies the rule.
+    pub passed: bool,
+    /// A human-readable description of the outcome.
+    pub details: String
+}
+
+impl RuleCheck {
+    /// Creates a new rule check.
+    pub fn new(rule: &'static str, passed: bool, details: impl Into<String>) -> Self {
+        Self { rule, passed, details: details.into() }
+    }
+}
+
+impl fmt::Display for RuleCheck {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}] {}: {}", if self.passed { "pass" } else { "FAIL" }, self.rule, self.details)
+    }
+}
+
+impl GameState {
+    /// Checks the given move against every applicable rule and
+    /// returns the individual outcomes. Unlike `validate_move`,
+    /// this does not stop at the first violated rule, which makes
+    /// it useful for teaching tools and for debugging the validator.
+    /// The move is valid iff all checks passed.
+    pub fn explain_move(&self, color: PlayerColor, game_move: &Move) -> Vec<RuleCheck> {
+        match game_move {
+            Move::SetMove { piece, destination } => self.explain_set_move(color, *piece, destination.coords),
+            Move::DragMove { start, destination } => self.explain_drag_move(color, start.coords, destination.coords)
+        }
+    }
+
+    fn explain_set_move(&self, color: PlayerColor, piece: Piece, destination: AxialCoords) -> Vec<RuleCheck> {
+        let mut checks = Vec::new();
+        let field = self.board.field(destination);
+
+        checks.push(RuleCheck::new("Destination in bounds", field.is_some(), format!("{} is {}on the board", destination, if field.is_some() { "" } else { "not " })));
+        if let Some(field) = field {
+            checks.push(RuleCheck::new("Destination not obstructed", !field.is_obstructed(), format!("{} is {}obstructed", destination, if field.is_obstructed() { "" } else { "not " })));
+            checks.push(RuleCheck::new("Destination empty", !field.has_pieces(), format!("{} contains {} piece(s)", destination, field.piece_stack().len())));
+        }
+        checks.push(RuleCheck::new("Own piece", piece.owner == color, format!("{:?} is owned by {:?}", piece.piece_type, piece.owner)));
+        let undeployed = self.undeployed_pieces(color).contains(&piece);
+        checks.push(RuleCheck::new("Piece undeployed", undeployed, format!("{:?} is {}among the undeployed pieces", piece.piece_type, if undeployed { "" } else { "not " })));
+
+        if !self.board.has_pieces() {
+            checks.push(RuleCheck::new("First placement", true, "The first piece may be placed anywhere"));
+        } else if self.board.fields_owned_by(color).count() == 0 {
+            let next_to_opponent = self.board.is_next_to(color.opponent(), destination);
+            checks.push(RuleCheck::new("Next to opponent", next_to_opponent, format!("The first own piece has {}been placed next to an opponent's piece", if next_to_opponent { "" } else { "not " })));
+        } else {
+            let bee_deadline = !self.rules().must_place_bee(self.round()) || self.board.has_placed_bee(color) || piece.piece_type == PieceType::Bee;
+            checks.push(RuleCheck::new("Bee placement deadline", bee_deadline, format!("Round {}, bee {}placed", self.round(), if self.board.has_placed_bee(color) { "" } else { "not " })));
+            let next_to_own = self.board.is_next_to(color, destination);
+            checks.push(RuleCheck::new("Next to own piece", next_to_own, format!("{} is {}adjacent to an own piece", destination, if next_to_own { "" } else { "not " })));
+            let next_to_opponent = self.board.is_next_to(color.opponent(), destination);
+            checks.push(RuleCheck::new("Not next to opponent", !next_to_opponent, format!("{} is {}adjacent to an opponent's piece", destination, if next_to_opponent { "" } else { "not " })));
+        }
+
+        checks
+    }
+
+    fn explain_drag_move(&self, color: PlayerColor, start: AxialCoords, destination: AxialCoords) -> Vec<RuleCheck> {
+        let mut checks = Vec::new();
+        let mut ctx = SearchContext::new();
+        let has_placed_bee = self.board.has_placed_bee(color);
+        checks.push(RuleCheck::new("Bee placed", has_placed_bee, format!("The bee has {}been placed", if has_placed_bee { "" } else { "not " })));
+
+        let start_field = self.board.field(start);
+        let destination_field = self.board.field(destination);
+        checks.push(RuleCheck::new("Start in bounds", start_field.is_some(), format!("{} is {}on the board", start, if start_field.is_some() { "" } else { "not " })));
+        checks.push(RuleCheck::new("Destination in bounds", destination_field.is_some(), format!("{} is {}on the board", destination, if destination_field.is_some() { "" } else { "not " })));
+
+        let dragged_piece = start_field.and_then(|f| f.piece());
+        checks.push(RuleCheck::new("Piece at start", dragged_piece.is_some(), match dragged_piece {
+            Some(piece) => format!("{} contains {:?}'s {:?}", start, piece.owner, piece.piece_type),
+            None => format!("{} contains no piece", start)
+        }));
+
+        if let (Some(piece), Some(destination_field)) = (dragged_piece, destination_field) {
+            #[cfg(feature = "expansions")]
+            {
+                let throw = self.validate_pillbug_throw(&mut ctx, color, start, destination);
+                if throw.is_ok() || piece.owner != color {
+                    checks.push(RuleCheck::new("Pillbug ability", throw.is_ok(), match throw {
+                        Ok(()) => "The piece is moved by an adjacent pillbug".to_owned(),
+                        Err(e) => e.to_string()
+                    }));
+                    return checks;
+                }
+            }
+
+            checks.push(RuleCheck::new("Own piece", piece.owner == color, format!("The piece is owned by {:?}", piece.owner)));
+            checks.push(RuleCheck::new("Start differs from destination", start != destination, format!("{} -> {}", start, destination)));
+            let may_climb = !destination_field.has_pieces() || self.can_climb(piece.piece_type, start);
+            checks.push(RuleCheck::new("Climbing", may_climb, format!("{} contains {} piece(s)", destination, destination_field.piece_stack().len())));
+            let connected = self.is_swarm_connected_without(&mut ctx, start).unwrap_or(false);
+            checks.push(RuleCheck::new("Swarm stays connected", connected, format!("Removing the piece at {} {} the swarm", start, if connected { "keeps" } else { "disconnects" })));
+            let movement = self.validate_piece_move(&mut ctx, piece.piece_type, start, destination);
+            checks.push(RuleCheck::new("Piece movement", movement.is_ok(), match movement {
+                Ok(()) => format!("{:?} can move from {} to {}", piece.piece_type, start, destination),
+                Err(e) => e.to_string()
+            }));
+        }
+
+        checks
+    }
+}
@@ -0,0 +1,72 @@
+use super::{PieceType, BOARD_RADIUS, INITIAL_PIECE_TYPES, ROUND_LIMIT};
+#[cfg(feature = "expansions")]
+use super::EXPANSION_PIECE_TYPES;
+
+/// The parameters of the rules, which default to those of
+/// the Software Challenge, but may be changed e.g. to play
+/// on smaller test boards or with expansion pieces.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RuleSet {
+    /// The number of rounds after which the game ends.
+    pub round_limit: u32,
+    /// The radius of the (hexagonal) board.
+    pub board_radius: usize,
+    /// The pieces each player starts with.
+    pub initial_piece_types: Vec<PieceType>,
+    /// The (zero-based) round in which a player has to
+    /// place the bee at the latest, if any.
+    pub bee_deadline_round: Option<u32>
+}
+
+impl RuleSet {
+    /// Creates the rules of the Software Challenge.
+    pub fn standard() -> Self {
+        Self {
+            round_limit: ROUND_LIMIT as u32,
+            board_radius: BOARD_RADIUS,
+            initial_piece_types: INITIAL_PIECE_TYPES.to_vec(),
+            bee_deadline_round: Some(3)
+        }
+    }
+
+    /// Creates the standard rules with the expansion pieces.
+    #[cfg(feature = "expansions")]
+    pub fn with_expansions() -> Self {
+        let mut rules = Self::standard();
+        rules.initial_piece_types.extend_from_slice(&EXPANSION_PIECE_TYPES);
+        rules
+    }
+
+    /// Sets the number of rounds after which the game ends.
+    pub fn with_round_limit(self, round_limit: u32) -> Self {
+        Self { round_limit, ..self }
+    }
+
+    /// Sets the radius of the board.
+    pub fn with_board_radius(self, board_radius: usize) -> Self {
+        Self { board_radius, ..self }
+    }
+
+    /// Sets the pieces each player starts with.
+    pub fn with_initial_piece_types(self, initial_piece_types: impl Into<Vec<PieceType>>) -> Self {
+        Self { initial_piece_types: initial_piece_types.into(), ..self }
+    }
+
+    /// Sets the round in which the bee has to be placed at the latest.
+    pub fn with_bee_deadline_round(self, bee_deadline_round: Option<u32>) -> Self {
+        Self { bee_deadline_round, ..self }
+    }
+
+    /// Fetches the number of turns after which the game ends.
+    pub fn turn_limit(&self) -> u32 { 2 * self.round_limit }
+
+    /// Tests whether a player without a placed bee
+    /// has to place it in the given round.
+    pub fn must_place_bee(&self, round: u32) -> bool {
+        self.bee_deadline_round.map(|r| round >= r).unwrap_or(false)
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self { Self::standard() }
+}
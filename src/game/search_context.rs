@@ -0,0 +1,114 @@
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use arrayvec::ArrayVec;
+use super::{AxialCoords, Move, BOARD_RADIUS};
+
+/// A set of coordinates backed by a bitset, which can
+/// be cleared without releasing its memory.
+#[derive(Debug, Clone, Default)]
+pub struct CoordsSet {
+    bits: Vec<u64>,
+    radius: i32
+}
+
+impl CoordsSet {
+    /// Creates a set covering the given radius without reallocating.
+    pub fn with_radius(radius: usize) -> Self {
+        let radius = i32::try_from(radius).expect("Radius is too large");
+        let side = (2 * radius + 1) as usize;
+        Self { bits: vec![0; (side * side).div_ceil(64)], radius }
+    }
+
+    /// Computes the bit index of the given coordinates.
+    fn index(&self, coords: AxialCoords) -> Option<usize> {
+        let side = 2 * self.radius + 1;
+        let (x, y) = (coords.x() + self.radius, coords.y() + self.radius);
+        if self.bits.is_empty() || x < 0 || y < 0 || x >= side || y >= side {
+            None
+        } else {
+            Some((x * side + y) as usize)
+        }
+    }
+
+    /// Tests whether the set contains the given coordinates.
+    pub fn contains(&self, coords: AxialCoords) -> bool {
+        self.index(coords).map(|i| self.bits[i / 64] & (1 << (i % 64)) != 0).unwrap_or(false)
+    }
+
+    /// Inserts the given coordinates and returns whether
+    /// they were not contained before.
+    pub fn insert(&mut self, coords: AxialCoords) -> bool {
+        let index = match self.index(coords) {
+            Some(index) => index,
+            None => {
+                self.grow(coords.x().abs().max(coords.y().abs()));
+                self.index(coords).unwrap()
+            }
+        };
+        let (word, bit) = (index / 64, 1 << (index % 64));
+        let inserted = self.bits[word] & bit == 0;
+        self.bits[word] |= bit;
+        inserted
+    }
+
+    /// Removes all coordinates from the set.
+    pub fn clear(&mut self) {
+        self.bits.iter_mut().for_each(|w| *w = 0);
+    }
+
+    /// Enlarges the set to cover at least the given radius.
+    fn grow(&mut self, min_radius: i32) {
+        let radius = min_radius.max(2 * self.radius).max(BOARD_RADIUS as i32);
+        let mut grown = Self::with_radius(radius as usize);
+        for x in -self.radius..=self.radius {
+            for y in -self.radius..=self.radius {
+                let coords = AxialCoords::new(x, y);
+                if self.contains(coords) {
+                    grown.insert(coords);
+                }
+            }
+        }
+        *self = grown;
+    }
+}
+
+/// Reusable scratch buffers for traversals of the board,
+/// move validation and search, which avoid allocating
+/// at every node.
+#[derive(Debug, Clone)]
+pub struct SearchContext {
+    pub(super) visited: CoordsSet,
+    pub(super) stack: Vec<AxialCoords>,
+    pub(super) queue: VecDeque<AxialCoords>,
+    pub(super) paths: VecDeque<ArrayVec<AxialCoords, 4>>,
+    move_buffers: Vec<Vec<Move>>
+}
+
+impl SearchContext {
+    /// Creates a new context with buffers sized for the game board.
+    pub fn new() -> Self {
+        Self {
+            visited: CoordsSet::with_radius(BOARD_RADIUS),
+            stack: Vec::new(),
+            queue: VecDeque::new(),
+            paths: VecDeque::new(),
+            move_buffers: Vec::new()
+        }
+    }
+
+    /// Fetches an empty move vector, reusing a
+    /// previously recycled one if possible.
+    pub fn take_moves(&mut self) -> Vec<Move> {
+        self.move_buffers.pop().unwrap_or_default()
+    }
+
+    /// Returns a move vector to the context for reuse.
+    pub fn recycle_moves(&mut self, mut moves: Vec<Move>) {
+        moves.clear();
+        self.move_buffers.push(moves);
+    }
+}
+
+impl Default for SearchContext {
+    fn default() -> Self { Self::new() }
+}
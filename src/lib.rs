@@ -1,5 +1,23 @@
+#[cfg(feature = "client")]
 pub mod logic;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+#[cfg(feature = "client")]
 pub mod client;
+pub mod analysis;
+#[cfg(feature = "client")]
+pub mod channel;
+pub mod eval;
 pub mod game;
+#[cfg(feature = "client")]
+pub mod net;
+#[cfg(feature = "protocol")]
 pub mod protocol;
+#[cfg(feature = "protocol")]
+pub mod replaydb;
+pub mod search;
 pub mod util;
+#[cfg(feature = "tui")]
+pub mod viewer;
+#[cfg(feature = "web")]
+pub mod web;
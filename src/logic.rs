@@ -1,24 +1,298 @@
-use rand::seq::SliceRandom;
+mod vote_mode;
+mod voting_logic;
+
+pub use vote_mode::*;
+pub use voting_logic::*;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rand::{Rng, SeedableRng, rngs::StdRng, seq::SliceRandom};
 use log::{info, debug};
-use crate::{client::SCClientDelegate, game::{GameState, PlayerColor, Move}};
+use crate::{client::SCClientDelegate, eval::{Evaluator, HeuristicEvaluator}, game::{GameState, PlayerColor, Move}, util::{CancellationToken, SCError, SCResult}};
+use crate::protocol::{GameResult, MoveAnnotation, RttStats, TimeSettings};
+use crate::search::{AlphaBetaSearch, MctsOptions, MctsSearch, RootPolicy, SearchStats, TranspositionTable, DEFAULT_MCTS_NODE_LIMIT, DEFAULT_TABLE_MEMORY_LIMIT, MCTS_NODE_SIZE};
+
+/// The maximum depth of the alpha-beta search, which
+/// is usually limited by the time budget instead.
+const MAX_SEARCH_DEPTH: u32 = 8;
+
+/// A built-in move selection strategy.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Strategy {
+    /// Picks a uniformly random move.
+    Random,
+    /// Picks the move leading to the best heuristic
+    /// evaluation, breaking ties using the `TieBreak`.
+    Greedy,
+    /// Searches the game tree using alpha-beta pruning within
+    /// the soft timeout, breaking ties between the best root
+    /// moves using the `TieBreak`.
+    AlphaBeta,
+    /// Runs a Monte Carlo tree search within the soft timeout,
+    /// breaking ties between the most visited root moves
+    /// using the `TieBreak`.
+    Mcts
+}
+
+impl FromStr for Strategy {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "random" => Ok(Self::Random),
+            "greedy" => Ok(Self::Greedy),
+            "alphabeta" => Ok(Self::AlphaBeta),
+            "mcts" => Ok(Self::Mcts),
+            _ => Err(format!("Did not recognize strategy {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for Strategy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Random => write!(f, "random"),
+            Self::Greedy => write!(f, "greedy"),
+            Self::AlphaBeta => write!(f, "alphabeta"),
+            Self::Mcts => write!(f, "mcts")
+        }
+    }
+}
+
+/// How to choose between moves that are rated equally.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Picks the first move in the canonical move order,
+    /// which makes the choice reproducible.
+    Canonical,
+    /// Picks a random move (which is reproducible if seeded).
+    Random,
+    /// Picks the move changing the number of free fields around
+    /// the bees the least, falling back to the canonical order.
+    Quiet
+}
+
+impl FromStr for TieBreak {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "canonical" => Ok(Self::Canonical),
+            "random" => Ok(Self::Random),
+            "quiet" => Ok(Self::Quiet),
+            _ => Err(format!("Did not recognize tie-breaking policy {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for TieBreak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Canonical => write!(f, "canonical"),
+            Self::Random => write!(f, "random"),
+            Self::Quiet => write!(f, "quiet")
+        }
+    }
+}
+
+/// The game logic structure that implements the
+/// client delegate trait and thus is responsible
+/// e.g. for picking a move when requested.
+pub struct OwnGameLogic {
+    strategy: Strategy,
+    tie_break: TieBreak,
+    random: StdRng,
+    evaluator: HeuristicEvaluator,
+    search: AlphaBetaSearch<HeuristicEvaluator>,
+    mcts_options: MctsOptions,
+    mcts_node_limit: usize,
+    time_settings: TimeSettings,
+    move_time_share: f64,
+    network_margin: Duration,
+    game_stats: SearchStats,
+    last_annotation: Option<MoveAnnotation<Move>>
+}
+
+impl OwnGameLogic {
+    /// Creates a logic using the given strategy, optionally
+    /// seeding the random number generator for reproducible games.
+    pub fn new(strategy: Strategy, seed: Option<u64>) -> Self {
+        let random = seed.map(StdRng::seed_from_u64).unwrap_or_else(StdRng::from_entropy);
+        Self {
+            strategy,
+            tie_break: TieBreak::Random,
+            random,
+            evaluator: HeuristicEvaluator::default(),
+            search: AlphaBetaSearch::new(HeuristicEvaluator::default()),
+            mcts_options: MctsOptions::default(),
+            mcts_node_limit: DEFAULT_MCTS_NODE_LIMIT,
+            time_settings: TimeSettings::default(),
+            move_time_share: 0.75,
+            network_margin: Duration::ZERO,
+            game_stats: SearchStats::default(),
+            last_annotation: None
+        }
+    }
 
-/// An empty game logic structure that
-/// implements the client delegate trait
-/// and thus is responsible e.g. for picking
-/// a move when requested.
-pub struct OwnGameLogic;
+    /// Sets the policy for choosing between equally rated moves.
+    pub fn with_tie_break(mut self, tie_break: TieBreak) -> Self {
+        self.tie_break = tie_break;
+        self
+    }
+
+    /// Sets the evaluation function used by the greedy,
+    /// the alpha-beta and the Monte Carlo strategy.
+    pub fn with_evaluator(mut self, evaluator: HeuristicEvaluator) -> Self {
+        self.search = self.search.take_root_policies().into_iter()
+            .fold(AlphaBetaSearch::with_table(evaluator.clone(), self.search.table().clone()), AlphaBetaSearch::with_root_policy);
+        self.evaluator = evaluator;
+        self
+    }
+
+    /// Limits the memory of the alpha-beta strategy's transposition
+    /// table and of the Monte Carlo strategy's tree to the given number
+    /// of bytes. Once the table is full, new entries replace older ones,
+    /// while a full tree is no longer expanded.
+    pub fn with_hash_size(mut self, bytes: usize) -> Self {
+        self.mcts_node_limit = bytes.min(DEFAULT_TABLE_MEMORY_LIMIT) / MCTS_NODE_SIZE;
+        self.search = self.search.take_root_policies().into_iter()
+            .fold(AlphaBetaSearch::with_table(self.evaluator.clone(), Arc::new(TranspositionTable::with_memory(bytes))), AlphaBetaSearch::with_root_policy);
+        self
+    }
+
+    /// Adds a policy filtering and rating the root moves
+    /// of the alpha-beta strategy before searching them.
+    pub fn with_root_policy(mut self, policy: impl RootPolicy + Send + 'static) -> Self {
+        self.search = self.search.with_root_policy(policy);
+        self
+    }
+
+    /// Sets the enhancements of the Monte Carlo strategy.
+    pub fn with_mcts_options(mut self, options: MctsOptions) -> Self {
+        self.mcts_options = options;
+        self
+    }
+
+    /// Sets the share of the soft timeout that the search strategies
+    /// may use for searching, leaving the rest as a safety margin for
+    /// the network (0.75 by default). If the observed round trips to
+    /// the server require a larger margin, the search time shrinks.
+    pub fn with_move_time_share(mut self, share: f64) -> Self {
+        self.move_time_share = share;
+        self
+    }
+
+    /// Chooses one of the equally rated candidate moves.
+    fn break_tie(&mut self, state: &GameState, candidates: Vec<Move>) -> Option<Move> {
+        match self.tie_break {
+            TieBreak::Canonical => candidates.into_iter().min(),
+            TieBreak::Random => candidates.choose(&mut self.random).cloned(),
+            TieBreak::Quiet => candidates.into_iter().min_by_key(|m| {
+                let noise = state.apply_move_with_delta(m)
+                    .map(|(_, delta)| [PlayerColor::Red, PlayerColor::Blue].iter().map(|&c| delta.bee_freedom_diff(c).abs()).sum())
+                    .unwrap_or(i32::MAX);
+                (noise, m.clone())
+            })
+        }
+    }
+
+    /// Fetches the search statistics aggregated over the current game.
+    pub fn game_stats(&self) -> &SearchStats { &self.game_stats }
+}
+
+impl Default for OwnGameLogic {
+    fn default() -> Self { Self::new(Strategy::Random, None) }
+}
 
 impl SCClientDelegate for OwnGameLogic {
-    fn request_move(&mut self, state: &GameState, my_color: PlayerColor) -> Move {
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
         // Implement custom game logic here!
-        let mut random = rand::thread_rng();
-        let moves = state.possible_moves(my_color);
-        let game_move = moves.choose(&mut random).cloned().expect("No move found");
+        let start = Instant::now();
+        let mut moves = state.possible_moves(my_color);
+        moves.shuffle(&mut self.random);
+        let mut annotation = MoveAnnotation::default();
+        annotation.debug.insert("strategy", self.strategy);
+        annotation.debug.insert("legalMoves", moves.len());
+        let game_move = match self.strategy {
+            Strategy::Random => moves.first().cloned(),
+            Strategy::Greedy => {
+                let rated: Vec<_> = moves.iter()
+                    .filter_map(|m| state.apply_move(m).ok().map(|next| (m, self.evaluator.evaluate(&next, my_color))))
+                    .collect();
+                let best = rated.iter().map(|&(_, e)| e).fold(f64::NEG_INFINITY, f64::max);
+                let candidates = rated.into_iter().filter(|&(_, e)| e >= best).map(|(m, _)| m.clone()).collect();
+                annotation.evaluation = Some(best).filter(|e| e.is_finite());
+                annotation.depth = Some(1);
+                self.break_tie(state, candidates)
+            },
+            Strategy::AlphaBeta | Strategy::Mcts => {
+                // Leave a safety margin for the network (timeouts
+                // too large to represent a deadline are unlimited)
+                let soft_timeout = self.time_settings.soft_timeout;
+                let deadline = start.checked_add(soft_timeout.mul_f64(self.move_time_share).min(soft_timeout.saturating_sub(self.network_margin)));
+                let result = match self.strategy {
+                    Strategy::Mcts => MctsSearch::new(self.evaluator.clone(), self.mcts_options.clone())
+                        .with_seed(self.random.gen())
+                        .with_node_limit(self.mcts_node_limit)
+                        .search(state, deadline, cancel),
+                    _ => self.search.search(state, MAX_SEARCH_DEPTH, deadline, cancel)
+                };
+                info!("Search: {} (score {:.2})", result.stats, result.score);
+                self.game_stats += result.stats;
+                annotation.evaluation = Some(result.score);
+                annotation.depth = Some(result.stats.depth);
+                annotation.nodes = Some(result.stats.nodes);
+                annotation.debug.insert("tiedMoves", result.tied_moves.len());
+                let chosen = match result.tied_moves.len() {
+                    0 | 1 => result.best_move,
+                    _ => self.break_tie(state, result.tied_moves)
+                };
+                // The continuation of another tied move is only stored by the alpha-beta search
+                annotation.principal_variation = match &chosen {
+                    Some(chosen) if result.principal_variation.first() != Some(chosen) => match self.strategy {
+                        Strategy::AlphaBeta => self.search.principal_variation(state, chosen, result.stats.depth.max(1)),
+                        _ => vec![chosen.clone()]
+                    },
+                    _ => result.principal_variation
+                };
+                // The search may have been cancelled before completing any iteration
+                chosen.or_else(|| moves.first().cloned())
+            }
+        }.expect("No move found");
         info!("Chose {:?} from {} moves", game_move, moves.len());
+        if annotation.principal_variation.is_empty() {
+            annotation.principal_variation.push(game_move.clone());
+        }
+        annotation.time = Some(start.elapsed());
+        self.last_annotation = Some(annotation);
         game_move
     }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.last_annotation.clone()
+    }
     
-    fn on_update_state(&mut self, state: &GameState) {
-        debug!("New board:\n{}", state.board);
+    fn on_update_state(&mut self, state: &GameState, _my_color: Option<PlayerColor>) {
+        debug!("New board:\n{}", state.board.display_with_axes());
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.time_settings = *settings;
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.network_margin = stats.margin();
+    }
+
+    fn on_game_end(&mut self, _result: GameResult, _my_color: Option<PlayerColor>) {
+        if self.game_stats.nodes > 0 {
+            info!("Search over the game: {}", self.game_stats);
+            if self.strategy == Strategy::AlphaBeta {
+                let table = self.search.table();
+                info!("Transposition table: {:.0}% of {:.1} MiB in use", table.occupancy() * 100.0, table.memory_usage() as f64 / (1 << 20) as f64);
+            }
+        }
     }
 }
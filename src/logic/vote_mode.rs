@@ -0,0 +1,36 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::util::{SCError, SCResult};
+
+/// How a `VotingLogic` chooses between the moves proposed by its voters.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum VoteMode {
+    /// Picks the move with the highest sum of voter weights.
+    Weighted,
+    /// Picks the proposed move with the best score according to
+    /// a common verification search, since the evaluations of
+    /// different strategies are not comparable. Falls back to
+    /// the weighted vote if the verification runs out of time.
+    BestScore
+}
+
+impl FromStr for VoteMode {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "weighted" => Ok(Self::Weighted),
+            "score" => Ok(Self::BestScore),
+            _ => Err(format!("Did not recognize vote mode {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for VoteMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Weighted => write!(f, "weighted"),
+            Self::BestScore => write!(f, "score")
+        }
+    }
+}
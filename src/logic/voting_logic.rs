@@ -0,0 +1,198 @@
+use std::sync::Arc;
+use std::time::Instant;
+use log::{info, warn};
+use crate::client::SCClientDelegate;
+use crate::eval::HeuristicEvaluator;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use crate::search::{AlphaBetaSearch, TranspositionTable};
+use crate::util::CancellationToken;
+use super::VoteMode;
+
+/// The share of the soft timeout that the voters may use in total.
+const VOTING_SHARE: f64 = 0.6;
+/// The share of the soft timeout after which the verification stops.
+const VERIFICATION_SHARE: f64 = 0.8;
+/// The memory of the verification search's transposition table.
+const VERIFICATION_TABLE_SIZE: usize = 1 << 20;
+
+/// A child strategy of a `VotingLogic`.
+struct Voter {
+    name: String,
+    delegate: Box<dyn SCClientDelegate>,
+    weight: f64
+}
+
+/// A meta-delegate that asks several child delegates (the voters)
+/// for a move, one after another within the soft timeout, and picks
+/// one of their proposals according to the `VoteMode`. Voters observe
+/// the whole game and are given an equal slice of the time budget
+/// via their time settings.
+pub struct VotingLogic {
+    voters: Vec<Voter>,
+    mode: VoteMode,
+    time_settings: TimeSettings,
+    verifier: AlphaBetaSearch<HeuristicEvaluator>,
+    verification_depth: u32,
+    last_annotation: Option<MoveAnnotation<Move>>
+}
+
+impl VotingLogic {
+    /// Creates a logic without voters using the given mode.
+    pub fn new(mode: VoteMode) -> Self {
+        Self {
+            voters: Vec::new(),
+            mode,
+            time_settings: TimeSettings::default(),
+            verifier: AlphaBetaSearch::with_table(HeuristicEvaluator::default(), Arc::new(TranspositionTable::with_memory(VERIFICATION_TABLE_SIZE))),
+            verification_depth: 2,
+            last_annotation: None
+        }
+    }
+
+    /// Adds a voter with the given name (used in the move annotations)
+    /// and weight. Voters are asked in the order they are added.
+    pub fn with_voter(mut self, name: impl Into<String>, delegate: impl SCClientDelegate + 'static, weight: f64) -> Self {
+        self.voters.push(Voter { name: name.into(), delegate: Box::new(delegate), weight });
+        self.share_time();
+        self
+    }
+
+    /// Sets the evaluation function used to verify the proposals.
+    pub fn with_evaluator(mut self, evaluator: HeuristicEvaluator) -> Self {
+        self.verifier = AlphaBetaSearch::with_table(evaluator, self.verifier.table().clone());
+        self
+    }
+
+    /// Sets the depth (in plies after the proposed move) of the
+    /// search verifying the proposals (2 by default).
+    pub fn with_verification_depth(mut self, depth: u32) -> Self {
+        self.verification_depth = depth;
+        self
+    }
+
+    /// Passes each voter its slice of the time budget.
+    fn share_time(&mut self) {
+        let count = self.voters.len().max(1) as f64;
+        let settings = TimeSettings::new(self.time_settings.soft_timeout.mul_f64(VOTING_SHARE / count), self.time_settings.hard_timeout);
+        self.voters.iter_mut().for_each(|v| v.delegate.on_time_settings(&settings));
+    }
+
+    /// Scores the given move by searching the resulting position, from
+    /// the perspective of the moving player. Yields nothing if the
+    /// search could not complete in time.
+    fn verify(&mut self, state: &GameState, game_move: &Move, deadline: Option<Instant>, cancel: &CancellationToken) -> Option<f64> {
+        let next = state.apply_move(game_move).ok()?;
+        let result = self.verifier.search(&next, self.verification_depth, deadline, cancel);
+        Some(-result.score).filter(|_| result.stats.depth >= self.verification_depth)
+    }
+}
+
+impl SCClientDelegate for VotingLogic {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_update_state(state, my_color));
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_game_end(result.clone(), my_color));
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_welcome_message(color));
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.time_settings = *settings;
+        self.share_time();
+    }
+
+    fn on_game_paused(&mut self) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_game_paused());
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_game_resumed());
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_round_trip(stats));
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_opponent(name, stats));
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.voters.iter_mut().for_each(|v| v.delegate.on_move_observed(game_move));
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let start = Instant::now();
+        let mut annotation = MoveAnnotation::default();
+        annotation.debug.insert("mode", self.mode);
+
+        // Collects the legal proposals, tallying the weights of equal moves
+        let mut tally: Vec<(Move, f64)> = Vec::new();
+        for voter in &mut self.voters {
+            if cancel.is_cancelled() && !tally.is_empty() {
+                warn!("Skipping voter {}, since the move request was cancelled", voter.name);
+                continue;
+            }
+            let proposal = voter.delegate.request_move(state, my_color, cancel);
+            annotation.debug.insert(format!("vote.{}", voter.name), &proposal);
+            if let Err(e) = state.validate_move(my_color, &proposal) {
+                warn!("Ignoring the illegal proposal {} of voter {}: {}", proposal, voter.name, e);
+                continue;
+            }
+            match tally.iter_mut().find(|(m, _)| *m == proposal) {
+                Some((_, weight)) => *weight += voter.weight,
+                None => tally.push((proposal, voter.weight))
+            }
+        }
+
+        // Ties are broken in favor of the earlier proposal
+        let by_weight = tally.iter().fold(None, |best: Option<&(Move, f64)>, candidate| match best {
+            Some(best) if best.1 >= candidate.1 => Some(best),
+            _ => Some(candidate)
+        }).map(|(m, _)| m.clone());
+        let verified = match self.mode {
+            VoteMode::BestScore if tally.len() > 1 => {
+                // Timeouts too large to represent a deadline are unlimited
+                let deadline = start.checked_add(self.time_settings.soft_timeout.mul_f64(VERIFICATION_SHARE));
+                let scores: Option<Vec<f64>> = tally.iter().map(|(m, _)| self.verify(state, m, deadline, cancel)).collect();
+                if scores.is_none() {
+                    warn!("Could not verify the proposals in time, falling back to the weighted vote");
+                }
+                scores.and_then(|scores| tally.iter().zip(scores).fold(None, |best: Option<(&Move, f64)>, ((m, _), score)| match best {
+                    Some(best) if best.1 >= score => Some(best),
+                    _ => Some((m, score))
+                })).map(|(m, score)| (m.clone(), score))
+            },
+            _ => None
+        };
+
+        let game_move = match verified {
+            Some((game_move, score)) => {
+                annotation.evaluation = Some(score);
+                annotation.depth = Some(self.verification_depth);
+                Some(game_move)
+            },
+            None => by_weight
+        }.or_else(|| state.possible_moves(my_color).into_iter().next()).expect("No move found");
+        let votes = tally.iter().find(|(m, _)| *m == game_move).map(|&(_, w)| w).unwrap_or(0.0);
+        info!("Chose {} with {} votes from {} proposals", game_move, votes, tally.len());
+        annotation.debug.insert("votes", votes);
+        annotation.principal_variation.push(game_move.clone());
+        annotation.time = Some(start.elapsed());
+        self.last_annotation = Some(annotation);
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.last_annotation.clone()
+    }
+}
+
+impl Default for VotingLogic {
+    fn default() -> Self { Self::new(VoteMode::Weighted) }
+}
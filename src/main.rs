@@ -1,48 +1,46 @@
+mod commands;
+
 use std::env;
-use std::str::FromStr;
-use simplelog::{SimpleLogger, Config};
-use log::LevelFilter;
-use getopts::Options;
-use socha_client_2020::client::{SCClient, DebugMode};
-use socha_client_2020::logic::OwnGameLogic;
+use std::process;
+use commands::{CommandError, ExitStatus, print_subcommands};
+#[cfg(feature = "alloc-stats")]
+use commands::bench::CountingAllocator;
 
-fn print_usage(program: &str, options: Options) {
-    let brief = format!("Usage: {} [options]", program);
-    print!("{}", options.usage(&brief));
-}
+#[cfg(feature = "alloc-stats")]
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
 
 fn main() {
-    // Parse command line arguments
     let args = env::args().collect::<Vec<_>>();
-    let mut options = Options::new();
-    options.optopt("h", "host", "The game server's host address", "HOST");
-    options.optopt("p", "port", "The game server's port", "PORT");
-    options.optopt("r", "reservation", "A game reservation", "RESERVATION");
-    options.optopt("l", "level", "Optionally provides a custom log level ('Info' by default)", "LEVEL");
-    options.optflag("d", "debug-reader", "Reads incoming XML messages from the console for debugging");
-    options.optflag("D", "debug-writer", "Prints incoming XML messages to the console for debugging");
-    options.optflag("H", "help", "Prints usage info");
-    
-    let parsed_args = options.parse(&args[1..]).expect("Could not parse arguments!");
-    if parsed_args.opt_present("help") {
-        print_usage(&args[0], options);
-        return;
-    }
-    
-    let host = parsed_args.opt_str("host").unwrap_or("localhost".to_owned());
-    let port = parsed_args.opt_str("port").unwrap_or("13050".to_owned()).parse::<u16>().expect("Invalid port.");
-    let reservation = parsed_args.opt_str("reservation");
-    let level = parsed_args.opt_str("level").unwrap_or("Info".to_owned());
-    
-    // Setup logging
-    SimpleLogger::init(LevelFilter::from_str(&level).expect("Invalid log level."), Config::default()).expect("Could not initialize logger.");
-    
-    // Setup the client and the delegate
-    let debug_mode = DebugMode {
-        debug_reader: parsed_args.opt_present("debug-reader"),
-        debug_writer: parsed_args.opt_present("debug-writer")
+    let program = &args[0];
+    let result = match args.get(1).map(|s| s.as_str()) {
+        Some("play") => commands::play::run(program, &args[2..]),
+        Some("analyze") => commands::analyze::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("repl") => commands::repl::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("selfplay") => commands::selfplay::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("gauntlet") => commands::gauntlet::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("selftest") => commands::selftest::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("bench") => commands::bench::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("replaydb") => commands::replaydb::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("book") => commands::book::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("puzzles") => commands::puzzles::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("dump") => commands::dump::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("perft") => commands::perft::run(program, &args[2..]).map(|_| ExitStatus::Success),
+        Some("doctor") => commands::doctor::run(program, &args[2..]),
+        Some("help") => {
+            print_subcommands(program);
+            Ok(ExitStatus::Success)
+        },
+        Some(arg) if !arg.starts_with('-') => Err(CommandError::Usage(format!("Unknown subcommand '{}'", arg))),
+        _ => commands::play::run(program, &args[1..])
     };
-    let client = SCClient::new(OwnGameLogic, debug_mode);
-    
-    client.run(&host, port, reservation.as_ref().map(|s| s.as_str())).expect("Error while running client.");
+
+    let status = result.unwrap_or_else(|e| {
+        eprintln!("Error: {}", e);
+        if let CommandError::Usage(_) = e {
+            eprintln!("Run '{} help' for a list of subcommands or '{} SUBCOMMAND --help' for their options.", program, program);
+        }
+        e.exit_status()
+    });
+    process::exit(status as i32);
 }
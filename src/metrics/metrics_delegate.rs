@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+use crate::client::SCClientDelegate;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use crate::util::CancellationToken;
+use super::Metrics;
+
+/// A delegate that records games and moves in the given
+/// metrics and otherwise forwards to the wrapped delegate.
+pub struct MetricsDelegate<D> {
+    delegate: D,
+    metrics: Metrics
+}
+
+impl<D> MetricsDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate, recording into the given metrics.
+    pub fn new(delegate: D, metrics: Metrics) -> Self {
+        Self { delegate, metrics }
+    }
+}
+
+impl<D> SCClientDelegate for MetricsDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.metrics.record_game(&result, my_color);
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let start = Instant::now();
+        let game_move = self.delegate.request_move(state, my_color, cancel);
+        let search = self.delegate.move_annotation().and_then(|a| a.nodes.zip(a.time));
+        self.metrics.record_move(start.elapsed(), search);
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
@@ -0,0 +1,124 @@
+use std::fmt::Write;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use crate::game::PlayerColor;
+use crate::protocol::{GameResult, TurnClock};
+use super::Histogram;
+
+/// The upper bounds of the move latency buckets in seconds.
+const LATENCY_BUCKETS: [f64; 10] = [0.05, 0.1, 0.25, 0.5, 1.0, 1.5, 2.0, 3.0, 5.0, 10.0];
+/// The upper bounds of the search speed buckets in nodes per second.
+const NPS_BUCKETS: [f64; 8] = [1e3, 1e4, 3e4, 1e5, 3e5, 1e6, 3e6, 1e7];
+
+#[derive(Debug)]
+struct MetricsData {
+    wins: u64,
+    losses: u64,
+    draws: u64,
+    moves: u64,
+    move_latency: Histogram,
+    nodes_per_second: Histogram,
+    thinking_time: Duration,
+    longest_turn: Duration
+}
+
+/// The metrics collected over all games of a session.
+/// Clones share the same underlying counters.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+    data: Arc<Mutex<MetricsData>>
+}
+
+impl Default for Metrics {
+    fn default() -> Self {
+        Self { data: Arc::new(Mutex::new(MetricsData {
+            wins: 0,
+            losses: 0,
+            draws: 0,
+            moves: 0,
+            move_latency: Histogram::new(&LATENCY_BUCKETS),
+            nodes_per_second: Histogram::new(&NPS_BUCKETS),
+            thinking_time: Duration::ZERO,
+            longest_turn: Duration::ZERO
+        })) }
+    }
+}
+
+impl Metrics {
+    /// Creates empty metrics.
+    pub fn new() -> Self { Self::default() }
+
+    fn with_data<T>(&self, action: impl FnOnce(&mut MetricsData) -> T) -> T {
+        let mut data = self.data.lock().unwrap_or_else(|e| e.into_inner());
+        action(&mut data)
+    }
+
+    /// Records the result of a game from the
+    /// perspective of the given color.
+    pub fn record_game(&self, result: &GameResult, my_color: Option<PlayerColor>) {
+        self.with_data(|data| match result.winners.as_slice() {
+            [] => data.draws += 1,
+            winners if winners.iter().any(|w| Some(w.color) == my_color) => data.wins += 1,
+            _ => data.losses += 1
+        });
+    }
+
+    /// Records an own move that took the given time to choose,
+    /// optionally with the number of positions searched in the
+    /// given time.
+    pub fn record_move(&self, latency: Duration, search: Option<(u64, Duration)>) {
+        self.with_data(|data| {
+            data.moves += 1;
+            data.move_latency.observe(latency.as_secs_f64());
+            if let Some((nodes, time)) = search.filter(|(_, t)| !t.is_zero()) {
+                data.nodes_per_second.observe(nodes as f64 / time.as_secs_f64());
+            }
+        });
+    }
+
+    /// Records the time measured by the clock of a finished game.
+    pub fn record_clock(&self, clock: &TurnClock) {
+        self.with_data(|data| {
+            data.thinking_time += clock.thinking_time();
+            data.longest_turn = data.longest_turn.max(clock.longest_turn());
+        });
+    }
+
+    /// The number of finished games.
+    pub fn games(&self) -> u64 { self.with_data(|d| d.wins + d.losses + d.draws) }
+
+    /// The number of own moves.
+    pub fn moves(&self) -> u64 { self.with_data(|d| d.moves) }
+
+    /// Renders the metrics in the OpenMetrics text format.
+    pub fn render(&self) -> String {
+        self.with_data(|data| {
+            let mut out = String::new();
+            let _ = writeln!(out, "# HELP sc_games Finished games by outcome.");
+            let _ = writeln!(out, "# TYPE sc_games counter");
+            for (outcome, count) in [("win", data.wins), ("loss", data.losses), ("draw", data.draws)] {
+                let _ = writeln!(out, "sc_games_total{{outcome=\"{}\"}} {}", outcome, count);
+            }
+            let _ = writeln!(out, "# HELP sc_moves Moves sent to the server.");
+            let _ = writeln!(out, "# TYPE sc_moves counter");
+            let _ = writeln!(out, "sc_moves_total {}", data.moves);
+            let _ = writeln!(out, "# HELP sc_move_latency_seconds The time spent choosing a move.");
+            let _ = writeln!(out, "# TYPE sc_move_latency_seconds histogram");
+            let _ = writeln!(out, "# UNIT sc_move_latency_seconds seconds");
+            data.move_latency.write_samples(&mut out, "sc_move_latency_seconds");
+            let _ = writeln!(out, "# HELP sc_search_nodes_per_second The search speed per move.");
+            let _ = writeln!(out, "# TYPE sc_search_nodes_per_second histogram");
+            data.nodes_per_second.write_samples(&mut out, "sc_search_nodes_per_second");
+            let _ = writeln!(out, "# HELP sc_thinking_seconds The time between move requests and sent moves in finished games.");
+            let _ = writeln!(out, "# TYPE sc_thinking_seconds counter");
+            let _ = writeln!(out, "# UNIT sc_thinking_seconds seconds");
+            let _ = writeln!(out, "sc_thinking_seconds_total {}", data.thinking_time.as_secs_f64());
+            let _ = writeln!(out, "# HELP sc_longest_turn_seconds The time spent on the longest turn of the finished games.");
+            let _ = writeln!(out, "# TYPE sc_longest_turn_seconds gauge");
+            let _ = writeln!(out, "# UNIT sc_longest_turn_seconds seconds");
+            let _ = writeln!(out, "sc_longest_turn_seconds {}", data.longest_turn.as_secs_f64());
+            out.push_str("# EOF\n");
+            out
+        })
+    }
+}
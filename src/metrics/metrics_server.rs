@@ -0,0 +1,63 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::thread;
+use log::{info, debug, warn};
+use crate::util::SCResult;
+use super::Metrics;
+
+/// A tiny HTTP server running on a background thread
+/// that serves the metrics at `/metrics`.
+#[derive(Debug, Clone)]
+pub struct MetricsServer {
+    address: SocketAddr,
+    metrics: Metrics
+}
+
+impl MetricsServer {
+    /// Binds the server to the given address and starts
+    /// serving the given metrics on a background thread.
+    pub fn start(address: impl ToSocketAddrs, metrics: Metrics) -> SCResult<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        info!("Serving metrics on http://{}/metrics", address);
+
+        let server = Self { address, metrics };
+        let handler = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => if let Err(e) = handler.handle(stream) {
+                        debug!("Could not handle HTTP request: {}", e);
+                    },
+                    Err(e) => warn!("Could not accept HTTP connection: {}", e)
+                }
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// Fetches the address the server is bound to.
+    pub fn address(&self) -> SocketAddr { self.address }
+
+    /// Fetches the served metrics.
+    pub fn metrics(&self) -> &Metrics { &self.metrics }
+
+    /// Responds to a single HTTP request.
+    fn handle(&self, stream: TcpStream) -> SCResult<()> {
+        let mut request_line = String::new();
+        BufReader::new(&stream).read_line(&mut request_line)?;
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+        debug!("Got HTTP request for {}", path);
+
+        let (status, content_type, body) = match path {
+            "/metrics" => ("200 OK", "application/openmetrics-text; version=1.0.0; charset=utf-8", self.metrics.render()),
+            _ => ("404 Not Found", "text/plain", "Not found".to_owned())
+        };
+
+        let mut writer = &stream;
+        write!(writer, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, body.len(), body)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
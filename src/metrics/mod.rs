@@ -0,0 +1,13 @@
+//! Counters and histograms describing a long-running
+//! client, exposed in the OpenMetrics text format (as
+//! scraped by Prometheus) via an embedded HTTP endpoint.
+
+mod histogram;
+mod metrics_delegate;
+mod metrics_registry;
+mod metrics_server;
+
+pub use histogram::*;
+pub use metrics_delegate::*;
+pub use metrics_registry::*;
+pub use metrics_server::*;
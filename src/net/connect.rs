@@ -0,0 +1,49 @@
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+use log::{debug, info};
+use crate::util::SCResult;
+
+/// Formats a host and a port as an address,
+/// enclosing IPv6 addresses in brackets.
+pub fn format_address(host: &str, port: u16) -> String {
+    if host.contains(':') && !host.starts_with('[') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Resolves the given host, which may be a hostname or
+/// an IPv4/IPv6 address (optionally in brackets).
+pub fn resolve(host: &str, port: u16) -> SCResult<Vec<SocketAddr>> {
+    let unbracketed = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+    let addresses: Vec<_> = (unbracketed, port).to_socket_addrs()
+        .map_err(|e| io::Error::new(e.kind(), format!("Could not resolve {}: {}", host, e)))?
+        .collect();
+    if addresses.is_empty() {
+        Err(io::Error::new(io::ErrorKind::NotFound, format!("Could not resolve {}: no addresses found", host)).into())
+    } else {
+        Ok(addresses)
+    }
+}
+
+/// Connects to the given host, trying all of its
+/// addresses in order until one of them succeeds.
+pub fn connect(host: &str, port: u16) -> SCResult<TcpStream> {
+    let mut failures = Vec::new();
+    let mut last_kind = io::ErrorKind::NotConnected;
+    for address in resolve(host, port)? {
+        match TcpStream::connect(address) {
+            Ok(stream) => {
+                info!("Connected to {}", address);
+                return Ok(stream);
+            },
+            Err(e) => {
+                debug!("Could not connect to {}: {}", address, e);
+                last_kind = e.kind();
+                failures.push(format!("{} ({})", address, e));
+            }
+        }
+    }
+    Err(io::Error::new(last_kind, format!("Could not connect to {}, tried {}", format_address(host, port), failures.join(", "))).into())
+}
@@ -0,0 +1,8 @@
+//! Establishing the connection to the game server,
+//! either directly or through a proxy.
+
+mod connect;
+mod proxy;
+
+pub use connect::*;
+pub use proxy::*;
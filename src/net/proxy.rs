@@ -0,0 +1,206 @@
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::net::{IpAddr, TcpStream};
+use std::str::FromStr;
+use log::info;
+use crate::util::{SCError, SCResult};
+use super::{connect, format_address};
+
+/// A proxy through which the connection to the game server
+/// is tunneled, e.g. in networks with egress restrictions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Proxy {
+    /// A SOCKS5 proxy (`socks5://[user:password@]host:port`),
+    /// which also resolves the game server's hostname.
+    Socks5 { host: String, port: u16, credentials: Option<(String, String)> },
+    /// An HTTP proxy supporting `CONNECT` tunnels
+    /// (`http://[user:password@]host:port`).
+    Http { host: String, port: u16, credentials: Option<(String, String)> }
+}
+
+/// The error for a failed handshake with the proxy.
+fn handshake_error(message: impl Into<String>) -> SCError {
+    io::Error::new(io::ErrorKind::ConnectionRefused, message.into()).into()
+}
+
+/// Encodes the given bytes in Base64 (with padding).
+fn base64(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let triple = chunk.iter().enumerate().fold(0u32, |acc, (i, &b)| acc | (u32::from(b) << (16 - 8 * i)));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(triple >> (18 - 6 * i) & 0x3F) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+    encoded
+}
+
+impl Proxy {
+    /// Connects to the given game server through the proxy.
+    pub fn connect(&self, host: &str, port: u16) -> SCResult<TcpStream> {
+        let mut stream = match self {
+            Self::Socks5 { host: proxy_host, port: proxy_port, .. } | Self::Http { host: proxy_host, port: proxy_port, .. } => connect(proxy_host, *proxy_port)?
+        };
+        match self {
+            Self::Socks5 { credentials, .. } => Self::socks5_handshake(&mut stream, host, port, credentials.as_ref())?,
+            Self::Http { credentials, .. } => Self::http_handshake(&mut stream, host, port, credentials.as_ref())?
+        }
+        info!("Tunneled to {} through {}", format_address(host, port), self);
+        Ok(stream)
+    }
+
+    fn socks5_handshake(stream: &mut TcpStream, host: &str, port: u16, credentials: Option<&(String, String)>) -> SCResult<()> {
+        // Negotiate the authentication method
+        let methods: &[u8] = if credentials.is_some() { &[0x00, 0x02] } else { &[0x00] };
+        stream.write_all(&[0x05, methods.len() as u8])?;
+        stream.write_all(methods)?;
+        let mut reply = [0; 2];
+        stream.read_exact(&mut reply)?;
+        match (reply, credentials) {
+            ([0x05, 0x00], _) => {},
+            ([0x05, 0x02], Some((user, password))) => {
+                if user.len() > 255 || password.len() > 255 {
+                    return Err(handshake_error("The SOCKS5 credentials are too long"));
+                }
+                stream.write_all(&[0x01, user.len() as u8])?;
+                stream.write_all(user.as_bytes())?;
+                stream.write_all(&[password.len() as u8])?;
+                stream.write_all(password.as_bytes())?;
+                stream.read_exact(&mut reply)?;
+                if reply[1] != 0x00 {
+                    return Err(handshake_error("The SOCKS5 proxy rejected the credentials"));
+                }
+            },
+            _ => return Err(handshake_error("The SOCKS5 proxy does not accept any supported authentication method"))
+        }
+
+        // Request the tunnel, letting the proxy resolve hostnames
+        let unbracketed = host.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(host);
+        let mut request = vec![0x05, 0x01, 0x00];
+        match unbracketed.parse::<IpAddr>() {
+            Ok(IpAddr::V4(ip)) => {
+                request.push(0x01);
+                request.extend_from_slice(&ip.octets());
+            },
+            Ok(IpAddr::V6(ip)) => {
+                request.push(0x04);
+                request.extend_from_slice(&ip.octets());
+            },
+            Err(_) if host.len() <= 255 => {
+                request.extend_from_slice(&[0x03, host.len() as u8]);
+                request.extend_from_slice(host.as_bytes());
+            },
+            Err(_) => return Err(handshake_error(format!("The hostname {} is too long for SOCKS5", host)))
+        }
+        request.extend_from_slice(&port.to_be_bytes());
+        stream.write_all(&request)?;
+
+        let mut header = [0; 4];
+        stream.read_exact(&mut header)?;
+        if header[1] != 0x00 {
+            let reason = match header[1] {
+                0x01 => "general failure",
+                0x02 => "connection not allowed by ruleset",
+                0x03 => "network unreachable",
+                0x04 => "host unreachable",
+                0x05 => "connection refused",
+                0x06 => "TTL expired",
+                0x07 => "command not supported",
+                0x08 => "address type not supported",
+                _ => "unknown error"
+            };
+            return Err(handshake_error(format!("The SOCKS5 proxy could not connect to {}: {}", format_address(host, port), reason)));
+        }
+
+        // Skip the address the proxy bound to
+        let address_len = match header[3] {
+            0x01 => 4,
+            0x04 => 16,
+            0x03 => {
+                let mut len = [0];
+                stream.read_exact(&mut len)?;
+                usize::from(len[0])
+            },
+            atyp => return Err(handshake_error(format!("Invalid address type {} from the SOCKS5 proxy", atyp)))
+        };
+        stream.read_exact(&mut vec![0; address_len + 2])?;
+        Ok(())
+    }
+
+    fn http_handshake(stream: &mut TcpStream, host: &str, port: u16, credentials: Option<&(String, String)>) -> SCResult<()> {
+        let address = format_address(host, port);
+        let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", address);
+        if let Some((user, password)) = credentials {
+            request += &format!("Proxy-Authorization: Basic {}\r\n", base64(format!("{}:{}", user, password).as_bytes()));
+        }
+        request += "\r\n";
+        stream.write_all(request.as_bytes())?;
+
+        // Read the response byte by byte to not consume
+        // anything the game server sends afterwards
+        let mut response = Vec::new();
+        let mut byte = [0];
+        while !response.ends_with(b"\r\n\r\n") {
+            if response.len() > 8192 {
+                return Err(handshake_error("The HTTP proxy's response is too long"));
+            }
+            stream.read_exact(&mut byte)?;
+            response.push(byte[0]);
+        }
+        let response = String::from_utf8_lossy(&response);
+        let status_line = response.lines().next().unwrap_or_default();
+        match status_line.split_whitespace().nth(1) {
+            Some("200") => Ok(()),
+            _ => Err(handshake_error(format!("The HTTP proxy could not connect to {}: {}", address, status_line)))
+        }
+    }
+}
+
+impl FromStr for Proxy {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        let (scheme, rest) = raw.split_once("://").ok_or_else(|| format!("Missing scheme in proxy {} (e.g. socks5:// or http://)", raw))?;
+        let rest = rest.trim_end_matches('/');
+        let (credentials, address) = match rest.rsplit_once('@') {
+            Some((credentials, address)) => {
+                let (user, password) = credentials.split_once(':').unwrap_or((credentials, ""));
+                (Some((user.to_owned(), password.to_owned())), address)
+            },
+            None => (None, rest)
+        };
+        let (host, port) = match address.rsplit_once(':') {
+            Some((host, port)) if !host.is_empty() && !port.contains(']') => (host, Some(port.parse::<u16>().map_err(|_| format!("Invalid port in proxy {}", raw))?)),
+            _ => (address, None)
+        };
+        if host.is_empty() {
+            return Err(format!("Missing host in proxy {}", raw).into());
+        }
+        let host = host.to_owned();
+        match scheme.to_lowercase().as_str() {
+            "socks5" | "socks5h" => Ok(Self::Socks5 { host, port: port.unwrap_or(1080), credentials }),
+            "http" => Ok(Self::Http { host, port: port.unwrap_or(8080), credentials }),
+            _ => Err(format!("Unsupported proxy scheme {} (expected socks5 or http)", scheme).into())
+        }
+    }
+}
+
+impl fmt::Display for Proxy {
+    /// Formats the proxy as a URL, omitting the password.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let (scheme, host, port, credentials) = match self {
+            Self::Socks5 { host, port, credentials } => ("socks5", host, port, credentials),
+            Self::Http { host, port, credentials } => ("http", host, port, credentials)
+        };
+        write!(f, "{}://", scheme)?;
+        if let Some((user, _)) = credentials {
+            write!(f, "{}@", user)?;
+        }
+        write!(f, "{}:{}", host, port)
+    }
+}
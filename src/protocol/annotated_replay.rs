@@ -0,0 +1,57 @@
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
+use super::{GamePlugin, MoveAnnotation};
+
+/// A state together with the annotation of the move chosen in it.
+pub type AnnotatedState<P> = (<P as GamePlugin>::GameState, Option<MoveAnnotation<<P as GamePlugin>::Move>>);
+
+/// A recorded game whose moves optionally carry the engine's
+/// annotations. In XML, each `<state>` is followed by the
+/// `<annotation>` of the move chosen in it (if any), which
+/// viewers not knowing about annotations can skip.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedReplay<P> where P: GamePlugin {
+    pub entries: Vec<AnnotatedState<P>>
+}
+
+impl<P> AnnotatedReplay<P> where P: GamePlugin {
+    /// Creates an empty replay.
+    pub fn new() -> Self {
+        Self { entries: Vec::new() }
+    }
+
+    /// Appends a state with the annotation of the move chosen in it.
+    pub fn push(&mut self, state: P::GameState, annotation: Option<MoveAnnotation<P::Move>>) {
+        self.entries.push((state, annotation));
+    }
+}
+
+impl<P> Default for AnnotatedReplay<P> where P: GamePlugin {
+    fn default() -> Self { Self::new() }
+}
+
+impl<P> FromXmlNode for AnnotatedReplay<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let mut replay = Self::new();
+        for child in node.childs() {
+            match child.name() {
+                "state" => replay.push(P::GameState::from_node(child)?, None),
+                "annotation" => match replay.entries.last_mut() {
+                    Some((_, annotation)) => *annotation = Some(MoveAnnotation::from_node(child)?),
+                    None => return Err("Annotation without a preceding state".into())
+                },
+                _ => {}
+            }
+        }
+        Ok(replay)
+    }
+}
+
+impl<P> From<AnnotatedReplay<P>> for XmlNode where P: GamePlugin {
+    fn from(replay: AnnotatedReplay<P>) -> Self {
+        XmlNode::new("protocol")
+            .childs(replay.entries.into_iter().flat_map(|(state, annotation)| {
+                std::iter::once(state.into()).chain(annotation.map(XmlNode::from))
+            }))
+            .build()
+    }
+}
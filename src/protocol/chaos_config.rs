@@ -0,0 +1,50 @@
+use std::time::Duration;
+
+/// Faults to inject into the connection to the server,
+/// which lets integration tests verify that the client
+/// fails with errors rather than hanging or panicking.
+/// By default, no faults are injected.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct ChaosConfig {
+    /// The artificial latency before each incoming message is delivered.
+    pub latency: Duration,
+    /// Delivers every n-th incoming message twice.
+    pub duplicate_every: Option<usize>,
+    /// Splits reads and writes into chunks of at most this many bytes.
+    pub chunk_size: Option<usize>,
+    /// Ends the incoming stream after this many bytes.
+    pub truncate_after: Option<usize>
+}
+
+impl ChaosConfig {
+    /// Delays each incoming message by the given duration.
+    pub fn with_latency(self, latency: Duration) -> Self {
+        Self { latency, ..self }
+    }
+
+    /// Delivers every n-th incoming message twice.
+    pub fn with_duplicate_every(self, n: usize) -> Self {
+        Self { duplicate_every: Some(n.max(1)), ..self }
+    }
+
+    /// Splits reads and writes into chunks of at most the given size.
+    pub fn with_chunk_size(self, bytes: usize) -> Self {
+        Self { chunk_size: Some(bytes.max(1)), ..self }
+    }
+
+    /// Ends the incoming stream after the given number of bytes.
+    pub fn with_truncate_after(self, bytes: usize) -> Self {
+        Self { truncate_after: Some(bytes), ..self }
+    }
+
+    /// Tests whether the incoming message with the
+    /// given (zero-based) index should be duplicated.
+    pub fn duplicates(&self, index: usize) -> bool {
+        self.duplicate_every.map(|n| (index + 1).is_multiple_of(n)).unwrap_or(false)
+    }
+
+    /// Tests whether any faults are injected.
+    pub fn is_enabled(&self) -> bool {
+        *self != Self::default()
+    }
+}
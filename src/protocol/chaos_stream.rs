@@ -0,0 +1,54 @@
+use std::io::{self, Read, Write};
+use super::ChaosConfig;
+
+/// A reader or writer that splits and truncates
+/// the data passing through it as configured.
+pub struct ChaosStream<S> {
+    inner: S,
+    chunk_size: Option<usize>,
+    remaining: Option<usize>
+}
+
+impl<S> ChaosStream<S> {
+    /// Wraps the given stream, injecting the configured faults.
+    pub fn new(inner: S, config: &ChaosConfig) -> Self {
+        Self { inner, chunk_size: config.chunk_size, remaining: config.truncate_after }
+    }
+
+    /// The number of bytes to pass through at most.
+    fn limit(&self, len: usize) -> usize {
+        len.min(self.chunk_size.unwrap_or(usize::MAX)).min(self.remaining.unwrap_or(usize::MAX))
+    }
+}
+
+impl<S> Read for ChaosStream<S> where S: Read {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = self.limit(buf.len());
+        if limit == 0 && !buf.is_empty() {
+            return Ok(0);
+        }
+        let read = self.inner.read(&mut buf[..limit])?;
+        if let Some(remaining) = &mut self.remaining {
+            *remaining -= read;
+        }
+        Ok(read)
+    }
+}
+
+impl<S> Write for ChaosStream<S> where S: Write {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.chunk_size {
+            Some(chunk_size) => {
+                // Flush each chunk so it is sent separately
+                let written = self.inner.write(&buf[..buf.len().min(chunk_size)])?;
+                self.inner.flush()?;
+                Ok(written)
+            },
+            None => self.inner.write(buf)
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
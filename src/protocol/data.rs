@@ -1,38 +1,73 @@
 use std::convert::TryFrom;
-use crate::{util::{SCError, SCResult, FromXmlNode, XmlNode}, game::{Move, PlayerColor, GameState}};
-use super::GameResult;
+use crate::{util::{SCError, SCResult, FromXmlNode, XmlElement, XmlNode}, game::HivePlugin};
+use super::{GamePlugin, GameResult, Memento, MoveRequest, WelcomeMessage};
 
 /// A container for game data used by the protocol.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub enum Data {
-    WelcomeMessage { color: PlayerColor },
-    Memento { state: GameState },
-    Move(Move),
-    MoveRequest,
-    GameResult(GameResult),
+pub enum Data<P = HivePlugin> where P: GamePlugin {
+    WelcomeMessage(WelcomeMessage<P>),
+    Memento(Memento<P>),
+    /// A move relayed by the server (e.g. to observers).
+    Move(P::Move),
+    MoveRequest(MoveRequest),
+    /// Sent when the game is paused by the server.
+    Paused,
+    /// Sent when a paused game continues.
+    Resumed,
+    /// Sent to observers once they are admitted to the room.
+    Observed,
+    GameResult(GameResult<P>),
     Error { message: String }
 }
 
-impl FromXmlNode for Data {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+impl<P> Data<P> where P: GamePlugin {
+    /// Fetches the attributes (besides the class) that
+    /// data of the given class is parsed from.
+    pub fn known_attributes(class: &str) -> &'static [&'static str] {
+        match class {
+            "welcomeMessage" => &["color"],
+            "paused" => &["paused"],
+            "error" => &["message"],
+            _ => &[]
+        }
+    }
+}
+
+impl<P> FromXmlNode for Data<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         let class = node.attribute("class")?;
         match class {
-            "welcomeMessage" => Ok(Self::WelcomeMessage { color: node.attribute("color")?.parse()? }),
-            "memento" => Ok(Self::Memento { state: GameState::from_node(node.child_by_name("state")?)? }),
-            "sc.framework.plugins.protocol.MoveRequest" => Ok(Self::MoveRequest),
+            "welcomeMessage" => Ok(Self::WelcomeMessage(WelcomeMessage::from_node(node)?)),
+            "memento" => Ok(Self::Memento(Memento::from_node(node)?)),
+            MoveRequest::CLASS => Ok(Self::MoveRequest(MoveRequest::from_node(node)?)),
+            // Some servers announce both pausing and resuming via this class
+            "paused" => match node.attribute("paused") {
+                Ok("false") => Ok(Self::Resumed),
+                _ => Ok(Self::Paused)
+            },
+            "resumed" => Ok(Self::Resumed),
+            "observed" => Ok(Self::Observed),
             "result" => Ok(Self::GameResult(GameResult::from_node(node)?)),
             "error" => Ok(Self::Error { message: node.attribute("message")?.to_owned() }),
-            _ => Err(format!("Unrecognized data class: {}", class).into())
+            // Moves are identified by their own classes
+            _ => P::Move::from_node(node).map(Self::Move).map_err(|_| format!("Unrecognized data class: {}", class).into())
         }
     }
 }
 
-impl TryFrom<Data> for XmlNode {
+impl<P> TryFrom<Data<P>> for XmlNode where P: GamePlugin {
     type Error = SCError;
 
-    fn try_from(data: Data) -> SCResult<XmlNode> {
+    fn try_from(data: Data<P>) -> SCResult<XmlNode> {
         match data {
+            Data::WelcomeMessage(message) => Ok(message.into()),
+            Data::Memento(memento) => Ok(memento.into()),
             Data::Move(game_move) => Ok(game_move.into()),
+            Data::MoveRequest(request) => Ok(request.into()),
+            Data::Paused => Ok(XmlNode::new("data").attribute("class", "paused").build()),
+            Data::Resumed => Ok(XmlNode::new("data").attribute("class", "resumed").build()),
+            Data::Observed => Ok(XmlNode::new("data").attribute("class", "observed").build()),
+            Data::Error { message } => Ok(XmlNode::new("data").attribute("class", "error").attribute("message", message).build()),
             _ => Err(format!("{:?} can currently not be serialized", data).into())
         }
     }
@@ -0,0 +1,23 @@
+use crate::game::HivePlugin;
+use super::{GamePlugin, GameResult, SessionEnd, Termination};
+
+/// What the client observed of a game once
+/// the session with the server ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOutcome<P = HivePlugin> where P: GamePlugin {
+    /// The game result, if the server sent one.
+    pub result: Option<GameResult<P>>,
+    /// The own color, if the server sent a welcome message.
+    pub color: Option<P::PlayerColor>,
+    /// The turn of the last received game state.
+    pub turns: u32,
+    /// How the session ended.
+    pub end: SessionEnd
+}
+
+impl<P> GameOutcome<P> where P: GamePlugin {
+    /// Determines why the game ended, if a result was received.
+    pub fn termination(&self) -> Option<Termination> {
+        self.result.as_ref().map(GameResult::termination)
+    }
+}
@@ -0,0 +1,44 @@
+use std::{fmt::Debug, hash::Hash, str::FromStr};
+use crate::util::{SCError, SCResult, FromXmlNode, XmlNode};
+
+/// A game played in the Software Challenge. Provides
+/// the year-specific types that the (otherwise year-independent)
+/// protocol structures and the client are parameterized over.
+pub trait GamePlugin: Debug + Clone + PartialEq + Eq {
+    /// The game type identifier used when joining a game.
+    const GAME_TYPE: &'static str;
+
+    /// A player color (i.e. a team).
+    type PlayerColor: FromStr<Err=SCError> + Into<String> + Debug + Copy + Clone + PartialEq + Eq + Hash;
+    /// Metadata about a player.
+    type Player: FromXmlNode + Debug + Clone + PartialEq + Eq;
+    /// A snapshot of the game's state.
+    type GameState: FromXmlNode + Into<XmlNode> + Debug + Clone + PartialEq + Eq;
+    /// A transition between two game states.
+    type Move: FromXmlNode + Into<XmlNode> + Debug + Clone + PartialEq + Eq;
+
+    /// Fetches the current turn of the given state.
+    fn turn(state: &Self::GameState) -> u32;
+
+    /// Fetches the color of the player whose turn it is.
+    fn current_player_color(state: &Self::GameState) -> Self::PlayerColor;
+
+    /// Fetches the index of the given color's score in a game result.
+    fn player_index(color: Self::PlayerColor) -> usize;
+
+    /// Tests whether the given move is valid for the given
+    /// color, explaining the violated rule otherwise.
+    fn validate_move(state: &Self::GameState, color: Self::PlayerColor, game_move: &Self::Move) -> SCResult<()>;
+
+    /// Picks any legal move for the given color, which is
+    /// sent in place of the delegate's move if it fails.
+    fn fallback_move(state: &Self::GameState, color: Self::PlayerColor) -> Option<Self::Move>;
+
+    /// Checks the consistency of a state received from the server
+    /// (e.g. its piece counts), which is only enforced in strict mode.
+    fn validate_state(_state: &Self::GameState) -> SCResult<()> { Ok(()) }
+
+    /// Creates the state at the start of a game, which is used
+    /// if the server requests a move before sending any state.
+    fn initial_state(_start_color: Self::PlayerColor) -> Option<Self::GameState> { None }
+}
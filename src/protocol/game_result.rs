@@ -1,20 +1,46 @@
-use crate::{util::{SCResult, FromXmlNode, XmlNode}, game::Player};
-use super::{PlayerScore, ScoreDefinition};
+use crate::{util::{SCResult, FromXmlNode, XmlElement}, game::HivePlugin};
+use super::{GamePlugin, PlayerScore, ScoreCause, ScoreDefinition, Termination};
 
 /// The final result of a game.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct GameResult {
+pub struct GameResult<P = HivePlugin> where P: GamePlugin {
     pub definition: ScoreDefinition,
     pub scores: Vec<PlayerScore>,
-    pub winners: Vec<Player>
+    pub winners: Vec<P::Player>
 }
 
-impl FromXmlNode for GameResult {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+impl<P> GameResult<P> where P: GamePlugin {
+    /// Determines why the game ended from the causes of the scores.
+    pub fn termination(&self) -> Termination {
+        let non_empty = |reason: &str| Some(reason.to_owned()).filter(|r| !r.is_empty());
+        match self.scores.iter().position(|s| s.cause != ScoreCause::Regular) {
+            Some(i) => Termination { cause: self.scores[i].cause, reason: non_empty(&self.scores[i].reason), player_index: Some(i) },
+            None => Termination { cause: ScoreCause::Regular, reason: self.scores.iter().find_map(|s| non_empty(&s.reason)), player_index: None }
+        }
+    }
+
+    /// Tests whether a player exceeded the time limit.
+    pub fn ended_by_timeout(&self) -> bool {
+        self.scores.iter().any(|s| s.cause.is_timeout())
+    }
+
+    /// Tests whether a player violated the rules.
+    pub fn ended_by_rule_violation(&self) -> bool {
+        self.scores.iter().any(|s| s.cause == ScoreCause::RuleViolation)
+    }
+
+    /// Tests whether a player left the game.
+    pub fn ended_by_leaving(&self) -> bool {
+        self.scores.iter().any(|s| s.cause == ScoreCause::Left)
+    }
+}
+
+impl<P> FromXmlNode for GameResult<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             definition: ScoreDefinition::from_node(node.child_by_name("definition")?)?,
             scores: node.childs_by_name("score").map(PlayerScore::from_node).collect::<SCResult<_>>()?,
-            winners: node.childs_by_name("winner").map(Player::from_node).collect::<SCResult<_>>()?
+            winners: node.childs_by_name("winner").map(P::Player::from_node).collect::<SCResult<_>>()?
         })
     }
 }
@@ -0,0 +1,22 @@
+use std::fmt;
+use crate::util::SCError;
+use super::SessionPhase;
+
+/// A message that is not valid in the current phase of a session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IllegalTransition {
+    /// The phase of the session when the message arrived.
+    pub phase: SessionPhase,
+    /// A description of the message.
+    pub input: String
+}
+
+impl fmt::Display for IllegalTransition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Unexpected {} while {}", self.input, self.phase)
+    }
+}
+
+impl From<IllegalTransition> for SCError {
+    fn from(error: IllegalTransition) -> Self { Self::Custom(error.to_string()) }
+}
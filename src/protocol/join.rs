@@ -0,0 +1,42 @@
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
+
+/// The message the client opens the session with,
+/// asking the server to join a game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Join {
+    /// Joins any open game of the given type.
+    Any { game_type: String },
+    /// Joins the game prepared for the given reservation code.
+    Prepared { reservation_code: String }
+}
+
+impl Join {
+    /// Creates the join message for the given reservation,
+    /// falling back to any game of the given type.
+    pub fn new(game_type: &str, reservation: Option<&str>) -> Self {
+        match reservation {
+            Some(reservation_code) => Self::Prepared { reservation_code: reservation_code.to_owned() },
+            None => Self::Any { game_type: game_type.to_owned() }
+        }
+    }
+}
+
+impl FromXmlNode for Join {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        match node.name() {
+            "join" => Ok(Self::Any { game_type: node.attribute("gameType")?.to_owned() }),
+            "joinPrepared" => Ok(Self::Prepared { reservation_code: node.attribute("reservationCode")?.to_owned() }),
+            name => Err(format!("Unrecognized join message: <{}>", name).into())
+        }
+    }
+}
+
+impl From<Join> for XmlNode {
+    fn from(join: Join) -> Self {
+        // Built as a node, so the writer escapes the values
+        match join {
+            Join::Any { game_type } => XmlNode::new("join").attribute("gameType", game_type).build(),
+            Join::Prepared { reservation_code } => XmlNode::new("joinPrepared").attribute("reservationCode", reservation_code).build()
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use crate::util::{SCResult, FromXmlNode, XmlElement};
 
 /// A message indicating that the client
 /// has joined a room with the specified id.
@@ -8,5 +8,5 @@ pub struct Joined {
 }
 
 impl FromXmlNode for Joined {
-    fn from_node(node: &XmlNode) -> SCResult<Self> { Ok(Self { room_id: node.attribute("roomId")?.to_owned() }) }
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> { Ok(Self { room_id: node.attribute("roomId")?.to_owned() }) }
 }
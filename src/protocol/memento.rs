@@ -0,0 +1,23 @@
+use crate::{util::{SCResult, FromXmlNode, XmlElement, XmlNode}, game::HivePlugin};
+use super::GamePlugin;
+
+/// A message containing the updated game state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Memento<P = HivePlugin> where P: GamePlugin {
+    pub state: P::GameState
+}
+
+impl<P> FromXmlNode for Memento<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        Ok(Self { state: P::GameState::from_node(node.child_by_name("state")?)? })
+    }
+}
+
+impl<P> From<Memento<P>> for XmlNode where P: GamePlugin {
+    fn from(memento: Memento<P>) -> Self {
+        XmlNode::new("data")
+            .attribute("class", "memento")
+            .child(memento.state)
+            .build()
+    }
+}
@@ -0,0 +1,59 @@
+use std::io::Write;
+use std::time::Duration;
+use log::{debug, warn};
+use crate::util::{XmlFormat, XmlNode};
+
+/// The direction in which a message travels.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum Direction {
+    /// A message received from the server.
+    Incoming,
+    /// A message sent to the server.
+    Outgoing
+}
+
+/// Determines what happens to a message after
+/// it passed through a middleware.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum MiddlewareAction {
+    /// Passes the (possibly rewritten) message on.
+    Continue,
+    /// Waits for the given duration, then passes the message on.
+    Delay(Duration),
+    /// Discards the message.
+    Drop
+}
+
+/// Intercepts the messages exchanged with the server, e.g. to
+/// log, rewrite, delay or drop them. Incoming messages pass
+/// through the chain of middlewares in order, outgoing ones
+/// in reverse order, thus the first middleware is closest to the wire.
+pub type Middleware = Box<dyn FnMut(&mut XmlNode, Direction) -> MiddlewareAction + Send>;
+
+/// A middleware that logs every message as it
+/// is sent or received. Installed by default.
+pub fn log_wire(node: &mut XmlNode, direction: Direction) -> MiddlewareAction {
+    match direction {
+        Direction::Incoming => debug!("Got XML node {}", node),
+        Direction::Outgoing => debug!("Sending XML node {}", node)
+    }
+    MiddlewareAction::Continue
+}
+
+/// Creates a middleware that records every message on a line of
+/// its own, prefixed by `<` if it was received and `>` if it was
+/// sent, e.g. to replay a session against a fake server later.
+pub fn record_wire(mut writer: impl Write + Send) -> impl FnMut(&mut XmlNode, Direction) -> MiddlewareAction + Send {
+    move |node, direction| {
+        let prefix = match direction {
+            Direction::Incoming => '<',
+            Direction::Outgoing => '>'
+        };
+        let written = node.to_string_with(&XmlFormat::compact())
+            .and_then(|xml| Ok(writeln!(writer, "{} {}", prefix, xml).and_then(|_| writer.flush())?));
+        if let Err(e) = written {
+            warn!("Could not record XML node: {}", e);
+        }
+        MiddlewareAction::Continue
+    }
+}
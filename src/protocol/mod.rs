@@ -1,24 +1,79 @@
-//! The data structures used by the XML protocol.
+//! The data structures used by the XML protocol. These
+//! are independent of the specific game and parameterized
+//! over a `GamePlugin` where necessary.
 
+mod annotated_replay;
+mod chaos_config;
+mod chaos_stream;
 mod data;
+mod game_outcome;
+mod game_plugin;
 mod game_result;
+mod illegal_transition;
+mod join;
 mod joined;
 mod left;
+mod memento;
+mod middleware;
+mod move_annotation;
+mod move_debug_info;
+mod move_preflight;
+mod move_record;
+mod move_request;
+mod move_sequence;
+mod opponent_stats;
+mod parse_mode;
 mod player_score;
+mod protocol_event;
+mod replay;
 mod room;
+mod rtt_stats;
 mod score_aggregation;
 mod score_cause;
 mod score_definition;
 mod score_fragment;
+mod session;
+mod session_end;
+mod session_phase;
+mod termination;
+mod time_settings;
+mod turn_clock;
+mod welcome_message;
 
+pub use annotated_replay::*;
+pub use chaos_config::*;
+pub use chaos_stream::*;
 pub use data::*;
+pub use game_outcome::*;
+pub use game_plugin::*;
 pub use game_result::*;
+pub use illegal_transition::*;
+pub use join::*;
 pub use joined::*;
 pub use left::*;
-pub use data::*;
+pub use memento::*;
+pub use middleware::*;
+pub use move_annotation::*;
+pub use move_debug_info::*;
+pub use move_preflight::*;
+pub use move_record::*;
+pub use move_request::*;
+pub use move_sequence::*;
+pub use opponent_stats::*;
+pub use parse_mode::*;
 pub use player_score::*;
+pub use protocol_event::*;
+pub use replay::*;
 pub use room::*;
+pub use rtt_stats::*;
 pub use score_definition::*;
 pub use score_fragment::*;
 pub use score_aggregation::*;
 pub use score_cause::*;
+pub use session::*;
+pub use session_end::*;
+pub use session_phase::*;
+pub use termination::*;
+pub use time_settings::*;
+pub use turn_clock::*;
+pub use welcome_message::*;
@@ -0,0 +1,78 @@
+use std::time::Duration;
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
+#[cfg(feature = "json")]
+use crate::game::Move;
+use super::MoveDebugInfo;
+
+/// How the engine chose a move, as recorded in annotated replays.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveAnnotation<M> {
+    /// The evaluation from the perspective of the moving player.
+    pub evaluation: Option<f64>,
+    /// The depth of the search that chose the move.
+    pub depth: Option<u32>,
+    /// The time spent choosing the move.
+    pub time: Option<Duration>,
+    /// The number of positions searched.
+    pub nodes: Option<u64>,
+    /// The expected continuation, starting with the chosen move.
+    pub principal_variation: Vec<M>,
+    /// Further metadata for post-mortem analysis.
+    pub debug: MoveDebugInfo
+}
+
+impl<M> Default for MoveAnnotation<M> {
+    fn default() -> Self {
+        Self { evaluation: None, depth: None, time: None, nodes: None, principal_variation: Vec::new(), debug: MoveDebugInfo::new() }
+    }
+}
+
+impl<M> FromXmlNode for MoveAnnotation<M> where M: FromXmlNode {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        Ok(Self {
+            evaluation: node.attribute("evaluation").ok().map(str::parse).transpose()?,
+            depth: node.attribute("depth").ok().map(str::parse).transpose()?,
+            time: node.attribute("timeMs").ok().map(str::parse).transpose()?.map(Duration::from_millis),
+            nodes: node.attribute("nodes").ok().map(str::parse).transpose()?,
+            principal_variation: node.childs().iter().filter(|c| c.name() != "debug").map(M::from_node).collect::<SCResult<_>>()?,
+            debug: node.child_by_name("debug").ok().map(MoveDebugInfo::from_node).transpose()?.unwrap_or_default()
+        })
+    }
+}
+
+impl<M> From<MoveAnnotation<M>> for XmlNode where M: Into<XmlNode> {
+    fn from(annotation: MoveAnnotation<M>) -> Self {
+        let mut builder = XmlNode::new("annotation");
+        if let Some(evaluation) = annotation.evaluation {
+            builder = builder.attribute("evaluation", evaluation.to_string());
+        }
+        if let Some(depth) = annotation.depth {
+            builder = builder.attribute("depth", depth.to_string());
+        }
+        if let Some(time) = annotation.time {
+            builder = builder.attribute("timeMs", time.as_millis().to_string());
+        }
+        if let Some(nodes) = annotation.nodes {
+            builder = builder.attribute("nodes", nodes.to_string());
+        }
+        builder = builder.childs(annotation.principal_variation.into_iter().map(Into::into));
+        if !annotation.debug.is_empty() {
+            builder = builder.child(annotation.debug);
+        }
+        builder.build()
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&MoveAnnotation<Move>> for serde_json::Value {
+    fn from(annotation: &MoveAnnotation<Move>) -> Self {
+        serde_json::json!({
+            "evaluation": annotation.evaluation,
+            "depth": annotation.depth,
+            "timeMs": annotation.time.map(|t| t.as_millis() as u64),
+            "nodes": annotation.nodes,
+            "principalVariation": annotation.principal_variation.iter().map(serde_json::Value::from).collect::<Vec<_>>(),
+            "debug": serde_json::Value::from(&annotation.debug)
+        })
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
+
+/// Arbitrary metadata the engine attaches to a chosen move for
+/// post-mortem analysis (e.g. the strategy or a score breakdown).
+/// It is recorded locally and never sent to the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveDebugInfo {
+    entries: BTreeMap<String, String>
+}
+
+impl MoveDebugInfo {
+    /// Creates empty metadata.
+    pub fn new() -> Self { Self::default() }
+
+    /// Adds (or replaces) an entry.
+    pub fn with(mut self, key: impl Into<String>, value: impl ToString) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    /// Adds (or replaces) an entry in place.
+    pub fn insert(&mut self, key: impl Into<String>, value: impl ToString) {
+        self.entries.insert(key.into(), value.to_string());
+    }
+
+    /// Fetches the value of an entry.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(String::as_str)
+    }
+
+    /// Iterates the entries ordered by key.
+    pub fn iter(&self) -> impl Iterator<Item=(&str, &str)> {
+        self.entries.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Tests whether there are no entries.
+    pub fn is_empty(&self) -> bool { self.entries.is_empty() }
+}
+
+impl FromXmlNode for MoveDebugInfo {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let mut info = Self::new();
+        for entry in node.childs_by_name("entry") {
+            info.insert(entry.attribute("key")?, entry.attribute("value")?);
+        }
+        Ok(info)
+    }
+}
+
+impl From<MoveDebugInfo> for XmlNode {
+    fn from(info: MoveDebugInfo) -> Self {
+        XmlNode::new("debug")
+            .childs(info.entries.into_iter().map(|(key, value)| XmlNode::new("entry").attribute("key", key).attribute("value", value).build()))
+            .build()
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<&MoveDebugInfo> for serde_json::Value {
+    fn from(info: &MoveDebugInfo) -> Self {
+        info.iter().map(|(k, v)| (k.to_owned(), serde_json::Value::from(v))).collect::<serde_json::Map<_, _>>().into()
+    }
+}
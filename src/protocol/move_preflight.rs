@@ -0,0 +1,37 @@
+use std::fmt;
+use std::str::FromStr;
+use crate::util::{SCError, SCResult};
+
+/// Determines how the client reacts if the delegate
+/// chooses a move that the local rules reject.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum MovePreflight {
+    /// Logs the violation, but sends the move anyway
+    /// (e.g. if the local rules might be wrong).
+    Log,
+    /// Logs the violation and sends the first
+    /// legal move instead.
+    #[default]
+    Substitute
+}
+
+impl FromStr for MovePreflight {
+    type Err = SCError;
+
+    fn from_str(raw: &str) -> SCResult<Self> {
+        match raw.to_lowercase().as_str() {
+            "log" => Ok(Self::Log),
+            "substitute" => Ok(Self::Substitute),
+            _ => Err(format!("Did not recognize preflight mode {}", raw).into())
+        }
+    }
+}
+
+impl fmt::Display for MovePreflight {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Log => write!(f, "log"),
+            Self::Substitute => write!(f, "substitute")
+        }
+    }
+}
@@ -0,0 +1,18 @@
+use crate::game::HivePlugin;
+use super::{GamePlugin, MoveAnnotation};
+
+/// A move the client sent to the server, kept in the
+/// client's history together with how it was chosen.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MoveRecord<P = HivePlugin> where P: GamePlugin {
+    /// The turn in which the move was made.
+    pub turn: u32,
+    /// The own color.
+    pub color: P::PlayerColor,
+    /// The move as sent to the server.
+    pub game_move: P::Move,
+    /// The delegate's annotation, which is only kept if the
+    /// delegate's move was sent unchanged (i.e. not replaced
+    /// by a fallback move).
+    pub annotation: Option<MoveAnnotation<P::Move>>
+}
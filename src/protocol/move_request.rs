@@ -0,0 +1,20 @@
+use crate::util::{SCResult, FromXmlNode, XmlElement, XmlNode};
+
+/// A message requesting a move from the client.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub struct MoveRequest;
+
+impl MoveRequest {
+    /// The data class used by the protocol.
+    pub const CLASS: &'static str = "sc.framework.plugins.protocol.MoveRequest";
+}
+
+impl FromXmlNode for MoveRequest {
+    fn from_node(_node: &impl XmlElement) -> SCResult<Self> { Ok(Self) }
+}
+
+impl From<MoveRequest> for XmlNode {
+    fn from(_request: MoveRequest) -> Self {
+        XmlNode::new("data").attribute("class", MoveRequest::CLASS).build()
+    }
+}
@@ -0,0 +1,62 @@
+/// Tracks the move requests of a room, which tolerates the
+/// server re-sending a request or delivering the memento after
+/// the move request it belongs to (as observed with flaky relays)
+/// instead of assuming that states and requests strictly alternate.
+///
+/// Both events are passed the turn of the latest state if the
+/// own player is to move in it, and return whether the request
+/// should be answered from that state now.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveSequence {
+    answered_turn: Option<u32>,
+    pending: bool,
+    answered_ahead: bool
+}
+
+impl MoveSequence {
+    /// Handles a move request from the server.
+    pub fn on_request(&mut self, own_turn: Option<u32>) -> bool {
+        match own_turn {
+            Some(turn) if self.answered_turn != Some(turn) => {
+                self.answer(turn);
+                self.answered_ahead = false;
+                true
+            },
+            // The request belongs to a state that has already been
+            // answered when its (late) memento arrived
+            Some(_) if self.answered_ahead => {
+                self.answered_ahead = false;
+                false
+            },
+            // Either a duplicate or a request whose state is still
+            // to come, which is answered once a new state arrives
+            _ => {
+                self.pending = true;
+                false
+            }
+        }
+    }
+
+    /// Handles a new game state from the server.
+    pub fn on_state(&mut self, own_turn: Option<u32>) -> bool {
+        match own_turn {
+            Some(turn) if self.pending && self.answered_turn != Some(turn) => {
+                self.answer(turn);
+                self.answered_ahead = true;
+                true
+            },
+            _ => false
+        }
+    }
+
+    /// Tests whether a request is waiting for its state.
+    pub fn is_pending(&self) -> bool { self.pending }
+
+    /// Fetches the turn of the last answered state.
+    pub fn answered_turn(&self) -> Option<u32> { self.answered_turn }
+
+    fn answer(&mut self, turn: u32) {
+        self.answered_turn = Some(turn);
+        self.pending = false;
+    }
+}
@@ -0,0 +1,39 @@
+use std::collections::BTreeMap;
+
+/// What the client remembers about an opponent from previous
+/// games, with the results from the own perspective.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OpponentStats<M> {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+    /// The first moves the opponent played with their frequency.
+    pub openings: BTreeMap<M, u32>
+}
+
+impl<M> Default for OpponentStats<M> {
+    fn default() -> Self {
+        Self { wins: 0, draws: 0, losses: 0, openings: BTreeMap::new() }
+    }
+}
+
+impl<M> OpponentStats<M> {
+    /// The number of finished games against the opponent.
+    pub fn games(&self) -> u32 { self.wins + self.draws + self.losses }
+
+    /// The own score against the opponent (draws
+    /// counting half), if any game has finished.
+    pub fn score(&self) -> Option<f64> {
+        let games = self.games();
+        if games == 0 {
+            None
+        } else {
+            Some((f64::from(self.wins) + 0.5 * f64::from(self.draws)) / f64::from(games))
+        }
+    }
+
+    /// The opening the opponent played most often.
+    pub fn favorite_opening(&self) -> Option<&M> {
+        self.openings.iter().max_by_key(|(_, &count)| count).map(|(m, _)| m)
+    }
+}
@@ -0,0 +1,15 @@
+/// Determines how the client reacts to messages from the server
+/// that it does not understand, i.e. unknown or malformed messages,
+/// unknown attributes of room messages and their data, and
+/// inconsistent game states. Unknown parts of the game state
+/// itself are kept in its extensions in either mode.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ParseMode {
+    /// Ends the game with an error on any
+    /// unexpected or malformed message.
+    Strict,
+    /// Logs unexpected or malformed messages
+    /// and continues with the next message.
+    #[default]
+    Lenient
+}
@@ -1,4 +1,4 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use crate::util::{SCResult, FromXmlNode, XmlElement};
 use super::ScoreCause;
 
 /// The score of a game player.
@@ -9,7 +9,7 @@ pub struct PlayerScore {
 }
 
 impl FromXmlNode for PlayerScore {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             cause: node.attribute("cause")?.parse()?,
             reason: node.attribute("reason").map(|s| s.to_owned()).unwrap_or_default()
@@ -0,0 +1,39 @@
+use crate::util::{SCResult, FromXmlNode, XmlElement};
+use super::{Joined, Left};
+
+/// A message from the server that concerns the
+/// connection rather than a specific room.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolEvent {
+    /// The client joined the room with the given id.
+    Joined { room_id: String },
+    /// The client left the room with the given id.
+    Left { room_id: String },
+    /// The server closes the connection, optionally
+    /// stating the reason.
+    Closed { reason: Option<String> }
+}
+
+impl ProtocolEvent {
+    /// The element names of close messages used by the protocol.
+    pub const CLOSE_NAMES: [&'static str; 2] = ["close", "sc.protocol.responses.CloseConnection"];
+}
+
+impl From<Joined> for ProtocolEvent {
+    fn from(joined: Joined) -> Self { Self::Joined { room_id: joined.room_id } }
+}
+
+impl From<Left> for ProtocolEvent {
+    fn from(left: Left) -> Self { Self::Left { room_id: left.room_id } }
+}
+
+impl FromXmlNode for ProtocolEvent {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        match node.name() {
+            "joined" => Ok(Joined::from_node(node)?.into()),
+            "left" => Ok(Left::from_node(node)?.into()),
+            name if Self::CLOSE_NAMES.contains(&name) => Ok(Self::Closed { reason: node.attribute("reason").ok().map(str::to_owned) }),
+            name => Err(format!("Unrecognized protocol event: <{}>", name).into())
+        }
+    }
+}
@@ -0,0 +1,70 @@
+use std::io::Read;
+use crate::{util::{SCResult, FromXmlNode, XmlElement, XmlNode, XmlNodeRef, BINARY_XML_MAGIC}, game::HivePlugin};
+use super::{AnnotatedReplay, GamePlugin, GameResult, Memento};
+
+/// A finished game as recorded by the official server, i.e. a
+/// protocol dump consisting of `<room>` messages with mementos
+/// and a final result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Replay<P = HivePlugin> where P: GamePlugin {
+    /// The successive states, one per turn.
+    pub states: Vec<P::GameState>,
+    /// The result, unless the recording is incomplete.
+    pub result: Option<GameResult<P>>
+}
+
+impl<P> Replay<P> where P: GamePlugin {
+    /// Parses a replay from the given source (either
+    /// XML text or a binary dump of it). The text is
+    /// parsed without building an owned tree.
+    pub fn read_from(mut source: impl Read) -> SCResult<Self> {
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+        if bytes.starts_with(BINARY_XML_MAGIC) {
+            Self::from_node(&XmlNode::read_binary(&mut bytes.as_slice())?)
+        } else {
+            Self::from_node(&XmlNodeRef::parse(std::str::from_utf8(&bytes).map_err(|e| e.to_string())?)?)
+        }
+    }
+
+    /// Adds a state, replacing the previous one if it
+    /// belongs to the same turn (e.g. if the server resent it).
+    fn push_state(&mut self, state: P::GameState) {
+        if self.states.last().map(|last| P::turn(last) == P::turn(&state)).unwrap_or(false) {
+            self.states.pop();
+        }
+        self.states.push(state);
+    }
+
+    /// Collects the states and the result from the given
+    /// node, descending into rooms and nested protocol dumps.
+    fn collect(&mut self, node: &impl XmlElement) -> SCResult<()> {
+        match node.name() {
+            "protocol" | "room" => for child in node.childs() {
+                self.collect(child)?;
+            },
+            "state" => self.push_state(P::GameState::from_node(node)?),
+            "data" => match node.attribute("class")? {
+                "memento" => self.push_state(Memento::<P>::from_node(node)?.state),
+                "result" => self.result = Some(GameResult::from_node(node)?),
+                _ => {}
+            },
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+impl<P> FromXmlNode for Replay<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        let mut replay = Self { states: Vec::new(), result: None };
+        replay.collect(node)?;
+        Ok(replay)
+    }
+}
+
+impl<P> From<Replay<P>> for AnnotatedReplay<P> where P: GamePlugin {
+    fn from(replay: Replay<P>) -> Self {
+        Self { entries: replay.states.into_iter().map(|state| (state, None)).collect() }
+    }
+}
@@ -1,16 +1,16 @@
 use std::convert::TryFrom;
-use crate::util::{SCError, SCResult, FromXmlNode, XmlNode};
-use super::Data;
+use crate::{util::{SCError, SCResult, FromXmlNode, XmlElement, XmlNode}, game::HivePlugin};
+use super::{Data, GamePlugin};
 
 /// A message in a room together with some data.
 #[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Room {
+pub struct Room<P = HivePlugin> where P: GamePlugin {
     pub room_id: String,
-    pub data: Data
+    pub data: Data<P>
 }
 
-impl FromXmlNode for Room {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+impl<P> FromXmlNode for Room<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             room_id: node.attribute("roomId")?.to_owned(),
             data: Data::from_node(node.child_by_name("data")?)?
@@ -18,10 +18,24 @@ impl FromXmlNode for Room {
     }
 }
 
-impl TryFrom<Room> for XmlNode {
+impl<P> Room<P> where P: GamePlugin {
+    /// Finds the attributes of a room message (or its data)
+    /// that are not understood by the parser, e.g. since they
+    /// were added in a newer version of the protocol.
+    pub fn unknown_attributes(node: &XmlNode) -> Vec<String> {
+        let room = node.attributes().filter(|&(k, _)| k != "roomId").map(|(k, _)| format!("room.{}", k));
+        let data = node.child_by_name("data").ok().into_iter().flat_map(|data| {
+            let known = Data::<P>::known_attributes(data.attribute("class").unwrap_or_default());
+            data.attributes().filter(move |&(k, _)| k != "class" && !known.contains(&k)).map(|(k, _)| format!("data.{}", k))
+        });
+        room.chain(data).collect()
+    }
+}
+
+impl<P> TryFrom<Room<P>> for XmlNode where P: GamePlugin {
     type Error = SCError;
 
-    fn try_from(room: Room) -> SCResult<XmlNode> {
+    fn try_from(room: Room<P>) -> SCResult<XmlNode> {
         Ok(XmlNode::new("room")
             .attribute("roomId", room.room_id)
             .try_child(room.data)?
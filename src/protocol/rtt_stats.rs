@@ -0,0 +1,62 @@
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// The number of recent round trips the statistics are based on.
+pub const RTT_WINDOW: usize = 16;
+
+/// Rolling statistics over the most recent round-trip times to
+/// the server, i.e. the time between sending a move and
+/// receiving the next game state.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RttStats {
+    samples: VecDeque<Duration>
+}
+
+impl RttStats {
+    /// Records a measured round trip, discarding the
+    /// oldest one once the window is full.
+    pub fn record(&mut self, rtt: Duration) {
+        if self.samples.len() == RTT_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(rtt);
+    }
+
+    /// The number of round trips in the window.
+    pub fn len(&self) -> usize { self.samples.len() }
+
+    /// Tests whether no round trip has been measured yet.
+    pub fn is_empty(&self) -> bool { self.samples.is_empty() }
+
+    /// The most recent round trip.
+    pub fn last(&self) -> Option<Duration> { self.samples.back().copied() }
+
+    /// The longest round trip in the window.
+    pub fn max(&self) -> Option<Duration> { self.samples.iter().max().copied() }
+
+    /// The average round trip in the window.
+    pub fn mean(&self) -> Option<Duration> {
+        if self.is_empty() {
+            None
+        } else {
+            Some(self.samples.iter().sum::<Duration>() / self.samples.len() as u32)
+        }
+    }
+
+    /// The standard deviation of the round trips in the window.
+    pub fn jitter(&self) -> Option<Duration> {
+        let mean = self.mean()?.as_secs_f64();
+        let variance = self.samples.iter().map(|s| (s.as_secs_f64() - mean).powi(2)).sum::<f64>() / self.samples.len() as f64;
+        Some(Duration::from_secs_f64(variance.sqrt()))
+    }
+
+    /// The time to reserve for the network when answering
+    /// a move request, i.e. the mean round trip plus twice
+    /// the jitter (zero until a round trip has been measured).
+    pub fn margin(&self) -> Duration {
+        match (self.mean(), self.jitter()) {
+            (Some(mean), Some(jitter)) => mean + jitter * 2,
+            _ => Duration::ZERO
+        }
+    }
+}
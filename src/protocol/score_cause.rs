@@ -1,7 +1,7 @@
 use std::str::FromStr;
 
 /// Determines the cause of a game score.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
 pub enum ScoreCause {
     Regular,
     Left,
@@ -11,6 +11,13 @@ pub enum ScoreCause {
     Unknown
 }
 
+impl ScoreCause {
+    /// Tests whether the cause is a soft or hard timeout.
+    pub fn is_timeout(&self) -> bool {
+        matches!(self, Self::SoftTimeout | Self::HardTimeout)
+    }
+}
+
 impl FromStr for ScoreCause {
     type Err = String;
 
@@ -1,4 +1,4 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use crate::util::{SCResult, FromXmlNode, XmlElement};
 use super::ScoreFragment;
 
 /// The definition of a score.
@@ -8,7 +8,7 @@ pub struct ScoreDefinition {
 }
 
 impl FromXmlNode for ScoreDefinition {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             fragments: node.childs_by_name("fragment").map(ScoreFragment::from_node).collect::<SCResult<_>>()?
         })
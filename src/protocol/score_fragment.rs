@@ -1,4 +1,4 @@
-use crate::util::{SCResult, FromXmlNode, XmlNode};
+use crate::util::{SCResult, FromXmlNode, XmlElement};
 use super::ScoreAggregation;
 
 /// A single score fragment.
@@ -10,7 +10,7 @@ pub struct ScoreFragment {
 }
 
 impl FromXmlNode for ScoreFragment {
-    fn from_node(node: &XmlNode) -> SCResult<Self> {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
         Ok(Self {
             name: node.attribute("name")?.to_owned(),
             aggregation: node.child_by_name("aggregation")?.content().parse()?,
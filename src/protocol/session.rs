@@ -0,0 +1,99 @@
+use crate::game::HivePlugin;
+use super::{GamePlugin, GameResult, IllegalTransition, ProtocolEvent, ScoreCause, SessionEnd, SessionPhase};
+
+/// The protocol-level state of a session with the server, which
+/// advances through the phases `Connecting`, `Joined`, `Playing`
+/// and `Finished`. Every transition checks that the message is
+/// valid in the current phase, independently of any connection.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session<P = HivePlugin> where P: GamePlugin {
+    phase: SessionPhase,
+    result: Option<GameResult<P>>,
+    error: Option<String>
+}
+
+impl<P> Default for Session<P> where P: GamePlugin {
+    fn default() -> Self {
+        Self { phase: SessionPhase::Connecting, result: None, error: None }
+    }
+}
+
+impl<P> Session<P> where P: GamePlugin {
+    /// Creates a session that waits for the server to assign a room.
+    pub fn new() -> Self { Self::default() }
+
+    /// Fetches the current phase.
+    pub fn phase(&self) -> &SessionPhase { &self.phase }
+
+    /// Fetches the game result, once the game ended.
+    pub fn result(&self) -> Option<&GameResult<P>> { self.result.as_ref() }
+
+    /// Handles the server assigning a room.
+    pub fn join(&mut self, room_id: &str) -> Result<(), IllegalTransition> {
+        match self.phase {
+            SessionPhase::Connecting => {
+                self.phase = SessionPhase::Joined { room_id: room_id.to_owned() };
+                Ok(())
+            },
+            _ => Err(self.illegal(format!("joined message for room {}", room_id)))
+        }
+    }
+
+    /// Handles a message of the running game (e.g. a game state or a move
+    /// request). The server may omit the joined message, in which case
+    /// the room of the first game message is joined implicitly.
+    pub fn play(&mut self, room_id: &str) -> Result<(), IllegalTransition> {
+        match &self.phase {
+            SessionPhase::Connecting => {},
+            SessionPhase::Joined { room_id: joined } | SessionPhase::Playing { room_id: joined } if joined == room_id => {},
+            _ => return Err(self.illegal(format!("game message for room {}", room_id)))
+        }
+        self.phase = SessionPhase::Playing { room_id: room_id.to_owned() };
+        Ok(())
+    }
+
+    /// Handles the result of the game, which ends the game as lost by
+    /// violation if the own player (if known) violated the rules.
+    pub fn finish(&mut self, room_id: &str, result: GameResult<P>, my_color: Option<P::PlayerColor>) -> Result<SessionEnd, IllegalTransition> {
+        self.play(room_id).map_err(|e| IllegalTransition { input: format!("game result for room {}", room_id), ..e })?;
+        let violation = my_color
+            .and_then(|c| result.scores.get(P::player_index(c)))
+            .filter(|s| s.cause == ScoreCause::RuleViolation);
+        let end = match violation {
+            Some(score) => SessionEnd::LostByViolation { reason: Some(score.reason.clone()).filter(|r| !r.is_empty()) },
+            None => SessionEnd::Completed
+        };
+        self.result = Some(result);
+        self.phase = SessionPhase::Finished { end: end.clone() };
+        Ok(end)
+    }
+
+    /// Records an error reported by the server, which
+    /// is possible in any phase.
+    pub fn fail(&mut self, message: String) {
+        self.error = Some(message);
+    }
+
+    /// Handles the server closing the connection, which is possible
+    /// in any phase and determines how the session ended.
+    pub fn close(&mut self, event: &ProtocolEvent) -> SessionEnd {
+        let end = match (&self.phase, self.error.take(), event) {
+            (SessionPhase::Finished { end }, _, _) => end.clone(),
+            (_, Some(message), _) => SessionEnd::Failed { message },
+            (_, None, ProtocolEvent::Closed { reason }) => SessionEnd::Closed { reason: reason.clone() },
+            (_, None, _) => SessionEnd::Closed { reason: None }
+        };
+        self.phase = SessionPhase::Finished { end: end.clone() };
+        end
+    }
+
+    /// Handles the client shutting the session down itself.
+    pub fn shut_down(&mut self) -> SessionEnd {
+        self.phase = SessionPhase::Finished { end: SessionEnd::ShutDown };
+        SessionEnd::ShutDown
+    }
+
+    fn illegal(&self, input: String) -> IllegalTransition {
+        IllegalTransition { phase: self.phase.clone(), input }
+    }
+}
@@ -0,0 +1,40 @@
+use std::fmt;
+
+/// Describes how a session with the server ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEnd {
+    /// The server closed the connection after
+    /// sending the game result.
+    Completed,
+    /// The server closed the connection after ending
+    /// the game because the own move violated the rules.
+    LostByViolation { reason: Option<String> },
+    /// The server closed the connection after
+    /// reporting an error and without a game result.
+    Failed { message: String },
+    /// The server closed the connection before the
+    /// game ended, optionally stating the reason.
+    Closed { reason: Option<String> },
+    /// The client shut the session down itself
+    /// (see `ClientHandle::shutdown`).
+    ShutDown
+}
+
+impl SessionEnd {
+    /// Tests whether the game was played to the end.
+    pub fn is_completed(&self) -> bool { matches!(self, Self::Completed | Self::LostByViolation { .. }) }
+}
+
+impl fmt::Display for SessionEnd {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Completed => write!(f, "Completed"),
+            Self::LostByViolation { reason: Some(reason) } => write!(f, "Lost by violating the rules: {}", reason),
+            Self::LostByViolation { reason: None } => write!(f, "Lost by violating the rules"),
+            Self::Failed { message } => write!(f, "Failed: {}", message),
+            Self::Closed { reason: Some(reason) } => write!(f, "Closed by the server: {}", reason),
+            Self::Closed { reason: None } => write!(f, "Closed by the server"),
+            Self::ShutDown => write!(f, "Shut down by the client")
+        }
+    }
+}
@@ -0,0 +1,29 @@
+use std::fmt;
+use super::SessionEnd;
+
+/// The phases of a session with the server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionPhase {
+    /// The join message was sent, but the
+    /// server has not assigned a room yet.
+    Connecting,
+    /// The server assigned a room, but the
+    /// game has not started yet.
+    Joined { room_id: String },
+    /// The game in the given room is running.
+    Playing { room_id: String },
+    /// The game ended (or the connection was closed before),
+    /// ending the session as given once the connection closes.
+    Finished { end: SessionEnd }
+}
+
+impl fmt::Display for SessionPhase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Connecting => write!(f, "connecting"),
+            Self::Joined { room_id } => write!(f, "joined to room {}", room_id),
+            Self::Playing { room_id } => write!(f, "playing in room {}", room_id),
+            Self::Finished { end } => write!(f, "finished ({})", end)
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use super::ScoreCause;
+
+/// Why a game ended, as derived from the scores of its result.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Termination {
+    /// The first irregular cause among the scores or
+    /// `Regular` if the game was played to the end.
+    pub cause: ScoreCause,
+    /// The server's explanation, if it provided one.
+    pub reason: Option<String>,
+    /// The index of the score with the irregular cause (i.e. the
+    /// player responsible for the termination), if there is one.
+    pub player_index: Option<usize>
+}
+
+impl Termination {
+    /// Tests whether the game was played to the end.
+    pub fn is_regular(&self) -> bool { self.cause == ScoreCause::Regular }
+}
@@ -0,0 +1,32 @@
+use std::time::{Duration, Instant};
+
+/// The time limits the game server enforces for each move.
+/// Exceeding the soft timeout loses the game, exceeding the
+/// hard timeout additionally causes the server to stop waiting.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TimeSettings {
+    pub soft_timeout: Duration,
+    pub hard_timeout: Duration
+}
+
+impl TimeSettings {
+    /// Creates new time settings, ensuring that the
+    /// soft timeout does not exceed the hard one.
+    pub fn new(soft_timeout: Duration, hard_timeout: Duration) -> Self {
+        Self { soft_timeout: soft_timeout.min(hard_timeout), hard_timeout }
+    }
+
+    /// The instant at which a move requested at
+    /// the given instant exceeds the soft timeout.
+    pub fn soft_deadline(&self, requested_at: Instant) -> Instant {
+        requested_at + self.soft_timeout
+    }
+}
+
+impl Default for TimeSettings {
+    /// The limits used in the Software Challenge 2020,
+    /// i.e. 2 seconds (soft) and 10 seconds (hard).
+    fn default() -> Self {
+        Self::new(Duration::from_secs(2), Duration::from_secs(10))
+    }
+}
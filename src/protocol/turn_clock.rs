@@ -0,0 +1,72 @@
+use std::time::{Duration, Instant};
+use super::{RttStats, TimeSettings};
+
+/// Tracks when moves were requested and sent
+/// to measure the client's thinking time and the
+/// round trips to the server.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TurnClock {
+    requested_at: Option<Instant>,
+    sent_at: Option<Instant>,
+    thinking_time: Duration,
+    longest_turn: Duration,
+    moves: u32,
+    rtt: RttStats
+}
+
+impl TurnClock {
+    /// Records that a move has been requested.
+    pub fn start_turn(&mut self) {
+        self.requested_at = Some(Instant::now());
+    }
+
+    /// Records that the move has been sent and
+    /// returns the time taken for this turn.
+    pub fn end_turn(&mut self) -> Duration {
+        let elapsed = self.elapsed_this_turn().unwrap_or_default();
+        self.requested_at = None;
+        self.sent_at = Some(Instant::now());
+        self.thinking_time += elapsed;
+        self.longest_turn = self.longest_turn.max(elapsed);
+        self.moves += 1;
+        elapsed
+    }
+
+    /// Records that a game state has been received, which
+    /// completes the round trip of the last sent move (if any)
+    /// and returns its duration.
+    pub fn record_state(&mut self) -> Option<Duration> {
+        let rtt = self.sent_at.take()?.elapsed();
+        self.rtt.record(rtt);
+        Some(rtt)
+    }
+
+    /// The time since the current move was requested,
+    /// if one is currently requested.
+    pub fn elapsed_this_turn(&self) -> Option<Duration> {
+        self.requested_at.map(|t| t.elapsed())
+    }
+
+    /// The time left for the current move until the soft
+    /// timeout is reached, if one is currently requested.
+    pub fn remaining_this_turn(&self, settings: &TimeSettings) -> Option<Duration> {
+        self.elapsed_this_turn().map(|e| settings.soft_timeout.saturating_sub(e))
+    }
+
+    /// The cumulative time spent on all finished turns.
+    pub fn thinking_time(&self) -> Duration { self.thinking_time }
+
+    /// The time spent on the longest finished turn.
+    pub fn longest_turn(&self) -> Duration { self.longest_turn }
+
+    /// The statistics over the recent round trips to the server.
+    pub fn rtt(&self) -> &RttStats { &self.rtt }
+
+    /// The number of finished turns.
+    pub fn moves(&self) -> u32 { self.moves }
+
+    /// The average time spent per finished turn.
+    pub fn average_turn(&self) -> Option<Duration> {
+        if self.moves > 0 { Some(self.thinking_time / self.moves) } else { None }
+    }
+}
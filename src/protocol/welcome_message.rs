@@ -0,0 +1,24 @@
+use crate::{util::{SCResult, FromXmlNode, XmlElement, XmlNode}, game::HivePlugin};
+use super::GamePlugin;
+
+/// A message sent at the start of the game,
+/// which assigns a color to the client.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WelcomeMessage<P = HivePlugin> where P: GamePlugin {
+    pub color: P::PlayerColor
+}
+
+impl<P> FromXmlNode for WelcomeMessage<P> where P: GamePlugin {
+    fn from_node(node: &impl XmlElement) -> SCResult<Self> {
+        Ok(Self { color: node.attribute("color")?.parse()? })
+    }
+}
+
+impl<P> From<WelcomeMessage<P>> for XmlNode where P: GamePlugin {
+    fn from(message: WelcomeMessage<P>) -> Self {
+        XmlNode::new("data")
+            .attribute("class", "welcomeMessage")
+            .attribute("color", message.color)
+            .build()
+    }
+}
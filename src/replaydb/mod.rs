@@ -0,0 +1,17 @@
+//! An index of the positions occurring in recorded games,
+//! which provides move statistics and opening books, and
+//! a memory of the opponents faced in previous games.
+
+mod move_stats;
+mod opening_book;
+mod opponent_memory;
+#[cfg(feature = "client")]
+mod opponent_memory_delegate;
+mod replay_db;
+
+pub use move_stats::*;
+pub use opening_book::*;
+pub use opponent_memory::*;
+#[cfg(feature = "client")]
+pub use opponent_memory_delegate::*;
+pub use replay_db::*;
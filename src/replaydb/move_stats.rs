@@ -0,0 +1,34 @@
+use std::ops::AddAssign;
+
+/// How often a move was played in a position and how the games
+/// ended from the perspective of the moving player. Games without
+/// a recorded result only count towards the plays.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct MoveStats {
+    pub plays: u32,
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32
+}
+
+impl MoveStats {
+    /// The fraction of decided points won (draws counting
+    /// half), if the move occurred in any finished game.
+    pub fn score(&self) -> Option<f64> {
+        let games = self.wins + self.draws + self.losses;
+        if games > 0 {
+            Some((f64::from(self.wins) + f64::from(self.draws) / 2.0) / f64::from(games))
+        } else {
+            None
+        }
+    }
+}
+
+impl AddAssign for MoveStats {
+    fn add_assign(&mut self, rhs: Self) {
+        self.plays += rhs.plays;
+        self.wins += rhs.wins;
+        self.draws += rhs.draws;
+        self.losses += rhs.losses;
+    }
+}
@@ -0,0 +1,121 @@
+use std::collections::{BTreeSet, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::game::{GameState, Move};
+use crate::util::SCResult;
+use super::{ReplayDb, canonical_move, read_u32, read_u64};
+
+/// The bytes every book file starts with (including a format version).
+const MAGIC: &[u8; 8] = b"SCBOOK\0\x01";
+
+/// A mapping from canonical position hashes to weighted
+/// moves (in the canonical orientation), built from the
+/// statistics of a replay database.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpeningBook {
+    positions: HashMap<u64, Vec<(Move, u32)>>
+}
+
+impl OpeningBook {
+    /// Builds a book from the moves played in at least `min_games`
+    /// finished games with at least the given win rate (draws
+    /// counting half), weighting them by how often they were played.
+    pub fn build(db: &ReplayDb, min_games: u32, min_win_rate: f64) -> Self {
+        let positions = db.positions()
+            .filter_map(|(hash, moves)| {
+                let weighted: Vec<_> = moves.iter()
+                    .filter(|(_, stats)| stats.wins + stats.draws + stats.losses >= min_games)
+                    .filter(|(_, stats)| stats.score().map(|s| s >= min_win_rate).unwrap_or(min_win_rate <= 0.0))
+                    .map(|(m, stats)| (m.clone(), stats.plays))
+                    .collect();
+                if weighted.is_empty() { None } else { Some((hash, weighted)) }
+            })
+            .collect();
+        Self { positions }
+    }
+
+    /// Fetches the number of positions in the book.
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Tests whether the book contains no position.
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Fetches the legal book moves of the given position (in
+    /// its orientation) with their weights, the heaviest first.
+    pub fn probe(&self, state: &GameState) -> Vec<(Move, u32)> {
+        let (hash, symmetries) = state.canonical_form();
+        let book_moves = match self.positions.get(&hash) {
+            Some(book_moves) => book_moves,
+            None => return Vec::new()
+        };
+        let mut legal_moves = state.possible_moves(state.current_player_color);
+        legal_moves.sort();
+        let mut seen = BTreeSet::new();
+        let mut found: Vec<_> = legal_moves.into_iter()
+            .filter_map(|m| {
+                let canonical = canonical_move(&m, &symmetries);
+                let &(_, weight) = book_moves.iter().find(|(b, _)| *b == canonical)?;
+                if seen.insert(canonical) { Some((m, weight)) } else { None }
+            })
+            .collect();
+        found.sort_by(|(m1, w1), (m2, w2)| w2.cmp(w1).then_with(|| m1.cmp(m2)));
+        found
+    }
+
+    /// Writes the book in a compact binary format.
+    pub fn write_to(&self, writer: &mut impl Write) -> SCResult<()> {
+        let mut hashes: Vec<_> = self.positions.keys().cloned().collect();
+        hashes.sort_unstable();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(hashes.len() as u32).to_le_bytes())?;
+        for hash in hashes {
+            let moves: Vec<_> = self.positions[&hash].iter().filter_map(|(m, w)| Some((m.packed()?, *w))).collect();
+            writer.write_all(&hash.to_le_bytes())?;
+            writer.write_all(&(moves.len() as u32).to_le_bytes())?;
+            for (packed, weight) in moves {
+                writer.write_all(&packed.to_le_bytes())?;
+                writer.write_all(&weight.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a book written using `write_to`.
+    pub fn read_from(reader: &mut impl Read) -> SCResult<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err("Not an opening book (or an unsupported version)".into());
+        }
+        let position_count = read_u32(reader)?;
+        let mut positions = HashMap::with_capacity(position_count as usize);
+        for _ in 0..position_count {
+            let hash = read_u64(reader)?;
+            let move_count = read_u32(reader)?;
+            let moves = (0..move_count)
+                .map(|_| {
+                    let packed = read_u32(reader)?;
+                    let weight = read_u32(reader)?;
+                    let game_move = Move::unpack(packed).ok_or_else(|| format!("Invalid move {:#x} in opening book", packed))?;
+                    Ok((game_move, weight))
+                })
+                .collect::<SCResult<_>>()?;
+            positions.insert(hash, moves);
+        }
+        Ok(Self { positions })
+    }
+
+    /// Saves the book to the given file.
+    pub fn save(&self, path: impl AsRef<Path>) -> SCResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a book from the given file.
+    pub fn load(path: impl AsRef<Path>) -> SCResult<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
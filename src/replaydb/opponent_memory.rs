@@ -0,0 +1,113 @@
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, OpponentStats};
+use crate::util::SCResult;
+use super::{canonical_move, read_u32};
+
+/// The bytes every memory file starts with (including a format version).
+const MAGIC: &[u8; 8] = b"SCOPP\0\0\x01";
+
+/// A small local store of statistics about the opponents faced
+/// in previous games, keyed by their display names. Openings are
+/// stored in the canonical orientation (see `ReplayDb`).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct OpponentMemory {
+    opponents: BTreeMap<String, OpponentStats<Move>>
+}
+
+impl OpponentMemory {
+    /// Creates an empty memory.
+    pub fn new() -> Self { Self::default() }
+
+    /// Fetches the number of remembered opponents.
+    pub fn len(&self) -> usize { self.opponents.len() }
+
+    /// Tests whether no opponent is remembered.
+    pub fn is_empty(&self) -> bool { self.opponents.is_empty() }
+
+    /// Fetches the statistics about the given opponent.
+    pub fn get(&self, name: &str) -> Option<&OpponentStats<Move>> { self.opponents.get(name) }
+
+    /// Records the first move the given opponent played in a game.
+    pub fn record_opening(&mut self, name: &str, state: &GameState, game_move: &Move) {
+        let (_, symmetries) = state.canonical_form();
+        let stats = self.opponents.entry(name.to_owned()).or_default();
+        *stats.openings.entry(canonical_move(game_move, &symmetries)).or_default() += 1;
+    }
+
+    /// Records the result of a game against the given
+    /// opponent, in which the client played the given color.
+    pub fn record_result(&mut self, name: &str, result: &GameResult, my_color: PlayerColor) {
+        let stats = self.opponents.entry(name.to_owned()).or_default();
+        match result.winners.as_slice() {
+            [] => stats.draws += 1,
+            winners if winners.iter().any(|w| w.color == my_color) => stats.wins += 1,
+            _ => stats.losses += 1
+        }
+    }
+
+    /// Writes the memory in a compact binary format. Openings whose
+    /// coordinates cannot be packed (see `Move::packed`) are omitted.
+    pub fn write_to(&self, writer: &mut impl Write) -> SCResult<()> {
+        writer.write_all(MAGIC)?;
+        writer.write_all(&(self.opponents.len() as u32).to_le_bytes())?;
+        for (name, stats) in &self.opponents {
+            writer.write_all(&(name.len() as u32).to_le_bytes())?;
+            writer.write_all(name.as_bytes())?;
+            let openings: Vec<_> = stats.openings.iter().filter_map(|(m, &count)| Some((m.packed()?, count))).collect();
+            for value in [stats.wins, stats.draws, stats.losses, openings.len() as u32] {
+                writer.write_all(&value.to_le_bytes())?;
+            }
+            for (packed, count) in openings {
+                writer.write_all(&packed.to_le_bytes())?;
+                writer.write_all(&count.to_le_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a memory written using `write_to`.
+    pub fn read_from(reader: &mut impl Read) -> SCResult<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err("Not an opponent memory (or an unsupported version)".into());
+        }
+        let opponent_count = read_u32(reader)?;
+        let mut opponents = BTreeMap::new();
+        for _ in 0..opponent_count {
+            let mut name = vec![0; read_u32(reader)? as usize];
+            reader.read_exact(&mut name)?;
+            let name = String::from_utf8(name).map_err(|_| "Invalid opponent name in opponent memory")?;
+            let mut stats = OpponentStats { wins: read_u32(reader)?, draws: read_u32(reader)?, losses: read_u32(reader)?, openings: BTreeMap::new() };
+            for _ in 0..read_u32(reader)? {
+                let packed = read_u32(reader)?;
+                let game_move = Move::unpack(packed).ok_or_else(|| format!("Invalid move {:#x} in opponent memory", packed))?;
+                stats.openings.insert(game_move, read_u32(reader)?);
+            }
+            opponents.insert(name, stats);
+        }
+        Ok(Self { opponents })
+    }
+
+    /// Saves the memory to the given file.
+    pub fn save(&self, path: impl AsRef<Path>) -> SCResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a memory from the given file, starting
+    /// with an empty one if it does not exist yet.
+    pub fn load_or_default(path: impl AsRef<Path>) -> SCResult<Self> {
+        match File::open(path) {
+            Ok(file) => Self::read_from(&mut BufReader::new(file)),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into())
+        }
+    }
+}
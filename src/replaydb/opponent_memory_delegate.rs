@@ -0,0 +1,106 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use log::info;
+use crate::client::SCClientDelegate;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use crate::util::CancellationToken;
+use super::OpponentMemory;
+
+/// A delegate that records the opponent's opening and the result
+/// in an opponent memory, passes what is remembered about the
+/// opponent to the wrapped delegate once its name is known and
+/// otherwise forwards to the wrapped delegate.
+pub struct OpponentMemoryDelegate<D> {
+    delegate: D,
+    memory: Rc<RefCell<OpponentMemory>>,
+    opponent: Option<String>,
+    last_state: Option<GameState>,
+    opening_recorded: bool
+}
+
+impl<D> OpponentMemoryDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate, recording into the given memory.
+    pub fn new(delegate: D, memory: Rc<RefCell<OpponentMemory>>) -> Self {
+        Self { delegate, memory, opponent: None, last_state: None, opening_recorded: false }
+    }
+
+    /// Records the opponent's first move if the given state follows it.
+    fn observe_opening(&mut self, state: &GameState, my_color: PlayerColor) {
+        let (name, last) = match (&self.opponent, &self.last_state) {
+            (Some(name), Some(last)) if !self.opening_recorded && last.turn + 1 == state.turn => (name, last),
+            _ => return
+        };
+        if last.current_player_color == my_color.opponent() {
+            if let Some(game_move) = last.infer_move(state) {
+                self.memory.borrow_mut().record_opening(name, last, &game_move);
+                self.opening_recorded = true;
+            }
+        }
+    }
+}
+
+impl<D> SCClientDelegate for OpponentMemoryDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        if let Some(my_color) = my_color {
+            let name = &state.player(my_color.opponent()).display_name;
+            if self.opponent.is_none() && !name.is_empty() {
+                let stats = self.memory.borrow().get(name).cloned().unwrap_or_default();
+                info!("Playing against {} ({} previous games)", name, stats.games());
+                self.delegate.on_opponent(name, &stats);
+                self.opponent = Some(name.clone());
+            }
+            self.observe_opening(state, my_color);
+        }
+        self.last_state = Some(state.clone());
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        if let (Some(name), Some(my_color)) = (&self.opponent, my_color) {
+            self.memory.borrow_mut().record_result(name, &result, my_color);
+        }
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        self.delegate.request_move(state, my_color, cancel)
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
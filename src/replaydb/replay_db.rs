@@ -0,0 +1,196 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::Path;
+use log::warn;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::Replay;
+use crate::util::{SCResult, BINARY_XML_EXTENSION};
+use super::MoveStats;
+
+/// The bytes every database file starts with (including a format version).
+const MAGIC: &[u8; 8] = b"SCRDB\0\0\x01";
+
+pub(super) fn read_u32(reader: &mut impl Read) -> SCResult<u32> {
+    let mut bytes = [0; 4];
+    reader.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+pub(super) fn read_u64(reader: &mut impl Read) -> SCResult<u64> {
+    let mut bytes = [0; 8];
+    reader.read_exact(&mut bytes)?;
+    Ok(u64::from_le_bytes(bytes))
+}
+
+/// Transforms the move into the canonical orientation, choosing the
+/// smallest variant if several symmetries lead to that orientation.
+pub(super) fn canonical_move(game_move: &Move, symmetries: &[usize]) -> Move {
+    symmetries.iter().map(|&i| game_move.symmetry(i)).min().unwrap_or_else(|| game_move.clone())
+}
+
+/// An index of the moves played in recorded games, keyed by the
+/// canonical hash of the position (i.e. independent of the board's
+/// orientation). The moves are stored in the canonical orientation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReplayDb {
+    games: u32,
+    positions: HashMap<u64, BTreeMap<Move, MoveStats>>
+}
+
+impl ReplayDb {
+    /// Creates an empty database.
+    pub fn new() -> Self { Self::default() }
+
+    /// Fetches the number of ingested games.
+    pub fn games(&self) -> u32 { self.games }
+
+    /// Fetches the number of distinct positions.
+    pub fn len(&self) -> usize { self.positions.len() }
+
+    /// Tests whether no position has been recorded.
+    pub fn is_empty(&self) -> bool { self.positions.is_empty() }
+
+    /// Iterates the positions' canonical hashes together with the
+    /// statistics of the moves (in the canonical orientation).
+    pub fn positions(&self) -> impl Iterator<Item=(u64, &BTreeMap<Move, MoveStats>)> {
+        self.positions.iter().map(|(&hash, moves)| (hash, moves))
+    }
+
+    /// Records a move played in the given position. The outcome is
+    /// the winner (`None` for a draw) if the game's result is known.
+    pub fn record(&mut self, state: &GameState, game_move: &Move, outcome: Option<Option<PlayerColor>>) {
+        let (hash, symmetries) = state.canonical_form();
+        let color = state.current_player_color;
+        let stats = self.positions.entry(hash).or_default().entry(canonical_move(game_move, &symmetries)).or_default();
+        *stats += MoveStats {
+            plays: 1,
+            wins: (outcome == Some(Some(color))) as u32,
+            draws: (outcome == Some(None)) as u32,
+            losses: (outcome == Some(Some(color.opponent()))) as u32
+        };
+    }
+
+    /// Records the moves of a replay, returning their number. Moves
+    /// are reconstructed from successive states, gaps are skipped.
+    pub fn add_game(&mut self, replay: &Replay) -> usize {
+        let outcome = replay.result.as_ref().map(|result| match result.winners.as_slice() {
+            [winner] => Some(winner.color),
+            _ => None
+        });
+        let mut recorded = 0;
+        for pair in replay.states.windows(2) {
+            if let Some(game_move) = pair[0].infer_move(&pair[1]) {
+                self.record(&pair[0], &game_move, outcome);
+                recorded += 1;
+            }
+        }
+        self.games += 1;
+        recorded
+    }
+
+    /// Ingests the replays (as XML or binary dumps) in the given directory,
+    /// returning the number of games. Files that cannot be parsed are skipped.
+    pub fn ingest_dir(&mut self, dir: impl AsRef<Path>) -> SCResult<usize> {
+        let mut paths = fs::read_dir(dir)?
+            .map(|entry| entry.map(|e| e.path()))
+            .collect::<Result<Vec<_>, _>>()?;
+        paths.retain(|p| p.extension().map(|e| e == "xml" || e == BINARY_XML_EXTENSION).unwrap_or(false));
+        paths.sort();
+
+        let mut ingested = 0;
+        for path in paths {
+            match File::open(&path).map_err(Into::into).and_then(|f| Replay::read_from(BufReader::new(f))) {
+                Ok(replay) => {
+                    self.add_game(&replay);
+                    ingested += 1;
+                },
+                Err(e) => warn!("Skipping replay {}: {}", path.display(), e)
+            }
+        }
+        Ok(ingested)
+    }
+
+    /// Fetches the legal moves played in the given position (in its
+    /// orientation) with their statistics, the most frequent first.
+    /// Equivalent moves in symmetric positions are only listed once.
+    pub fn lookup(&self, state: &GameState) -> Vec<(Move, MoveStats)> {
+        let (hash, symmetries) = state.canonical_form();
+        let moves = match self.positions.get(&hash) {
+            Some(moves) => moves,
+            None => return Vec::new()
+        };
+        let mut legal_moves = state.possible_moves(state.current_player_color);
+        legal_moves.sort();
+        let mut seen = BTreeSet::new();
+        let mut found: Vec<_> = legal_moves.into_iter()
+            .filter_map(|m| {
+                // Of equivalent moves (in symmetric positions) only the first is listed
+                let canonical = canonical_move(&m, &symmetries);
+                let stats = *moves.get(&canonical)?;
+                if seen.insert(canonical) { Some((m, stats)) } else { None }
+            })
+            .collect();
+        found.sort_by(|(m1, s1), (m2, s2)| s2.plays.cmp(&s1.plays).then_with(|| m1.cmp(m2)));
+        found
+    }
+
+    /// Writes the database in a compact binary format. Moves whose
+    /// coordinates cannot be packed (see `Move::packed`) are omitted.
+    pub fn write_to(&self, writer: &mut impl Write) -> SCResult<()> {
+        let mut hashes: Vec<_> = self.positions.keys().cloned().collect();
+        hashes.sort_unstable();
+        writer.write_all(MAGIC)?;
+        writer.write_all(&self.games.to_le_bytes())?;
+        writer.write_all(&(hashes.len() as u32).to_le_bytes())?;
+        for hash in hashes {
+            let moves: Vec<_> = self.positions[&hash].iter().filter_map(|(m, &s)| Some((m.packed()?, s))).collect();
+            writer.write_all(&hash.to_le_bytes())?;
+            writer.write_all(&(moves.len() as u32).to_le_bytes())?;
+            for (packed, stats) in moves {
+                for value in [packed, stats.plays, stats.wins, stats.draws, stats.losses] {
+                    writer.write_all(&value.to_le_bytes())?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Reads a database written using `write_to`.
+    pub fn read_from(reader: &mut impl Read) -> SCResult<Self> {
+        let mut magic = [0; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != MAGIC {
+            return Err("Not a replay database (or an unsupported version)".into());
+        }
+        let games = read_u32(reader)?;
+        let position_count = read_u32(reader)?;
+        let mut positions = HashMap::with_capacity(position_count as usize);
+        for _ in 0..position_count {
+            let hash = read_u64(reader)?;
+            let move_count = read_u32(reader)?;
+            let mut moves = BTreeMap::new();
+            for _ in 0..move_count {
+                let packed = read_u32(reader)?;
+                let stats = MoveStats { plays: read_u32(reader)?, wins: read_u32(reader)?, draws: read_u32(reader)?, losses: read_u32(reader)? };
+                let game_move = Move::unpack(packed).ok_or_else(|| format!("Invalid move {:#x} in replay database", packed))?;
+                moves.insert(game_move, stats);
+            }
+            positions.insert(hash, moves);
+        }
+        Ok(Self { games, positions })
+    }
+
+    /// Saves the database to the given file.
+    pub fn save(&self, path: impl AsRef<Path>) -> SCResult<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write_to(&mut writer)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Loads a database from the given file.
+    pub fn load(path: impl AsRef<Path>) -> SCResult<Self> {
+        Self::read_from(&mut BufReader::new(File::open(path)?))
+    }
+}
@@ -0,0 +1,303 @@
+use std::sync::Arc;
+use std::time::Instant;
+use crate::eval::Evaluator;
+use crate::game::{GameState, Move, SearchContext};
+use crate::util::CancellationToken;
+use super::{Bound, RootPolicy, SearchStats, TableEntry, TranspositionTable, state_hash};
+
+/// The score of a won position (before adding the remaining
+/// depth, which makes the search prefer quicker wins).
+pub const WIN_SCORE: f64 = 1_000_000.0;
+
+/// The number of slots in a search's own transposition table.
+const DEFAULT_TABLE_SLOTS: usize = 1 << 18;
+/// The maximum difference between the scores of root
+/// moves that are considered to be rated equally.
+const TIE_EPSILON: f64 = 1e-9;
+
+/// The outcome of a search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchResult {
+    /// The best move found (if there is any legal move).
+    pub best_move: Option<Move>,
+    /// The score of the best move from the perspective
+    /// of the player to move.
+    pub score: f64,
+    /// The expected continuation, starting with the best move.
+    pub principal_variation: Vec<Move>,
+    /// The root moves rated equally to the best move (including
+    /// it) in the deepest completed iteration, in search order.
+    pub tied_moves: Vec<Move>,
+    /// Counters collected during the search.
+    pub stats: SearchStats
+}
+
+/// An iterative deepening negamax search with alpha-beta
+/// pruning and a transposition table, which is kept across
+/// searches and may be shared with other searches.
+pub struct AlphaBetaSearch<E> {
+    evaluator: E,
+    table: Arc<TranspositionTable>,
+    context: SearchContext,
+    stats: SearchStats,
+    deadline: Option<Instant>,
+    cancel: CancellationToken,
+    node_limit: Option<u64>,
+    root_policies: Vec<Box<dyn RootPolicy + Send>>,
+    aborted: bool
+}
+
+impl<E> AlphaBetaSearch<E> where E: Evaluator {
+    /// Creates a new search using the given evaluator.
+    pub fn new(evaluator: E) -> Self {
+        Self::with_table(evaluator, Arc::new(TranspositionTable::new(DEFAULT_TABLE_SLOTS)))
+    }
+
+    /// Creates a new search using the given evaluator and a
+    /// transposition table shared with other (e.g. concurrent) searches.
+    pub fn with_table(evaluator: E, table: Arc<TranspositionTable>) -> Self {
+        Self { evaluator, table, context: SearchContext::new(), stats: SearchStats::default(), deadline: None, cancel: CancellationToken::new(), node_limit: None, root_policies: Vec::new(), aborted: false }
+    }
+
+    /// Limits the number of nodes visited per search, after which
+    /// the result of the deepest completed iteration is returned.
+    pub fn with_node_limit(mut self, node_limit: u64) -> Self {
+        self.node_limit = Some(node_limit);
+        self
+    }
+
+    /// Adds a policy that filters and rates the root moves, whose
+    /// priors are included in the scores of the search results.
+    pub fn with_root_policy(mut self, policy: impl RootPolicy + Send + 'static) -> Self {
+        self.root_policies.push(Box::new(policy));
+        self
+    }
+
+    /// Removes the root policies, e.g. to move them to another search.
+    pub fn take_root_policies(&mut self) -> Vec<Box<dyn RootPolicy + Send>> {
+        std::mem::take(&mut self.root_policies)
+    }
+
+    /// Fetches the transposition table used by the search.
+    pub fn table(&self) -> &Arc<TranspositionTable> { &self.table }
+
+    /// Searches the position up to the given depth or until the
+    /// deadline passes or the token is cancelled, returning the
+    /// result of the deepest completed iteration.
+    pub fn search(&mut self, state: &GameState, max_depth: u32, deadline: Option<Instant>, cancel: &CancellationToken) -> SearchResult {
+        let start = Instant::now();
+        self.stats = SearchStats::default();
+        self.deadline = deadline;
+        self.cancel = cancel.clone();
+        self.aborted = false;
+
+        let mut best_move = None;
+        let mut score = 0.0;
+        let mut tied_moves = Vec::new();
+
+        for depth in 1..=max_depth {
+            let (iteration_move, iteration_score, iteration_ties) = self.search_root(state, depth);
+            if self.aborted {
+                break;
+            }
+            best_move = iteration_move;
+            score = iteration_score;
+            tied_moves = iteration_ties;
+            self.stats.depth = depth;
+            if score.abs() >= WIN_SCORE {
+                break;
+            }
+        }
+
+        if best_move.is_none() {
+            // Not even the first iteration completed
+            let moves = state.possible_moves(state.current_player_color);
+            best_move = moves.iter().find(|m| self.allows(state, m)).or(moves.first()).cloned();
+        }
+
+        let principal_variation = best_move.as_ref()
+            .map(|m| self.principal_variation(state, m, self.stats.depth.max(1)))
+            .unwrap_or_default();
+        self.stats.elapsed = start.elapsed();
+        SearchResult { best_move, score, principal_variation, tied_moves, stats: self.stats }
+    }
+
+    /// Follows the stored best moves from the given first move up
+    /// to the given length, stopping at the first unknown position.
+    pub fn principal_variation(&self, state: &GameState, first_move: &Move, length: u32) -> Vec<Move> {
+        let mut variation = vec![first_move.clone()];
+        let mut current = match state.apply_move(first_move) {
+            Ok(next) => next,
+            Err(_) => return variation
+        };
+        while variation.len() < length as usize {
+            let next_move = match self.table.probe(state_hash(&current)).and_then(|e| e.best_move) {
+                Some(next_move) => next_move,
+                None => break
+            };
+            current = match current.apply_move(&next_move) {
+                Ok(next) => next,
+                Err(_) => break
+            };
+            variation.push(next_move);
+        }
+        variation
+    }
+
+    /// Fetches the legal moves into a reused buffer, trying
+    /// the stored best move first. Sorting them canonically
+    /// makes the choice between equally rated moves independent
+    /// of the move generation order.
+    fn ordered_moves(&mut self, state: &GameState, key: u64, sorted: bool) -> Vec<Move> {
+        let mut moves = self.context.take_moves();
+        state.extend_possible_moves(&mut self.context, state.current_player_color, &mut moves);
+        if sorted {
+            moves.sort();
+        }
+        if let Some(best) = self.table.probe(key).and_then(|e| e.best_move) {
+            if let Some(i) = moves.iter().position(|m| *m == best) {
+                moves.swap(0, i);
+            }
+        }
+        moves
+    }
+
+    /// Tests whether all root policies allow the move.
+    fn allows(&self, state: &GameState, game_move: &Move) -> bool {
+        self.root_policies.iter().all(|p| p.allows(state, game_move))
+    }
+
+    /// Applies the root policies to the moves, yielding the allowed
+    /// moves (or all of them, if none is allowed) with their priors.
+    fn root_moves<'a>(&self, state: &GameState, moves: &'a [Move]) -> Vec<(&'a Move, f64)> {
+        if self.root_policies.is_empty() {
+            return moves.iter().map(|m| (m, 0.0)).collect();
+        }
+        let prior = |m: &Move| self.root_policies.iter().map(|p| p.prior(state, m)).sum();
+        let allowed: Vec<_> = moves.iter().filter(|m| self.allows(state, m)).map(|m| (m, prior(m))).collect();
+        if allowed.is_empty() {
+            moves.iter().map(|m| (m, prior(m))).collect()
+        } else {
+            allowed
+        }
+    }
+
+    fn search_root(&mut self, state: &GameState, depth: u32) -> (Option<Move>, f64, Vec<Move>) {
+        let key = state_hash(state);
+        let mut alpha = f64::NEG_INFINITY;
+        let mut best_move = None;
+        let mut best_score = f64::NEG_INFINITY;
+        let mut tied_moves = Vec::new();
+
+        let moves = self.ordered_moves(state, key, true);
+        for (game_move, prior) in self.root_moves(state, &moves) {
+            let child = match state.apply_move(game_move) {
+                Ok(child) => child,
+                Err(_) => continue
+            };
+            // Searches the child with the window shifted by the prior, which
+            // only affects the choice of the move, not the reported score.
+            // The window includes the best score to find equally rated moves.
+            let score = -self.negamax(&child, depth - 1, f64::NEG_INFINITY, prior - alpha + TIE_EPSILON);
+            if self.aborted {
+                break;
+            }
+            if score + prior > alpha + TIE_EPSILON || best_move.is_none() {
+                alpha = score + prior;
+                best_score = score;
+                best_move = Some(game_move.clone());
+                tied_moves.clear();
+                tied_moves.push(game_move.clone());
+            } else if score + prior >= alpha - TIE_EPSILON {
+                tied_moves.push(game_move.clone());
+            }
+        }
+        self.context.recycle_moves(moves);
+
+        if !self.aborted {
+            self.table.store(key, &TableEntry { depth, score: best_score, bound: Bound::Exact, best_move: best_move.clone() });
+        }
+        (best_move, best_score, tied_moves)
+    }
+
+    fn negamax(&mut self, state: &GameState, depth: u32, mut alpha: f64, mut beta: f64) -> f64 {
+        self.stats.nodes += 1;
+        if self.stats.nodes.is_multiple_of(256) && (self.cancel.is_cancelled() || self.deadline.map(|d| Instant::now() >= d).unwrap_or(false)) {
+            self.aborted = true;
+        }
+        if self.node_limit.map(|l| self.stats.nodes > l).unwrap_or(false) {
+            self.aborted = true;
+        }
+        if self.aborted {
+            return 0.0;
+        }
+
+        let color = state.current_player_color;
+        match (state.board.is_bee_surrounded(color), state.board.is_bee_surrounded(color.opponent())) {
+            (true, true) => return 0.0,
+            (true, false) => return -WIN_SCORE - depth as f64,
+            (false, true) => return WIN_SCORE + depth as f64,
+            (false, false) => {}
+        }
+        if depth == 0 {
+            return self.evaluator.evaluate(state, color);
+        }
+
+        let key = state_hash(state);
+        self.stats.tt_probes += 1;
+        if let Some(entry) = self.table.probe(key).filter(|e| e.depth >= depth) {
+            let hit = match entry.bound {
+                Bound::Exact => Some(entry.score),
+                Bound::Lower => { alpha = alpha.max(entry.score); None },
+                Bound::Upper => { beta = beta.min(entry.score); None }
+            }.or(if alpha >= beta { Some(entry.score) } else { None });
+            if let Some(score) = hit {
+                self.stats.tt_hits += 1;
+                return score;
+            }
+        }
+
+        let original_alpha = alpha;
+        let mut best_score = f64::NEG_INFINITY;
+        let mut best_move = None;
+
+        let moves = self.ordered_moves(state, key, false);
+        for game_move in &moves {
+            let child = match state.apply_move(game_move) {
+                Ok(child) => child,
+                Err(_) => continue
+            };
+            let score = -self.negamax(&child, depth - 1, -beta, -alpha);
+            if self.aborted {
+                break;
+            }
+            if score > best_score {
+                best_score = score;
+                best_move = Some(game_move.clone());
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+        self.context.recycle_moves(moves);
+        if self.aborted {
+            return 0.0;
+        }
+
+        if best_move.is_none() {
+            // No legal moves, the player has to pass
+            return self.evaluator.evaluate(state, color);
+        }
+
+        let bound = if best_score <= original_alpha {
+            Bound::Upper
+        } else if best_score >= beta {
+            Bound::Lower
+        } else {
+            Bound::Exact
+        };
+        self.table.store(key, &TableEntry { depth, score: best_score, bound, best_move });
+        best_score
+    }
+}
@@ -0,0 +1,368 @@
+use std::time::Instant;
+use rand::{SeedableRng, rngs::StdRng, seq::SliceRandom};
+use crate::eval::Evaluator;
+use crate::game::{GameState, Move, PlayerColor, SearchContext};
+use crate::util::CancellationToken;
+use super::{SearchResult, SearchStats};
+
+/// The bounds of the win rates converted to scores, which
+/// keeps the scores of (nearly) decided positions finite.
+const MIN_WIN_RATE: f64 = 1e-6;
+
+/// The approximate memory of a tree node in bytes, which
+/// is dominated by the fields of its game state's board.
+pub const MCTS_NODE_SIZE: usize = 8 << 10;
+
+/// The default maximum number of tree nodes (about 128 MiB).
+pub const DEFAULT_MCTS_NODE_LIMIT: usize = 1 << 14;
+
+/// Limits the number of children of a node with `n` visits
+/// to `ceil(coefficient * n^exponent)`, which makes the search
+/// focus on the most promising moves (by their static evaluation)
+/// first and only consider further ones as the node is revisited.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct ProgressiveWidening {
+    pub coefficient: f64,
+    pub exponent: f64
+}
+
+/// The settings of a `MctsSearch`. By default, progressive widening
+/// and RAVE are enabled, since plain UCT spreads its playouts too
+/// thinly across Hive's many placements.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MctsOptions {
+    /// The UCT exploration constant.
+    pub exploration: f64,
+    /// Restricts the children of rarely visited nodes (`None`
+    /// expands every legal move before revisiting any child).
+    pub progressive_widening: Option<ProgressiveWidening>,
+    /// The equivalence parameter `k` of RAVE, which blends in the
+    /// All-Moves-As-First statistics of a move (i.e. the results of all
+    /// playouts in which the player made it later on) with the weight
+    /// `sqrt(k / (3n + k))` for a child with `n` visits (`None` disables RAVE).
+    pub rave_equivalence: Option<f64>,
+    /// The number of random moves played out from a new
+    /// node before evaluating the reached position.
+    pub playout_depth: u32,
+    /// The evaluation that corresponds to a win rate of about 73%,
+    /// which scales the evaluations of the playouts to win rates.
+    pub evaluation_scale: f64
+}
+
+impl MctsOptions {
+    /// Plain UCT without progressive widening and RAVE.
+    pub fn vanilla() -> Self {
+        Self { progressive_widening: None, rave_equivalence: None, ..Self::default() }
+    }
+
+    /// Sets the UCT exploration constant.
+    pub fn with_exploration(mut self, exploration: f64) -> Self {
+        self.exploration = exploration;
+        self
+    }
+
+    /// Enables (or disables) progressive widening.
+    pub fn with_progressive_widening(mut self, widening: Option<ProgressiveWidening>) -> Self {
+        self.progressive_widening = widening;
+        self
+    }
+
+    /// Enables RAVE with the given equivalence parameter (or disables it).
+    pub fn with_rave_equivalence(mut self, equivalence: Option<f64>) -> Self {
+        self.rave_equivalence = equivalence;
+        self
+    }
+
+    /// Sets the number of random moves per playout.
+    pub fn with_playout_depth(mut self, depth: u32) -> Self {
+        self.playout_depth = depth;
+        self
+    }
+
+    /// The number of children a node with the given visits may have.
+    fn child_limit(&self, visits: u32) -> usize {
+        match self.progressive_widening {
+            Some(w) => (w.coefficient * f64::from(visits).powf(w.exponent)).ceil().max(1.0) as usize,
+            None => usize::MAX
+        }
+    }
+}
+
+impl Default for MctsOptions {
+    fn default() -> Self {
+        Self {
+            exploration: 0.7,
+            progressive_widening: Some(ProgressiveWidening { coefficient: 2.0, exponent: 0.5 }),
+            rave_equivalence: Some(500.0),
+            playout_depth: 6,
+            evaluation_scale: 10.0
+        }
+    }
+}
+
+/// A node of the search tree, whose statistics are kept from
+/// the perspective of the player who made the move leading to it.
+struct Node {
+    game_move: Option<Move>,
+    state: GameState,
+    children: Vec<usize>,
+    /// The moves not expanded yet, the most promising one last.
+    untried: Option<Vec<Move>>,
+    visits: u32,
+    wins: f64,
+    amaf_visits: u32,
+    amaf_wins: f64
+}
+
+impl Node {
+    fn new(game_move: Option<Move>, state: GameState) -> Self {
+        Self { game_move, state, children: Vec::new(), untried: None, visits: 0, wins: 0.0, amaf_visits: 0, amaf_wins: 0.0 }
+    }
+
+    /// The player who made the move leading to this node.
+    fn mover(&self) -> PlayerColor {
+        self.state.current_player_color.opponent()
+    }
+}
+
+/// A Monte Carlo tree search that guides short random playouts,
+/// whose final positions are rated by the static evaluation,
+/// with UCT and optionally progressive widening and RAVE.
+pub struct MctsSearch<E> {
+    evaluator: E,
+    options: MctsOptions,
+    random: StdRng,
+    context: SearchContext,
+    iteration_limit: Option<u64>,
+    node_limit: usize
+}
+
+impl<E> MctsSearch<E> where E: Evaluator {
+    /// Creates a new search using the given evaluator and options.
+    pub fn new(evaluator: E, options: MctsOptions) -> Self {
+        Self { evaluator, options, random: StdRng::from_entropy(), context: SearchContext::new(), iteration_limit: None, node_limit: DEFAULT_MCTS_NODE_LIMIT }
+    }
+
+    /// Seeds the random playouts for reproducible searches.
+    pub fn with_seed(mut self, seed: u64) -> Self {
+        self.random = StdRng::seed_from_u64(seed);
+        self
+    }
+
+    /// Limits the number of playouts per search.
+    pub fn with_iteration_limit(mut self, limit: u64) -> Self {
+        self.iteration_limit = Some(limit);
+        self
+    }
+
+    /// Limits the number of tree nodes, after which the search
+    /// stops expanding the tree and only continues the playouts.
+    pub fn with_node_limit(mut self, limit: usize) -> Self {
+        self.node_limit = limit.max(1);
+        self
+    }
+
+    /// Fetches the options of the search.
+    pub fn options(&self) -> &MctsOptions { &self.options }
+
+    /// Runs playouts until the iteration limit is reached, the
+    /// deadline passes or the token is cancelled, then picks the most
+    /// visited move. Its score is the win rate of the move converted
+    /// back to the units of the evaluation. The stats count the
+    /// playouts as nodes and the deepest expanded node as the depth.
+    pub fn search(&mut self, state: &GameState, deadline: Option<Instant>, cancel: &CancellationToken) -> SearchResult {
+        let start = Instant::now();
+        let mut stats = SearchStats::default();
+        let mut tree = vec![Node::new(None, state.clone())];
+
+        while self.iteration_limit.map(|l| stats.nodes < l).unwrap_or(true) {
+            if stats.nodes > 0 && (cancel.is_cancelled() || deadline.map(|d| Instant::now() >= d).unwrap_or(false)) {
+                break;
+            }
+            let path = self.select_and_expand(&mut tree);
+            stats.depth = stats.depth.max(path.len() as u32 - 1);
+            let leaf = &tree[*path.last().unwrap()];
+            let (red_result, playout) = self.playout(&leaf.state);
+            self.backpropagate(&mut tree, &path, red_result, &playout);
+            stats.nodes += 1;
+        }
+
+        let most_visits = tree[0].children.iter().map(|&c| tree[c].visits).max();
+        let tied_moves: Vec<Move> = tree[0].children.iter()
+            .filter(|&&c| Some(tree[c].visits) == most_visits)
+            .filter_map(|&c| tree[c].game_move.clone())
+            .collect();
+        let best = tree[0].children.iter().copied().find(|&c| Some(tree[c].visits) == most_visits);
+        let score = best.map(|c| self.score(&tree[c])).unwrap_or(0.0);
+        let mut principal_variation = Vec::new();
+        let mut current = best;
+        while let Some(node) = current {
+            principal_variation.extend(tree[node].game_move.clone());
+            current = tree[node].children.iter().copied().max_by_key(|&c| tree[c].visits).filter(|&c| tree[c].visits > 0);
+        }
+
+        let best_move = principal_variation.first().cloned()
+            .or_else(|| state.possible_moves(state.current_player_color).into_iter().min());
+        stats.elapsed = start.elapsed();
+        SearchResult { best_move, score, principal_variation, tied_moves, stats }
+    }
+
+    /// Converts the win rate of a node to a score.
+    fn score(&self, node: &Node) -> f64 {
+        let win_rate = (node.wins / f64::from(node.visits.max(1))).clamp(MIN_WIN_RATE, 1.0 - MIN_WIN_RATE);
+        self.options.evaluation_scale * (win_rate / (1.0 - win_rate)).ln()
+    }
+
+    /// Descends the tree by the UCT (and RAVE) values until reaching
+    /// a node that may be widened, then expands its most promising
+    /// untried move unless the node limit is reached. Returns the
+    /// indices of the visited nodes.
+    fn select_and_expand(&mut self, tree: &mut Vec<Node>) -> Vec<usize> {
+        let mut path = vec![0];
+        loop {
+            let index = *path.last().unwrap();
+            if is_decided(&tree[index].state) {
+                return path;
+            }
+            let is_full = tree.len() >= self.node_limit;
+            if !is_full && tree[index].untried.is_none() {
+                let moves = self.ordered_moves(&tree[index].state);
+                tree[index].untried = Some(moves);
+            }
+            let node = &tree[index];
+            let can_widen = !is_full && node.children.len() < self.options.child_limit(node.visits);
+            if can_widen {
+                if let Some(game_move) = tree[index].untried.as_mut().and_then(Vec::pop) {
+                    if let Ok(child_state) = tree[index].state.apply_move(&game_move) {
+                        tree.push(Node::new(Some(game_move), child_state));
+                        let child = tree.len() - 1;
+                        tree[index].children.push(child);
+                        path.push(child);
+                        return path;
+                    }
+                    continue;
+                }
+            }
+            match self.select_child(tree, index) {
+                Some(child) => path.push(child),
+                None => return path
+            }
+        }
+    }
+
+    /// Picks the child with the highest UCT value, blending in
+    /// the All-Moves-As-First statistics if RAVE is enabled.
+    fn select_child(&self, tree: &[Node], index: usize) -> Option<usize> {
+        let parent = &tree[index];
+        let log_visits = f64::from(parent.visits.max(1)).ln();
+        let value = |child: &Node| {
+            let visits = f64::from(child.visits.max(1));
+            let mean = child.wins / visits;
+            let blended = match self.options.rave_equivalence {
+                Some(k) if child.amaf_visits > 0 => {
+                    let beta = (k / (3.0 * f64::from(child.visits) + k)).sqrt();
+                    (1.0 - beta) * mean + beta * child.amaf_wins / f64::from(child.amaf_visits)
+                },
+                _ => mean
+            };
+            blended + self.options.exploration * (log_visits / visits).sqrt()
+        };
+        parent.children.iter().copied().max_by(|&a, &b| value(&tree[a]).total_cmp(&value(&tree[b])))
+    }
+
+    /// Fetches the distinct legal moves, ordered by the rating of the
+    /// resulting positions (thus winning moves first) if progressive widening
+    /// is enabled. The canonical order makes seeded searches reproducible.
+    fn ordered_moves(&mut self, state: &GameState) -> Vec<Move> {
+        let color = state.current_player_color;
+        let mut moves = Vec::new();
+        state.extend_possible_moves(&mut self.context, color, &mut moves);
+        // Placements of equal pieces are generated once per piece
+        moves.sort();
+        moves.dedup();
+        if self.options.progressive_widening.is_some() {
+            let mut rated: Vec<_> = moves.into_iter()
+                .map(|m| {
+                    let red_result = state.apply_move(&m).map(|next| self.red_result(&next)).unwrap_or(0.5);
+                    (m, if color == PlayerColor::Red { red_result } else { 1.0 - red_result })
+                })
+                .collect();
+            // Stable, thus equally rated moves stay in canonical order
+            rated.sort_by(|a, b| a.1.total_cmp(&b.1));
+            moves = rated.into_iter().map(|(m, _)| m).collect();
+        } else {
+            moves.reverse();
+        }
+        moves
+    }
+
+    /// Plays random moves from the given position, returning the
+    /// result from red's perspective (1 for a win, 0 for a loss)
+    /// and the moves played with the colors that made them.
+    fn playout(&mut self, state: &GameState) -> (f64, Vec<(PlayerColor, Move)>) {
+        let mut state = state.clone();
+        let mut playout = Vec::new();
+        for _ in 0..self.options.playout_depth {
+            if is_decided(&state) {
+                break;
+            }
+            let color = state.current_player_color;
+            let mut moves = self.context.take_moves();
+            state.extend_possible_moves(&mut self.context, color, &mut moves);
+            moves.sort();
+            moves.dedup();
+            let next = moves.choose(&mut self.random).and_then(|m| state.apply_move(m).ok().map(|next| (m.clone(), next)));
+            self.context.recycle_moves(moves);
+            match next {
+                Some((game_move, next)) => {
+                    playout.push((color, game_move));
+                    state = next;
+                },
+                None => break
+            }
+        }
+        (self.red_result(&state), playout)
+    }
+
+    /// Rates a position as red's expected result.
+    fn red_result(&self, state: &GameState) -> f64 {
+        match (state.board.is_bee_surrounded(PlayerColor::Red), state.board.is_bee_surrounded(PlayerColor::Blue)) {
+            (true, true) => 0.5,
+            (true, false) => 0.0,
+            (false, true) => 1.0,
+            (false, false) => 1.0 / (1.0 + (-self.evaluator.evaluate(state, PlayerColor::Red) / self.options.evaluation_scale).exp())
+        }
+    }
+
+    /// Updates the statistics of the visited nodes and, for RAVE,
+    /// of their children whose moves were made later on by the same
+    /// player (in the tree or in the playout).
+    fn backpropagate(&self, tree: &mut [Node], path: &[usize], red_result: f64, playout: &[(PlayerColor, Move)]) {
+        let result_for = |color: PlayerColor| if color == PlayerColor::Red { red_result } else { 1.0 - red_result };
+        let mut later_moves: Vec<(PlayerColor, Move)> = playout.to_vec();
+        for &index in path.iter().rev() {
+            let mover = tree[index].mover();
+            let node = &mut tree[index];
+            node.visits += 1;
+            node.wins += result_for(mover);
+            if self.options.rave_equivalence.is_some() {
+                let to_move = tree[index].state.current_player_color;
+                for child in tree[index].children.clone() {
+                    let played_later = tree[child].game_move.as_ref().is_some_and(|m| later_moves.iter().any(|(c, l)| *c == to_move && l == m));
+                    if played_later {
+                        tree[child].amaf_visits += 1;
+                        tree[child].amaf_wins += result_for(to_move);
+                    }
+                }
+            }
+            if let Some(game_move) = tree[index].game_move.clone() {
+                later_moves.push((mover, game_move));
+            }
+        }
+    }
+}
+
+/// Tests whether a bee is surrounded or the turn limit is reached.
+fn is_decided(state: &GameState) -> bool {
+    state.board.is_bee_surrounded(PlayerColor::Red) || state.board.is_bee_surrounded(PlayerColor::Blue) || state.is_turn_limit_reached()
+}
@@ -0,0 +1,19 @@
+//! Game tree searches based on the static evaluation.
+
+mod alpha_beta;
+#[cfg(feature = "client")]
+mod mcts;
+mod root_policy;
+mod safe_placements;
+mod search_stats;
+mod state_hash;
+mod transposition_table;
+
+pub use alpha_beta::*;
+#[cfg(feature = "client")]
+pub use mcts::*;
+pub use root_policy::*;
+pub use safe_placements::*;
+pub use search_stats::*;
+pub use state_hash::*;
+pub use transposition_table::*;
@@ -0,0 +1,42 @@
+use crate::game::{GameState, Move};
+
+/// Domain knowledge applied to the moves at the root of a search
+/// before searching them, e.g. to rule out moves that are known to
+/// be bad or to nudge the search towards promising ones.
+pub trait RootPolicy {
+    /// Tests whether the move may be chosen. If no move of
+    /// a position is allowed, all of them are searched.
+    fn allows(&self, _state: &GameState, _game_move: &Move) -> bool { true }
+
+    /// A bonus added to the move's score (in the units of the
+    /// evaluation), which may also be negative.
+    fn prior(&self, _state: &GameState, _game_move: &Move) -> f64 { 0.0 }
+}
+
+impl<P> RootPolicy for Box<P> where P: RootPolicy + ?Sized {
+    fn allows(&self, state: &GameState, game_move: &Move) -> bool {
+        (**self).allows(state, game_move)
+    }
+
+    fn prior(&self, state: &GameState, game_move: &Move) -> f64 {
+        (**self).prior(state, game_move)
+    }
+}
+
+/// A policy ruling out the moves not matching a predicate.
+pub struct MoveFilter<F>(pub F);
+
+impl<F> RootPolicy for MoveFilter<F> where F: Fn(&GameState, &Move) -> bool {
+    fn allows(&self, state: &GameState, game_move: &Move) -> bool {
+        (self.0)(state, game_move)
+    }
+}
+
+/// A policy rating moves by a function.
+pub struct MovePrior<F>(pub F);
+
+impl<F> RootPolicy for MovePrior<F> where F: Fn(&GameState, &Move) -> f64 {
+    fn prior(&self, state: &GameState, game_move: &Move) -> f64 {
+        (self.0)(state, game_move)
+    }
+}
@@ -0,0 +1,32 @@
+use std::cell::RefCell;
+use crate::game::{AxialCoords, GameState, Move};
+use super::{RootPolicy, state_hash};
+
+/// A root policy ruling out the placements that are not
+/// among the state's safe set destinations, i.e. that the
+/// opponent could attack or exploit right away. The safe
+/// destinations are computed once per root position.
+#[derive(Debug, Default)]
+pub struct SafePlacements {
+    cache: RefCell<Option<(u64, Vec<AxialCoords>)>>
+}
+
+impl SafePlacements {
+    /// Creates the policy.
+    pub fn new() -> Self { Self::default() }
+}
+
+impl RootPolicy for SafePlacements {
+    fn allows(&self, state: &GameState, game_move: &Move) -> bool {
+        let destination = match game_move {
+            Move::SetMove { destination, .. } => destination.coords,
+            Move::DragMove { .. } => return true
+        };
+        let key = state_hash(state);
+        let mut cache = self.cache.borrow_mut();
+        if cache.as_ref().is_none_or(|(k, _)| *k != key) {
+            *cache = Some((key, state.safe_set_destinations(state.current_player_color)));
+        }
+        cache.as_ref().is_some_and(|(_, safe)| safe.contains(&destination))
+    }
+}
@@ -0,0 +1,50 @@
+use std::fmt;
+use std::ops::AddAssign;
+use std::time::Duration;
+
+/// Counters collected during a search.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub struct SearchStats {
+    /// The number of visited nodes.
+    pub nodes: u64,
+    /// The number of transposition table lookups.
+    pub tt_probes: u64,
+    /// The number of lookups that found a usable entry.
+    pub tt_hits: u64,
+    /// The deepest completed iteration.
+    pub depth: u32,
+    /// The time spent searching.
+    pub elapsed: Duration
+}
+
+impl SearchStats {
+    /// The number of visited nodes per second.
+    pub fn nps(&self) -> f64 {
+        self.nodes as f64 / self.elapsed.as_secs_f64().max(f64::EPSILON)
+    }
+
+    /// The fraction of transposition table lookups that hit.
+    pub fn tt_hit_rate(&self) -> f64 {
+        if self.tt_probes == 0 { 0.0 } else { self.tt_hits as f64 / self.tt_probes as f64 }
+    }
+}
+
+impl AddAssign for SearchStats {
+    /// Aggregates the stats, e.g. over all moves of a game.
+    fn add_assign(&mut self, rhs: Self) {
+        self.nodes += rhs.nodes;
+        self.tt_probes += rhs.tt_probes;
+        self.tt_hits += rhs.tt_hits;
+        self.depth = self.depth.max(rhs.depth);
+        self.elapsed += rhs.elapsed;
+    }
+}
+
+impl fmt::Display for SearchStats {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f, "depth {}, {} nodes in {:.3}s ({:.0} nps), TT hit rate {:.1}%",
+            self.depth, self.nodes, self.elapsed.as_secs_f64(), self.nps(), self.tt_hit_rate() * 100.0
+        )
+    }
+}
@@ -0,0 +1,16 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use crate::game::GameState;
+
+fn hash_of(value: impl Hash) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Computes a 64-bit hash of the position including the
+/// turn, which is independent of the board's internal field
+/// order and thus suitable as a transposition table key.
+pub fn state_hash(state: &GameState) -> u64 {
+    state.position_hash() ^ hash_of(state.turn)
+}
@@ -0,0 +1,157 @@
+use std::mem;
+use std::sync::atomic::{AtomicU64, Ordering};
+use log::warn;
+use crate::game::Move;
+
+/// How a stored score relates to the actual score.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Bound {
+    Exact,
+    Lower,
+    Upper
+}
+
+/// A transposition table entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TableEntry {
+    pub depth: u32,
+    pub score: f64,
+    pub bound: Bound,
+    pub best_move: Option<Move>
+}
+
+/// The bit set in every packed entry, which distinguishes
+/// stored entries from empty slots (whose data is 0).
+const OCCUPIED: u64 = 1 << 31;
+
+/// The largest depth that can be stored in an entry.
+const MAX_DEPTH: u32 = 0x7F;
+
+/// Packs an entry into 64 bits: the move (22 bits), the bound
+/// (2 bits), the depth (7 bits), the occupied tag and the score
+/// as `f32`. The tag ensures that no entry packs to 0.
+fn encode_entry(entry: &TableEntry) -> u64 {
+    let best_move = entry.best_move.as_ref().and_then(Move::packed).map(u64::from).unwrap_or(0);
+    let bound = match entry.bound {
+        Bound::Exact => 0,
+        Bound::Lower => 1,
+        Bound::Upper => 2
+    };
+    best_move | (bound << 22) | (u64::from(entry.depth.min(MAX_DEPTH)) << 24) | OCCUPIED | (u64::from((entry.score as f32).to_bits()) << 32)
+}
+
+fn decode_entry(data: u64) -> Option<TableEntry> {
+    if data & OCCUPIED == 0 {
+        return None;
+    }
+    let bound = match (data >> 22) & 0x3 {
+        0 => Bound::Exact,
+        1 => Bound::Lower,
+        2 => Bound::Upper,
+        _ => return None
+    };
+    Some(TableEntry {
+        depth: ((data >> 24) & u64::from(MAX_DEPTH)) as u32,
+        score: f64::from(f32::from_bits((data >> 32) as u32)),
+        bound,
+        best_move: Move::unpack((data & 0x3F_FFFF) as u32)
+    })
+}
+
+/// A slot storing the packed entry together with its
+/// XOR with the key, which detects torn writes.
+#[derive(Default)]
+struct Slot {
+    check: AtomicU64,
+    data: AtomicU64
+}
+
+/// A fixed-size transposition table that can be shared between
+/// threads without locking. Concurrent writes to the same slot may
+/// interleave, in which case the key check fails and the probe misses
+/// instead of returning a mixed-up entry.
+pub struct TranspositionTable {
+    slots: Box<[Slot]>
+}
+
+/// The number of bytes occupied by each slot of a table.
+pub const TABLE_SLOT_SIZE: usize = mem::size_of::<Slot>();
+
+/// The default upper bound on the memory of a table in bytes.
+pub const DEFAULT_TABLE_MEMORY_LIMIT: usize = 1 << 30;
+
+/// The number of slots sampled to estimate a table's occupancy.
+const OCCUPANCY_SAMPLE: usize = 1024;
+
+impl TranspositionTable {
+    /// Creates a table with the given number of slots
+    /// (rounded up to a power of two).
+    pub fn new(slots: usize) -> Self {
+        Self { slots: (0..slots.max(1).next_power_of_two()).map(|_| Slot::default()).collect() }
+    }
+
+    /// Creates the largest table fitting into the given number
+    /// of bytes, but at most into `DEFAULT_TABLE_MEMORY_LIMIT`.
+    pub fn with_memory(bytes: usize) -> Self {
+        Self::with_memory_limit(bytes, DEFAULT_TABLE_MEMORY_LIMIT)
+    }
+
+    /// Creates the largest table fitting into the given number of
+    /// bytes, capping the budget at the given limit rather than relying
+    /// on the allocation to fail (which it rarely does on systems
+    /// overcommitting memory, until the process is killed).
+    pub fn with_memory_limit(bytes: usize, limit: usize) -> Self {
+        if bytes > limit {
+            warn!("Limiting the transposition table to {} bytes instead of {}", limit, bytes);
+        }
+        let requested = (bytes.min(limit) / TABLE_SLOT_SIZE).max(1);
+        // Round down to a power of two
+        Self::new(1 << (usize::BITS - 1 - requested.leading_zeros()))
+    }
+
+    /// Fetches the number of slots.
+    pub fn capacity(&self) -> usize { self.slots.len() }
+
+    /// Fetches the number of bytes occupied by the slots.
+    pub fn memory_usage(&self) -> usize { self.slots.len() * TABLE_SLOT_SIZE }
+
+    /// Estimates the fraction of occupied slots
+    /// from a sample at the start of the table.
+    pub fn occupancy(&self) -> f64 {
+        let sample = &self.slots[..self.slots.len().min(OCCUPANCY_SAMPLE)];
+        sample.iter().filter(|s| s.data.load(Ordering::Relaxed) & OCCUPIED != 0).count() as f64 / sample.len() as f64
+    }
+
+    fn slot(&self, key: u64) -> &Slot {
+        &self.slots[(key as usize) & (self.slots.len() - 1)]
+    }
+
+    /// Looks up the entry for the given key.
+    pub fn probe(&self, key: u64) -> Option<TableEntry> {
+        let slot = self.slot(key);
+        let data = slot.data.load(Ordering::Relaxed);
+        let check = slot.check.load(Ordering::Relaxed);
+        if check ^ data == key {
+            decode_entry(data)
+        } else {
+            None
+        }
+    }
+
+    /// Stores the entry for the given key, replacing
+    /// the slot's previous entry.
+    pub fn store(&self, key: u64, entry: &TableEntry) {
+        let slot = self.slot(key);
+        let data = encode_entry(entry);
+        slot.data.store(data, Ordering::Relaxed);
+        slot.check.store(key ^ data, Ordering::Relaxed);
+    }
+
+    /// Removes all entries.
+    pub fn clear(&self) {
+        for slot in self.slots.iter() {
+            slot.data.store(0, Ordering::Relaxed);
+            slot.check.store(0, Ordering::Relaxed);
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use super::{SCResult, XmlElement, XmlNode, XmlNodeRef};
+
+/// The magic bytes at the start of a binary XML dump.
+pub const BINARY_XML_MAGIC: &[u8] = b"SCXML\0\x01";
+
+/// The file extension of binary XML dumps.
+pub const BINARY_XML_EXTENSION: &str = "bxml";
+
+/// Writes strings to a binary dump, replacing repeated
+/// ones (e.g. tag names) by their index in a string table.
+#[derive(Default)]
+struct StringTable {
+    indices: HashMap<String, u32>
+}
+
+impl StringTable {
+    fn write(&mut self, writer: &mut impl Write, value: &str) -> SCResult<()> {
+        match self.indices.get(value) {
+            Some(&index) => write_varint(writer, index + 1),
+            None => {
+                self.indices.insert(value.to_owned(), self.indices.len() as u32);
+                write_varint(writer, 0)?;
+                write_varint(writer, value.len() as u32)?;
+                writer.write_all(value.as_bytes())?;
+                Ok(())
+            }
+        }
+    }
+}
+
+fn read_string(reader: &mut impl Read, table: &mut Vec<String>) -> SCResult<String> {
+    match read_varint(reader)? {
+        0 => {
+            let mut bytes = vec![0; read_varint(reader)? as usize];
+            reader.read_exact(&mut bytes)?;
+            let value = String::from_utf8(bytes).map_err(|e| e.to_string())?;
+            table.push(value.clone());
+            Ok(value)
+        },
+        index => table.get(index as usize - 1).cloned().ok_or_else(|| format!("Invalid string index {} in binary XML", index).into())
+    }
+}
+
+/// Writes an unsigned LEB128 number.
+fn write_varint(writer: &mut impl Write, mut value: u32) -> SCResult<()> {
+    while value >= 0x80 {
+        writer.write_all(&[(value as u8) | 0x80])?;
+        value >>= 7;
+    }
+    writer.write_all(&[value as u8])?;
+    Ok(())
+}
+
+fn read_varint(reader: &mut impl Read) -> SCResult<u32> {
+    let mut value = 0u32;
+    for shift in (0..35).step_by(7) {
+        let mut byte = [0];
+        reader.read_exact(&mut byte)?;
+        value |= u32::from(byte[0] & 0x7F) << shift;
+        if byte[0] & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err("Overlong number in binary XML".into())
+}
+
+fn write_node(writer: &mut impl Write, table: &mut StringTable, node: &XmlNode) -> SCResult<()> {
+    table.write(writer, node.name())?;
+    table.write(writer, node.content())?;
+    let mut attributes: Vec<_> = node.attributes().collect();
+    attributes.sort();
+    write_varint(writer, attributes.len() as u32)?;
+    for (key, value) in attributes {
+        table.write(writer, key)?;
+        table.write(writer, value)?;
+    }
+    write_varint(writer, node.childs().len() as u32)?;
+    for child in node.childs() {
+        write_node(writer, table, child)?;
+    }
+    Ok(())
+}
+
+fn read_node(reader: &mut impl Read, table: &mut Vec<String>) -> SCResult<XmlNode> {
+    let name = read_string(reader, table)?;
+    let content = read_string(reader, table)?;
+    let attributes = (0..read_varint(reader)?)
+        .map(|_| Ok((read_string(reader, table)?, read_string(reader, table)?)))
+        .collect::<SCResult<Vec<_>>>()?;
+    let childs = (0..read_varint(reader)?)
+        .map(|_| read_node(reader, table))
+        .collect::<SCResult<Vec<_>>>()?;
+    Ok(XmlNode::new(&name).content(&content).attributes(attributes).childs(childs).build())
+}
+
+impl XmlNode {
+    /// Writes the node in a compact binary form, which is much
+    /// faster to load than XML text (e.g. for recorded fixtures).
+    pub fn write_binary(&self, writer: &mut impl Write) -> SCResult<()> {
+        writer.write_all(BINARY_XML_MAGIC)?;
+        write_node(writer, &mut StringTable::default(), self)
+    }
+
+    /// Reads a node written by `write_binary`.
+    pub fn read_binary(reader: &mut impl Read) -> SCResult<Self> {
+        let mut magic = [0; BINARY_XML_MAGIC.len()];
+        reader.read_exact(&mut magic)?;
+        if magic != BINARY_XML_MAGIC {
+            return Err("Not a binary XML dump".into());
+        }
+        read_node(reader, &mut Vec::new())
+    }
+
+    /// Reads a node either in binary form or as XML text.
+    pub fn read_any(mut source: impl Read) -> SCResult<Self> {
+        let mut bytes = Vec::new();
+        source.read_to_end(&mut bytes)?;
+        if bytes.starts_with(BINARY_XML_MAGIC) {
+            Self::read_binary(&mut bytes.as_slice())
+        } else {
+            Ok(XmlNodeRef::parse(std::str::from_utf8(&bytes).map_err(|e| e.to_string())?)?.to_node())
+        }
+    }
+
+    /// Loads a node from a file either in binary form or as XML text.
+    pub fn load(path: impl AsRef<Path>) -> SCResult<Self> {
+        Self::read_any(File::open(path)?)
+    }
+}
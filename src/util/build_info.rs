@@ -0,0 +1,41 @@
+use std::fmt;
+
+/// Identifies the exact revision the client was built
+/// from, which is embedded by the build script.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct BuildInfo {
+    /// The version of the crate.
+    pub version: &'static str,
+    /// The abbreviated hash of the git commit
+    /// (unknown if built outside of a repository).
+    pub git_hash: Option<&'static str>,
+    /// The time of the commit in ISO 8601 format (or the
+    /// time given by SOURCE_DATE_EPOCH, if set).
+    pub timestamp: Option<&'static str>
+}
+
+impl BuildInfo {
+    /// The build info of this binary.
+    pub const CURRENT: Self = Self {
+        version: env!("CARGO_PKG_VERSION"),
+        git_hash: option_env!("SC_GIT_HASH"),
+        timestamp: option_env!("SC_BUILD_TIMESTAMP")
+    };
+}
+
+impl fmt::Display for BuildInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({}, built {})", self.version, self.git_hash.unwrap_or("unknown revision"), self.timestamp.unwrap_or("at an unknown time"))
+    }
+}
+
+#[cfg(feature = "json")]
+impl From<BuildInfo> for serde_json::Value {
+    fn from(info: BuildInfo) -> Self {
+        serde_json::json!({
+            "version": info.version,
+            "gitHash": info.git_hash,
+            "timestamp": info.timestamp
+        })
+    }
+}
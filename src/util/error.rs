@@ -1,7 +1,10 @@
+use std::fmt;
 use std::io::Error as IoError;
 use std::str::ParseBoolError;
 use std::num::{ParseIntError, ParseFloatError};
+#[cfg(feature = "protocol")]
 use xml::reader::Error as XmlReaderError;
+#[cfg(feature = "protocol")]
 use xml::writer::Error as XmlWriterError;
 
 /// A custom error type that abstracts over
@@ -11,22 +14,47 @@ use xml::writer::Error as XmlWriterError;
 #[derive(Debug)]
 pub enum SCError {
     Io(IoError),
+    #[cfg(feature = "protocol")]
     XmlReader(XmlReaderError),
+    #[cfg(feature = "protocol")]
     XmlWriter(XmlWriterError),
     ParseInt(ParseIntError),
     ParseFloat(ParseFloatError),
     ParseBool(ParseBoolError),
+    /// The connection to the server was closed unexpectedly.
+    Disconnected,
     Custom(String)
 }
 
+impl fmt::Display for SCError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "IO error: {}", e),
+            #[cfg(feature = "protocol")]
+            Self::XmlReader(e) => write!(f, "XML reader error: {}", e),
+            #[cfg(feature = "protocol")]
+            Self::XmlWriter(e) => write!(f, "XML writer error: {}", e),
+            Self::ParseInt(e) => write!(f, "Could not parse int: {}", e),
+            Self::ParseFloat(e) => write!(f, "Could not parse float: {}", e),
+            Self::ParseBool(e) => write!(f, "Could not parse bool: {}", e),
+            Self::Disconnected => write!(f, "The connection to the server was closed"),
+            Self::Custom(e) => write!(f, "{}", e)
+        }
+    }
+}
+
+impl std::error::Error for SCError {}
+
 impl From<IoError> for SCError {
     fn from(error: IoError) -> Self { Self::Io(error) }
 }
 
+#[cfg(feature = "protocol")]
 impl From<XmlReaderError> for SCError {
     fn from(error: XmlReaderError) -> Self { Self::XmlReader(error) }
 }
 
+#[cfg(feature = "protocol")]
 impl From<XmlWriterError> for SCError {
     fn from(error: XmlWriterError) -> Self { Self::XmlWriter(error) }
 }
@@ -1,9 +1,34 @@
+mod build_info;
+mod cancellation_token;
 mod error;
 mod result;
 mod macros;
+#[cfg(feature = "protocol")]
+mod binary_xml;
+#[cfg(feature = "protocol")]
+mod xml_element;
+#[cfg(feature = "protocol")]
+mod xml_format;
+#[cfg(feature = "protocol")]
+mod xml_name;
+#[cfg(feature = "protocol")]
 mod xml_node;
+#[cfg(feature = "protocol")]
+mod xml_node_ref;
 
+pub use build_info::*;
+pub use cancellation_token::*;
 pub use error::*;
 pub use result::*;
-pub use macros::*;
+#[cfg(feature = "protocol")]
+pub use binary_xml::*;
+#[cfg(feature = "protocol")]
+pub use xml_element::*;
+#[cfg(feature = "protocol")]
+pub use xml_format::*;
+#[cfg(feature = "protocol")]
+pub use xml_name::*;
+#[cfg(feature = "protocol")]
 pub use xml_node::*;
+#[cfg(feature = "protocol")]
+pub use xml_node_ref::*;
@@ -0,0 +1,51 @@
+use std::str::FromStr;
+use super::{SCError, SCResult, XmlNode};
+
+/// Read access to an XML element, which is provided both by
+/// owned nodes (`XmlNode`) and by borrowed views into a parsed
+/// document (`XmlNodeRef`), thus `FromXmlNode` implementations
+/// can parse either of them.
+pub trait XmlElement: Sized {
+    /// Fetches the element's tag name.
+    fn name(&self) -> &str;
+
+    /// Fetches the element's textual contents.
+    fn content(&self) -> &str;
+
+    /// Looks up an attribute's value by key.
+    fn attribute_value(&self, key: &str) -> Option<&str>;
+
+    /// Iterates the element's attributes as key-value pairs.
+    fn attributes(&self) -> impl Iterator<Item=(&str, &str)>;
+
+    /// Fetches the element's child elements.
+    fn childs(&self) -> &[Self];
+
+    /// Copies the element into an owned node.
+    fn to_node(&self) -> XmlNode;
+
+    /// Fetches an attribute's value by key.
+    fn attribute(&self, key: &str) -> SCResult<&str> {
+        self.attribute_value(key).ok_or_else(|| format!("No attribute with key '{}' found in <{}>!", key, self.name()).into())
+    }
+
+    /// Fetches and parses an optional attribute's value by key.
+    fn optional_attribute<T>(&self, key: &str) -> SCResult<Option<T>> where T: FromStr, SCError: From<T::Err> {
+        Ok(self.attribute_value(key).map(|s| s.parse()).transpose()?)
+    }
+
+    /// Copies the attributes whose keys are not among the given ones.
+    fn unknown_attributes(&self, known: &[&str]) -> Vec<(String, String)> {
+        self.attributes().filter(|(k, _)| !known.contains(k)).map(|(k, v)| (k.to_owned(), v.to_owned())).collect()
+    }
+
+    /// Finds the first child element with the provided tag name.
+    fn child_by_name(&self, name: &str) -> SCResult<&Self> {
+        self.childs().iter().find(|c| c.name() == name).ok_or_else(|| format!("No <{}> found in <{}>!", name, self.name()).into())
+    }
+
+    /// Fetches a list of all child elements matching the provided tag name.
+    fn childs_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Self> + 'a {
+        self.childs().iter().filter(move |c| c.name() == name)
+    }
+}
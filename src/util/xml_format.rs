@@ -0,0 +1,55 @@
+use std::io::Write;
+use xml::writer::{EmitterConfig, EventWriter};
+
+/// Settings for writing XML, which only affect the
+/// layout of the output, not the parsed content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlFormat {
+    /// The string used to indent nested elements
+    /// (`None` writes everything on one line).
+    pub indent: Option<String>,
+    /// Whether empty elements are written as
+    /// `<a />` instead of `<a></a>`.
+    pub normalize_empty_elements: bool
+}
+
+impl Default for XmlFormat {
+    fn default() -> Self { Self::compact() }
+}
+
+impl XmlFormat {
+    /// Writes everything on one line, as sent to the server.
+    pub fn compact() -> Self {
+        Self { indent: None, normalize_empty_elements: true }
+    }
+
+    /// Indents nested elements by two spaces, e.g. for logging.
+    pub fn pretty() -> Self {
+        Self { indent: Some("  ".to_owned()), ..Self::compact() }
+    }
+
+    /// Sets the string used to indent nested elements.
+    pub fn with_indent(self, indent: Option<String>) -> Self {
+        Self { indent, ..self }
+    }
+
+    /// Sets whether empty elements are written as `<a />`.
+    pub fn with_normalized_empty_elements(self, normalize_empty_elements: bool) -> Self {
+        Self { normalize_empty_elements, ..self }
+    }
+
+    /// Creates a writer emitting XML in this format (without
+    /// a document declaration) into the given sink. Text and
+    /// attribute values are always escaped.
+    pub fn create_writer<W>(&self, sink: W) -> EventWriter<W> where W: Write {
+        let mut config = EmitterConfig::new();
+        config.write_document_declaration = false;
+        config.perform_escaping = true;
+        config.normalize_empty_elements = self.normalize_empty_elements;
+        if let Some(indent) = &self.indent {
+            config.perform_indent = true;
+            config.indent_string = indent.clone().into();
+        }
+        config.create_writer(sink)
+    }
+}
@@ -0,0 +1,146 @@
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use smartstring::alias::String as XmlString;
+
+/// Declares the vocabulary of known names together
+/// with the conversions from and to strings.
+macro_rules! vocabulary {
+    [ $($variant:ident => $name:literal),* $(,)? ] => {
+        /// A name from the fixed vocabulary of the protocol.
+        #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+        enum KnownName {
+            $($variant),*
+        }
+
+        impl KnownName {
+            fn lookup(name: &str) -> Option<Self> {
+                match name {
+                    $($name => Some(Self::$variant),)*
+                    _ => None
+                }
+            }
+
+            fn as_str(self) -> &'static str {
+                match self {
+                    $(Self::$variant => $name),*
+                }
+            }
+        }
+    }
+}
+
+vocabulary![
+    Aggregation => "aggregation",
+    Annotation => "annotation",
+    Blue => "blue",
+    Board => "board",
+    CanTimeout => "canTimeout",
+    Cause => "cause",
+    Class => "class",
+    Close => "close",
+    Color => "color",
+    CurrentPlayerColor => "currentPlayerColor",
+    Data => "data",
+    Definition => "definition",
+    Depth => "depth",
+    Destination => "destination",
+    DisplayName => "displayName",
+    Evaluation => "evaluation",
+    Field => "field",
+    Fields => "fields",
+    Fragment => "fragment",
+    HardTimeout => "hardTimeout",
+    IsObstructed => "isObstructed",
+    Joined => "joined",
+    LastMove => "lastMove",
+    Left => "left",
+    Message => "message",
+    Name => "name",
+    Owner => "owner",
+    Part => "part",
+    Piece => "piece",
+    Protocol => "protocol",
+    Reason => "reason",
+    Red => "red",
+    RelevantForRanking => "relevantForRanking",
+    Room => "room",
+    RoomId => "roomId",
+    Score => "score",
+    ShouldBePaused => "shouldBePaused",
+    SoftTimeout => "softTimeout",
+    Start => "start",
+    StartPlayerColor => "startPlayerColor",
+    State => "state",
+    TimeMs => "timeMs",
+    Turn => "turn",
+    Type => "type",
+    UndeployedBluePieces => "undeployedBluePieces",
+    UndeployedRedPieces => "undeployedRedPieces",
+    Value => "value",
+    Violated => "violated",
+    Winner => "winner",
+    X => "x",
+    Y => "y",
+    Z => "z"
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Repr {
+    Known(KnownName),
+    Custom(XmlString)
+}
+
+/// An element or attribute name. Names from the protocol's
+/// vocabulary (e.g. `field`, `piece` or `owner`) are interned,
+/// thus storing and comparing them is as cheap as for integers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlName(Repr);
+
+impl XmlName {
+    /// Interns the given name if it is part of the vocabulary.
+    pub fn new(name: &str) -> Self {
+        match KnownName::lookup(name) {
+            Some(known) => Self(Repr::Known(known)),
+            None => Self(Repr::Custom(name.into()))
+        }
+    }
+
+    /// Tests whether the name is part of the protocol's vocabulary.
+    pub fn is_known(&self) -> bool { matches!(self.0, Repr::Known(_)) }
+
+    /// Fetches the name as a string.
+    pub fn as_str(&self) -> &str {
+        match &self.0 {
+            Repr::Known(known) => known.as_str(),
+            Repr::Custom(name) => name.as_str()
+        }
+    }
+}
+
+impl Default for XmlName {
+    fn default() -> Self { Self::new("") }
+}
+
+impl From<&str> for XmlName {
+    fn from(name: &str) -> Self { Self::new(name) }
+}
+
+impl PartialEq<str> for XmlName {
+    fn eq(&self, other: &str) -> bool { self.as_str() == other }
+}
+
+impl PartialOrd for XmlName {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> { Some(self.cmp(other)) }
+}
+
+impl Ord for XmlName {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering { self.as_str().cmp(other.as_str()) }
+}
+
+impl Hash for XmlName {
+    fn hash<H: Hasher>(&self, state: &mut H) { self.as_str().hash(state) }
+}
+
+impl fmt::Display for XmlName {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result { write!(f, "{}", self.as_str()) }
+}
@@ -1,20 +1,28 @@
-use std::collections::{HashMap, VecDeque};
+use std::collections::VecDeque;
 use std::convert::TryInto;
 use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::str;
 use std::io::{Read, Write, Cursor};
 use xml::reader::{EventReader, XmlEvent as XmlReadEvent};
-use xml::writer::{EventWriter, EmitterConfig, XmlEvent as XmlWriteEvent};
+use xml::writer::{EventWriter, XmlEvent as XmlWriteEvent};
 use log::{warn, error};
-use super::{SCResult, SCError};
+use smartstring::alias::String as XmlString;
+use super::{SCResult, SCError, XmlElement, XmlFormat, XmlName};
 
 /// A deserialized, in-memory tree-representation
 /// of an XML node.
-#[derive(Debug, Default)]
+///
+/// The names are interned and the other strings are stored inline
+/// up to a length of 23 bytes, which covers virtually all values of
+/// the protocol. The attributes are kept in a small list (compared
+/// regardless of their order), thus a node only allocates for its
+/// attributes and childs.
+#[derive(Debug, Clone, Default)]
 pub struct XmlNode {
-    name: String,
-    content: String,
-    attributes: HashMap<String, String>,
+    name: XmlName,
+    content: XmlString,
+    attributes: Vec<(XmlName, XmlString)>,
     childs: Vec<XmlNode>
 }
 
@@ -23,21 +31,41 @@ pub struct XmlNode {
 pub struct XmlNodeBuilder<'a> {
     name: &'a str,
     content: &'a str,
-    attributes: HashMap<String, String>,
+    attributes: Vec<(XmlName, XmlString)>,
     childs: Vec<XmlNode>
 }
 
-/// Indicates that the type can be created from an XML node.
+/// Sets an attribute in the given list, replacing
+/// the previous value for the same key.
+fn set_attribute(attributes: &mut Vec<(XmlName, XmlString)>, key: XmlName, value: XmlString) {
+    match attributes.iter_mut().find(|(k, _)| *k == key) {
+        Some((_, existing)) => *existing = value,
+        None => attributes.push((key, value))
+    }
+}
+
+/// Indicates that the type can be created from an XML node,
+/// either an owned one or a borrowed view into a document.
 pub trait FromXmlNode where Self: Sized {
-    fn from_node(node: &XmlNode) -> SCResult<Self>;
+    fn from_node(node: &impl XmlElement) -> SCResult<Self>;
 }
 
 impl XmlNode {
     /// Creates a new XML node builder.
-    pub fn new(name: &str) -> XmlNodeBuilder {
+    #[allow(clippy::new_ret_no_self)]
+    pub fn new(name: &str) -> XmlNodeBuilder<'_> {
         XmlNodeBuilder::new(name)
     }
 
+    /// Assembles a node from borrowed parts.
+    pub(super) fn from_parts<'a>(name: &str, content: &str, attributes: impl IntoIterator<Item=(&'a str, &'a str)>, childs: Vec<XmlNode>) -> Self {
+        let mut node = XmlNode { name: XmlName::new(name), content: content.into(), attributes: Vec::new(), childs };
+        for (key, value) in attributes {
+            set_attribute(&mut node.attributes, XmlName::new(key), value.into());
+        }
+        node
+    }
+
     /// Deserializes an XML node tree
     /// from the given XML event reader.
     pub fn read_from<R>(reader: &mut EventReader<R>) -> SCResult<XmlNode> where R: Read {
@@ -46,12 +74,15 @@ impl XmlNode {
         loop {
             match reader.next() {
                 Ok(XmlReadEvent::StartElement { name, attributes, .. }) => {
-                    let node = XmlNode {
-                        name: name.local_name,
-                        content: String::new(),
-                        attributes: attributes.iter().cloned().map(|attr| (attr.name.local_name, attr.value)).collect(),
+                    let mut node = XmlNode {
+                        name: XmlName::new(&name.local_name),
+                        content: XmlString::new(),
+                        attributes: Vec::with_capacity(attributes.len()),
                         childs: Vec::new()
                     };
+                    for attribute in attributes {
+                        set_attribute(&mut node.attributes, XmlName::new(&attribute.name.local_name), attribute.value.as_str().into());
+                    }
                     node_stack.push_back(node);
                 },
                 Ok(XmlReadEvent::EndElement { name }) => {
@@ -68,11 +99,12 @@ impl XmlNode {
                 },
                 Ok(XmlReadEvent::Characters(content)) => {
                     if let Some(node) = node_stack.back_mut() {
-                        node.content += content.as_str();
+                        node.content.push_str(&content);
                     } else {
                         warn!("Found characters {} outside of any node", content);
                     }
                 },
+                Ok(XmlReadEvent::EndDocument) => return Err("Reached the end of the XML document".into()),
                 Err(e) => return Err(e.into()),
                 _ => ()
             }
@@ -99,6 +131,13 @@ impl XmlNode {
         Ok(())
     }
     
+    /// Serializes the node to an XML string in the given format.
+    pub fn to_string_with(&self, format: &XmlFormat) -> SCResult<String> {
+        let mut writer = format.create_writer(Cursor::new(Vec::new()));
+        self.write_to(&mut writer)?;
+        String::from_utf8(writer.into_inner().into_inner()).map_err(|e| format!("Wrote invalid UTF-8: {}", e).into())
+    }
+    
     /// Fetches the node's tag name.
     pub fn name(&self) -> &str {
         self.name.as_str()
@@ -109,9 +148,29 @@ impl XmlNode {
         self.content.as_str()
     }
     
+    /// Fetches the node's child elements.
+    pub fn childs(&self) -> &[XmlNode] {
+        &self.childs
+    }
+    
+    /// Iterates the node's attributes as key-value pairs.
+    pub fn attributes(&self) -> impl Iterator<Item=(&str, &str)> {
+        self.attributes.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+    
     /// Fetches an attribute's value by key.
     pub fn attribute(&self, key: &str) -> SCResult<&str> {
-        self.attributes.get(key).map(|s| s.as_str()).ok_or_else(|| format!("No attribute with key '{}' found in <{}>!", key, self.name).into())
+        self.attribute_value(key).ok_or_else(|| format!("No attribute with key '{}' found in <{}>!", key, self.name).into())
+    }
+    
+    /// Fetches and parses an optional attribute's value by key.
+    pub fn optional_attribute<T>(&self, key: &str) -> SCResult<Option<T>> where T: str::FromStr, SCError: From<T::Err> {
+        Ok(self.attribute_value(key).map(|s| s.parse()).transpose()?)
+    }
+    
+    fn attribute_value(&self, key: &str) -> Option<&str> {
+        let key = XmlName::new(key);
+        self.attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_str())
     }
     
     /// Finds the first child element with the provided tag name.
@@ -121,19 +180,53 @@ impl XmlNode {
     
     /// Fetches a list of all child elements matching the provided tag name.
     pub fn childs_by_name<'a, 'n: 'a>(&'a self, name: &'n str) -> impl Iterator<Item=&'a XmlNode> + 'a {
+        let name = XmlName::new(name);
         self.childs.iter().filter(move |c| c.name == name)
     }
 }
 
+impl XmlElement for XmlNode {
+    fn name(&self) -> &str { XmlNode::name(self) }
+
+    fn content(&self) -> &str { XmlNode::content(self) }
+
+    fn attribute_value(&self, key: &str) -> Option<&str> { XmlNode::attribute_value(self, key) }
+
+    fn attributes(&self) -> impl Iterator<Item=(&str, &str)> { XmlNode::attributes(self) }
+
+    fn childs(&self) -> &[Self] { XmlNode::childs(self) }
+
+    fn to_node(&self) -> XmlNode { self.clone() }
+
+    fn childs_by_name<'a>(&'a self, name: &'a str) -> impl Iterator<Item=&'a Self> + 'a { XmlNode::childs_by_name(self, name) }
+}
+
+impl PartialEq for XmlNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.content == other.content
+            && self.attributes.len() == other.attributes.len()
+            && self.attributes.iter().all(|(k, v)| other.attributes.iter().any(|(ok, ov)| ok == k && ov == v))
+            && self.childs == other.childs
+    }
+}
+
+impl Eq for XmlNode {}
+
+impl Hash for XmlNode {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.content.hash(state);
+        let mut attributes: Vec<_> = self.attributes.iter().collect();
+        attributes.sort();
+        attributes.hash(state);
+        self.childs.hash(state);
+    }
+}
+
 impl fmt::Display for XmlNode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        // Writes the node as XML
-        let mut config = EmitterConfig::new();
-        config.write_document_declaration = false;
-        config.perform_indent = true;
-        let mut writer = config.create_writer(Cursor::new(Vec::new()));
-        self.write_to(&mut writer).map_err(|_| fmt::Error)?;
-        write!(f, "{}", str::from_utf8(&writer.into_inner().into_inner()).map_err(|_| fmt::Error)?)
+        write!(f, "{}", self.to_string_with(&XmlFormat::pretty()).map_err(|_| fmt::Error)?)
     }
 }
 
@@ -141,7 +234,7 @@ impl<'a> XmlNodeBuilder<'a> {
     /// Creates a new XML node builder with the
     /// specified tag name.
     pub fn new(name: &'a str) -> Self {
-        Self { name: name, content: "", attributes: HashMap::new(), childs: Vec::new() }
+        Self { name, content: "", attributes: Vec::new(), childs: Vec::new() }
     }
     
     /// Sets the tag name of the XML node.
@@ -158,13 +251,15 @@ impl<'a> XmlNodeBuilder<'a> {
     
     /// Adds the specified attributes.
     pub fn attributes(mut self, attributes: impl IntoIterator<Item=(String, String)>) -> Self {
-        self.attributes.extend(attributes);
+        for (key, value) in attributes {
+            set_attribute(&mut self.attributes, XmlName::new(&key), value.into());
+        }
         self
     }
     
     /// Adds the specified attribute.
-    pub fn attribute(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
-        self.attributes.insert(key.into(), value.into());
+    pub fn attribute(mut self, key: impl AsRef<str>, value: impl Into<String>) -> Self {
+        set_attribute(&mut self.attributes, XmlName::new(key.as_ref()), value.into().into());
         self
     }
     
@@ -189,8 +284,8 @@ impl<'a> XmlNodeBuilder<'a> {
     /// Builds the XML node.
     pub fn build(self) -> XmlNode {
         XmlNode {
-            name: self.name.to_owned(),
-            content: self.content.to_owned(),
+            name: self.name.into(),
+            content: self.content.into(),
             attributes: self.attributes,
             childs: self.childs
         }
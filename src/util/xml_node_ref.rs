@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+use super::{SCResult, SCError, XmlElement, XmlNode};
+
+/// A borrowed view of an XML element that points into
+/// the parsed text wherever possible. Only values containing
+/// entities are unescaped into owned strings, thus `FromXmlNode`
+/// implementations can parse numbers and enums directly from
+/// the document without building an owned tree first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XmlNodeRef<'a> {
+    name: &'a str,
+    content: Cow<'a, str>,
+    attributes: Vec<(&'a str, Cow<'a, str>)>,
+    childs: Vec<XmlNodeRef<'a>>
+}
+
+impl<'a> XmlNodeRef<'a> {
+    /// Parses the root element of the given XML document,
+    /// skipping its declaration, comments and processing instructions.
+    pub fn parse(text: &'a str) -> SCResult<Self> {
+        let mut parser = Parser { text, pos: 0 };
+        parser.skip_misc()?;
+        parser.element()
+    }
+
+    /// Iterates the element's attributes as key-value pairs.
+    pub fn attributes(&self) -> impl Iterator<Item=(&str, &str)> {
+        self.attributes.iter().map(|(k, v)| (*k, v.as_ref()))
+    }
+}
+
+impl<'a> XmlElement for XmlNodeRef<'a> {
+    fn name(&self) -> &str { self.name }
+
+    fn content(&self) -> &str { &self.content }
+
+    fn attribute_value(&self, key: &str) -> Option<&str> {
+        self.attributes.iter().find(|(k, _)| *k == key).map(|(_, v)| v.as_ref())
+    }
+
+    fn attributes(&self) -> impl Iterator<Item=(&str, &str)> { XmlNodeRef::attributes(self) }
+
+    fn childs(&self) -> &[Self] { &self.childs }
+
+    fn to_node(&self) -> XmlNode {
+        XmlNode::from_parts(self.name, &self.content, self.attributes(), self.childs.iter().map(XmlElement::to_node).collect())
+    }
+}
+
+impl<'a> From<XmlNodeRef<'a>> for XmlNode {
+    fn from(node: XmlNodeRef<'a>) -> Self { node.to_node() }
+}
+
+/// Strips the namespace prefix from a qualified name.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\n' | '\r')
+}
+
+/// Resolves the predefined and numeric entities in the given text.
+fn unescape(raw: &str) -> SCResult<Cow<'_, str>> {
+    if !raw.contains('&') {
+        return Ok(Cow::Borrowed(raw));
+    }
+    let mut unescaped = String::with_capacity(raw.len());
+    let mut rest = raw;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        let end = rest[start..].find(';').ok_or_else(|| SCError::from(format!("Unterminated entity in '{}'", raw)))? + start;
+        let entity = &rest[start + 1..end];
+        unescaped.push(match entity {
+            "lt" => '<',
+            "gt" => '>',
+            "amp" => '&',
+            "quot" => '"',
+            "apos" => '\'',
+            _ => entity.strip_prefix("#x").map(|hex| u32::from_str_radix(hex, 16))
+                .or_else(|| entity.strip_prefix('#').map(|dec| dec.parse()))
+                .and_then(|code| code.ok())
+                .and_then(std::char::from_u32)
+                .ok_or_else(|| SCError::from(format!("Unknown entity &{};", entity)))?
+        });
+        rest = &rest[end + 1..];
+    }
+    unescaped.push_str(rest);
+    Ok(Cow::Owned(unescaped))
+}
+
+/// Appends a run of text to an element's content.
+fn append<'a>(content: &mut Cow<'a, str>, text: Cow<'a, str>) {
+    if content.is_empty() {
+        *content = text;
+    } else {
+        content.to_mut().push_str(&text);
+    }
+}
+
+/// A recursive descent parser over a document's text.
+struct Parser<'a> {
+    text: &'a str,
+    pos: usize
+}
+
+impl<'a> Parser<'a> {
+    fn rest(&self) -> &'a str { &self.text[self.pos..] }
+
+    fn error(&self, message: &str) -> SCError {
+        format!("{} at byte {} of the XML document", message, self.pos).into()
+    }
+
+    fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches(is_whitespace).len();
+    }
+
+    fn expect(&mut self, token: &str) -> SCResult<()> {
+        if self.rest().starts_with(token) {
+            self.pos += token.len();
+            Ok(())
+        } else {
+            Err(self.error(&format!("Expected '{}'", token)))
+        }
+    }
+
+    /// Consumes the text up to and including the terminator,
+    /// returning the text before it.
+    fn take_until(&mut self, terminator: &str) -> SCResult<&'a str> {
+        let rest = self.rest();
+        let end = rest.find(terminator).ok_or_else(|| self.error(&format!("Missing '{}'", terminator)))?;
+        self.pos += end + terminator.len();
+        Ok(&rest[..end])
+    }
+
+    /// Skips whitespace, comments, processing instructions and doctypes.
+    fn skip_misc(&mut self) -> SCResult<()> {
+        loop {
+            self.skip_whitespace();
+            let rest = self.rest();
+            if rest.starts_with("<?") {
+                self.take_until("?>")?;
+            } else if rest.starts_with("<!--") {
+                self.take_until("-->")?;
+            } else if rest.starts_with("<!DOCTYPE") {
+                self.take_until(">")?;
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    fn name(&mut self) -> SCResult<&'a str> {
+        let rest = self.rest();
+        let len = rest.find(|c| is_whitespace(c) || matches!(c, '/' | '>' | '=')).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("Expected a name"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn element(&mut self) -> SCResult<XmlNodeRef<'a>> {
+        self.expect("<")?;
+        let qualified_name = self.name()?;
+        let mut node = XmlNodeRef { name: local_name(qualified_name), content: Cow::Borrowed(""), attributes: Vec::new(), childs: Vec::new() };
+
+        loop {
+            self.skip_whitespace();
+            if self.rest().starts_with("/>") {
+                self.pos += 2;
+                return Ok(node);
+            } else if self.rest().starts_with('>') {
+                self.pos += 1;
+                break;
+            }
+            let key = self.name()?;
+            self.skip_whitespace();
+            self.expect("=")?;
+            self.skip_whitespace();
+            let quote = match self.rest().chars().next() {
+                Some(quote @ ('"' | '\'')) => quote,
+                _ => return Err(self.error("Expected a quoted attribute value"))
+            };
+            self.pos += 1;
+            let raw = self.take_until(if quote == '"' { "\"" } else { "'" })?;
+            if raw.contains('<') {
+                return Err(self.error("Unescaped '<' in attribute value"));
+            }
+            if key == "xmlns" || key.starts_with("xmlns:") {
+                continue;
+            }
+            let key = local_name(key);
+            if node.attributes.iter().any(|(k, _)| *k == key) {
+                return Err(self.error(&format!("Duplicate attribute '{}'", key)));
+            }
+            let value = unescape(raw)?;
+            node.attributes.push((key, value));
+        }
+
+        loop {
+            let rest = self.rest();
+            let end = rest.find('<').ok_or_else(|| self.error(&format!("Missing </{}>", qualified_name)))?;
+            let text = &rest[..end];
+            self.pos += end;
+            if !text.chars().all(is_whitespace) {
+                append(&mut node.content, unescape(text)?);
+            }
+
+            let rest = self.rest();
+            if rest.starts_with("</") {
+                self.pos += 2;
+                let closing_name = self.name()?;
+                if closing_name != qualified_name {
+                    return Err(self.error(&format!("Expected </{}>, but found </{}>", qualified_name, closing_name)));
+                }
+                self.skip_whitespace();
+                self.expect(">")?;
+                return Ok(node);
+            } else if rest.starts_with("<!--") {
+                self.take_until("-->")?;
+            } else if rest.starts_with("<![CDATA[") {
+                // Like the event-based reader, CDATA sections are ignored
+                self.take_until("]]>")?;
+            } else if rest.starts_with("<?") {
+                self.take_until("?>")?;
+            } else {
+                node.childs.push(self.element()?);
+            }
+        }
+    }
+}
@@ -0,0 +1,6 @@
+//! A terminal UI that renders the live game
+//! in place of the scrolling log output.
+
+mod tui_delegate;
+
+pub use tui_delegate::*;
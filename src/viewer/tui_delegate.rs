@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::io::{self, Stdout};
+use std::time::{Duration, Instant};
+use crossterm::execute;
+use crossterm::terminal::{EnterAlternateScreen, LeaveAlternateScreen};
+use tui::Terminal;
+use tui::backend::CrosstermBackend;
+use tui::layout::{Constraint, Direction, Layout};
+use tui::widgets::{Block, Borders, Paragraph};
+use crate::client::SCClientDelegate;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use crate::util::{CancellationToken, SCResult};
+
+/// A delegate that renders the board, the undeployed pieces,
+/// the players' clocks and the engine's latest decision (including
+/// its principal variation, if annotated) in the terminal after
+/// every turn and otherwise forwards to the wrapped delegate.
+pub struct TuiDelegate<D> {
+    delegate: D,
+    terminal: Terminal<CrosstermBackend<Stdout>>,
+    state: Option<GameState>,
+    my_color: Option<PlayerColor>,
+    clocks: HashMap<PlayerColor, Duration>,
+    last_update: Option<(Instant, PlayerColor)>,
+    last_decision: Option<(Move, Duration, Option<MoveAnnotation<Move>>)>,
+    result: Option<String>
+}
+
+impl<D> TuiDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate and switches the
+    /// terminal to the alternate screen.
+    pub fn new(delegate: D) -> SCResult<Self> {
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen)?;
+        let mut viewer = Self {
+            delegate,
+            terminal: Terminal::new(CrosstermBackend::new(stdout))?,
+            state: None,
+            my_color: None,
+            clocks: HashMap::new(),
+            last_update: None,
+            last_decision: None,
+            result: None
+        };
+        viewer.render();
+        Ok(viewer)
+    }
+
+    /// Charges the time since the last update to
+    /// the player who was to move.
+    fn tick(&mut self, next_color: PlayerColor) {
+        let now = Instant::now();
+        if let Some((since, color)) = self.last_update {
+            *self.clocks.entry(color).or_default() += now - since;
+        }
+        self.last_update = Some((now, next_color));
+    }
+
+    fn render(&mut self) {
+        let status = match (&self.result, &self.state, self.my_color) {
+            (Some(result), _, _) => format!("Game over: {}", result),
+            (None, Some(state), my_color) => format!(
+                "Turn {} (round {}), {:?} to move{}",
+                state.turn, state.round(), state.current_player_color,
+                my_color.map(|c| format!(", playing {:?}", c)).unwrap_or_default()
+            ),
+            (None, None, _) => "Waiting for the game to start...".to_owned()
+        };
+        let board = self.state.as_ref().map(|s| s.board.to_string()).unwrap_or_default();
+        let pieces = self.state.as_ref().map(|s| [PlayerColor::Red, PlayerColor::Blue].iter().map(|&color| format!(
+            "{:?}: {}",
+            color,
+            s.undeployed_pieces(color).iter().map(|p| p.to_string()).collect::<Vec<_>>().join(" ")
+        )).collect::<Vec<_>>().join("\n")).unwrap_or_default();
+        let clocks = [PlayerColor::Red, PlayerColor::Blue].iter().map(|color| format!(
+            "{:?}: {:.1}s", color, self.clocks.get(color).copied().unwrap_or_default().as_secs_f64()
+        )).collect::<Vec<_>>().join("\n");
+        let engine = self.last_decision.as_ref()
+            .map(|(game_move, time, annotation)| engine_summary(game_move, *time, annotation.as_ref()))
+            .unwrap_or_else(|| "No move chosen yet".to_owned());
+
+        let drawn = self.terminal.draw(|f| {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(3), Constraint::Min(0)])
+                .split(f.size());
+            let columns = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+                .split(rows[1]);
+            let panels = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(4), Constraint::Length(4), Constraint::Min(0)])
+                .split(columns[1]);
+
+            let block = |title| Block::default().title(title).borders(Borders::ALL);
+            f.render_widget(Paragraph::new(status).block(block("Game")), rows[0]);
+            f.render_widget(Paragraph::new(board).block(block("Board")), columns[0]);
+            f.render_widget(Paragraph::new(pieces).block(block("Undeployed")), panels[0]);
+            f.render_widget(Paragraph::new(clocks).block(block("Clocks")), panels[1]);
+            f.render_widget(Paragraph::new(engine).block(block("Engine")), panels[2]);
+        });
+        if let Err(e) = drawn {
+            log::warn!("Could not render terminal UI: {}", e);
+        }
+    }
+}
+
+/// Describes the engine's decision, followed by its evaluation
+/// and principal variation if the delegate annotated the move.
+pub fn engine_summary(game_move: &Move, time: Duration, annotation: Option<&MoveAnnotation<Move>>) -> String {
+    let mut summary = format!("Chose {} after {:.2}s", game_move, time.as_secs_f64());
+    if let Some(annotation) = annotation {
+        if let Some(evaluation) = annotation.evaluation {
+            summary += &format!("\nEvaluation: {:+.2}", evaluation);
+            if let Some(depth) = annotation.depth {
+                summary += &format!(" (depth {})", depth);
+            }
+        }
+        if !annotation.principal_variation.is_empty() {
+            summary += &format!("\nPV: {}", annotation.principal_variation.iter().map(|m| m.to_string()).collect::<Vec<_>>().join(" "));
+        }
+    }
+    summary
+}
+
+impl<D> Drop for TuiDelegate<D> {
+    /// Restores the terminal, after which the caller
+    /// prints the summary of the game.
+    fn drop(&mut self) {
+        let _ = execute!(self.terminal.backend_mut(), LeaveAlternateScreen);
+        if let Some(result) = &self.result {
+            log::info!("Game over: {}", result);
+        }
+    }
+}
+
+impl<D> SCClientDelegate for TuiDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        self.tick(state.current_player_color);
+        self.state = Some(state.clone());
+        self.render();
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.result = Some(match result.winners.as_slice() {
+            [] => "Draw".to_owned(),
+            winners => format!("{} won", winners.iter().map(|w| w.display_name.as_str()).collect::<Vec<_>>().join(", "))
+        });
+        self.render();
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.my_color = Some(*color);
+        self.render();
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let start = Instant::now();
+        let game_move = self.delegate.request_move(state, my_color, cancel);
+        self.last_decision = Some((game_move.clone(), start.elapsed(), self.delegate.move_annotation()));
+        self.render();
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
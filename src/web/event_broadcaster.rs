@@ -0,0 +1,84 @@
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use log::{info, debug, warn};
+use tungstenite::Message;
+use crate::util::SCResult;
+
+/// A WebSocket server running on background threads that
+/// pushes game events as JSON to every connected subscriber.
+///
+/// Each event is an object of the form
+/// `{"type": "...", "data": ...}`. The `WebDelegate` publishes
+/// the types `welcome`, `state`, `move`, `search` (the annotation
+/// of the own move, e.g. its evaluation, depth and principal
+/// variation) and `gameEnd`.
+#[derive(Debug, Clone)]
+pub struct EventBroadcaster {
+    address: SocketAddr,
+    subscribers: Arc<Mutex<Vec<Sender<String>>>>
+}
+
+impl EventBroadcaster {
+    /// Binds the server to the given address and starts
+    /// accepting subscribers on a background thread.
+    pub fn start(address: impl ToSocketAddrs) -> SCResult<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        info!("Broadcasting game events on ws://{}", address);
+
+        let broadcaster = Self { address, subscribers: Arc::new(Mutex::new(Vec::new())) };
+        let acceptor = broadcaster.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => if let Err(e) = acceptor.subscribe(stream) {
+                        debug!("Could not accept WebSocket subscriber: {}", e);
+                    },
+                    Err(e) => warn!("Could not accept WebSocket connection: {}", e)
+                }
+            }
+        });
+
+        Ok(broadcaster)
+    }
+
+    /// Fetches the address the server is bound to.
+    pub fn address(&self) -> SocketAddr { self.address }
+
+    /// Fetches the number of currently connected subscribers.
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().map(|s| s.len()).unwrap_or(0)
+    }
+
+    /// Sends an event with the given type and payload to all subscribers,
+    /// dropping those that have disconnected.
+    pub fn publish(&self, event_type: &str, data: serde_json::Value) {
+        let event = serde_json::json!({ "type": event_type, "data": data }).to_string();
+        if let Ok(mut subscribers) = self.subscribers.lock() {
+            subscribers.retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    /// Performs the WebSocket handshake and spawns a thread
+    /// forwarding published events to the new subscriber.
+    fn subscribe(&self, stream: TcpStream) -> SCResult<()> {
+        let peer = stream.peer_addr()?;
+        let mut socket = tungstenite::accept(stream).map_err(|e| format!("WebSocket handshake failed: {}", e))?;
+        let (sender, receiver) = mpsc::channel::<String>();
+        self.subscribers.lock().map_err(|_| "Subscriber lock is poisoned")?.push(sender);
+        info!("WebSocket subscriber {} connected", peer);
+
+        thread::spawn(move || {
+            for event in receiver {
+                if let Err(e) = socket.send(Message::text(event)) {
+                    debug!("WebSocket subscriber {} disconnected: {}", peer, e);
+                    break;
+                }
+            }
+        });
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+//! An embedded HTTP server that exposes the live
+//! game state, e.g. for using a browser as a dashboard,
+//! and a WebSocket channel pushing game events to viewers.
+
+mod event_broadcaster;
+mod status_server;
+mod web_delegate;
+
+pub use event_broadcaster::*;
+pub use status_server::*;
+pub use web_delegate::*;
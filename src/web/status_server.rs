@@ -0,0 +1,79 @@
+use std::io::{BufRead, BufReader, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use log::{info, debug, warn};
+use crate::game::GameState;
+use crate::util::SCResult;
+
+const INDEX_HTML: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>Software Challenge Client</title><meta http-equiv="refresh" content="2"></head>
+<body><img src="/board.svg" style="height: 90vh"><p><a href="/state">State (JSON)</a></p></body>
+</html>"#;
+
+/// A tiny HTTP server running on a background thread that
+/// serves the most recent game state as JSON (`/state`) and
+/// the board as SVG (`/board.svg`).
+#[derive(Debug, Clone)]
+pub struct StatusServer {
+    address: SocketAddr,
+    state: Arc<Mutex<Option<GameState>>>
+}
+
+impl StatusServer {
+    /// Binds the server to the given address and starts
+    /// serving requests on a background thread.
+    pub fn start(address: impl ToSocketAddrs) -> SCResult<Self> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        info!("Serving game state on http://{}", address);
+
+        let server = Self { address, state: Arc::new(Mutex::new(None)) };
+        let handler = server.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                match stream {
+                    Ok(stream) => if let Err(e) = handler.handle(stream) {
+                        debug!("Could not handle HTTP request: {}", e);
+                    },
+                    Err(e) => warn!("Could not accept HTTP connection: {}", e)
+                }
+            }
+        });
+
+        Ok(server)
+    }
+
+    /// Fetches the address the server is bound to.
+    pub fn address(&self) -> SocketAddr { self.address }
+
+    /// Replaces the served game state.
+    pub fn update(&self, state: &GameState) {
+        if let Ok(mut current) = self.state.lock() {
+            *current = Some(state.clone());
+        }
+    }
+
+    /// Responds to a single HTTP request.
+    fn handle(&self, stream: TcpStream) -> SCResult<()> {
+        let mut request_line = String::new();
+        BufReader::new(&stream).read_line(&mut request_line)?;
+        let path = request_line.split_whitespace().nth(1).unwrap_or("/");
+        debug!("Got HTTP request for {}", path);
+
+        let state = self.state.lock().map_err(|_| "Game state lock is poisoned")?.clone();
+        let (status, content_type, body) = match (path, state) {
+            ("/", _) => ("200 OK", "text/html", INDEX_HTML.to_owned()),
+            ("/state", Some(state)) => ("200 OK", "application/json", serde_json::Value::from(&state).to_string()),
+            ("/board.svg", Some(state)) => ("200 OK", "image/svg+xml", state.board.to_svg(20.0)),
+            ("/state", None) | ("/board.svg", None) => ("503 Service Unavailable", "text/plain", "No game state available yet".to_owned()),
+            _ => ("404 Not Found", "text/plain", "Not found".to_owned())
+        };
+
+        let mut writer = &stream;
+        write!(writer, "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}", status, content_type, body.len(), body)?;
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use std::time::Duration;
+use crate::client::SCClientDelegate;
+use crate::util::CancellationToken;
+use crate::game::{GameState, Move, PlayerColor};
+use crate::protocol::{GameResult, MoveAnnotation, OpponentStats, RttStats, TimeSettings};
+use super::{EventBroadcaster, StatusServer};
+
+/// A delegate that publishes every state update to a status server
+/// and/or pushes game events to WebSocket subscribers and otherwise
+/// forwards to the wrapped delegate.
+pub struct WebDelegate<D> {
+    delegate: D,
+    server: Option<StatusServer>,
+    broadcaster: Option<EventBroadcaster>
+}
+
+impl<D> WebDelegate<D> where D: SCClientDelegate {
+    /// Wraps the given delegate.
+    pub fn new(delegate: D) -> Self {
+        Self { delegate, server: None, broadcaster: None }
+    }
+
+    /// Publishes state updates to the given status server.
+    pub fn with_server(self, server: StatusServer) -> Self {
+        Self { server: Some(server), ..self }
+    }
+
+    /// Pushes game events to the given broadcaster.
+    pub fn with_broadcaster(self, broadcaster: EventBroadcaster) -> Self {
+        Self { broadcaster: Some(broadcaster), ..self }
+    }
+
+    fn publish(&self, event_type: &str, data: serde_json::Value) {
+        if let Some(broadcaster) = &self.broadcaster {
+            broadcaster.publish(event_type, data);
+        }
+    }
+}
+
+impl<D> SCClientDelegate for WebDelegate<D> where D: SCClientDelegate {
+    fn on_update_state(&mut self, state: &GameState, my_color: Option<PlayerColor>) {
+        if let Some(server) = &self.server {
+            server.update(state);
+        }
+        self.publish("state", serde_json::Value::from(state));
+        self.delegate.on_update_state(state, my_color);
+    }
+
+    fn on_game_end(&mut self, result: GameResult, my_color: Option<PlayerColor>) {
+        self.publish("gameEnd", serde_json::json!({
+            "winners": result.winners.iter().map(serde_json::Value::from).collect::<Vec<_>>()
+        }));
+        self.delegate.on_game_end(result, my_color);
+    }
+
+    fn on_welcome_message(&mut self, color: &PlayerColor) {
+        self.publish("welcome", serde_json::json!({ "color": serde_json::Value::from(*color) }));
+        self.delegate.on_welcome_message(color);
+    }
+
+    fn on_time_settings(&mut self, settings: &TimeSettings) {
+        self.delegate.on_time_settings(settings);
+    }
+
+    fn on_idle(&mut self, duration: Duration) {
+        self.delegate.on_idle(duration);
+    }
+
+    fn on_game_paused(&mut self) {
+        self.delegate.on_game_paused();
+    }
+
+    fn on_game_resumed(&mut self) {
+        self.delegate.on_game_resumed();
+    }
+
+    fn on_round_trip(&mut self, stats: &RttStats) {
+        self.delegate.on_round_trip(stats);
+    }
+
+    fn on_opponent(&mut self, name: &str, stats: &OpponentStats<Move>) {
+        self.delegate.on_opponent(name, stats);
+    }
+
+    fn on_move_observed(&mut self, game_move: &Move) {
+        self.delegate.on_move_observed(game_move);
+    }
+
+    fn request_move(&mut self, state: &GameState, my_color: PlayerColor, cancel: &CancellationToken) -> Move {
+        let game_move = self.delegate.request_move(state, my_color, cancel);
+        self.publish("move", serde_json::json!({
+            "color": serde_json::Value::from(my_color),
+            "move": serde_json::Value::from(&game_move)
+        }));
+        if let Some(annotation) = self.delegate.move_annotation() {
+            self.publish("search", serde_json::Value::from(&annotation));
+        }
+        game_move
+    }
+
+    fn move_annotation(&self) -> Option<MoveAnnotation<Move>> {
+        self.delegate.move_annotation()
+    }
+}
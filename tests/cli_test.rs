@@ -0,0 +1,306 @@
+#![cfg(feature = "cli")]
+
+use std::{env, fs, process, thread};
+use std::io::{Read, Write};
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use socha_client_2020::game::{GameState, PlayerColor};
+use socha_client_2020::util::XmlNode;
+
+fn run(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_socha-client-2020")).args(args).output().unwrap()
+}
+
+/// Creates an empty temporary directory for the given test.
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = env::temp_dir().join(format!("socha-cli-{}-{}", name, process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// Starts a server on a local port that sends the given messages to
+/// the first client, then drains its messages, which are returned.
+fn recording_server(messages: &[&str]) -> (u16, thread::JoinHandle<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let messages: Vec<String> = messages.iter().map(|&m| m.to_owned()).collect();
+    let received = thread::spawn(move || {
+        let (mut stream, _) = listener.accept().unwrap();
+        let mut received = String::new();
+        for message in messages {
+            if stream.write_all(message.as_bytes()).is_err() {
+                return received;
+            }
+        }
+        let mut buffer = [0; 1024];
+        while let Ok(length @ 1..) = stream.read(&mut buffer) {
+            received += &String::from_utf8_lossy(&buffer[..length]);
+        }
+        received
+    });
+    (port, received)
+}
+
+/// Starts a server on a local port that sends the given
+/// messages to the first client, then drains its messages.
+fn fake_server(messages: &[&str]) -> u16 {
+    recording_server(messages).0
+}
+
+/// The messages of a game that ends right after the welcome
+/// message, with the given players (if any) as the winners.
+fn short_game(winners: &str) -> Vec<String> {
+    vec![
+        "<protocol>".to_owned(),
+        "<room roomId=\"r\"><data class=\"welcomeMessage\" color=\"red\"/></room>".to_owned(),
+        format!("<room roomId=\"r\"><data class=\"result\"><definition/><score cause=\"REGULAR\"/><score cause=\"REGULAR\"/>{}</data></room>", winners),
+        "<close/>".to_owned()
+    ]
+}
+
+#[test]
+fn gauntlet_json() {
+    let max_seed = u64::MAX.to_string();
+    let output = run(&["gauntlet", "-n", "2", "-c", "random", "-r", "random", "-S", &max_seed, "-R", &max_seed, "-j"]);
+    assert!(output.status.success());
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["games"], 2);
+    assert_eq!(String::from_utf8_lossy(&output.stderr).lines().filter(|l| l.starts_with("Game")).count(), 2);
+}
+
+#[test]
+fn bench_report() {
+    let dir = temp_dir("bench");
+    let state_path = dir.join("state.xml");
+    fs::write(&state_path, XmlNode::from(GameState::initial(PlayerColor::Red)).to_string()).unwrap();
+
+    let output = run(&["bench", "-s", state_path.to_str().unwrap(), "-n", "20", "-S", "1"]);
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "Generated 20 positions (seed 1)");
+    for (line, name) in lines[1..].iter().zip(["Move generation", "Validation", "State parsing ", "State parsing (borrowed)"]) {
+        assert!(line.starts_with(name) && line.contains("positions/s") && line.contains("p99"), "{}", line);
+    }
+    assert_eq!(lines.len(), 5);
+
+    assert_eq!(run(&["bench"]).status.code(), Some(2));
+    assert!(!run(&["bench", "-s", dir.join("missing.xml").to_str().unwrap()]).status.success());
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[cfg(feature = "expansions")]
+#[test]
+fn selfplay_expansions() {
+    let output = run(&["selfplay", "-x", "-1", "random", "-2", "random", "-S", "1"]);
+    assert!(output.status.success());
+    assert!(!run(&["selfplay", "-x", "-s", "state.xml"]).status.success());
+}
+
+#[test]
+fn play_config_precedence() {
+    let dir = temp_dir("config");
+    let port = fake_server(&short_game("<winner color=\"RED\" displayName=\"A\"/>").iter().map(String::as_str).collect::<Vec<_>>());
+    let config_path = dir.join("client.toml");
+    fs::write(&config_path, format!(r#"
+        port = 1
+        strategy = "greedy"
+
+        [search]
+        seed = 1
+        tie_break = "canonical"
+        hash_size_mb = 8
+
+        [log]
+        level = "Off"
+
+        [wire]
+        log = "{}"
+
+        [report]
+        dir = "{}"
+    "#, dir.join("wire.log").display(), dir.join("reports").display())).unwrap();
+
+    // The port is taken from the environment and the seed from
+    // the flags, the other settings are taken from the file
+    let output = Command::new(env!("CARGO_BIN_EXE_socha-client-2020"))
+        .args(["play", "--config", config_path.to_str().unwrap(), "--host", "127.0.0.1", "--seed", "3"])
+        .env("SC_PORT", port.to_string())
+        .env("SC_SEED", "2")
+        .output().unwrap();
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+    assert_eq!(summary["outcome"], "win");
+
+    let report_path = fs::read_dir(dir.join("reports")).unwrap()
+        .map(|e| e.unwrap().path())
+        .find(|p| p.extension().is_some_and(|e| e == "json"))
+        .unwrap();
+    let report: serde_json::Value = serde_json::from_str(&fs::read_to_string(report_path).unwrap()).unwrap();
+    let config = &report["config"];
+    assert_eq!(config["port"], port);
+    assert_eq!(config["seed"], 3);
+    assert_eq!(config["strategy"], "greedy");
+    assert_eq!(config["tieBreak"], "canonical");
+    assert_eq!(config["hashSizeMb"], 8);
+    assert_eq!(config["wireLog"], dir.join("wire.log").to_str().unwrap());
+
+    let wire_log = fs::read_to_string(dir.join("wire.log")).unwrap();
+    assert!(wire_log.lines().any(|l| l.starts_with("< ") && l.contains("welcomeMessage")), "{}", wire_log);
+    assert!(wire_log.lines().any(|l| l.starts_with("< ") && l.contains("result")));
+
+    // Invalid settings in the file are reported with its path
+    fs::write(&config_path, "[search]\nseed = \"many\"\n").unwrap();
+    let output = run(&["play", "--config", config_path.to_str().unwrap()]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr).contains("client.toml"));
+    for (section, key) in [("search", "seed"), ("search", "hash_size_mb"), ("network", "idle_timeout"), ("time", "soft_timeout_ms"), ("time", "hard_timeout_ms"), ("time", "move_time_percent")] {
+        fs::write(&config_path, format!("[{}]\n{} = -1\n", section, key)).unwrap();
+        let output = run(&["play", "--config", config_path.to_str().unwrap()]);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(&format!("non-negative integer for {}.{}", section, key)) && stderr.contains("client.toml"), "{}", stderr);
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn play_invalid_reload() {
+    let dir = temp_dir("reload");
+    let config_path = dir.join("client.toml");
+    fs::write(&config_path, "[time]\nmove_time_percent = 50\n").unwrap();
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    let invalid_path = config_path.clone();
+    let server = thread::spawn(move || {
+        // Keep the connections open until the client exits
+        let mut connections = Vec::new();
+        for game in 0..2 {
+            let (mut stream, _) = listener.accept().unwrap();
+            if game == 0 {
+                // Swap in an invalid file before the first game ends
+                thread::sleep(std::time::Duration::from_millis(50));
+                fs::write(&invalid_path, "[time]\nmove_time_percent = 0\n").unwrap();
+            }
+            for message in short_game("<winner color=\"RED\" displayName=\"A\"/>") {
+                let _ = stream.write_all(message.as_bytes());
+            }
+            connections.push(stream);
+        }
+        connections
+    });
+
+    // The second game keeps the previous configuration
+    let output = run(&["play", "--config", config_path.to_str().unwrap(), "--host", "127.0.0.1", "--port", &port.to_string(), "--level", "Warn", "--games", "2", "--ab-strategy", "greedy"]);
+    // Connect to the server if the client did not, so it stops waiting
+    let _ = std::net::TcpStream::connect(("127.0.0.1", port));
+    drop(server.join().unwrap());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout);
+    assert!(stdout.contains("keeping the previous one"), "{}", stdout);
+    let json: Vec<serde_json::Value> = stdout.lines().filter_map(|l| serde_json::from_str(l).ok()).collect();
+    assert_eq!(json.iter().filter(|j| j["outcome"] == "win").count(), 2, "{}", stdout);
+    assert_eq!(json.last().unwrap()["abTest"]["variants"][1]["wins"], 1, "{}", stdout);
+    fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn play_event_log_stdout() {
+    let port = fake_server(&short_game("<winner color=\"RED\" displayName=\"A\"/>").iter().map(String::as_str).collect::<Vec<_>>());
+    let output = run(&["play", "--host", "127.0.0.1", "--port", &port.to_string(), "--level", "Info", "--event-log", "-"]);
+    assert!(output.status.success());
+
+    // Only events are written to stdout, the log output goes to stderr
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let events: Vec<serde_json::Value> = stdout.lines().map(|l| serde_json::from_str(l).unwrap_or_else(|e| panic!("{} in {}", e, l))).collect();
+    assert!(events.iter().all(|e| e["event"].is_string()), "{}", stdout);
+    let summary = events.iter().find(|e| e["event"] == "summary").unwrap();
+    assert_eq!(summary["outcome"], "win");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[INFO]"));
+}
+
+#[test]
+fn play_exit_status() {
+    let play = |port: u16| {
+        let output = run(&["play", "--host", "127.0.0.1", "--port", &port.to_string(), "--level", "Off"]);
+        let summary: serde_json::Value = serde_json::from_slice(&output.stdout).unwrap();
+        (output.status.code(), summary["outcome"].as_str().unwrap().to_owned())
+    };
+    let game = |winners: &str| fake_server(&short_game(winners).iter().map(String::as_str).collect::<Vec<_>>());
+
+    assert_eq!(play(game("<winner color=\"RED\" displayName=\"A\"/>")), (Some(0), "win".to_owned()));
+    assert_eq!(play(game("<winner color=\"BLUE\" displayName=\"B\"/>")), (Some(3), "loss".to_owned()));
+    assert_eq!(play(game("")), (Some(4), "draw".to_owned()));
+    assert_eq!(play(fake_server(&["<protocol>", "<close/>"])), (Some(5), "protocolError".to_owned()));
+
+    // Idle timeouts beyond the representable instants never elapse
+    let port = game("<winner color=\"RED\" displayName=\"A\"/>");
+    let output = run(&["play", "--host", "127.0.0.1", "--port", &port.to_string(), "--level", "Off", "--idle-timeout", "18446744073709551615"]);
+    assert_eq!(output.status.code(), Some(0), "{}", String::from_utf8_lossy(&output.stderr));
+
+    // Nothing listens on a port that was just released
+    let port = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+    assert_eq!(play(port), (Some(6), "connectionError".to_owned()));
+}
+
+#[test]
+fn play_reservation() {
+    let dir = temp_dir("reservation");
+    let play = |args: &[&str], env: &[(&str, &str)], stdin: &str| {
+        let (port, received) = recording_server(&["<protocol>", "<close/>"]);
+        let mut child = Command::new(env!("CARGO_BIN_EXE_socha-client-2020"))
+            .args(["play", "--host", "127.0.0.1", "--port", &port.to_string(), "--level", "Off"])
+            .args(args)
+            .envs(env.iter().copied())
+            .stdin(process::Stdio::piped())
+            .stdout(process::Stdio::piped())
+            .stderr(process::Stdio::piped())
+            .spawn().unwrap();
+        child.stdin.take().unwrap().write_all(stdin.as_bytes()).unwrap();
+        let output = child.wait_with_output().unwrap();
+        // Connect to the server if the client did not, so it stops waiting
+        let _ = std::net::TcpStream::connect(("127.0.0.1", port));
+        (output, received.join().unwrap())
+    };
+    let rejected = |args: &[&str], stdin: &str, message: &str| {
+        let (output, _) = play(args, &[], stdin);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(2), "{}", stderr);
+        assert!(stderr.contains(message), "{}", stderr);
+    };
+
+    // Surrounding whitespace is trimmed, regardless of the source
+    let reservation_path = dir.join("reservation");
+    fs::write(&reservation_path, "  abc-123\n").unwrap();
+    for (args, stdin) in [(vec!["--reservation", " abc-123 "], ""), (vec!["--reservation", "-"], "\tabc-123\r\n"), (vec!["--reservation-file", reservation_path.to_str().unwrap()], "")] {
+        let (_, received) = play(&args, &[], stdin);
+        assert!(received.contains("<joinPrepared reservationCode=\"abc-123\""), "{}", received);
+    }
+
+    // Empty codes and codes with inner whitespace are rejected without revealing them
+    rejected(&["--reservation", ""], "", "The reservation code is empty");
+    rejected(&["--reservation", " \t "], "", "The reservation code is empty");
+    rejected(&["--reservation", "-"], "\n", "The reservation code is empty");
+    fs::write(&reservation_path, "").unwrap();
+    rejected(&["--reservation-file", reservation_path.to_str().unwrap()], "", "The reservation code is empty");
+    rejected(&["--reservation", "secret code"], "", "must not contain whitespace");
+    let (output, _) = play(&["--reservation", "secret\u{7}code"], &[], "");
+    assert!(!String::from_utf8_lossy(&output.stderr).contains("secret"));
+
+    // Codes from the environment and the configuration file are checked alike
+    let config_path = dir.join("client.toml");
+    fs::write(&config_path, "reservation = \" abc-123\\n\"\n").unwrap();
+    let (_, received) = play(&["--config", config_path.to_str().unwrap()], &[], "");
+    assert!(received.contains("<joinPrepared reservationCode=\"abc-123\""), "{}", received);
+    let (_, received) = play(&[], &[("SC_RESERVATION", " abc-123 ")], "");
+    assert!(received.contains("<joinPrepared reservationCode=\"abc-123\""), "{}", received);
+    fs::write(&config_path, "reservation = \"secret code\"\n").unwrap();
+    for (args, env) in [(vec!["--config", config_path.to_str().unwrap()], vec![]), (vec![], vec![("SC_RESERVATION", "secret code")])] {
+        let (output, received) = play(&args, &env, "");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(!output.status.success() && stderr.contains("must not contain whitespace") && !stderr.contains("secret"), "{}", stderr);
+        assert!(!received.contains("secret"), "{}", received);
+    }
+    fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,117 @@
+#![cfg(feature = "expansions")]
+
+use std::convert::TryFrom;
+use socha_client_2020::game::{AxialCoords, GameState, Move, Piece, PieceType, PlayerColor, PositionedField, RuleSet, EXPANSION_PIECE_TYPES};
+
+#[test]
+fn expansion_piece_notation() {
+    for piece_type in EXPANSION_PIECE_TYPES {
+        assert_eq!(PieceType::try_from(char::from(piece_type)).unwrap(), piece_type);
+        assert_eq!(String::from(piece_type).parse::<PieceType>().unwrap(), piece_type);
+    }
+}
+
+fn position(notation: &str) -> GameState {
+    GameState::from_position_notation_with_rules(notation, RuleSet::with_expansions()).unwrap()
+}
+
+/// Fetches the sorted destinations of the drag moves from the given start.
+fn destinations(state: &GameState, x: i32, y: i32) -> Vec<AxialCoords> {
+    let mut destinations: Vec<_> = state.possible_moves(state.current_player_color).into_iter()
+        .filter_map(|m| match m {
+            Move::DragMove { start, destination } if start.coords == AxialCoords::new(x, y) => Some(destination.coords),
+            _ => None
+        })
+        .collect();
+    destinations.sort();
+    destinations
+}
+
+fn coords(list: &[(i32, i32)]) -> Vec<AxialCoords> {
+    let mut coords: Vec<_> = list.iter().map(|&(x, y)| AxialCoords::new(x, y)).collect();
+    coords.sort();
+    coords
+}
+
+fn drag(from: (i32, i32), to: (i32, i32)) -> Move {
+    Move::DragMove { start: PositionedField::new(AxialCoords::new(from.0, from.1)), destination: PositionedField::new(AxialCoords::new(to.0, to.1)) }
+}
+
+#[test]
+fn ladybug_moves() {
+    // Two steps on top of the swarm, then one down
+    let state = position("6 R R -1,0=RL 0,0=RB 1,0=BB");
+    assert_eq!(destinations(&state, -1, 0), coords(&[(0, 1), (1, -1), (1, 1), (2, -1), (2, 0)]));
+    assert!(state.validate_move(PlayerColor::Red, &drag((-1, 0), (-1, 1))).is_err());
+}
+
+#[test]
+fn mosquito_moves() {
+    // Mimics the adjacent grasshopper
+    let mosquito = position("6 R R -1,0=RM 0,0=RG 1,0=RB 2,0=BB");
+    let grasshopper = position("6 R R -1,0=RG 0,0=RG 1,0=RB 2,0=BB");
+    assert_eq!(destinations(&mosquito, -1, 0), coords(&[(3, 0)]));
+    assert_eq!(destinations(&mosquito, -1, 0), destinations(&grasshopper, -1, 0));
+
+    // Climbs like the adjacent beetle
+    let state = position("6 R R -1,0=RM 0,0=RT 1,0=RB 2,0=BB");
+    assert_eq!(destinations(&state, -1, 0), coords(&[(0, 0)]));
+
+    // Cannot mimic other mosquitos
+    let state = position("6 R R -1,0=RM 0,0=BM 1,0=BB 2,0=RB");
+    assert!(destinations(&state, -1, 0).is_empty());
+    assert!(state.validate_move(PlayerColor::Red, &drag((-1, 0), (-1, 1))).is_err());
+}
+
+#[test]
+fn pillbug_moves() {
+    // Moves like the bee
+    let pillbug = position("6 R R 0,0=RP 1,0=BBRT 2,0=RB");
+    let bee = position("6 R R 0,0=RB 1,0=BBRT 2,0=RP");
+    assert_eq!(destinations(&pillbug, 0, 0), coords(&[(0, 1), (1, -1)]));
+    assert_eq!(destinations(&pillbug, 0, 0), destinations(&bee, 0, 0));
+}
+
+#[test]
+fn pillbug_throw() {
+    // Moves adjacent pieces of either color to its empty neighbors
+    let state = position("6 R R 0,0=RP 1,0=RB -1,0=BB");
+    let neighbors = coords(&[(-1, 1), (0, -1), (0, 1), (1, -1)]);
+    assert_eq!(destinations(&state, -1, 0), neighbors);
+    assert_eq!(destinations(&state, 1, 0), neighbors);
+    assert!(state.validate_move(PlayerColor::Red, &drag((-1, 0), (-2, 0))).is_err());
+
+    let thrown = state.apply_move(&drag((-1, 0), (0, 1))).unwrap();
+    assert_eq!(thrown.board.field(AxialCoords::new(0, 1)).and_then(|f| f.piece()), Some(Piece { piece_type: PieceType::Bee, owner: PlayerColor::Blue }));
+}
+
+#[test]
+fn pillbug_throw_gate() {
+    // The stacks at (1, 0) and (-1, 1) form a gate above the pillbug towards (0, 1)
+    let state = position("6 R R 0,0=RP 1,0=RBBT -1,0=BB -1,1=BART");
+    assert_eq!(destinations(&state, -1, 0), coords(&[(0, -1), (1, -1)]));
+    let error = state.validate_move(PlayerColor::Red, &drag((-1, 0), (0, 1))).unwrap_err();
+    assert!(error.to_string().contains("gate"), "{}", error);
+}
+
+#[test]
+fn expansion_game() {
+    let mut state = GameState::initial_with_rules(PlayerColor::Red, RuleSet::with_expansions());
+    for piece_type in EXPANSION_PIECE_TYPES {
+        assert!(state.undeployed_pieces(PlayerColor::Red).contains(&Piece { piece_type, owner: PlayerColor::Red }));
+    }
+
+    // Plays a deterministic game preferring the expansion pieces
+    let is_expansion = |m: &Move| matches!(m, Move::SetMove { piece, .. } if EXPANSION_PIECE_TYPES.contains(&piece.piece_type));
+    for i in 0..24 {
+        let color = state.current_player_color;
+        let mut moves = state.possible_moves(color);
+        moves.sort();
+        assert!(moves.iter().all(|m| state.validate_move(color, m).is_ok()));
+        let game_move = moves.iter().find(|m| is_expansion(m)).unwrap_or(&moves[(moves.len() / 2 + i * 7) % moves.len()]);
+        state = state.apply_move(game_move).unwrap();
+    }
+    for color in [PlayerColor::Red, PlayerColor::Blue] {
+        assert!(state.undeployed_pieces(color).iter().all(|p| !EXPANSION_PIECE_TYPES.contains(&p.piece_type)));
+    }
+}
@@ -2,7 +2,8 @@ use std::iter::once;
 use std::collections::HashMap;
 use std::convert::TryFrom;
 use more_asserts::assert_lt;
-use socha_client_2020::game::{Board, PlayerColor, Field, Piece, PieceType, BOARD_RADIUS, FIELD_COUNT, AxialCoords, CubeCoords};
+use socha_client_2020::game::{Board, GameState, PlayerColor, Field, Piece, PieceType, PieceId, PieceTracker, Move, OpeningPlacement, RuleSet, PositionedField, SearchContext, GameFixedBoard, GAME_BOARD_SLOTS, BOARD_RADIUS, FIELD_COUNT, AxialCoords, CubeCoords, DoubledCoords, OffsetCoords, OffsetLayout, HexDirection, Adjacentable};
+use socha_client_2020::search::{RootPolicy, SafePlacements};
 
 macro_rules! assert_unordered_eq {
     ($a:expr, $b:expr) => {
@@ -114,3 +115,520 @@ fn neighbors_in_bounds() {
         assert_lt!(coords.z().abs(), radius);
     }
 }
+
+#[test]
+fn piece_tracking() {
+    let spider = Piece { piece_type: PieceType::Spider, owner: PlayerColor::Red };
+    let ant = Piece { piece_type: PieceType::Ant, owner: PlayerColor::Blue };
+    let positioned = |x, y| PositionedField::new(AxialCoords::new(x, y));
+    let mut tracker = PieceTracker::new();
+
+    let first = Move::SetMove { piece: spider, destination: positioned(0, 0) };
+    assert_eq!(tracker.notation(&first).unwrap(), "RS1");
+    tracker.apply(&first).unwrap();
+
+    let second = Move::SetMove { piece: ant, destination: positioned(1, -1) };
+    assert_eq!(tracker.notation(&second).unwrap(), "BA1 RS1-");
+    tracker.apply(&second).unwrap();
+
+    let third = Move::SetMove { piece: spider, destination: positioned(-1, 1) };
+    assert_eq!(tracker.notation(&third).unwrap(), "RS2 -RS1");
+    assert_eq!(tracker.apply(&third).unwrap(), PieceId { piece: spider, number: 2 });
+
+    let drag = Move::DragMove { start: positioned(-1, 1), destination: positioned(0, 1) };
+    assert_eq!(tracker.notation(&drag).unwrap(), "RS2 \\RS1");
+    tracker.apply(&drag).unwrap();
+    assert_eq!(tracker.position_of(PieceId { piece: spider, number: 2 }), Some(AxialCoords::new(0, 1)));
+    assert_eq!(tracker.move_count(PieceId { piece: spider, number: 2 }), 1);
+    assert_eq!(tracker.id_at(AxialCoords::new(-1, 1)), None);
+}
+
+#[test]
+pub fn test_bee_surrounded() {
+    let piece = |piece_type, owner| Field::new(once(Piece { piece_type, owner }), false);
+    let origin = AxialCoords::new(0, 0);
+    let mut fields: HashMap<_, _> = origin.coord_neighbors().iter().skip(1)
+        .map(|&c| (c, piece(PieceType::Ant, PlayerColor::Blue)))
+        .collect();
+    fields.insert(origin, piece(PieceType::Bee, PlayerColor::Red));
+
+    let board = Board::filling_radius(BOARD_RADIUS, fields.clone());
+    assert!(!board.is_bee_surrounded(PlayerColor::Red));
+    assert!(!board.is_bee_surrounded(PlayerColor::Blue));
+
+    fields.insert(origin.coord_neighbors()[0], Field::new(None, true));
+    let board = Board::filling_radius(BOARD_RADIUS, fields);
+    assert!(board.is_bee_surrounded(PlayerColor::Red));
+}
+
+#[test]
+fn swarm_connectivity_with_context() {
+    let ant = Field::new(once(Piece { piece_type: PieceType::Ant, owner: PlayerColor::Red }), false);
+    let fields: HashMap<_, _> = [(0, 0), (1, 0), (2, 0)].iter()
+        .map(|&(x, y)| (AxialCoords::new(x, y), ant.clone()))
+        .collect();
+    let board = Board::filling_radius(BOARD_RADIUS, fields);
+    let mut ctx = SearchContext::new();
+    assert!(board.is_swarm_connected_except(&mut ctx, None));
+    assert!(board.is_swarm_connected_except(&mut ctx, Some(AxialCoords::new(0, 0))));
+    assert!(!board.is_swarm_connected_except(&mut ctx, Some(AxialCoords::new(1, 0))));
+    assert!(board.is_swarm_connected());
+}
+
+#[test]
+fn fixed_board_conversion() {
+    let origin = AxialCoords::new(0, 0);
+    let mut fields: HashMap<_, _> = origin.coord_neighbors().iter()
+        .map(|&c| (c, Field::new(once(Piece { piece_type: PieceType::Ant, owner: PlayerColor::Blue }), false)))
+        .collect();
+    fields.insert(origin, Field::new(once(Piece { piece_type: PieceType::Bee, owner: PlayerColor::Red }), false));
+    let board = Board::filling_radius(BOARD_RADIUS, fields);
+
+    let fixed = GameFixedBoard::try_from(&board).unwrap();
+    assert_eq!(fixed.fields().count(), FIELD_COUNT);
+    assert_eq!(GameFixedBoard::index(AxialCoords::new(BOARD_RADIUS as i32, 0)), None);
+    assert!(fixed.is_bee_surrounded(PlayerColor::Red));
+    assert_eq!(Board::from(&fixed), board);
+}
+
+#[test]
+fn move_hashes() {
+    let set_move = |x, y| Move::SetMove { piece: Piece { piece_type: PieceType::Ant, owner: PlayerColor::Red }, destination: PositionedField::new(AxialCoords::new(x, y)) };
+    let with_field = Move::SetMove {
+        piece: Piece { piece_type: PieceType::Ant, owner: PlayerColor::Red },
+        destination: PositionedField::with_field(AxialCoords::new(1, 0), Field::new(None, false))
+    };
+    assert_eq!(set_move(1, 0).stable_hash(), with_field.stable_hash());
+    assert_ne!(set_move(1, 0).stable_hash(), set_move(0, 1).stable_hash());
+    assert_eq!(set_move(1, 0).canonical_hash(), set_move(0, 1).canonical_hash());
+    assert_eq!(set_move(1, 0).canonical_hash(), set_move(-1, 1).canonical_hash());
+    assert_ne!(set_move(1, 0).canonical_hash(), set_move(2, 0).canonical_hash());
+    assert_eq!(AxialCoords::new(2, -1).reflected().reflected(), AxialCoords::new(2, -1));
+    assert_eq!((0..6).fold(AxialCoords::new(2, -1), |c, _| c.rotated()), AxialCoords::new(2, -1));
+}
+
+#[test]
+fn initial_state() {
+    let state = GameState::initial(PlayerColor::Blue);
+    assert_eq!(state.turn, 0);
+    assert_eq!(state.current_player_color, PlayerColor::Blue);
+    assert_eq!(state.board.fields().count(), FIELD_COUNT);
+    assert!(!state.board.has_pieces());
+    assert_eq!(state.undeployed_pieces(PlayerColor::Red).len(), 11);
+    assert_eq!(state.possible_moves(PlayerColor::Blue).len(), 1001);
+}
+
+#[test]
+fn opening_placement() {
+    let state = GameState::initial(PlayerColor::Red);
+    let first = OpeningPlacement::of(&state, PlayerColor::Red).unwrap();
+    assert!(first.is_first_turn());
+    assert_eq!(first.first_turn_destinations().count(), FIELD_COUNT);
+    assert_eq!(first.second_turn_destinations().count(), 0);
+
+    let placed = state.apply_move(&Move::SetMove {
+        piece: Piece { piece_type: PieceType::Ant, owner: PlayerColor::Red },
+        destination: PositionedField::new(AxialCoords::new(0, 0))
+    }).unwrap();
+    assert!(OpeningPlacement::of(&placed, PlayerColor::Red).is_none());
+    let second = OpeningPlacement::of(&placed, PlayerColor::Blue).unwrap();
+    assert!(!second.is_first_turn());
+    let mut destinations = second.destinations();
+    destinations.sort();
+    let mut neighbors: Vec<_> = HexDirection::ALL.iter().map(|&d| AxialCoords::new(0, 0).neighbor(d)).collect();
+    neighbors.sort();
+    assert_eq!(destinations, neighbors);
+}
+
+#[test]
+fn any_legal_move() {
+    // Plays a deterministic game, since the generation order is not stable
+    let mut state = GameState::initial(PlayerColor::Red);
+    let mut plies = 0;
+    for _ in 0..12 {
+        let color = state.current_player_color;
+        let mut moves = state.possible_moves(color);
+        moves.sort();
+        assert_eq!(state.has_any_legal_move(color), !moves.is_empty());
+        match moves.get(moves.len() / 2) {
+            Some(game_move) => state = state.apply_move(game_move).unwrap(),
+            None => break
+        }
+        plies += 1;
+    }
+    assert_eq!(plies, 12);
+    let empty = GameState::initial_with_rules(PlayerColor::Red, RuleSet::default().with_initial_piece_types(vec![]));
+    assert!(!empty.has_any_legal_move(PlayerColor::Red));
+}
+
+#[test]
+fn move_deltas() {
+    let freedom = |state: &GameState, color| state.board.bee_freedom(color).unwrap_or(0) as i32;
+    let mut state = GameState::initial(PlayerColor::Red);
+    let mut plies = 0;
+    for i in 0..16 {
+        // Sorts the moves to play a deterministic game
+        let mut moves = state.possible_moves(state.current_player_color);
+        moves.sort();
+        if moves.is_empty() {
+            break;
+        }
+        let game_move = &moves[(moves.len() / 2 + i * 7) % moves.len()];
+        let (next, delta) = state.apply_move_with_delta(game_move).unwrap();
+        assert_eq!(state.position_hash() ^ delta.hash_diff, next.position_hash());
+        for &color in &[PlayerColor::Red, PlayerColor::Blue] {
+            assert_eq!(delta.bee_freedom_diff(color), freedom(&next, color) - freedom(&state, color));
+        }
+        let occupied = |s: &GameState| s.board.occupied_fields().count() as i32;
+        assert_eq!(occupied(&next) - occupied(&state), delta.occupied.is_some() as i32 - delta.vacated.is_some() as i32);
+        assert_eq!(state.infer_move(&next).as_ref(), Some(game_move));
+        state = next;
+        plies += 1;
+    }
+    assert_eq!(plies, 16);
+}
+
+#[test]
+fn custom_rules() {
+    let rules = RuleSet::default()
+        .with_board_radius(3)
+        .with_initial_piece_types(vec![PieceType::Bee, PieceType::Ant])
+        .with_round_limit(5);
+    let state = GameState::initial_with_rules(PlayerColor::Red, rules.clone());
+    let fields = state.board.fields().count();
+    assert!(fields < FIELD_COUNT);
+    assert_eq!(state.rules().turn_limit(), 10);
+    assert_eq!(state.undeployed_pieces(PlayerColor::Blue).len(), 2);
+    assert_eq!(state.possible_moves(PlayerColor::Red).len(), 2 * fields);
+
+    let hurried = state.clone().with_rules(rules.with_bee_deadline_round(Some(0)));
+    assert_eq!(hurried.possible_moves(PlayerColor::Red).len(), fields);
+    assert!(hurried.must_place_bee(PlayerColor::Red) && !state.must_place_bee(PlayerColor::Red));
+    assert_eq!(hurried.possible_moves_forced(PlayerColor::Red), Some(hurried.possible_moves(PlayerColor::Red)));
+    assert_eq!(state.possible_moves_forced(PlayerColor::Red), None);
+    assert!(!hurried.is_turn_limit_reached());
+}
+
+#[test]
+fn canonical_ordering() {
+    let red = |piece_type| Piece { piece_type, owner: PlayerColor::Red };
+    assert!(red(PieceType::Spider) < Piece { piece_type: PieceType::Ant, owner: PlayerColor::Blue });
+
+    let state = GameState::initial(PlayerColor::Red);
+    let first_move = Move::SetMove { piece: red(PieceType::Bee), destination: PositionedField::new(AxialCoords::new(0, 0)) };
+    let second = state.apply_move(&first_move).unwrap();
+    let mut moves = second.possible_moves(PlayerColor::Blue);
+    moves.sort();
+    assert!(moves.windows(2).all(|w| w[0] <= w[1]));
+    assert!(moves.iter().all(|m| first_move < *m));
+}
+
+#[test]
+fn board_display_with_axes() {
+    let board = Board::filling_radius(2, HashMap::new());
+    assert_eq!(board.display_with_axes().to_string(), r#"x\y  1 0-1
+  1 00[][]
+  0 []<>[]
+ -1 [][]00
+"#.to_owned());
+}
+
+#[test]
+fn coords_parsing() {
+    let axial = AxialCoords::new(-3, 2);
+    assert_eq!(axial.to_string().parse::<AxialCoords>().unwrap(), axial);
+    assert_eq!("0,2".parse::<AxialCoords>().unwrap(), AxialCoords::new(0, 2));
+    let cube = CubeCoords::from(axial);
+    assert_eq!(cube.to_string().parse::<CubeCoords>().unwrap(), cube);
+    assert!("(1, 1, 1)".parse::<CubeCoords>().is_err());
+    assert!("(1, 2, 3)".parse::<AxialCoords>().is_err());
+    assert!("(a, 2)".parse::<DoubledCoords>().is_err());
+}
+
+#[test]
+fn offset_and_pixel_coords() {
+    let layouts = [OffsetLayout::OddR, OffsetLayout::EvenR, OffsetLayout::OddQ, OffsetLayout::EvenQ];
+    for (coords, _) in Board::filling_radius(BOARD_RADIUS, HashMap::new()).fields() {
+        for &layout in &layouts {
+            assert_eq!(AxialCoords::from(OffsetCoords::from_axial(coords, layout)), coords);
+        }
+        let (x, y) = coords.to_pixel(10.0);
+        assert_eq!(AxialCoords::from_pixel((x, y), 10.0), coords);
+        assert_eq!(AxialCoords::from_pixel((x + 4.0, y - 4.0), 10.0), coords);
+    }
+    assert_eq!(OffsetCoords::from_axial(AxialCoords::new(0, -1), OffsetLayout::OddR), OffsetCoords::new(0, 1, OffsetLayout::OddR));
+}
+
+#[test]
+fn direction_algebra() {
+    let origin = AxialCoords::new(0, 0);
+    assert_eq!(origin.direction_to(AxialCoords::new(3, -3)), Some(HexDirection::Right));
+    assert_eq!(origin.direction_to(AxialCoords::new(0, 2)), Some(HexDirection::UpLeft));
+    assert_eq!(origin.direction_to(AxialCoords::new(2, 1)), None);
+    assert_eq!(origin.direction_to(origin), None);
+    assert_eq!(HexDirection::UpRight.opposite(), HexDirection::DownLeft);
+    assert_eq!(CubeCoords::new(2, -5, 3).length(), 5);
+    assert_eq!(CubeCoords::new(0, 3, -3).normalized(), Some(CubeCoords::new(0, 1, -1)));
+
+    let line = origin.line_to(AxialCoords::new(4, -1));
+    assert_eq!(line.len(), 5);
+    assert_eq!(line.last(), Some(&AxialCoords::new(4, -1)));
+    assert!(line.windows(2).all(|w| w[0].is_adjacent_to(w[1])));
+}
+
+#[test]
+fn grasshopper_jump() {
+    let set = |piece_type, owner, x, y| Move::SetMove { piece: Piece { piece_type, owner }, destination: PositionedField::new(AxialCoords::new(x, y)) };
+    let state = [
+        set(PieceType::Grasshopper, PlayerColor::Red, 0, 1),
+        set(PieceType::Bee, PlayerColor::Blue, 1, 0),
+        set(PieceType::Bee, PlayerColor::Red, 1, 1),
+        set(PieceType::Ant, PlayerColor::Blue, 2, -1)
+    ].iter().fold(GameState::initial(PlayerColor::Red), |s, m| s.apply_move(m).unwrap());
+    let jump = Move::DragMove { start: PositionedField::new(AxialCoords::new(0, 1)), destination: PositionedField::new(AxialCoords::new(3, -2)) };
+    assert!(state.validate_move(PlayerColor::Red, &jump).is_ok());
+    assert!(state.possible_moves(PlayerColor::Red).contains(&jump));
+}
+
+#[test]
+fn fixed_board_neighbor_tables() {
+    for index in 0..GAME_BOARD_SLOTS {
+        let coords = GameFixedBoard::coords(index);
+        if !GameFixedBoard::contains_coords(coords) {
+            continue;
+        }
+        let neighbors = GameFixedBoard::neighbor_indices(index);
+        for (direction, neighbor) in HexDirection::ALL.iter().zip(neighbors.iter()) {
+            assert_eq!(*neighbor, GameFixedBoard::index(coords.neighbor(*direction)));
+        }
+        let ring: Vec<_> = GameFixedBoard::second_ring_indices(index).iter().flatten().map(|&i| GameFixedBoard::coords(i)).collect();
+        assert!(ring.iter().all(|c| coords.distance_to(*c) == 2));
+        assert_eq!(ring.len(), ring.iter().collect::<std::collections::HashSet<_>>().len());
+    }
+    assert_eq!(GameFixedBoard::second_ring_indices(GameFixedBoard::index(AxialCoords::new(0, 0)).unwrap()).iter().flatten().count(), 12);
+}
+
+#[test]
+fn swarm_boundary_fields() {
+    let piece = |owner| Field::new(once(Piece { piece_type: PieceType::Ant, owner }), false);
+    let board = Board::filling_radius(BOARD_RADIUS, [
+        (AxialCoords::new(0, 0), piece(PlayerColor::Red)),
+        (AxialCoords::new(1, 0), piece(PlayerColor::Blue))
+    ].into_iter().collect::<HashMap<_, _>>());
+    let boundary = board.swarm_boundary_fields();
+    assert_eq!(boundary.len(), 8);
+    assert_lt!(boundary.len(), board.swarm_boundary().count());
+    let shared: Vec<_> = boundary.iter().filter(|f| f.borders(PlayerColor::Red) && f.borders(PlayerColor::Blue)).map(|f| f.coords).collect();
+    assert_unordered_eq!(shared, vec![AxialCoords::new(0, 1), AxialCoords::new(1, -1)]);
+    assert_eq!(board.possible_set_move_destinations(PlayerColor::Red).count(), 3);
+}
+
+#[test]
+fn batch_legality() {
+    let set = |piece_type, owner, x, y| Move::SetMove { piece: Piece { piece_type, owner }, destination: PositionedField::new(AxialCoords::new(x, y)) };
+    let state = [
+        set(PieceType::Bee, PlayerColor::Red, 0, 0),
+        set(PieceType::Bee, PlayerColor::Blue, 1, -1),
+        set(PieceType::Ant, PlayerColor::Red, -1, 1),
+        set(PieceType::Spider, PlayerColor::Blue, 2, -2)
+    ].iter().fold(GameState::initial(PlayerColor::Red), |s, m| s.apply_move(m).unwrap());
+    let coords: Vec<_> = state.board.fields().map(|(c, _)| c).collect();
+    let candidates: Vec<_> = coords.iter()
+        .flat_map(|&s| coords.iter().map(move |&d| Move::DragMove { start: PositionedField::new(s), destination: PositionedField::new(d) }))
+        .chain(coords.iter().map(|c| set(PieceType::Beetle, PlayerColor::Red, c.x(), c.y())))
+        .collect();
+    let legal = state.filter_legal(PlayerColor::Red, candidates.clone());
+    let expected: Vec<_> = candidates.into_iter().filter(|m| state.validate_move(PlayerColor::Red, m).is_ok()).collect();
+    assert!(!legal.is_empty());
+    assert_eq!(legal, expected);
+}
+
+#[test]
+fn state_snapshots() {
+    let state = GameState::from_position_notation("9 R B 0,0=RBBT 1,0=BB -1,1=RG 0,-1=BSRT 2,-2=#").unwrap();
+    let snapshot = state.encode().unwrap();
+    assert_eq!(snapshot.len(), 3 + (2 + 1) * 4 + 2);
+    let decoded = GameState::decode(&snapshot).unwrap();
+    assert_eq!(decoded, state);
+    assert_eq!(decoded.position_notation(), state.position_notation());
+    assert!(GameState::decode(&snapshot[..snapshot.len() - 1]).is_err());
+
+    // Positions exceeding the snapshot's ranges are rejected instead of being truncated
+    let encode = |turn: u32, coords: AxialCoords, height: usize| {
+        let mut state = GameState::initial(PlayerColor::Red);
+        state.turn = turn;
+        state.board = Board::new(HashMap::from([(coords, Field::new(vec![Piece { piece_type: PieceType::Beetle, owner: PlayerColor::Red }; height], false))]));
+        state.encode()
+    };
+    assert!(encode(65535, AxialCoords::new(15, -16), 15).is_ok());
+    assert!(encode(65536, AxialCoords::new(0, 0), 1).is_err());
+    assert!(encode(0, AxialCoords::new(0, 0), 16).is_err());
+    assert!(encode(0, AxialCoords::new(16, -16), 1).is_err());
+}
+
+#[test]
+fn safe_set_destinations() {
+    let set = |piece_type, owner, x, y| Move::SetMove { piece: Piece { piece_type, owner }, destination: PositionedField::new(AxialCoords::new(x, y)) };
+    let initial = GameState::initial(PlayerColor::Red);
+    let legal = |state: &GameState| state.possible_moves(PlayerColor::Red).iter()
+        .filter(|m| matches!(m, Move::SetMove { .. }))
+        .map(Move::destination_coords)
+        .collect::<std::collections::HashSet<_>>();
+    assert_unordered_eq!(initial.safe_set_destinations(PlayerColor::Red), legal(&initial));
+
+    let state = [
+        set(PieceType::Bee, PlayerColor::Red, 0, 0),
+        set(PieceType::Bee, PlayerColor::Blue, 1, -1),
+        set(PieceType::Ant, PlayerColor::Red, -1, 1),
+        set(PieceType::Spider, PlayerColor::Blue, 2, -2)
+    ].iter().fold(initial, |s, m| s.apply_move(m).unwrap());
+    // Placing next to the own bee would give the opponent a free tempo
+    let safe = state.safe_set_destinations(PlayerColor::Red);
+    assert_unordered_eq!(safe.clone(), vec![AxialCoords::new(-2, 1), AxialCoords::new(-2, 2), AxialCoords::new(-1, 2)]);
+    assert!(safe.iter().all(|c| legal(&state).contains(c) && !c.is_adjacent_to(AxialCoords::new(0, 0))));
+
+    let policy = SafePlacements::new();
+    assert!(policy.allows(&state, &set(PieceType::Ant, PlayerColor::Red, -2, 2)));
+    assert!(!policy.allows(&state, &set(PieceType::Ant, PlayerColor::Red, 0, 1)));
+}
+
+#[test]
+fn surround_threats() {
+    // Compares the threats with applying every move along a deterministic game
+    let mut state = GameState::initial(PlayerColor::Red);
+    let mut threatened = 0;
+    let mut plies = 0;
+    for i in 0..36 {
+        let color = state.current_player_color;
+        let mut moves = state.possible_moves(color);
+        moves.sort();
+        if moves.is_empty() {
+            break;
+        }
+        let liberties = state.bee_liberties(color.opponent());
+        let expected: Vec<_> = moves.iter()
+            .filter(|m| liberties.is_some() && state.apply_move(m).unwrap().bee_liberties(color.opponent()) < liberties)
+            .cloned()
+            .collect();
+        assert_eq!(state.surround_threats(color), expected);
+        threatened += usize::from(!expected.is_empty());
+        state = state.apply_move(&moves[(moves.len() / 2 + i * 7) % moves.len()]).unwrap();
+        plies += 1;
+    }
+    assert_eq!(plies, 36);
+    assert!(threatened > 0);
+    assert_eq!(GameState::initial(PlayerColor::Red).bee_liberties(PlayerColor::Red), None);
+    assert!(!GameState::initial(PlayerColor::Red).is_in_check(PlayerColor::Red));
+}
+
+#[test]
+fn perform_moves() {
+    let mut state = GameState::initial(PlayerColor::Red);
+    let first_move = state.possible_moves(PlayerColor::Red)[0].clone();
+    let expected = state.apply_move(&first_move).unwrap();
+    state.perform_move(&first_move).unwrap();
+    assert_eq!(state, expected);
+    assert_eq!((state.turn, state.current_player_color), (1, PlayerColor::Blue));
+    assert_eq!(state.undeployed_pieces(PlayerColor::Red).len() + 1, GameState::initial(PlayerColor::Red).undeployed_pieces(PlayerColor::Red).len());
+
+    // Illegal moves (here: a move by the wrong player) leave the state untouched
+    let red_move = state.possible_moves(PlayerColor::Red)[0].clone();
+    assert!(state.perform_move(&red_move).is_err());
+    assert_eq!(state, expected);
+
+    // Moves are performed like they are applied, including the lineage
+    let mut tracked = state.clone().with_lineage();
+    let blue_move = tracked.possible_moves(PlayerColor::Blue)[0].clone();
+    let expected = tracked.apply_move(&blue_move).unwrap();
+    tracked.perform_move(&blue_move).unwrap();
+    assert_eq!(tracked, expected);
+    assert_eq!(tracked.lineage().moves(), expected.lineage().moves());
+    assert_eq!(tracked.lineage().parent_hash(), Some(state.position_hash()));
+}
+
+#[test]
+fn lineage_repetitions() {
+    let reversed = |m: &Move| match m {
+        Move::DragMove { start, destination } => Move::DragMove { start: destination.clone(), destination: start.clone() },
+        _ => unreachable!()
+    };
+    let drags = |state: &GameState, color| {
+        let mut moves: Vec<_> = state.possible_moves(color).into_iter().filter(|m| matches!(m, Move::DragMove { .. })).collect();
+        moves.sort();
+        moves
+    };
+    let initial = GameState::from_position_notation("4 R R 0,0=RB 1,0=BB -1,0=RG 1,1=BG").unwrap().with_lineage();
+    assert_eq!(initial.repetitions(), 0);
+
+    // Finds two moves that both players can take back
+    let (red, blue) = drags(&initial, PlayerColor::Red).into_iter()
+        .flat_map(|r| {
+            let next = initial.apply_move(&r).unwrap();
+            drags(&next, PlayerColor::Blue).into_iter().map(move |b| (r.clone(), b))
+        })
+        .find(|(r, b)| {
+            let mut state = initial.clone();
+            [r.clone(), b.clone(), reversed(r), reversed(b)].iter().all(|m| state.perform_move(m).is_ok())
+        })
+        .expect("No repeatable moves found");
+    let mut state = initial.clone();
+    for game_move in &[red.clone(), blue.clone(), reversed(&red)] {
+        state.perform_move(game_move).unwrap();
+        assert_eq!(state.repetitions(), 0);
+    }
+    state.perform_move(&reversed(&blue)).unwrap();
+    assert_eq!(state.position_hash(), initial.position_hash());
+    assert_eq!(state.repetitions(), 1);
+    assert_eq!(state.lineage().len(), 4);
+    assert_eq!(state.lineage().moves(), vec![red.clone(), blue.clone(), reversed(&red), reversed(&blue)]);
+
+    // States differing only in their lineage are equal
+    let untracked = GameState::from_position_notation(&state.position_notation()).unwrap();
+    assert!(untracked.lineage().is_empty());
+    assert_eq!(untracked, state);
+    assert_ne!(state.apply_move(&red).unwrap().position_hash(), state.position_hash());
+}
+
+#[test]
+fn rule_explanations() {
+    let set = |piece_type, owner, x, y| Move::SetMove { piece: Piece { piece_type, owner }, destination: PositionedField::new(AxialCoords::new(x, y)) };
+    let drag = |(x1, y1), (x2, y2)| Move::DragMove { start: PositionedField::new(AxialCoords::new(x1, y1)), destination: PositionedField::new(AxialCoords::new(x2, y2)) };
+    // Explains the move for Red and yields the violated rules,
+    // which have to agree with the validator
+    let violations = |state: &GameState, game_move: Move| {
+        let failed: Vec<_> = state.explain_move(PlayerColor::Red, &game_move).into_iter().filter(|c| !c.passed).map(|c| c.rule).collect();
+        assert_eq!(failed.is_empty(), state.validate_move(PlayerColor::Red, &game_move).is_ok(), "{} violates {:?}", game_move, failed);
+        failed
+    };
+    let state = GameState::from_position_notation("6 R R -2,0=RG -1,0=RA 0,0=RB 1,0=BB 2,0=BA 3,-3=#").unwrap();
+
+    // Set moves
+    assert!(violations(&GameState::initial(PlayerColor::Red), set(PieceType::Ant, PlayerColor::Red, 0, 0)).is_empty());
+    assert!(violations(&state, set(PieceType::Spider, PlayerColor::Red, -3, 0)).is_empty());
+    assert!(violations(&state, set(PieceType::Spider, PlayerColor::Red, 10, 0)).contains(&"Destination in bounds"));
+    assert!(violations(&state, set(PieceType::Spider, PlayerColor::Red, 3, -3)).contains(&"Destination not obstructed"));
+    assert!(violations(&state, set(PieceType::Spider, PlayerColor::Red, -1, 0)).contains(&"Destination empty"));
+    assert!(violations(&state, set(PieceType::Spider, PlayerColor::Blue, -3, 0)).contains(&"Own piece"));
+    assert_eq!(violations(&state, set(PieceType::Bee, PlayerColor::Red, -3, 0)), vec!["Piece undeployed"]);
+    assert_eq!(violations(&state, set(PieceType::Spider, PlayerColor::Red, 0, 5)), vec!["Next to own piece"]);
+    assert_eq!(violations(&state, set(PieceType::Spider, PlayerColor::Red, 0, 1)), vec!["Not next to opponent"]);
+    let second = GameState::from_position_notation("1 B R 0,0=BB").unwrap();
+    assert_eq!(violations(&second, set(PieceType::Ant, PlayerColor::Red, 3, 0)), vec!["Next to opponent"]);
+    let without_bee = GameState::from_position_notation("6 R R -2,0=RG -1,0=RA 0,0=RS 1,0=BB 2,0=BA").unwrap();
+    assert_eq!(violations(&without_bee, set(PieceType::Spider, PlayerColor::Red, -3, 0)), vec!["Bee placement deadline"]);
+
+    // Drag moves
+    assert!(violations(&state, drag((-2, 0), (3, 0))).is_empty());
+    assert!(violations(&without_bee, drag((-2, 0), (3, 0))).contains(&"Bee placed"));
+    assert!(violations(&state, drag((10, 0), (3, 0))).contains(&"Start in bounds"));
+    assert!(violations(&state, drag((-2, 0), (10, 0))).contains(&"Destination in bounds"));
+    assert!(violations(&state, drag((0, 2), (0, 1))).contains(&"Piece at start"));
+    #[cfg(not(feature = "expansions"))]
+    assert!(violations(&state, drag((2, 0), (3, 0))).contains(&"Own piece"));
+    #[cfg(feature = "expansions")]
+    assert!(violations(&state, drag((2, 0), (3, 0))).contains(&"Pillbug ability"));
+    assert!(violations(&state, drag((-1, 0), (-1, 0))).contains(&"Start differs from destination"));
+    assert!(violations(&state, drag((-2, 0), (-1, 0))).contains(&"Climbing"));
+    assert!(violations(&state, drag((0, 0), (0, 1))).contains(&"Swarm stays connected"));
+    assert_eq!(violations(&state, drag((-2, 0), (-2, 1))), vec!["Piece movement"]);
+}
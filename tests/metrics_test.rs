@@ -0,0 +1,52 @@
+#![cfg(feature = "metrics")]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use socha_client_2020::game::PlayerColor;
+use socha_client_2020::metrics::{Histogram, Metrics, MetricsServer};
+use socha_client_2020::protocol::{GameResult, TurnClock};
+use socha_client_2020::util::{FromXmlNode, XmlNode};
+use xml::reader::EventReader;
+
+#[test]
+fn histogram_buckets() {
+    let mut histogram = Histogram::new(&[1.0, 2.0]);
+    for value in [0.5, 1.5, 3.0] {
+        histogram.observe(value);
+    }
+    let mut out = String::new();
+    histogram.write_samples(&mut out, "x");
+    assert_eq!(out, "x_bucket{le=\"1\"} 1\nx_bucket{le=\"2\"} 2\nx_bucket{le=\"+Inf\"} 3\nx_sum 5\nx_count 3\n");
+}
+
+#[test]
+fn metrics_endpoint() {
+    let server = MetricsServer::start("127.0.0.1:0", Metrics::new()).unwrap();
+    let metrics = server.metrics();
+    metrics.record_move(Duration::from_millis(300), Some((30_000, Duration::from_millis(300))));
+    metrics.record_move(Duration::from_millis(10), None);
+    let draw = "<data class=\"result\"><definition/><score cause=\"REGULAR\" reason=\"\"/><score cause=\"REGULAR\" reason=\"\"/></data>";
+    metrics.record_game(&GameResult::from_node(&XmlNode::read_from(&mut EventReader::new(draw.as_bytes())).unwrap()).unwrap(), Some(PlayerColor::Red));
+    assert_eq!((metrics.games(), metrics.moves()), (1, 2));
+    let mut clock = TurnClock::default();
+    clock.start_turn();
+    thread::sleep(Duration::from_millis(20));
+    clock.end_turn();
+    metrics.record_clock(&clock);
+
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    write!(stream, "GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n").unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("sc_games_total{outcome=\"draw\"} 1\n"));
+    assert!(response.contains("sc_moves_total 2\n"));
+    assert!(response.contains("sc_move_latency_seconds_bucket{le=\"0.5\"} 2\n"));
+    assert!(response.contains("sc_search_nodes_per_second_count 1\n"));
+    let sample = |name: &str| response.lines().find_map(|l| l.strip_prefix(name)?.strip_prefix(' ')?.parse::<f64>().ok()).unwrap();
+    assert!(sample("sc_thinking_seconds_total") >= 0.02);
+    assert_eq!(sample("sc_longest_turn_seconds"), sample("sc_thinking_seconds_total"));
+    assert!(response.ends_with("# EOF\n"));
+}
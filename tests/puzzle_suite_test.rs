@@ -0,0 +1,22 @@
+use std::time::Duration;
+use socha_client_2020::analysis::{attempt_puzzle, parse_puzzle_suite};
+
+const SUITE: &str = include_str!("puzzles.txt");
+
+#[test]
+fn puzzle_suite_parses() {
+    let puzzles = parse_puzzle_suite(SUITE).unwrap();
+    assert_eq!(puzzles.len(), 9);
+    assert!(puzzles.iter().all(|p| p.accepts(&p.solution[0])));
+}
+
+/// Pins the engine's tactical strength, run using
+/// `cargo test --release -- --ignored`.
+#[test]
+#[ignore]
+fn puzzle_suite_solved() {
+    for puzzle in parse_puzzle_suite(SUITE).unwrap() {
+        let attempt = attempt_puzzle(&puzzle, 500_000, Duration::from_secs(5));
+        assert!(attempt.solved, "Chose {:?} instead of {} after {} nodes in {}", attempt.chosen, puzzle.solution[0], attempt.nodes, puzzle);
+    }
+}
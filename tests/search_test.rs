@@ -0,0 +1,257 @@
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use socha_client_2020::analysis::{self, Puzzle};
+use socha_client_2020::eval::{Evaluator, HeuristicEvaluator};
+use socha_client_2020::game::{AxialCoords, GameState, Move, Piece, PieceType, PlayerColor, PositionedField};
+use socha_client_2020::search::{AlphaBetaSearch, Bound, MoveFilter, MovePrior, SearchStats, TableEntry, TranspositionTable, TABLE_SLOT_SIZE};
+use socha_client_2020::util::CancellationToken;
+
+#[test]
+fn search_stats_aggregation() {
+    let mut game = SearchStats::default();
+    game += SearchStats { nodes: 300, tt_probes: 100, tt_hits: 25, depth: 3, elapsed: Duration::from_millis(500) };
+    game += SearchStats { nodes: 700, tt_probes: 300, tt_hits: 75, depth: 2, elapsed: Duration::from_millis(500) };
+    assert_eq!(game.nodes, 1000);
+    assert_eq!(game.depth, 3);
+    assert_eq!(game.elapsed, Duration::from_secs(1));
+    assert!((game.nps() - 1000.0).abs() < 1e-6);
+    assert!((game.tt_hit_rate() - 0.25).abs() < 1e-6);
+}
+
+#[test]
+fn shared_transposition_table() {
+    let set_entry = TableEntry {
+        depth: 3,
+        score: -2.5,
+        bound: Bound::Lower,
+        best_move: Some(Move::SetMove { piece: Piece { piece_type: PieceType::Spider, owner: PlayerColor::Blue }, destination: PositionedField::new(AxialCoords::new(-3, 5)) })
+    };
+    let drag_entry = TableEntry {
+        depth: 7,
+        score: 1_000_004.0,
+        bound: Bound::Exact,
+        best_move: Some(Move::DragMove { start: PositionedField::new(AxialCoords::new(4, -1)), destination: PositionedField::new(AxialCoords::new(0, -4)) })
+    };
+    let table = Arc::new(TranspositionTable::new(1));
+    table.store(42, &set_entry);
+    assert_eq!(table.probe(42), Some(set_entry.clone()));
+    assert_eq!(table.probe(43), None);
+
+    // Entries without any set bits are distinguished from empty slots
+    let zero_entry = TableEntry { depth: 0, score: 0.0, bound: Bound::Exact, best_move: None };
+    let zero_table = TranspositionTable::new(1);
+    assert_eq!(zero_table.probe(0), None);
+    zero_table.store(0, &zero_entry);
+    assert_eq!(zero_table.probe(0), Some(zero_entry));
+
+    // Writers racing on the only slot must never produce an
+    // entry that is returned for the wrong key
+    let writers: Vec<_> = [(42, set_entry.clone()), (1 << 40, drag_entry.clone())].iter().cloned().map(|(key, entry)| {
+        let table = table.clone();
+        thread::spawn(move || for _ in 0..100_000 { table.store(key, &entry) })
+    }).collect();
+    for _ in 0..100_000 {
+        assert!(table.probe(42).map(|e| e == set_entry).unwrap_or(true));
+        assert!(table.probe(1 << 40).map(|e| e == drag_entry).unwrap_or(true));
+    }
+    for writer in writers {
+        writer.join().unwrap();
+    }
+    table.clear();
+    assert_eq!(table.probe(42), None);
+
+    // Tables sized by memory stay within the budget
+    let budgeted = TranspositionTable::with_memory(3 << 20);
+    assert_eq!(budgeted.memory_usage(), 2 << 20);
+    assert_eq!(budgeted.capacity() * TABLE_SLOT_SIZE, budgeted.memory_usage());
+    assert_eq!(budgeted.occupancy(), 0.0);
+    budgeted.store(0, &set_entry);
+    assert!(budgeted.occupancy() > 0.0);
+    zero_table.clear();
+    assert_eq!(zero_table.occupancy(), 0.0);
+    zero_table.store(0, &TableEntry { depth: 0, score: 0.0, bound: Bound::Exact, best_move: None });
+    assert_eq!(zero_table.occupancy(), 1.0);
+
+    // Budgets exceeding the limit are capped
+    let capped = TranspositionTable::with_memory_limit(64 << 20, 1 << 20);
+    assert_eq!(capped.memory_usage(), 1 << 20);
+}
+
+struct TurnEvaluator;
+
+impl Evaluator for TurnEvaluator {
+    fn evaluate(&self, state: &GameState, _color: PlayerColor) -> f64 { f64::from(state.turn) }
+}
+
+#[test]
+fn evaluation_breakdown() {
+    let state = GameState::initial(PlayerColor::Red);
+    let next = state.apply_move(&state.possible_moves(PlayerColor::Red)[0]).unwrap();
+    let evaluator = HeuristicEvaluator::default();
+    let breakdown = evaluator.breakdown(&next, PlayerColor::Red);
+    assert!(!breakdown.is_empty());
+    assert!((breakdown.iter().map(|f| f.weighted()).sum::<f64>() - evaluator.evaluate(&next, PlayerColor::Red)).abs() < 1e-9);
+    assert!(TurnEvaluator.breakdown(&next, PlayerColor::Red).is_empty());
+}
+
+#[test]
+fn heuristic_features() {
+    let state = GameState::from_position_notation("7 R B 0,-2=BG 0,-1=RS 0,0=RB -1,0=BS -1,1=RG 1,-1=RA 1,0=BB").unwrap();
+    let evaluator = HeuristicEvaluator::default();
+    let value = |color, name| evaluator.features(&state, color).into_iter().find(|f| f.name == name).unwrap().value;
+    assert_eq!(value(PlayerColor::Blue, "opponent bee neighbors"), 5.0);
+    assert_eq!(value(PlayerColor::Blue, "own bee neighbors"), 2.0);
+    assert_eq!(value(PlayerColor::Red, "opponent bee neighbors"), 2.0);
+    assert_eq!(value(PlayerColor::Red, "deployed pieces"), 1.0);
+    assert_eq!(value(PlayerColor::Red, "movable pieces"), -value(PlayerColor::Blue, "movable pieces"));
+    assert_eq!(value(PlayerColor::Red, "bee placed"), 1.0);
+
+    // Apart from the placed bees, the features are zero-sum
+    let sum = evaluator.evaluate(&state, PlayerColor::Red) + evaluator.evaluate(&state, PlayerColor::Blue);
+    assert!((sum - 2.0 * evaluator.bee_placed_weight).abs() < 1e-9);
+}
+
+#[test]
+fn move_hints() {
+    let state = GameState::from_position_notation("7 R B 0,-2=BG 0,-1=RS 0,0=RB -1,0=BS -1,1=RG 1,-1=RA 1,0=BB").unwrap();
+    let evaluator = HeuristicEvaluator::default();
+    let hints = analysis::hints(&state, PlayerColor::Blue, &evaluator, 3);
+    assert_eq!(hints.len(), 3);
+    assert!(hints.windows(2).all(|w| w[0].evaluation >= w[1].evaluation));
+    for hint in &hints {
+        assert!(state.validate_move(PlayerColor::Blue, &hint.game_move).is_ok());
+        assert_eq!(hint.evaluation, evaluator.evaluate(&state.apply_move(&hint.game_move).unwrap(), PlayerColor::Blue));
+    }
+
+    // Weighting the bee's neighbors higher ranks the surrounding move first
+    let attacking = HeuristicEvaluator { opponent_bee_neighbors_weight: 10.0, ..HeuristicEvaluator::default() };
+    let best = analysis::hints(&state, PlayerColor::Blue, &attacking, 1).remove(0);
+    let surround = Move::DragMove { start: PositionedField::new(AxialCoords::new(0, -2)), destination: PositionedField::new(AxialCoords::new(0, 1)) };
+    assert_eq!(best.game_move, surround);
+    assert_eq!(best.justification, "opponent bee neighbors: 5 -> 6 (+10.00)");
+    assert!(best.to_string().contains(&surround.to_string()));
+
+    let all = analysis::hints(&state, PlayerColor::Blue, &evaluator, usize::MAX);
+    assert_eq!(all.len(), state.possible_moves(PlayerColor::Blue).len());
+    assert_eq!(&all[..3], &hints[..]);
+    assert!(analysis::hints(&state, PlayerColor::Blue, &evaluator, 0).is_empty());
+}
+
+#[test]
+fn puzzle_extraction() {
+    let state = GameState::from_position_notation("7 R B 0,-2=BG 0,-1=RS 0,0=RB -1,0=BS -1,1=RG 1,-1=RA 1,0=BB").unwrap();
+    assert_eq!(GameState::from_position_notation(&state.position_notation()).unwrap(), state);
+    assert_eq!(state.undeployed_pieces(PlayerColor::Blue).len(), 8);
+
+    let puzzle = Puzzle::find(&state, 3).unwrap();
+    assert_eq!(puzzle.plies(), 1);
+    assert_eq!(puzzle.solution[0], Move::DragMove { start: PositionedField::new(AxialCoords::new(0, -2)), destination: PositionedField::new(AxialCoords::new(0, 1)) });
+    assert_eq!(puzzle.to_string().parse::<Puzzle>().unwrap(), puzzle);
+    assert!(Puzzle::find(&GameState::initial(PlayerColor::Red), 3).is_none());
+}
+
+#[test]
+fn tied_root_moves() {
+    let state = GameState::from_position_notation("7 R B 0,-2=BG 0,-1=RS 0,0=RB -1,0=BS -1,1=RG 1,-1=RA 1,0=BB").unwrap();
+    let result = AlphaBetaSearch::new(HeuristicEvaluator::default()).search(&state, 1, None, &CancellationToken::new());
+    let mut winning: Vec<_> = state.possible_moves(PlayerColor::Blue).into_iter()
+        .filter(|m| state.apply_move(m).unwrap().board.is_bee_surrounded(PlayerColor::Red))
+        .collect();
+    let mut tied_moves = result.tied_moves.clone();
+    winning.sort();
+    tied_moves.sort();
+    assert!(!winning.is_empty());
+    assert_eq!(tied_moves, winning);
+    assert_eq!(result.tied_moves.first(), result.best_move.as_ref());
+
+    // Without a better move, all moves are rated equally
+    let initial = GameState::initial(PlayerColor::Red);
+    let result = AlphaBetaSearch::new(TurnEvaluator).search(&initial, 1, None, &CancellationToken::new());
+    assert_eq!(result.tied_moves.len(), initial.possible_moves(PlayerColor::Red).len());
+}
+
+#[test]
+fn root_policies() {
+    let state = GameState::initial(PlayerColor::Red);
+    let places_bee = |m: &Move| matches!(m, Move::SetMove { piece, .. } if piece.piece_type == PieceType::Bee);
+    let on_rim = |state: &GameState, m: &Move| match m {
+        Move::SetMove { destination, .. } => state.board.neighbors(destination.coords).count() < 6,
+        Move::DragMove { .. } => false
+    };
+    let search = |mut search: AlphaBetaSearch<HeuristicEvaluator>| search.search(&state, 1, None, &CancellationToken::new());
+
+    // Never place the bee on the rim, but prefer placing it elsewhere
+    let result = search(AlphaBetaSearch::new(HeuristicEvaluator::default())
+        .with_root_policy(MoveFilter(move |s: &GameState, m: &Move| !(places_bee(m) && on_rim(s, m))))
+        .with_root_policy(MovePrior(move |_: &GameState, m: &Move| if places_bee(m) { 1000.0 } else { 0.0 })));
+    let best = result.best_move.unwrap();
+    assert!(places_bee(&best) && !on_rim(&state, &best));
+    // The reported score excludes the prior
+    let unbiased = search(AlphaBetaSearch::new(HeuristicEvaluator::default()));
+    assert!(result.score <= unbiased.score);
+    assert!(result.score < 500.0);
+
+    // Filters ruling out every move are ignored
+    let result = search(AlphaBetaSearch::new(HeuristicEvaluator::default()).with_root_policy(MoveFilter(|_: &GameState, _: &Move| false)));
+    assert!(result.best_move.is_some());
+}
+
+#[cfg(feature = "client")]
+#[test]
+fn mcts_options() {
+    use socha_client_2020::client::SCClientDelegate;
+    use socha_client_2020::logic::{OwnGameLogic, Strategy};
+    use socha_client_2020::search::{MctsOptions, MctsSearch, ProgressiveWidening};
+
+    let state = GameState::from_position_notation("7 R B 0,-2=BG 0,-1=RS 0,0=RB -1,0=BS -1,1=RG 1,-1=RA 1,0=BB").unwrap();
+    let surround = Move::DragMove { start: PositionedField::new(AxialCoords::new(0, -2)), destination: PositionedField::new(AxialCoords::new(0, 1)) };
+    let search = |state: &GameState, options: MctsOptions, iterations: u64| MctsSearch::new(HeuristicEvaluator::default(), options)
+        .with_seed(0)
+        .with_iteration_limit(iterations)
+        .search(state, None, &CancellationToken::new());
+
+    // Every combination of the enhancements finds the winning move
+    for widening in [None, Some(ProgressiveWidening { coefficient: 2.0, exponent: 0.5 })] {
+        for rave in [None, Some(500.0)] {
+            let options = MctsOptions::default().with_progressive_widening(widening).with_rave_equivalence(rave).with_playout_depth(2);
+            let result = search(&state, options, 120);
+            assert_eq!(result.best_move, Some(surround.clone()), "widening {:?}, RAVE {:?}", widening, rave);
+            assert_eq!(result.principal_variation.first(), Some(&surround));
+            assert!(result.score > 0.0);
+            assert_eq!(result.stats.nodes, 120);
+        }
+    }
+    let principal_variation = |options: MctsOptions| search(&state, options, 50).principal_variation;
+    assert_eq!(principal_variation(MctsOptions::default()), principal_variation(MctsOptions::default()));
+
+    // Plain UCT tries every move once before searching deeper,
+    // while progressive widening focuses on the best few moves
+    let initial = GameState::initial(PlayerColor::Red);
+    let iterations = 20;
+    let mut initial_moves = initial.possible_moves(PlayerColor::Red);
+    initial_moves.sort();
+    initial_moves.dedup();
+    assert!(initial_moves.len() > iterations);
+    let vanilla = search(&initial, MctsOptions::vanilla(), iterations as u64);
+    assert_eq!((vanilla.stats.depth, vanilla.tied_moves.len()), (1, iterations));
+    let widened = search(&initial, MctsOptions::vanilla().with_progressive_widening(MctsOptions::default().progressive_widening), iterations as u64);
+    assert!(widened.stats.depth > 1);
+    assert!(widened.tied_moves.len() < iterations);
+
+    // Once the tree is full, the search only continues the playouts
+    let capped = MctsSearch::new(HeuristicEvaluator::default(), MctsOptions::vanilla())
+        .with_seed(0)
+        .with_iteration_limit(iterations as u64)
+        .with_node_limit(5)
+        .search(&initial, None, &CancellationToken::new());
+    assert_eq!((capped.stats.nodes, capped.stats.depth), (iterations as u64, 1));
+    assert!(capped.tied_moves.len() <= 4);
+
+    // The Monte Carlo strategy finds the winning move as well
+    let mut logic = OwnGameLogic::new("mcts".parse::<Strategy>().unwrap(), Some(0));
+    assert_eq!(logic.request_move(&state, PlayerColor::Blue, &CancellationToken::new()), surround);
+    let annotation = logic.move_annotation().unwrap();
+    assert_eq!(annotation.debug.get("strategy"), Some("mcts"));
+    assert!(annotation.nodes.unwrap() > 0);
+}
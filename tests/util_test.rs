@@ -1,5 +1,6 @@
 use std::fmt::Debug;
 use socha_client_2020::game::{DoubledCoords as Doubled, AxialCoords as Axial};
+use socha_client_2020::util::BuildInfo;
 
 /// Tests whether a bidirectional conversion
 /// succeeds in both directions.
@@ -19,3 +20,79 @@ fn doubled_axial_coords() {
     test_bi_conversion(Axial::new(-1, 0), Doubled::new(-1, 1));
     test_bi_conversion(Axial::new(0, -1), Doubled::new(1, 1));
 }
+
+#[cfg(feature = "protocol")]
+#[test]
+fn interned_xml_names() {
+    use socha_client_2020::util::{XmlName, XmlNode};
+
+    assert!(XmlName::new("piece").is_known());
+    assert!(!XmlName::new("lastMoveOfTheGame").is_known());
+    assert_eq!(XmlName::new("owner"), XmlName::from("owner"));
+    assert_ne!(XmlName::new("owner"), XmlName::new("other"));
+    assert_eq!(XmlName::new("custom").as_str(), "custom");
+
+    let node = XmlNode::new("field").attribute("x", "1").attribute("custom", "2").attribute("x", "3").build();
+    assert_eq!(node.attribute("x").unwrap(), "3");
+    assert_eq!(node.attribute("custom").unwrap(), "2");
+    assert_eq!(node, XmlNode::new("field").attribute("custom", "2").attribute("x", "3").build());
+}
+
+#[cfg(feature = "protocol")]
+#[test]
+fn borrowed_xml_nodes() {
+    use socha_client_2020::util::{XmlElement, XmlNode, XmlNodeRef};
+    use xml::reader::EventReader;
+
+    let xml = "<?xml version=\"1.0\"?>\n<!-- recorded -->\n<protocol xmlns:sc=\"urn:sc\">\n  <sc:room roomId=\"a&amp;b\" note='x\ny&#10;'>\n    <data class=\"welcomeMessage\" color=\"red\"/>\n    text &lt;1&gt;\n  </sc:room>\n</protocol>";
+    let borrowed = XmlNodeRef::parse(xml).unwrap();
+    let room = borrowed.child_by_name("room").unwrap();
+    assert_eq!(room.attribute("roomId").unwrap(), "a&b");
+    assert_eq!(room.attribute("note").unwrap(), "x\ny\n");
+    assert_eq!(room.childs_by_name("data").count(), 1);
+    assert_eq!(room.optional_attribute::<u32>("missing").unwrap(), None);
+    assert_eq!(borrowed.to_node(), XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap());
+
+    assert_eq!(XmlNodeRef::parse("<a>1<![CDATA[<b/>]]>2</a>").unwrap().content(), "12");
+    assert!(XmlNodeRef::parse("<a><b></a>").is_err());
+    assert!(XmlNodeRef::parse("<a x=\"1\" x=\"2\"/>").is_err());
+    assert!(XmlNodeRef::parse("<a>&unknown;</a>").is_err());
+}
+
+#[cfg(feature = "protocol")]
+#[test]
+fn xml_escaping() {
+    use socha_client_2020::util::{XmlFormat, XmlNode};
+    use xml::reader::EventReader;
+
+    let tricky = "Team \"Ä&Ö\" <'🐝'>\n";
+    let node = XmlNode::new("player")
+        .attribute("displayName", tricky)
+        .child(XmlNode::new("note").content(tricky))
+        .child(XmlNode::new("empty"))
+        .build();
+    for format in [XmlFormat::compact(), XmlFormat::pretty(), XmlFormat::compact().with_normalized_empty_elements(false)] {
+        let xml = node.to_string_with(&format).unwrap();
+        let parsed = XmlNode::read_from(&mut EventReader::new(xml.as_bytes())).unwrap();
+        assert_eq!(parsed.attribute("displayName").unwrap(), tricky);
+        assert_eq!(parsed.childs()[0].content(), tricky);
+    }
+
+    let compact = node.to_string_with(&XmlFormat::compact()).unwrap();
+    assert!(compact.contains("&quot;Ä&amp;Ö&quot; &lt;"));
+    assert!(compact.contains("<empty />"));
+    assert!(!compact.contains("\n  <"));
+    assert!(node.to_string_with(&XmlFormat::pretty().with_indent(Some("\t".to_owned()))).unwrap().contains("\n\t<empty />"));
+    assert!(node.to_string_with(&XmlFormat::compact().with_normalized_empty_elements(false)).unwrap().contains("<empty></empty>"));
+}
+
+#[test]
+fn build_info() {
+    let info = BuildInfo::CURRENT;
+    assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+    assert!(info.to_string().starts_with(info.version));
+    if let Some(timestamp) = info.timestamp {
+        assert_eq!(timestamp.len(), "2020-06-01T12:00:00Z".len());
+        assert!(timestamp.ends_with('Z'));
+    }
+}
@@ -0,0 +1,21 @@
+#![cfg(feature = "tui")]
+
+use std::time::Duration;
+use socha_client_2020::game::{GameState, PlayerColor};
+use socha_client_2020::protocol::MoveAnnotation;
+use socha_client_2020::viewer::engine_summary;
+
+#[test]
+fn engine_summary_lines() {
+    let state = GameState::initial(PlayerColor::Red);
+    let moves = state.possible_moves(PlayerColor::Red);
+    let (first, second) = (&moves[0], &moves[1]);
+    assert_eq!(engine_summary(first, Duration::from_millis(1500), None), format!("Chose {} after 1.50s", first));
+
+    let mut annotation = MoveAnnotation { depth: Some(3), ..MoveAnnotation::default() };
+    // The depth is only shown along with an evaluation
+    assert_eq!(engine_summary(first, Duration::ZERO, Some(&annotation)), format!("Chose {} after 0.00s", first));
+    annotation.evaluation = Some(-2.5);
+    annotation.principal_variation = vec![first.clone(), second.clone()];
+    assert_eq!(engine_summary(first, Duration::ZERO, Some(&annotation)), format!("Chose {} after 0.00s\nEvaluation: -2.50 (depth 3)\nPV: {} {}", first, first, second));
+}
@@ -0,0 +1,72 @@
+#![cfg(feature = "web")]
+
+use std::io::{Read, Write};
+use std::net::TcpStream;
+use std::thread;
+use std::time::Duration;
+use socha_client_2020::client::SCClientDelegate;
+use socha_client_2020::game::{GameState, PlayerColor};
+use socha_client_2020::logic::{OwnGameLogic, Strategy};
+use socha_client_2020::protocol::TimeSettings;
+use socha_client_2020::util::CancellationToken;
+use socha_client_2020::web::{EventBroadcaster, StatusServer, WebDelegate};
+
+fn get(server: &StatusServer, path: &str) -> String {
+    let mut stream = TcpStream::connect(server.address()).unwrap();
+    write!(stream, "GET {} HTTP/1.1\r\nHost: localhost\r\n\r\n", path).unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+#[test]
+fn status_server_routes() {
+    let server = StatusServer::start("127.0.0.1:0").unwrap();
+    assert!(get(&server, "/").starts_with("HTTP/1.1 200 OK"));
+    assert!(get(&server, "/state").starts_with("HTTP/1.1 503"));
+    assert!(get(&server, "/unknown").starts_with("HTTP/1.1 404"));
+}
+
+#[test]
+fn event_broadcaster_pushes_events() {
+    let broadcaster = EventBroadcaster::start("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(broadcaster.address()).unwrap();
+    let (mut socket, _) = tungstenite::client(format!("ws://{}", broadcaster.address()), stream).unwrap();
+    while broadcaster.subscriber_count() == 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    broadcaster.publish("state", serde_json::json!({ "turn": 3 }));
+    let message = socket.read().unwrap().into_text().unwrap();
+    let event: serde_json::Value = serde_json::from_str(&message).unwrap();
+    assert_eq!(event["type"], "state");
+    assert_eq!(event["data"]["turn"], 3);
+}
+
+#[test]
+fn web_delegate_pushes_search_stats() {
+    let broadcaster = EventBroadcaster::start("127.0.0.1:0").unwrap();
+    let stream = TcpStream::connect(broadcaster.address()).unwrap();
+    let (mut socket, _) = tungstenite::client(format!("ws://{}", broadcaster.address()), stream).unwrap();
+    while broadcaster.subscriber_count() == 0 {
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    let mut logic = OwnGameLogic::new(Strategy::AlphaBeta, Some(0));
+    logic.on_time_settings(&TimeSettings::new(Duration::from_secs(1), Duration::from_secs(2)));
+    let mut delegate = WebDelegate::new(logic).with_broadcaster(broadcaster);
+    let state = GameState::initial(PlayerColor::Red);
+    delegate.request_move(&state, PlayerColor::Red, &CancellationToken::new());
+
+    let mut read_event = || serde_json::from_str::<serde_json::Value>(&socket.read().unwrap().into_text().unwrap()).unwrap();
+    let chosen = read_event();
+    assert_eq!(chosen["type"], "move");
+    let search = read_event();
+    assert_eq!(search["type"], "search");
+    assert!(search["data"]["depth"].as_u64().unwrap() >= 1);
+    assert!(search["data"]["nodes"].as_u64().unwrap() > 0);
+    assert!(search["data"]["evaluation"].is_number());
+    assert!(search["data"]["timeMs"].is_number());
+    assert_eq!(search["data"]["principalVariation"][0], chosen["data"]["move"]);
+    assert_eq!(search["data"]["debug"]["strategy"], "alphabeta");
+}